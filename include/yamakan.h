@@ -0,0 +1,41 @@
+/*
+ * C interface of yamakan (built with the `ffi` feature).
+ *
+ * Functions returning `int` return `0` on success and `-1` on failure,
+ * and functions returning pointers return `NULL` on failure.
+ */
+#ifndef YAMAKAN_H
+#define YAMAKAN_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An opaque handle of an optimizer. */
+typedef struct yamakan_optimizer yamakan_optimizer;
+
+/* Makes a new random optimizer. Must be released by `yamakan_free`. */
+yamakan_optimizer *yamakan_random_new(const double *lows, const double *highs, size_t dim,
+                                      size_t objectives, uint64_t seed);
+
+/* Makes a new NSGA-II optimizer. Must be released by `yamakan_free`. */
+yamakan_optimizer *yamakan_nsga2_new(const double *lows, const double *highs, size_t dim,
+                                     size_t objectives, size_t population_size, uint64_t seed);
+
+/* Releases the given optimizer (or does nothing if `opt` is `NULL`). */
+void yamakan_free(yamakan_optimizer *opt);
+
+/* Asks the next parameter, writing its identifier to `id` and its `dim` elements to `param`. */
+int yamakan_ask(yamakan_optimizer *opt, uint64_t *id, double *param, size_t dim);
+
+/* Tells the `objectives` values of the observation identified by `id`. */
+int yamakan_tell(yamakan_optimizer *opt, uint64_t id, const double *values, size_t objectives);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* YAMAKAN_H */
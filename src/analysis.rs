@@ -0,0 +1,753 @@
+//! Post-hoc analysis of observations.
+use crate::domains::{ContinuousDomain, GridDomain};
+use crate::{ErrorKind, MeasuredValue, Obs, ObsId, Result};
+use ordered_float::OrderedFloat;
+use rand::distributions::Distribution;
+use rand::seq::SliceRandom;
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// The lower bound of the variances used to weight neighbors, so that exact observations get finite weights.
+const MIN_VARIANCE: f64 = 1e-12;
+
+/// A k-nearest neighbor regression model fitted on observations.
+///
+/// Parameters are normalized into `[0, 1)` by their domains before computing euclidean distances.
+///
+/// Each neighbor is weighted by the inverse of the sum of its measurement variance
+/// (which is `0` unless the model is fitted on measured values by `fit_measured`)
+/// and the variance of the neighbors' values, so that noisy observations contribute less to the predictions.
+///
+/// A fitted model is self-contained, so it can be used for what-if predictions after a study
+/// (e.g., one exported by `TurboOptimizer::surrogates`).
+/// If the `serde` feature is enabled, this can be serialized into any format supported by serde (e.g., JSON).
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KnnSurrogate {
+    domains: Vec<ContinuousDomain>,
+    points: Vec<Vec<f64>>,
+    values: Vec<f64>,
+    variances: Vec<f64>,
+    k: usize,
+}
+impl KnnSurrogate {
+    /// Fits a new model on the given observations.
+    ///
+    /// # Errors
+    ///
+    /// If one of the following conditions is satisfied, an `ErrorKind::InvalidInput` error will be returned:
+    ///
+    /// - `k` is `0`
+    /// - `obss` has fewer than two elements
+    /// - the dimension of a parameter differs from the number of `domains`
+    /// - a value is not finite
+    pub fn fit(
+        domains: &[ContinuousDomain],
+        obss: &[Obs<Vec<f64>, f64>],
+        k: usize,
+    ) -> Result<Self> {
+        let points = obss.iter().map(|o| (o.id, &o.param[..], o.value, 0.0));
+        track!(Self::fit_points(domains, points, k))
+    }
+
+    /// Fits a new model on the given observations having measured values.
+    ///
+    /// # Errors
+    ///
+    /// The same as `fit`.
+    pub fn fit_measured(
+        domains: &[ContinuousDomain],
+        obss: &[Obs<Vec<f64>, MeasuredValue>],
+        k: usize,
+    ) -> Result<Self> {
+        let points = obss
+            .iter()
+            .map(|o| (o.id, &o.param[..], o.value.mean(), o.value.variance()));
+        track!(Self::fit_points(domains, points, k))
+    }
+
+    pub(crate) fn fit_with_variances(
+        domains: &[ContinuousDomain],
+        obss: &[Obs<Vec<f64>, f64>],
+        variances: &[f64],
+        k: usize,
+    ) -> Result<Self> {
+        track_assert_eq!(obss.len(), variances.len(), ErrorKind::Bug);
+        let points = obss
+            .iter()
+            .zip(variances)
+            .map(|(o, &v)| (o.id, &o.param[..], o.value, v));
+        track!(Self::fit_points(domains, points, k))
+    }
+
+    fn fit_points<'a, I>(domains: &[ContinuousDomain], obss: I, k: usize) -> Result<Self>
+    where
+        I: ExactSizeIterator<Item = (ObsId, &'a [f64], f64, f64)>,
+    {
+        track_assert!(k > 0, ErrorKind::InvalidInput);
+        track_assert!(obss.len() > 1, ErrorKind::InvalidInput; obss.len());
+
+        let mut points = Vec::with_capacity(obss.len());
+        let mut values = Vec::with_capacity(obss.len());
+        let mut variances = Vec::with_capacity(obss.len());
+        for (id, param, value, variance) in obss {
+            track_assert_eq!(param.len(), domains.len(), ErrorKind::InvalidInput; id);
+            track_assert!(value.is_finite(), ErrorKind::InvalidInput; id, value);
+            track_assert!(variance.is_finite() && variance >= 0.0, ErrorKind::InvalidInput; id, variance);
+            points.push(normalize(domains, param));
+            values.push(value);
+            variances.push(variance);
+        }
+        Ok(Self {
+            domains: domains.to_vec(),
+            points,
+            values,
+            variances,
+            k,
+        })
+    }
+
+    /// Returns the domains of the parameters.
+    pub fn domains(&self) -> &[ContinuousDomain] {
+        &self.domains
+    }
+
+    /// Returns the number of the observations used to fit this model.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if this model has no observations.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Predicts the value of the given parameter.
+    pub fn predict(&self, param: &[f64]) -> f64 {
+        self.predict_normalized(&normalize(&self.domains, param), None)
+    }
+
+    /// Predicts the mean and the variance of the value of the given parameter.
+    ///
+    /// The variance is the weighted average of the squared deviations and the measurement variances of the nearest neighbors,
+    /// which is a rough measure of the uncertainty of the prediction.
+    pub fn predict_with_variance(&self, param: &[f64]) -> (f64, f64) {
+        let neighbors = self.weighted_neighbors(&normalize(&self.domains, param), None);
+        let mean = weighted_mean(&neighbors, |i| self.values[i]);
+        let var = weighted_mean(&neighbors, |i| {
+            (self.values[i] - mean).powi(2) + self.variances[i]
+        });
+        (mean, var)
+    }
+
+    fn predict_normalized(&self, x: &[f64], exclude: Option<usize>) -> f64 {
+        let neighbors = self.weighted_neighbors(x, exclude);
+        weighted_mean(&neighbors, |i| self.values[i])
+    }
+
+    fn weighted_neighbors(&self, x: &[f64], exclude: Option<usize>) -> Vec<(usize, f64)> {
+        let neighbors = self.neighbors(x, exclude);
+        let k = neighbors.len() as f64;
+        let mean = neighbors.iter().map(|&i| self.values[i]).sum::<f64>() / k;
+        let spread = neighbors
+            .iter()
+            .map(|&i| (self.values[i] - mean).powi(2))
+            .sum::<f64>()
+            / k;
+        neighbors
+            .into_iter()
+            .map(|i| {
+                let v = self.variances[i] + spread;
+                (i, 1.0 / v.max(MIN_VARIANCE))
+            })
+            .collect()
+    }
+
+    fn neighbors(&self, x: &[f64], exclude: Option<usize>) -> Vec<usize> {
+        let mut neighbors = self
+            .points
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| Some(i) != exclude)
+            .map(|(i, p)| (OrderedFloat(squared_distance(x, p)), i))
+            .collect::<Vec<_>>();
+        neighbors.sort();
+        neighbors.truncate(self.k);
+        neighbors.into_iter().map(|(_, i)| i).collect()
+    }
+
+    fn average_prediction(&self, fixed: &[(usize, f64)]) -> f64 {
+        let fixed = fixed
+            .iter()
+            .map(|&(dim, x)| {
+                (
+                    dim,
+                    (x - self.domains[dim].low()) / self.domains[dim].size(),
+                )
+            })
+            .collect::<Vec<_>>();
+        self.points
+            .iter()
+            .map(|p| {
+                let mut p = p.clone();
+                for &(dim, x) in &fixed {
+                    p[dim] = x;
+                }
+                self.predict_normalized(&p, None)
+            })
+            .sum::<f64>()
+            / self.points.len() as f64
+    }
+
+    fn loo_mse(&self, points: &[Vec<f64>]) -> f64 {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (self.predict_normalized(x, Some(i)) - self.values[i]).powi(2))
+            .sum::<f64>()
+            / points.len() as f64
+    }
+}
+
+/// Computes the permutation importance of each parameter dimension.
+///
+/// The importance of a dimension is the increase of the (leave-one-out) mean squared error of `surrogate`
+/// when the values of that dimension are randomly permuted, averaged over `repeats` permutations.
+/// The returned scores are normalized so that their sum is `1` (unless all of them are zero).
+///
+/// # Errors
+///
+/// If `repeats` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+pub fn permutation_importance<R: Rng>(
+    mut rng: R,
+    surrogate: &KnnSurrogate,
+    repeats: usize,
+) -> Result<Vec<f64>> {
+    track_assert!(repeats > 0, ErrorKind::InvalidInput);
+
+    let base = surrogate.loo_mse(&surrogate.points);
+    let mut importances = Vec::with_capacity(surrogate.domains.len());
+    for dim in 0..surrogate.domains.len() {
+        let mut total = 0.0;
+        for _ in 0..repeats {
+            let mut column = surrogate.points.iter().map(|p| p[dim]).collect::<Vec<_>>();
+            column.shuffle(&mut rng);
+
+            let mut points = surrogate.points.clone();
+            for (p, v) in points.iter_mut().zip(column) {
+                p[dim] = v;
+            }
+            total += surrogate.loo_mse(&points) - base;
+        }
+        importances.push((total / repeats as f64).max(0.0));
+    }
+
+    let sum = importances.iter().sum::<f64>();
+    if sum > 0.0 {
+        for x in &mut importances {
+            *x /= sum;
+        }
+    }
+    Ok(importances)
+}
+
+/// Sobol' sensitivity indices of parameter dimensions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SobolIndices {
+    /// First-order indices (the fraction of the variance explained by each dimension alone).
+    pub first_order: Vec<f64>,
+
+    /// Total-effect indices (the fraction of the variance involving each dimension, including interactions).
+    pub total: Vec<f64>,
+}
+
+/// Estimates the Sobol' sensitivity indices of each parameter dimension.
+///
+/// The indices are computed by Monte Carlo integration (Saltelli's estimator for the first-order indices and
+/// Jansen's estimator for the total-effect indices) over `samples` points drawn uniformly from the domains,
+/// where `surrogate` is used in place of the (expensive) objective function.
+///
+/// If the predicted values have no variance, all of the indices are `0`.
+///
+/// # Errors
+///
+/// If `samples` is less than `2`, an `ErrorKind::InvalidInput` error will be returned.
+///
+/// # References
+///
+/// - [Variance based sensitivity analysis of model output](https://doi.org/10.1016/j.cpc.2009.09.018)
+pub fn sobol_indices<R: Rng>(
+    mut rng: R,
+    surrogate: &KnnSurrogate,
+    samples: usize,
+) -> Result<SobolIndices> {
+    track_assert!(samples > 1, ErrorKind::InvalidInput; samples);
+
+    let mut sample = || {
+        surrogate
+            .domains
+            .iter()
+            .map(|d| d.sample(&mut rng))
+            .collect::<Vec<_>>()
+    };
+    let a = (0..samples).map(|_| sample()).collect::<Vec<_>>();
+    let b = (0..samples).map(|_| sample()).collect::<Vec<_>>();
+    let fa = a.iter().map(|x| surrogate.predict(x)).collect::<Vec<_>>();
+    let fb = b.iter().map(|x| surrogate.predict(x)).collect::<Vec<_>>();
+
+    let n = samples as f64;
+    let mean = fa.iter().chain(fb.iter()).sum::<f64>() / (2.0 * n);
+    let var = fa
+        .iter()
+        .chain(fb.iter())
+        .map(|f| (f - mean).powi(2))
+        .sum::<f64>()
+        / (2.0 * n - 1.0);
+
+    let dim = surrogate.domains.len();
+    let mut indices = SobolIndices {
+        first_order: vec![0.0; dim],
+        total: vec![0.0; dim],
+    };
+    if var == 0.0 {
+        return Ok(indices);
+    }
+    for i in 0..dim {
+        let mut first_order = 0.0;
+        let mut total = 0.0;
+        for j in 0..samples {
+            let mut ab = a[j].clone();
+            ab[i] = b[j][i];
+            let fab = surrogate.predict(&ab);
+            first_order += fb[j] * (fab - fa[j]);
+            total += (fa[j] - fab).powi(2);
+        }
+        indices.first_order[i] = first_order / n / var;
+        indices.total[i] = total / (2.0 * n) / var;
+    }
+    Ok(indices)
+}
+
+/// One-dimensional partial dependence data (i.e., a curve) for plotting.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartialDependence1d {
+    /// The parameter dimension.
+    pub dim: usize,
+
+    /// The points of the dimension.
+    pub xs: Vec<f64>,
+
+    /// The values at `xs`.
+    ///
+    /// This is `NaN` for the points that have no data (e.g., empty bins).
+    pub values: Vec<f64>,
+}
+
+/// Two-dimensional partial dependence data (i.e., a heatmap) for plotting.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PartialDependence2d {
+    /// The parameter dimensions corresponding to `xs` and `ys`.
+    pub dims: (usize, usize),
+
+    /// The points of the first dimension.
+    pub xs: Vec<f64>,
+
+    /// The points of the second dimension.
+    pub ys: Vec<f64>,
+
+    /// The values at the points (`values[i][j]` is the value at `(xs[i], ys[j])`).
+    ///
+    /// This is `NaN` for the points that have no data (e.g., empty bins).
+    pub values: Vec<Vec<f64>>,
+}
+
+/// Computes the partial dependence of `surrogate` on the given parameter dimension.
+///
+/// For each of `resolution` evenly spaced points of the dimension,
+/// the predictions are averaged over the observations used to fit `surrogate`
+/// with the dimension replaced by that point.
+///
+/// # Errors
+///
+/// If `dim` is out of range or `resolution` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+pub fn partial_dependence(
+    surrogate: &KnnSurrogate,
+    dim: usize,
+    resolution: usize,
+) -> Result<PartialDependence1d> {
+    track_assert!(dim < surrogate.domains.len(), ErrorKind::InvalidInput; dim);
+    track_assert!(resolution > 0, ErrorKind::InvalidInput);
+
+    let xs = surrogate.domains[dim].grid(resolution);
+    let values = xs
+        .iter()
+        .map(|&x| surrogate.average_prediction(&[(dim, x)]))
+        .collect();
+    Ok(PartialDependence1d { dim, xs, values })
+}
+
+/// Computes the partial dependence of `surrogate` on the given pair of parameter dimensions.
+///
+/// This is the two-dimensional version of `partial_dependence`.
+///
+/// # Errors
+///
+/// If a dimension is out of range, the dimensions are the same or `resolution` is `0`,
+/// an `ErrorKind::InvalidInput` error will be returned.
+pub fn partial_dependence_2d(
+    surrogate: &KnnSurrogate,
+    dims: (usize, usize),
+    resolution: usize,
+) -> Result<PartialDependence2d> {
+    let n = surrogate.domains.len();
+    track_assert!(dims.0 < n && dims.1 < n, ErrorKind::InvalidInput; dims);
+    track_assert_ne!(dims.0, dims.1, ErrorKind::InvalidInput);
+    track_assert!(resolution > 0, ErrorKind::InvalidInput);
+
+    let xs = surrogate.domains[dims.0].grid(resolution);
+    let ys = surrogate.domains[dims.1].grid(resolution);
+    let values = xs
+        .iter()
+        .map(|&x| {
+            ys.iter()
+                .map(|&y| surrogate.average_prediction(&[(dims.0, x), (dims.1, y)]))
+                .collect()
+        })
+        .collect();
+    Ok(PartialDependence2d {
+        dims,
+        xs,
+        ys,
+        values,
+    })
+}
+
+/// Computes the averages of the observed values binned by the given parameter dimension.
+///
+/// The domain of the dimension is divided into `bins` intervals of equal width,
+/// and `xs` of the result are their centers.
+/// Unlike `partial_dependence`, this does not require a surrogate,
+/// but the averages are affected by the distribution of the other dimensions.
+///
+/// # Errors
+///
+/// If `dim` is out of range, `bins` is `0` or the dimension of a parameter differs from the number of `domains`,
+/// an `ErrorKind::InvalidInput` error will be returned.
+pub fn binned_averages(
+    domains: &[ContinuousDomain],
+    obss: &[Obs<Vec<f64>, f64>],
+    dim: usize,
+    bins: usize,
+) -> Result<PartialDependence1d> {
+    track_assert!(dim < domains.len(), ErrorKind::InvalidInput; dim);
+    track_assert!(bins > 0, ErrorKind::InvalidInput);
+
+    let mut sums = vec![(0.0, 0); bins];
+    for obs in obss {
+        track_assert_eq!(obs.param.len(), domains.len(), ErrorKind::InvalidInput; obs.id);
+        let sum = &mut sums[bin_index(&domains[dim], obs.param[dim], bins)];
+        sum.0 += obs.value;
+        sum.1 += 1;
+    }
+    Ok(PartialDependence1d {
+        dim,
+        xs: bin_centers(&domains[dim], bins),
+        values: sums.into_iter().map(average).collect(),
+    })
+}
+
+/// Computes the averages of the observed values binned by the given pair of parameter dimensions.
+///
+/// This is the two-dimensional version of `binned_averages`.
+///
+/// # Errors
+///
+/// If a dimension is out of range, the dimensions are the same, `bins` is `0` or
+/// the dimension of a parameter differs from the number of `domains`,
+/// an `ErrorKind::InvalidInput` error will be returned.
+pub fn binned_averages_2d(
+    domains: &[ContinuousDomain],
+    obss: &[Obs<Vec<f64>, f64>],
+    dims: (usize, usize),
+    bins: usize,
+) -> Result<PartialDependence2d> {
+    let n = domains.len();
+    track_assert!(dims.0 < n && dims.1 < n, ErrorKind::InvalidInput; dims);
+    track_assert_ne!(dims.0, dims.1, ErrorKind::InvalidInput);
+    track_assert!(bins > 0, ErrorKind::InvalidInput);
+
+    let mut sums = vec![vec![(0.0, 0); bins]; bins];
+    for obs in obss {
+        track_assert_eq!(obs.param.len(), n, ErrorKind::InvalidInput; obs.id);
+        let i = bin_index(&domains[dims.0], obs.param[dims.0], bins);
+        let j = bin_index(&domains[dims.1], obs.param[dims.1], bins);
+        sums[i][j].0 += obs.value;
+        sums[i][j].1 += 1;
+    }
+    Ok(PartialDependence2d {
+        dims,
+        xs: bin_centers(&domains[dims.0], bins),
+        ys: bin_centers(&domains[dims.1], bins),
+        values: sums
+            .into_iter()
+            .map(|row| row.into_iter().map(average).collect())
+            .collect(),
+    })
+}
+
+/// Returns the pairs of the parameter of the given dimension and the value of each observation (i.e., a slice plot).
+///
+/// The pairs are sorted by the parameter.
+///
+/// # Errors
+///
+/// If `dim` is out of range for a parameter, an `ErrorKind::InvalidInput` error will be returned.
+pub fn slice(obss: &[Obs<Vec<f64>, f64>], dim: usize) -> Result<Vec<(f64, f64)>> {
+    let mut points = obss
+        .iter()
+        .map(|obs| {
+            let x = track_assert_some!(obs.param.get(dim), ErrorKind::InvalidInput; obs.id, dim);
+            Ok((*x, obs.value))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    Ok(points)
+}
+
+/// Returns the best-so-far values of the given evaluation results (in the evaluation order).
+///
+/// `NaN` values are ignored unless all of the preceding values are `NaN`.
+pub fn incumbent_curve(values: &[f64]) -> Vec<f64> {
+    values
+        .iter()
+        .scan(f64::NAN, |best, &v| {
+            *best = best.min(v);
+            Some(*best)
+        })
+        .collect()
+}
+
+/// Returns the best-so-far values of the given evaluation results against wall-clock time.
+///
+/// `values` are pairs of the elapsed time when an evaluation finished and its result (in any order).
+/// The returned curve is sorted by the elapsed time.
+pub fn incumbent_curve_by_time(values: &[(Duration, f64)]) -> Vec<(Duration, f64)> {
+    let mut values = values.to_vec();
+    values.sort_by_key(|&(t, _)| t);
+    let curve = incumbent_curve(&values.iter().map(|&(_, v)| v).collect::<Vec<_>>());
+    values.into_iter().map(|(t, _)| t).zip(curve).collect()
+}
+
+/// Returns the simple regrets (i.e., the differences between the best-so-far values and the known optimum).
+pub fn simple_regrets(values: &[f64], optimum: f64) -> Vec<f64> {
+    incumbent_curve(values)
+        .into_iter()
+        .map(|best| best - optimum)
+        .collect()
+}
+
+/// Returns the cumulative regrets (i.e., the running sums of the differences between the values and the known optimum).
+pub fn cumulative_regrets(values: &[f64], optimum: f64) -> Vec<f64> {
+    values
+        .iter()
+        .scan(0.0, |sum, &v| {
+            *sum += v - optimum;
+            Some(*sum)
+        })
+        .collect()
+}
+
+fn normalize(domains: &[ContinuousDomain], param: &[f64]) -> Vec<f64> {
+    domains
+        .iter()
+        .zip(param.iter())
+        .map(|(d, &x)| (x - d.low()) / d.size())
+        .collect()
+}
+
+fn bin_index(domain: &ContinuousDomain, x: f64, bins: usize) -> usize {
+    let i = ((x - domain.low()) / domain.size() * bins as f64).floor();
+    if i < 0.0 {
+        0
+    } else {
+        (i as usize).min(bins - 1)
+    }
+}
+
+fn bin_centers(domain: &ContinuousDomain, bins: usize) -> Vec<f64> {
+    let width = domain.size() / bins as f64;
+    (0..bins)
+        .map(|i| domain.low() + width * (i as f64 + 0.5))
+        .collect()
+}
+
+fn average((sum, count): (f64, usize)) -> f64 {
+    if count == 0 {
+        f64::NAN
+    } else {
+        sum / count as f64
+    }
+}
+
+fn weighted_mean<F>(neighbors: &[(usize, f64)], f: F) -> f64
+where
+    F: Fn(usize) -> f64,
+{
+    let total = neighbors.iter().map(|&(_, w)| w).sum::<f64>();
+    neighbors.iter().map(|&(i, w)| w * f(i)).sum::<f64>() / total
+}
+
+fn squared_distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(a, b)| (a - b).powi(2)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::distributions::Distribution;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn sensitivity_analysis_works() -> TestResult {
+        let domains = vec![
+            track!(ContinuousDomain::new(0.0, 1.0))?,
+            track!(ContinuousDomain::new(0.0, 1.0))?,
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+        let obss = (0..200)
+            .map(|i| {
+                let param = domains
+                    .iter()
+                    .map(|d| d.sample(&mut rng))
+                    .collect::<Vec<_>>();
+                let value = 10.0 * param[0] + 0.1 * param[1];
+                Obs {
+                    id: ObsId::new(i),
+                    param,
+                    value,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let surrogate = track!(KnnSurrogate::fit(&domains, &obss, 5))?;
+        assert!((surrogate.predict(&[0.5, 0.5]) - 5.05).abs() < 1.0);
+        let (mean, var) = surrogate.predict_with_variance(&[0.5, 0.5]);
+        assert_eq!(mean, surrogate.predict(&[0.5, 0.5]));
+        assert!(0.0 < var && var < 1.0, "{}", var);
+
+        let importances = track!(permutation_importance(&mut rng, &surrogate, 3))?;
+        assert!(importances[0] > 0.9, "{:?}", importances);
+
+        let indices = track!(sobol_indices(&mut rng, &surrogate, 500))?;
+        assert!(indices.first_order[0] > 0.8, "{:?}", indices);
+        assert!(indices.total[0] > 0.8, "{:?}", indices);
+        assert!(indices.total[1] < 0.2, "{:?}", indices);
+        Ok(())
+    }
+
+    #[test]
+    fn measured_surrogate_works() -> TestResult {
+        let domains = vec![track!(ContinuousDomain::new(0.0, 1.0))?];
+        let obs = |id, x: f64, mean, sem| -> Result<_> {
+            Ok(Obs {
+                id: ObsId::new(id),
+                param: vec![x],
+                value: track!(MeasuredValue::new(mean, sem, 10))?,
+            })
+        };
+        let obss = vec![
+            track!(obs(0, 0.4, 1.0, 0.01))?,
+            track!(obs(1, 0.6, 3.0, 10.0))?,
+            track!(obs(2, 0.9, 5.0, 0.01))?,
+        ];
+        let surrogate = track!(KnnSurrogate::fit_measured(&domains, &obss, 2))?;
+
+        // The noisy neighbor at 0.6 is mostly ignored.
+        let (mean, var) = surrogate.predict_with_variance(&[0.5]);
+        assert!((mean - 1.0).abs() < 0.2, "{}", mean);
+        assert!(var > 0.0);
+
+        // Without error bars, both neighbors are equally weighted.
+        let obss = obss
+            .into_iter()
+            .map(|o| o.map_value(MeasuredValue::mean))
+            .collect::<Vec<_>>();
+        let surrogate = track!(KnnSurrogate::fit(&domains, &obss, 2))?;
+        assert_eq!(surrogate.predict(&[0.5]), 2.0);
+        Ok(())
+    }
+
+    #[test]
+    fn partial_dependence_works() -> TestResult {
+        let domains = vec![
+            track!(ContinuousDomain::new(0.0, 1.0))?,
+            track!(ContinuousDomain::new(0.0, 1.0))?,
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+        let obss = (0..200)
+            .map(|i| {
+                let param = domains
+                    .iter()
+                    .map(|d| d.sample(&mut rng))
+                    .collect::<Vec<_>>();
+                let value = 10.0 * param[0] + 0.1 * param[1];
+                Obs {
+                    id: ObsId::new(i),
+                    param,
+                    value,
+                }
+            })
+            .collect::<Vec<_>>();
+        let surrogate = track!(KnnSurrogate::fit(&domains, &obss, 5))?;
+
+        let pd = track!(partial_dependence(&surrogate, 0, 5))?;
+        assert_eq!(pd.xs.len(), 5);
+        assert!(pd.values.windows(2).all(|w| w[0] < w[1]), "{:?}", pd);
+        assert!(pd.values[0] < 1.0 && pd.values[4] > 9.0, "{:?}", pd);
+        assert!(partial_dependence(&surrogate, 2, 5).is_err());
+
+        let pd = track!(partial_dependence_2d(&surrogate, (0, 1), 3))?;
+        assert_eq!((pd.xs.len(), pd.ys.len(), pd.values.len()), (3, 3, 3));
+        assert!(pd.values[2][0] > pd.values[0][2], "{:?}", pd);
+        assert!(partial_dependence_2d(&surrogate, (1, 1), 3).is_err());
+
+        let binned = track!(binned_averages(&domains, &obss, 0, 4))?;
+        assert_eq!(binned.xs, [0.125, 0.375, 0.625, 0.875]);
+        for (x, v) in binned.xs.iter().zip(binned.values.iter()) {
+            assert!((v - 10.0 * x).abs() < 1.0, "{:?}", binned);
+        }
+        let binned = track!(binned_averages_2d(&domains, &obss[..1], (0, 1), 2))?;
+        assert_eq!(
+            binned
+                .values
+                .iter()
+                .flatten()
+                .filter(|v| v.is_nan())
+                .count(),
+            3
+        );
+
+        let points = track!(slice(&obss, 1))?;
+        assert_eq!(points.len(), obss.len());
+        assert!(points.windows(2).all(|w| w[0].0 <= w[1].0));
+        Ok(())
+    }
+
+    #[test]
+    fn regrets_work() {
+        let values = [3.0, f64::NAN, 1.0, 2.0];
+        assert_eq!(incumbent_curve(&values), [3.0, 3.0, 1.0, 1.0]);
+        assert_eq!(simple_regrets(&values[2..], 0.5), [0.5, 0.5]);
+        assert_eq!(cumulative_regrets(&values[2..], 0.5), [0.5, 2.0]);
+
+        let secs = Duration::from_secs;
+        let curve = incumbent_curve_by_time(&[(secs(2), 1.0), (secs(1), 3.0), (secs(3), 2.0)]);
+        assert_eq!(curve, [(secs(1), 3.0), (secs(2), 1.0), (secs(3), 1.0)]);
+    }
+}
@@ -0,0 +1,287 @@
+//! Asynchronous ask-and-tell interface.
+//!
+//! `AsyncOptimizer` provides ask and tell operations returning futures,
+//! and `OffloadOptimizer` adapts an ordinary `Optimizer` to the trait by running its asks and tells on a thread pool.
+//! This keeps the executors of async runtimes (e.g., tokio) from being blocked by heavy asks.
+//!
+//! The futures in this module do not depend on any specific runtime.
+use crate::{Error, ErrorKind, IdGen, Obs, Optimizer, Result};
+use rand::Rng;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread::{self, JoinHandle};
+use trackable::error::ErrorKindExt;
+
+/// This trait provides asynchronous ask-and-tell interface for black-box optimization.
+///
+/// Since the returned futures may be executed on other threads,
+/// the random number generator and the identifier generator are passed by value
+/// (e.g., `StdRng::seed_from_u64(rng.gen())` and a `LeaseIdGenerator` sharing an `AtomicIdLeaser`).
+pub trait AsyncOptimizer {
+    /// The parameter to be optimized.
+    type Param;
+
+    /// The value obtained as a result of a parameter evaluation.
+    type Value;
+
+    /// Asks the next parameter to be evaluated.
+    fn ask<R, G>(&self, rng: R, idg: G) -> impl Future<Output = Result<Obs<Self::Param>>> + Send
+    where
+        R: Rng + Send + 'static,
+        G: IdGen + Send + 'static;
+
+    /// Tells the result of an observation to this optimizer.
+    fn tell(&self, obs: Obs<Self::Param, Self::Value>) -> impl Future<Output = Result<()>> + Send;
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed-size pool of threads used by `OffloadOptimizer`.
+///
+/// Cloned instances share the same threads, which are stopped when all the instances are dropped.
+#[derive(Debug, Clone)]
+pub struct ThreadPool {
+    inner: Arc<PoolInner>,
+}
+impl ThreadPool {
+    /// Makes a new `ThreadPool` instance having `threads` threads.
+    ///
+    /// # Errors
+    ///
+    /// If `threads` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    /// If failed to spawn a thread, an `ErrorKind::Other` error will be returned.
+    pub fn new(threads: usize) -> Result<Self> {
+        track_assert!(threads > 0, ErrorKind::InvalidInput);
+        let (tx, rx) = mpsc::channel::<Job>();
+        let rx = Arc::new(Mutex::new(rx));
+        let mut workers = Vec::with_capacity(threads);
+        for i in 0..threads {
+            let rx = Arc::clone(&rx);
+            let worker = thread::Builder::new()
+                .name(format!("yamakan-offload-{}", i))
+                .spawn(move || loop {
+                    let job = match rx.lock() {
+                        Ok(rx) => rx.recv(),
+                        Err(_) => break,
+                    };
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                })
+                .map_err(Error::from);
+            workers.push(track!(worker)?);
+        }
+        Ok(Self {
+            inner: Arc::new(PoolInner {
+                tx: Mutex::new(Some(tx)),
+                workers,
+            }),
+        })
+    }
+
+    /// Returns the number of the threads.
+    pub fn threads(&self) -> usize {
+        self.inner.workers.len()
+    }
+
+    fn spawn<T, F>(&self, f: F) -> Pending<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T> + Send + 'static,
+    {
+        let slot = Arc::new(Mutex::new(Slot {
+            result: None,
+            waker: None,
+        }));
+        let job_slot = Arc::clone(&slot);
+        let job: Job = Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+                Err(ErrorKind::Other
+                    .cause("An offloaded operation panicked")
+                    .into())
+            });
+            Slot::complete(&job_slot, result);
+        });
+
+        let sent = crate::lock(&self.inner.tx).and_then(|tx| match tx.as_ref() {
+            Some(tx) => tx
+                .send(job)
+                .map_err(|_| ErrorKind::Other.cause("The thread pool has stopped").into()),
+            None => Err(ErrorKind::Other.cause("The thread pool has stopped").into()),
+        });
+        if let Err(e) = sent {
+            Slot::complete(&slot, Err(track!(e)));
+        }
+        Pending { slot }
+    }
+}
+
+#[derive(Debug)]
+struct PoolInner {
+    tx: Mutex<Option<Sender<Job>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+impl Drop for PoolInner {
+    fn drop(&mut self) {
+        if let Ok(mut tx) = self.tx.lock() {
+            tx.take();
+        }
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Slot<T> {
+    result: Option<Result<T>>,
+    waker: Option<Waker>,
+}
+impl<T> Slot<T> {
+    fn complete(slot: &Mutex<Self>, result: Result<T>) {
+        if let Ok(mut slot) = slot.lock() {
+            slot.result = Some(result);
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// A future that will be resolved by a thread of `ThreadPool`.
+#[derive(Debug)]
+pub struct Pending<T> {
+    slot: Arc<Mutex<Slot<T>>>,
+}
+impl<T> Future for Pending<T> {
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut slot = match crate::lock(&self.slot) {
+            Ok(slot) => slot,
+            Err(e) => return Poll::Ready(Err(track!(e))),
+        };
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                slot.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// An `AsyncOptimizer` adapter that runs the asks and tells of an `Optimizer` on a `ThreadPool`.
+///
+/// The inner optimizer is guarded by a lock, so operations are serialized (but never block async executors).
+#[derive(Debug)]
+pub struct OffloadOptimizer<O> {
+    inner: Arc<Mutex<O>>,
+    pool: ThreadPool,
+}
+impl<O> OffloadOptimizer<O>
+where
+    O: Optimizer + Send + 'static,
+    O::Param: Send,
+    O::Value: Send,
+{
+    /// Makes a new `OffloadOptimizer` instance.
+    pub fn new(inner: O, pool: ThreadPool) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+            pool,
+        }
+    }
+
+    /// Returns a reference to the thread pool.
+    pub fn pool(&self) -> &ThreadPool {
+        &self.pool
+    }
+
+    /// Returns a reference to the underlying optimizer, which is shared with the running operations.
+    pub fn inner(&self) -> &Arc<Mutex<O>> {
+        &self.inner
+    }
+}
+impl<O> AsyncOptimizer for OffloadOptimizer<O>
+where
+    O: Optimizer + Send + 'static,
+    O::Param: Send,
+    O::Value: Send,
+{
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R, G>(&self, rng: R, idg: G) -> impl Future<Output = Result<Obs<Self::Param>>> + Send
+    where
+        R: Rng + Send + 'static,
+        G: IdGen + Send + 'static,
+    {
+        let inner = Arc::clone(&self.inner);
+        self.pool
+            .spawn(move || track!(track!(crate::lock(&inner))?.ask(rng, idg)))
+    }
+
+    fn tell(&self, obs: Obs<Self::Param, Self::Value>) -> impl Future<Output = Result<()>> + Send {
+        let inner = Arc::clone(&self.inner);
+        self.pool
+            .spawn(move || track!(track!(crate::lock(&inner))?.tell(obs)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::generators::{AtomicIdLeaser, LeaseIdGenerator};
+    use crate::optimizers::random::RandomOptimizer;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::task::Wake;
+    use trackable::result::TestResult;
+
+    struct ThreadWaker(thread::Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    #[test]
+    fn offload_works() -> TestResult {
+        let pool = track!(ThreadPool::new(2))?;
+        let inner = RandomOptimizer::new(track!(DiscreteDomain::new(10))?);
+        let opt = OffloadOptimizer::new(inner, pool);
+        let leaser = AtomicIdLeaser::new();
+
+        for i in 0..5 {
+            let idg = track!(LeaseIdGenerator::new(leaser.clone(), 1))?;
+            let obs = track!(block_on(opt.ask(StdRng::seed_from_u64(i), idg)))?;
+            assert_eq!(obs.id.get(), i);
+            track!(block_on(opt.tell(obs.map_value(|()| 1.0))))?;
+        }
+
+        let idg = track!(LeaseIdGenerator::new(leaser, 1))?;
+        let obs = track!(block_on(opt.ask(StdRng::seed_from_u64(0), idg)))?;
+        assert!(block_on(opt.tell(obs.map_param(|_| 10).map_value(|()| 1.0))).is_err());
+        assert!(ThreadPool::new(0).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+//! Runs optimizers on benchmark functions and reports the results.
+//!
+//! This binary is enabled by the `bench` feature:
+//!
+//! ```console
+//! $ cargo run --features bench --bin yamakan-bench -- --optimizers random,nelder-mead --problems sphere --seeds 5
+//! ```
+use ordered_float::OrderedFloat;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::env;
+use std::io::{self, Write};
+use std::time::Instant;
+use trackable::error::ErrorKindExt;
+use trackable::result::TopLevelResult;
+use trackable::{track, track_assert, track_panic};
+use yamakan::analysis::incumbent_curve;
+use yamakan::domains::{ContinuousDomain, VecDomain};
+use yamakan::generators::SerialIdGenerator;
+use yamakan::init::{LatinHypercube, Sobol};
+use yamakan::optimizers::map_value::MapValueOptimizer;
+use yamakan::optimizers::nelder_mead::NelderMeadOptimizer;
+use yamakan::optimizers::random::RandomOptimizer;
+use yamakan::optimizers::turbo::TurboOptimizer;
+use yamakan::{DynOptimizer, Error, ErrorKind, Optimizer, Result};
+
+const USAGE: &str = "Usage: yamakan-bench [OPTIONS]
+
+Options:
+  --optimizers <NAMES>  Comma separated optimizers [default: random,sobol,lhs,nelder-mead,turbo]
+  --problems <NAMES>    Comma separated problems [default: sphere,rosenbrock,rastrigin,ackley]
+  --dim <N>             Number of dimensions [default: 4]
+  --budget <N>          Number of evaluations per run [default: 100]
+  --seeds <N>           Number of runs (seeds 0..N) per pair of an optimizer and a problem [default: 5]
+  --format <FORMAT>     Output format, json or csv [default: json]
+  --help                Prints this message";
+
+const OPTIMIZERS: [&str; 5] = ["random", "sobol", "lhs", "nelder-mead", "turbo"];
+const PROBLEMS: [&str; 4] = ["sphere", "rosenbrock", "rastrigin", "ackley"];
+
+type BoxOptimizer = Box<dyn DynOptimizer<Param = Vec<f64>, Value = f64>>;
+type Objective = fn(&[f64]) -> f64;
+
+#[derive(Debug)]
+struct Config {
+    optimizers: Vec<String>,
+    problems: Vec<String>,
+    dim: usize,
+    budget: usize,
+    seeds: u64,
+    csv: bool,
+}
+impl Config {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>> {
+        let mut config = Self {
+            optimizers: OPTIMIZERS.iter().map(|s| s.to_string()).collect(),
+            problems: PROBLEMS.iter().map(|s| s.to_string()).collect(),
+            dim: 4,
+            budget: 100,
+            seeds: 5,
+            csv: false,
+        };
+        while let Some(arg) = args.next() {
+            if arg == "--help" {
+                return Ok(None);
+            }
+            let value = option_value(args.next(), &arg)?;
+            match arg.as_str() {
+                "--optimizers" => config.optimizers = split(&value),
+                "--problems" => config.problems = split(&value),
+                "--dim" => config.dim = track!(parse_number(&value))?,
+                "--budget" => config.budget = track!(parse_number(&value))?,
+                "--seeds" => config.seeds = track!(parse_number(&value))?,
+                "--format" => {
+                    track_assert!(
+                        value == "json" || value == "csv",
+                        ErrorKind::InvalidInput,
+                        "Unknown format: {}",
+                        value
+                    );
+                    config.csv = value == "csv";
+                }
+                _ => track_panic!(ErrorKind::InvalidInput, "Unknown option: {}", arg),
+            }
+        }
+        for name in &config.optimizers {
+            track_assert!(
+                OPTIMIZERS.contains(&name.as_str()),
+                ErrorKind::InvalidInput,
+                "Unknown optimizer: {}",
+                name
+            );
+        }
+        for name in &config.problems {
+            track_assert!(
+                PROBLEMS.contains(&name.as_str()),
+                ErrorKind::InvalidInput,
+                "Unknown problem: {}",
+                name
+            );
+        }
+        track_assert!(config.dim >= 2, ErrorKind::InvalidInput; config.dim);
+        Ok(Some(config))
+    }
+}
+
+fn split(s: &str) -> Vec<String> {
+    s.split(',').map(|x| x.trim().to_owned()).collect()
+}
+
+fn parse_number<T: std::str::FromStr>(s: &str) -> Result<T> {
+    s.parse().map_err(|_| {
+        ErrorKind::InvalidInput
+            .cause(format!("Not a number: {}", s))
+            .into()
+    })
+}
+
+fn option_value(value: Option<String>, option: &str) -> Result<String> {
+    value.ok_or_else(|| {
+        ErrorKind::InvalidInput
+            .cause(format!("Missing value of {}", option))
+            .into()
+    })
+}
+
+fn problem(name: &str, dim: usize) -> Result<(Vec<ContinuousDomain>, Objective)> {
+    let (low, high, f): (f64, f64, Objective) = match name {
+        "sphere" => (-5.0, 5.0, |x| x.iter().map(|x| x * x).sum()),
+        "rosenbrock" => (-5.0, 10.0, |x| {
+            x.windows(2)
+                .map(|w| 100.0 * (w[1] - w[0] * w[0]).powi(2) + (1.0 - w[0]).powi(2))
+                .sum()
+        }),
+        "rastrigin" => (-5.12, 5.12, |x| {
+            let tau = 2.0 * std::f64::consts::PI;
+            10.0 * x.len() as f64
+                + x.iter()
+                    .map(|x| x * x - 10.0 * (tau * x).cos())
+                    .sum::<f64>()
+        }),
+        "ackley" => (-32.768, 32.768, |x| {
+            let n = x.len() as f64;
+            let tau = 2.0 * std::f64::consts::PI;
+            let a = (x.iter().map(|x| x * x).sum::<f64>() / n).sqrt();
+            let b = x.iter().map(|x| (tau * x).cos()).sum::<f64>() / n;
+            -20.0 * (-0.2 * a).exp() - b.exp() + 20.0 + std::f64::consts::E
+        }),
+        _ => track_panic!(ErrorKind::InvalidInput, "Unknown problem: {}", name),
+    };
+    let domain = track!(ContinuousDomain::new(low, high))?;
+    Ok((vec![domain; dim], f))
+}
+
+fn optimizer(name: &str, domain: Vec<ContinuousDomain>, rng: &mut StdRng) -> Result<BoxOptimizer> {
+    Ok(match name {
+        "random" => Box::new(RandomOptimizer::<_, f64>::new(VecDomain(domain))),
+        "sobol" => Box::new(RandomOptimizer::<_, f64, _>::with_generator(
+            VecDomain(domain),
+            Sobol::new(),
+        )),
+        "lhs" => Box::new(RandomOptimizer::<_, f64, _>::with_generator(
+            VecDomain(domain),
+            track!(LatinHypercube::new(10))?,
+        )),
+        "nelder-mead" => Box::new(MapValueOptimizer::new(
+            track!(NelderMeadOptimizer::new(domain, rng))?,
+            OrderedFloat,
+        )),
+        "turbo" => Box::new(track!(TurboOptimizer::new(domain))?),
+        _ => track_panic!(ErrorKind::InvalidInput, "Unknown optimizer: {}", name),
+    })
+}
+
+#[derive(Debug)]
+struct Run {
+    optimizer: String,
+    problem: String,
+    seed: u64,
+    values: Vec<f64>,
+    elapsed_secs: f64,
+}
+
+fn run(config: &Config, optimizer_name: &str, problem_name: &str, seed: u64) -> Result<Run> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut idg = SerialIdGenerator::new();
+    let (domain, f) = track!(problem(problem_name, config.dim))?;
+    let mut opt = track!(optimizer(optimizer_name, domain, &mut rng))?;
+
+    let start = Instant::now();
+    let mut values = Vec::with_capacity(config.budget);
+    for _ in 0..config.budget {
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        let value = f(&obs.param);
+        values.push(value);
+        track!(opt.tell(obs.map_value(|()| value)))?;
+    }
+    Ok(Run {
+        optimizer: optimizer_name.to_owned(),
+        problem: problem_name.to_owned(),
+        seed,
+        values,
+        elapsed_secs: start.elapsed().as_secs_f64(),
+    })
+}
+
+fn write_json<W: Write>(mut writer: W, config: &Config, runs: &[Run]) -> Result<()> {
+    let runs = runs
+        .iter()
+        .map(|r| {
+            serde_json::json!({
+                "optimizer": r.optimizer,
+                "problem": r.problem,
+                "dim": config.dim,
+                "seed": r.seed,
+                "budget": config.budget,
+                "best": incumbent_curve(&r.values).last(),
+                "elapsed_secs": r.elapsed_secs,
+                "values": r.values,
+                "incumbent": incumbent_curve(&r.values),
+            })
+        })
+        .collect::<Vec<_>>();
+    track!(serde_json::to_writer_pretty(&mut writer, &runs)
+        .map_err(|e| Error::from(ErrorKind::Other.cause(e))))?;
+    track!(writeln!(writer).map_err(Error::from))?;
+    Ok(())
+}
+
+fn write_csv<W: Write>(mut writer: W, config: &Config, runs: &[Run]) -> Result<()> {
+    track!(writeln!(
+        writer,
+        "optimizer,problem,dim,seed,evaluation,value,incumbent"
+    )
+    .map_err(Error::from))?;
+    for r in runs {
+        for (i, (v, best)) in r.values.iter().zip(incumbent_curve(&r.values)).enumerate() {
+            track!(writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                r.optimizer, r.problem, config.dim, r.seed, i, v, best
+            )
+            .map_err(Error::from))?;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> TopLevelResult {
+    let config = match track!(Config::parse(env::args().skip(1)))? {
+        Some(config) => config,
+        None => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+    };
+
+    let mut runs = Vec::new();
+    for problem_name in &config.problems {
+        for optimizer_name in &config.optimizers {
+            for seed in 0..config.seeds {
+                runs.push(track!(run(&config, optimizer_name, problem_name, seed))?);
+            }
+        }
+    }
+
+    let stdout = io::stdout();
+    let stdout = stdout.lock();
+    if config.csv {
+        track!(write_csv(stdout, &config, &runs))?;
+    } else {
+        track!(write_json(stdout, &config, &runs))?;
+    }
+    Ok(())
+}
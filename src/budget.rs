@@ -1,6 +1,8 @@
 //! Budget for evaluating parameters.
+use crate::{ErrorKind, Result};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
 
 /// Budget.
 #[derive(Debug, Clone, Copy)]
@@ -40,4 +42,164 @@ impl Budget {
     pub fn is_consumed(&self) -> bool {
         self.consumption >= self.amount
     }
+
+    /// Adds the given quantity (e.g., elapsed seconds) to the consumption of this budget.
+    ///
+    /// # Errors
+    ///
+    /// If `unit` fails to convert `quantity`, the error is propagated.
+    pub fn consume<U: BudgetUnit>(&mut self, unit: &U, quantity: f64) -> Result<()> {
+        let consumption = track!(unit.to_budget(quantity))?;
+        self.consumption = self.consumption.saturating_add(consumption);
+        Ok(())
+    }
+}
+
+/// This trait allows interpreting budget amounts as real-world quantities (e.g., epochs, seconds or GPU-hours).
+pub trait BudgetUnit {
+    /// Converts the given quantity to a budget amount.
+    fn to_budget(&self, quantity: f64) -> Result<u64>;
+
+    /// Converts the given budget amount to a quantity.
+    fn to_quantity(&self, budget: u64) -> f64;
+}
+
+/// A `BudgetUnit` in which a budget amount of `1` corresponds to a fixed quantity.
+///
+/// Quantities are rounded up when converted to budget amounts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LinearUnit {
+    quantity_per_budget: f64,
+}
+impl LinearUnit {
+    /// Makes a new `LinearUnit` instance.
+    ///
+    /// For example, `LinearUnit::new(60.0)` means that a budget amount of `1` corresponds to 60 seconds.
+    ///
+    /// # Errors
+    ///
+    /// If `quantity_per_budget` is not a positive finite number, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(quantity_per_budget: f64) -> Result<Self> {
+        track_assert!(
+            quantity_per_budget.is_finite() && quantity_per_budget > 0.0,
+            ErrorKind::InvalidInput; quantity_per_budget
+        );
+        Ok(Self {
+            quantity_per_budget,
+        })
+    }
+
+    /// Returns the quantity corresponding to a budget amount of `1`.
+    pub fn quantity_per_budget(&self) -> f64 {
+        self.quantity_per_budget
+    }
+}
+impl BudgetUnit for LinearUnit {
+    fn to_budget(&self, quantity: f64) -> Result<u64> {
+        track_assert!(
+            quantity.is_finite() && quantity >= 0.0,
+            ErrorKind::InvalidInput; quantity
+        );
+        let budget = (quantity / self.quantity_per_budget).ceil();
+        track_assert!(budget <= u64::MAX as f64, ErrorKind::InvalidInput; quantity);
+        Ok(budget as u64)
+    }
+
+    fn to_quantity(&self, budget: u64) -> f64 {
+        budget as f64 * self.quantity_per_budget
+    }
+}
+
+/// Budget of a whole study (i.e., a sequence of evaluations).
+///
+/// A study budget limits the total number of evaluations and/or the elapsed (wall-clock) time.
+/// Budget-aware algorithms can adapt their behavior (e.g., annealing schedules) to `StudyBudget::remaining_fraction`.
+#[derive(Debug, Clone)]
+pub struct StudyBudget {
+    max_evaluations: Option<u64>,
+    max_duration: Option<Duration>,
+    evaluations: u64,
+    start: Instant,
+}
+impl StudyBudget {
+    /// Makes a new `StudyBudget` instance that has no limits.
+    ///
+    /// The elapsed time is measured from now.
+    pub fn new() -> Self {
+        Self {
+            max_evaluations: None,
+            max_duration: None,
+            evaluations: 0,
+            start: Instant::now(),
+        }
+    }
+
+    /// Limits the total number of evaluations.
+    pub fn max_evaluations(mut self, n: u64) -> Self {
+        self.max_evaluations = Some(n);
+        self
+    }
+
+    /// Limits the elapsed time.
+    pub fn max_duration(mut self, duration: Duration) -> Self {
+        self.max_duration = Some(duration);
+        self
+    }
+
+    /// Records that an evaluation has finished.
+    pub fn record_evaluation(&mut self) {
+        self.evaluations += 1;
+    }
+
+    /// Returns the number of the evaluations so far.
+    pub fn evaluations(&self) -> u64 {
+        self.evaluations
+    }
+
+    /// Returns the elapsed time since the start of the study.
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    /// Returns the number of the remaining evaluations (or `None` if unlimited).
+    pub fn remaining_evaluations(&self) -> Option<u64> {
+        self.max_evaluations
+            .map(|n| n.saturating_sub(self.evaluations))
+    }
+
+    /// Returns the remaining time (or `None` if unlimited).
+    pub fn remaining_time(&self) -> Option<Duration> {
+        self.max_duration.map(|d| d.saturating_sub(self.elapsed()))
+    }
+
+    /// Returns the remaining fraction of the budget in the range `[0.0, 1.0]`.
+    ///
+    /// If both limits are set, the smaller fraction is returned. If no limits are set, `1.0` is returned.
+    pub fn remaining_fraction(&self) -> f64 {
+        let mut fraction = 1.0f64;
+        if let (Some(max), Some(remaining)) = (self.max_evaluations, self.remaining_evaluations()) {
+            if max == 0 {
+                return 0.0;
+            }
+            fraction = fraction.min(remaining as f64 / max as f64);
+        }
+        if let (Some(max), Some(remaining)) = (self.max_duration, self.remaining_time()) {
+            if max.as_secs_f64() == 0.0 {
+                return 0.0;
+            }
+            fraction = fraction.min(remaining.as_secs_f64() / max.as_secs_f64());
+        }
+        fraction
+    }
+
+    /// Returns `true` if the budget has been exhausted, otherwise `false`.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_evaluations() == Some(0) || self.remaining_time() == Some(Duration::ZERO)
+    }
+}
+impl Default for StudyBudget {
+    fn default() -> Self {
+        Self::new()
+    }
 }
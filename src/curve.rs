@@ -0,0 +1,274 @@
+//! Learning curves and their extrapolation.
+//!
+//! A `Curve` holds the intermediate values of an evaluation (e.g., validation losses per epoch),
+//! so that multi-fidelity optimizers and pruners can reason about the whole curve instead of the last value.
+//!
+//! `CurvePruner` stops unpromising evaluations early
+//! by extrapolating their curves with a power law (`y = c + a * step^(-b)`).
+//!
+//! # References
+//!
+//! - [Speeding up Automatic Hyperparameter Optimization of Deep Neural Networks by Extrapolation of Learning Curves](https://www.ijcai.org/Proceedings/15/Papers/487.pdf)
+use crate::{ErrorKind, Result};
+use ordered_float::OrderedFloat;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const FIT_GRID_SIZE: usize = 64;
+
+/// A learning curve (i.e., a sequence of `(step, value)` pairs).
+///
+/// Steps are positive and strictly increasing. Lower values are better.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Curve {
+    points: Vec<(u64, f64)>,
+}
+impl Curve {
+    /// Makes a new empty `Curve` instance.
+    pub const fn new() -> Self {
+        Self { points: Vec::new() }
+    }
+
+    /// Appends a point to the curve.
+    ///
+    /// # Errors
+    ///
+    /// If `step` is not greater than the last step (or is `0`) or `value` is not finite,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn push(&mut self, step: u64, value: f64) -> Result<()> {
+        let last = self.last().map_or(0, |(s, _)| s);
+        track_assert!(last < step, ErrorKind::InvalidInput; last, step);
+        track_assert!(value.is_finite(), ErrorKind::InvalidInput; value);
+        self.points.push((step, value));
+        Ok(())
+    }
+
+    /// Returns the points of the curve.
+    pub fn points(&self) -> &[(u64, f64)] {
+        &self.points
+    }
+
+    /// Returns the number of the points.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns `true` if the curve has no points, otherwise `false`.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the last point.
+    pub fn last(&self) -> Option<(u64, f64)> {
+        self.points.last().copied()
+    }
+
+    /// Returns the point having the best (i.e., lowest) value.
+    pub fn best(&self) -> Option<(u64, f64)> {
+        self.points
+            .iter()
+            .copied()
+            .min_by_key(|&(_, v)| OrderedFloat(v))
+    }
+
+    /// Fits a power law to the curve.
+    ///
+    /// The offset `c` is searched over a grid below the lowest value,
+    /// and `a` and `b` are determined by the least squares regression in the log-log space.
+    ///
+    /// # Errors
+    ///
+    /// If the curve has less than three points, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn fit_power_law(&self) -> Result<PowerLaw> {
+        track_assert!(self.points.len() >= 3, ErrorKind::InvalidInput; self.points.len());
+
+        let min = self
+            .points
+            .iter()
+            .map(|p| p.1)
+            .fold(f64::INFINITY, f64::min);
+        let max = self
+            .points
+            .iter()
+            .map(|p| p.1)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let range = max - min;
+        if range == 0.0 {
+            return Ok(PowerLaw {
+                a: 0.0,
+                b: 0.0,
+                c: min,
+            });
+        }
+
+        let mut best: Option<(f64, PowerLaw)> = None;
+        for i in 0..FIT_GRID_SIZE {
+            let gap = range * 1e-3 * (4e3f64).powf(i as f64 / (FIT_GRID_SIZE - 1) as f64);
+            let law = self.fit_with_offset(min - gap);
+            let error = self
+                .points
+                .iter()
+                .map(|&(s, v)| (law.predict(s) - v).powi(2))
+                .sum::<f64>();
+            if best.as_ref().is_none_or(|(e, _)| error < *e) {
+                best = Some((error, law));
+            }
+        }
+        let (_, law) = track_assert_some!(best, ErrorKind::Bug);
+        Ok(law)
+    }
+
+    fn fit_with_offset(&self, c: f64) -> PowerLaw {
+        let n = self.points.len() as f64;
+        let xs = self.points.iter().map(|&(s, _)| (s as f64).ln());
+        let ys = self.points.iter().map(|&(_, v)| (v - c).ln());
+        let x_mean = xs.clone().sum::<f64>() / n;
+        let y_mean = ys.clone().sum::<f64>() / n;
+        let (mut sxy, mut sxx) = (0.0, 0.0);
+        for (x, y) in xs.zip(ys) {
+            sxy += (x - x_mean) * (y - y_mean);
+            sxx += (x - x_mean).powi(2);
+        }
+        let slope = sxy / sxx;
+        PowerLaw {
+            a: (y_mean - slope * x_mean).exp(),
+            b: -slope,
+            c,
+        }
+    }
+}
+
+/// A power law `y = c + a * step^(-b)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PowerLaw {
+    /// The scale.
+    pub a: f64,
+
+    /// The decay rate.
+    pub b: f64,
+
+    /// The asymptote.
+    pub c: f64,
+}
+impl PowerLaw {
+    /// Returns the predicted value at the given step.
+    pub fn predict(&self, step: u64) -> f64 {
+        self.c + self.a * (step as f64).powf(-self.b)
+    }
+}
+
+/// A pruner that stops evaluations whose extrapolated curves are unlikely to beat the best final value.
+#[derive(Debug, Clone)]
+pub struct CurvePruner {
+    target_step: u64,
+    min_points: usize,
+    margin: f64,
+    best: Option<f64>,
+}
+impl CurvePruner {
+    /// Makes a new `CurvePruner` instance.
+    ///
+    /// `target_step` is the final step of evaluations (e.g., the maximum number of epochs).
+    ///
+    /// By default, curves having less than `3` points are never pruned and the margin is `0.0`.
+    ///
+    /// # Errors
+    ///
+    /// If `target_step` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(target_step: u64) -> Result<Self> {
+        track_assert!(target_step > 0, ErrorKind::InvalidInput);
+        Ok(Self {
+            target_step,
+            min_points: 3,
+            margin: 0.0,
+            best: None,
+        })
+    }
+
+    /// Sets the minimum number of points required to prune a curve.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is less than `3`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn min_points(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n >= 3, ErrorKind::InvalidInput; n);
+        self.min_points = n;
+        Ok(self)
+    }
+
+    /// Sets the margin of pruning.
+    ///
+    /// A curve is pruned only if its extrapolated final value exceeds the best final value by more than `margin`.
+    ///
+    /// # Errors
+    ///
+    /// If `margin` is not a non-negative finite number, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn margin(&mut self, margin: f64) -> Result<&mut Self> {
+        track_assert!(margin.is_finite() && margin >= 0.0, ErrorKind::InvalidInput; margin);
+        self.margin = margin;
+        Ok(self)
+    }
+
+    /// Returns the best final value reported so far.
+    pub fn best(&self) -> Option<f64> {
+        self.best
+    }
+
+    /// Reports the final value of a completed evaluation.
+    pub fn report_final(&mut self, value: f64) {
+        if self.best.is_none_or(|best| value < best) {
+            self.best = Some(value);
+        }
+    }
+
+    /// Returns `true` if the evaluation having the given curve should be stopped, otherwise `false`.
+    ///
+    /// Curves are never pruned until a final value is reported.
+    pub fn should_prune(&self, curve: &Curve) -> Result<bool> {
+        let best = match self.best {
+            Some(best) => best,
+            None => return Ok(false),
+        };
+        if curve.len() < self.min_points || curve.last().is_none_or(|(s, _)| s >= self.target_step)
+        {
+            return Ok(false);
+        }
+        let law = track!(curve.fit_power_law())?;
+        Ok(law.predict(self.target_step) > best + self.margin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn curve_pruner_works() -> TestResult {
+        let mut good = Curve::new();
+        let mut bad = Curve::new();
+        for step in 1..=10 {
+            track!(good.push(step, 0.1 + 2.0 * (step as f64).powf(-0.5)))?;
+            track!(bad.push(step, 0.5 + 2.0 * (step as f64).powf(-0.5)))?;
+        }
+        assert!(good.push(10, 0.0).is_err());
+        assert_eq!(good.best().map(|p| p.0), Some(10));
+
+        let law = track!(good.fit_power_law())?;
+        let expected = 0.1 + 2.0 * 100f64.powf(-0.5);
+        assert!((law.predict(100) - expected).abs() < 0.05, "{:?}", law);
+
+        let mut pruner = track!(CurvePruner::new(100))?;
+        assert!(!track!(pruner.should_prune(&bad))?);
+
+        pruner.report_final(0.35);
+        assert!(track!(pruner.should_prune(&bad))?);
+        assert!(!track!(pruner.should_prune(&good))?);
+
+        track!(pruner.margin(1.0))?;
+        assert!(!track!(pruner.should_prune(&bad))?);
+        Ok(())
+    }
+}
@@ -1,8 +1,14 @@
 //! Parameter search domains.
-use crate::{Domain, ErrorKind, Result};
+use crate::{Domain, ErrorContext, ErrorKind, Result};
 use ordered_float::NotNan;
 use rand::distributions::Distribution;
+use rand::seq::SliceRandom;
 use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp;
+#[cfg(feature = "serde")]
+use std::convert::TryFrom;
 use std::num::NonZeroU64;
 
 /// Vector domain.
@@ -11,6 +17,10 @@ pub struct VecDomain<T>(pub Vec<T>);
 
 impl<T: Domain> Domain for VecDomain<T> {
     type Point = Vec<T::Point>;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        self.0.len() == point.len() && self.0.iter().zip(point.iter()).all(|(d, p)| d.contains(p))
+    }
 }
 
 impl<T> Distribution<Vec<T::Point>> for VecDomain<T>
@@ -22,6 +32,227 @@ where
     }
 }
 
+impl<T> VecDomain<T>
+where
+    T: GridDomain,
+    T::Point: Clone,
+{
+    /// Returns an iterator over the cartesian product of the grids of the elements.
+    ///
+    /// Unlike `grid`, the points are generated lazily, so this is preferable for large grids.
+    pub fn grid_iter(&self, resolution: usize) -> CartesianProduct<T::Point> {
+        CartesianProduct::new(self.0.iter().map(|d| d.grid(resolution)).collect())
+    }
+}
+
+/// This trait allows enumerating the points of a domain on a grid.
+pub trait GridDomain: Domain {
+    /// Returns the points of this domain on a grid having (at most) `resolution` points per dimension.
+    ///
+    /// Finite domains return all their points if they have no more than `resolution` points,
+    /// otherwise `resolution` evenly spaced points are returned.
+    fn grid(&self, resolution: usize) -> Vec<Self::Point>;
+}
+impl<T> GridDomain for VecDomain<T>
+where
+    T: GridDomain,
+    T::Point: Clone,
+{
+    fn grid(&self, resolution: usize) -> Vec<Self::Point> {
+        self.grid_iter(resolution).collect()
+    }
+}
+
+/// An iterator over the cartesian product of axes.
+///
+/// The last axis changes fastest.
+#[derive(Debug, Clone)]
+pub struct CartesianProduct<T> {
+    axes: Vec<Vec<T>>,
+    indices: Option<Vec<usize>>,
+}
+impl<T: Clone> CartesianProduct<T> {
+    /// Makes a new `CartesianProduct` instance.
+    ///
+    /// If any of `axes` is empty, the resulting iterator yields nothing.
+    pub fn new(axes: Vec<Vec<T>>) -> Self {
+        let indices = if axes.iter().any(|a| a.is_empty()) {
+            None
+        } else {
+            Some(vec![0; axes.len()])
+        };
+        Self { axes, indices }
+    }
+
+    /// Returns the total number of the points in the product.
+    ///
+    /// `None` is returned if the number overflows `usize`.
+    pub fn total(&self) -> Option<usize> {
+        self.axes
+            .iter()
+            .try_fold(1usize, |acc, a| acc.checked_mul(a.len()))
+    }
+}
+impl<T: Clone> Iterator for CartesianProduct<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let indices = self.indices.as_mut()?;
+        let point = self
+            .axes
+            .iter()
+            .zip(indices.iter())
+            .map(|(a, &i)| a[i].clone())
+            .collect();
+
+        let mut carried = true;
+        for (a, i) in self.axes.iter().zip(indices.iter_mut()).rev() {
+            *i += 1;
+            if *i < a.len() {
+                carried = false;
+                break;
+            }
+            *i = 0;
+        }
+        if carried {
+            self.indices = None;
+        }
+        Some(point)
+    }
+}
+
+fn grid_indices(len: u64, resolution: usize) -> Vec<u64> {
+    if len <= resolution as u64 {
+        (0..len).collect()
+    } else if resolution <= 1 {
+        (0..resolution).map(|_| (len - 1) / 2).collect()
+    } else {
+        let last = (len - 1) as f64;
+        let n = (resolution - 1) as f64;
+        (0..resolution)
+            .map(|i| (last * i as f64 / n).round() as u64)
+            .collect()
+    }
+}
+
+/// A domain having a name (and optionally a unit) for reporting.
+///
+/// `VecDomain<NamedDomain<D>>` can reference its elements by name
+/// (e.g., as the column names of `CsvWriter` or in error contexts).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct NamedDomain<D> {
+    name: String,
+    unit: Option<String>,
+    domain: D,
+}
+impl<D: Domain> NamedDomain<D> {
+    /// Makes a new `NamedDomain` instance.
+    pub fn new(name: &str, domain: D) -> Self {
+        Self {
+            name: name.to_owned(),
+            unit: None,
+            domain,
+        }
+    }
+
+    /// Sets the unit of the domain.
+    pub fn with_unit(mut self, unit: &str) -> Self {
+        self.unit = Some(unit.to_owned());
+        self
+    }
+
+    /// Returns the name of the domain.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the unit of the domain.
+    pub fn unit(&self) -> Option<&str> {
+        self.unit.as_deref()
+    }
+
+    /// Returns the label of the domain for reporting (e.g., `"timeout [s]"`).
+    pub fn label(&self) -> String {
+        match &self.unit {
+            Some(unit) => format!("{} [{}]", self.name, unit),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Returns a reference to the underlying domain.
+    pub fn inner(&self) -> &D {
+        &self.domain
+    }
+
+    /// Consumes the `NamedDomain`, returning the underlying domain.
+    pub fn into_inner(self) -> D {
+        self.domain
+    }
+}
+impl<D: Domain> Domain for NamedDomain<D> {
+    type Point = D::Point;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        self.domain.contains(point)
+    }
+}
+impl<D, T> Distribution<T> for NamedDomain<D>
+where
+    D: Domain<Point = T> + Distribution<T>,
+{
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> T {
+        self.domain.sample(rng)
+    }
+}
+
+impl<D: Domain> VecDomain<NamedDomain<D>> {
+    /// Returns the names of the elements.
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|d| d.name.clone()).collect()
+    }
+
+    /// Returns the labels of the elements.
+    pub fn labels(&self) -> Vec<String> {
+        self.0.iter().map(|d| d.label()).collect()
+    }
+
+    /// Returns the index of the element having the given name.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|d| d.name == name)
+    }
+
+    /// Associates the given per-element values (e.g., the result of `permutation_importance`) with the element names.
+    ///
+    /// # Errors
+    ///
+    /// If the number of `values` differs from the elements, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn zip_names<T>(&self, values: Vec<T>) -> Result<Vec<(&str, T)>> {
+        track_assert_eq!(values.len(), self.0.len(), ErrorKind::InvalidInput);
+        Ok(self.0.iter().map(|d| d.name()).zip(values).collect())
+    }
+
+    /// Checks whether the given point is contained in this domain.
+    ///
+    /// # Errors
+    ///
+    /// If the point is not contained, an `ErrorKind::InvalidInput` error will be returned.
+    /// Its context has the index and the name of the offending element.
+    pub fn check(&self, point: &[D::Point]) -> Result<()> {
+        track_assert_eq!(point.len(), self.0.len(), ErrorKind::InvalidInput);
+        for (i, (d, p)) in self.0.iter().zip(point.iter()).enumerate() {
+            if !d.contains(p) {
+                track_panic!(
+                    ErrorKind::InvalidInput,
+                    ErrorContext::new("Out of the domain parameter")
+                        .param_index(i)
+                        .param_name(d.name())
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Categorical domain.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CategoricalDomain {
@@ -45,6 +276,21 @@ impl CategoricalDomain {
 }
 impl Domain for CategoricalDomain {
     type Point = u64;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        *point < self.cardinality.get()
+    }
+}
+impl CategoricalDomain {
+    /// Returns an iterator over all the points of this domain.
+    pub fn iter_points(&self) -> impl Iterator<Item = u64> {
+        0..self.cardinality.get()
+    }
+}
+impl GridDomain for CategoricalDomain {
+    fn grid(&self, resolution: usize) -> Vec<u64> {
+        grid_indices(self.cardinality.get(), resolution)
+    }
 }
 impl From<NonZeroU64> for CategoricalDomain {
     fn from(cardinality: NonZeroU64) -> Self {
@@ -80,6 +326,21 @@ impl DiscreteDomain {
 }
 impl Domain for DiscreteDomain {
     type Point = u64;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        *point < self.size.get()
+    }
+}
+impl DiscreteDomain {
+    /// Returns an iterator over all the points of this domain.
+    pub fn iter_points(&self) -> impl Iterator<Item = u64> {
+        0..self.size.get()
+    }
+}
+impl GridDomain for DiscreteDomain {
+    fn grid(&self, resolution: usize) -> Vec<u64> {
+        grid_indices(self.size.get(), resolution)
+    }
 }
 impl From<NonZeroU64> for DiscreteDomain {
     fn from(size: NonZeroU64) -> Self {
@@ -92,16 +353,34 @@ impl Distribution<u64> for DiscreteDomain {
     }
 }
 
+/// Inclusivity of a bound of `ContinuousDomain`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Bound {
+    /// The bound value is contained in the domain.
+    Inclusive,
+
+    /// The bound value is not contained in the domain.
+    Exclusive,
+}
+
 /// Continuous numerical domain.
+///
+/// If the `serde` feature is enabled, deserialized domains are validated in the same way as `new` and `with_bounds`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawContinuousDomain"))]
 pub struct ContinuousDomain {
     low: NotNan<f64>,
     high: NotNan<f64>,
+    low_bound: Bound,
+    high_bound: Bound,
 }
 impl ContinuousDomain {
     /// Makes a new `ContinuousDomain` instance.
     ///
     /// The returned instance represents a half-closed interval, i.e., `[low..high)`.
+    /// Other bound semantics can be specified by `with_bounds`.
     ///
     /// # Errors
     ///
@@ -111,19 +390,47 @@ impl ContinuousDomain {
     /// - `low >= high`
     /// - `high - low` is not a finite number
     pub fn new(low: f64, high: f64) -> Result<Self> {
-        track_assert!(low.is_finite(), ErrorKind::InvalidInput; low, high);
-        track_assert!(high.is_finite(), ErrorKind::InvalidInput; low, high);
-        track_assert!(low < high, ErrorKind::InvalidInput; low, high);
-        track_assert!((high - low).is_finite(), ErrorKind::InvalidInput; low, high);
+        if !(low.is_finite() && high.is_finite() && low < high && (high - low).is_finite()) {
+            track_panic!(
+                ErrorKind::InvalidInput,
+                ErrorContext::new("Invalid domain bounds").bounds(low, high)
+            );
+        }
 
         Ok(unsafe {
             Self {
-                low: NotNan::unchecked_new(low),
-                high: NotNan::unchecked_new(high),
+                low: NotNan::new_unchecked(low),
+                high: NotNan::new_unchecked(high),
+                low_bound: Bound::Inclusive,
+                high_bound: Bound::Exclusive,
             }
         })
     }
 
+    /// Makes a new domain which has the same bound values as this one and the given inclusivity.
+    ///
+    /// For example, `with_bounds(Bound::Inclusive, Bound::Inclusive)` makes a closed interval `[low..=high]`.
+    ///
+    /// # Errors
+    ///
+    /// If the resulting domain contains no floating point numbers
+    /// (i.e., both bounds are exclusive and there are no numbers between them),
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn with_bounds(&self, low: Bound, high: Bound) -> Result<Self> {
+        let domain = Self {
+            low_bound: low,
+            high_bound: high,
+            ..self.clone()
+        };
+        if domain.lowest() > domain.highest() {
+            track_panic!(
+                ErrorKind::InvalidInput,
+                ErrorContext::new("Empty domain").bounds(domain.low(), domain.high())
+            );
+        }
+        Ok(domain)
+    }
+
     /// Returns the lower bound of this domain.
     pub fn low(&self) -> f64 {
         self.low.into_inner()
@@ -134,16 +441,823 @@ impl ContinuousDomain {
         self.high.into_inner()
     }
 
+    /// Returns the inclusivity of the lower bound.
+    pub fn low_bound(&self) -> Bound {
+        self.low_bound
+    }
+
+    /// Returns the inclusivity of the upper bound.
+    pub fn high_bound(&self) -> Bound {
+        self.high_bound
+    }
+
+    /// Returns the smallest number contained in this domain.
+    pub fn lowest(&self) -> f64 {
+        match self.low_bound {
+            Bound::Inclusive => self.low(),
+            Bound::Exclusive => self.low().next_up(),
+        }
+    }
+
+    /// Returns the largest number contained in this domain.
+    pub fn highest(&self) -> f64 {
+        match self.high_bound {
+            Bound::Inclusive => self.high(),
+            Bound::Exclusive => self.high().next_down(),
+        }
+    }
+
     /// Returns the size of this domain.
     pub fn size(&self) -> f64 {
         self.high() - self.low()
     }
+
+    /// Returns the center of this domain.
+    pub fn center(&self) -> f64 {
+        self.low() + self.size() / 2.0
+    }
+
+    /// Projects the given value into this domain.
+    ///
+    /// If `x` is greater than the largest number in this domain, the number is returned
+    /// (e.g., the largest floating point number less than the upper bound if the bound is exclusive).
+    /// If `x` is less than the smallest number in this domain or NaN, the number is returned.
+    pub fn clamp(&self, x: f64) -> f64 {
+        let lowest = self.lowest();
+        if x.is_nan() || x < lowest {
+            lowest
+        } else if x > self.highest() {
+            lowest.max(self.highest())
+        } else {
+            x
+        }
+    }
+
+    /// Returns `n` evenly spaced points starting from the lower bound of this domain.
+    ///
+    /// The `i`-th point is `low + size * i / n`, so the upper bound is never included.
+    pub fn linspace(&self, n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| self.clamp(self.low() + self.size() * i as f64 / n as f64))
+            .collect()
+    }
+
+    /// Makes a new domain which has the same center as this one and the size multiplied by `factor`.
+    ///
+    /// # Errors
+    ///
+    /// If `factor` is not a positive finite number or the resulting bounds are not finite,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn expand(&self, factor: f64) -> Result<Self> {
+        track_assert!(factor.is_finite() && factor > 0.0, ErrorKind::InvalidInput; factor);
+        let half = self.size() * factor / 2.0;
+        let center = self.center();
+        let domain = track!(Self::new(center - half, center + half))?;
+        track!(domain.with_bounds(self.low_bound, self.high_bound))
+    }
 }
+#[cfg(feature = "serde")]
+impl TryFrom<RawContinuousDomain> for ContinuousDomain {
+    type Error = crate::Error;
+
+    fn try_from(raw: RawContinuousDomain) -> Result<Self> {
+        let domain = track!(Self::new(raw.low, raw.high))?;
+        track!(domain.with_bounds(raw.low_bound, raw.high_bound))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawContinuousDomain {
+    low: f64,
+    high: f64,
+    low_bound: Bound,
+    high_bound: Bound,
+}
+
 impl Domain for ContinuousDomain {
     type Point = f64;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        self.lowest() <= *point && *point <= self.highest()
+    }
+}
+impl GridDomain for ContinuousDomain {
+    /// Returns `resolution` evenly spaced points from the smallest number in this domain to the largest one.
+    ///
+    /// If `resolution` is `1`, the center of this domain is returned.
+    fn grid(&self, resolution: usize) -> Vec<f64> {
+        if resolution == 1 {
+            return vec![self.clamp(self.center())];
+        }
+        let (lowest, highest) = (self.lowest(), self.highest());
+        let n = resolution.saturating_sub(1) as f64;
+        (0..resolution)
+            .map(|i| self.clamp(lowest + (highest - lowest) * i as f64 / n))
+            .collect()
+    }
 }
 impl Distribution<f64> for ContinuousDomain {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
-        rng.gen_range(self.low()..self.high())
+        if (self.low_bound, self.high_bound) == (Bound::Inclusive, Bound::Exclusive) {
+            rng.gen_range(self.low()..self.high())
+        } else {
+            rng.gen_range(self.lowest()..=self.highest())
+        }
+    }
+}
+
+/// Prior distribution of `UnboundedDomain` used for sampling.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Prior {
+    /// Normal distribution.
+    Normal {
+        /// Mean of the distribution.
+        mean: f64,
+
+        /// Standard deviation of the distribution.
+        std_dev: f64,
+    },
+
+    /// Cauchy distribution, which has heavier tails than the normal distribution.
+    Cauchy {
+        /// Location (median) of the distribution.
+        location: f64,
+
+        /// Scale (half width at half maximum) of the distribution.
+        scale: f64,
+    },
+}
+
+/// Continuous numerical domain without bounds.
+///
+/// All finite numbers are contained in this domain.
+/// Since there is no uniform distribution over the domain, a prior distribution is required for sampling.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnboundedDomain {
+    prior: Prior,
+}
+impl UnboundedDomain {
+    /// Makes a new `UnboundedDomain` instance.
+    ///
+    /// # Errors
+    ///
+    /// If the location of `prior` is not finite or the scale of it is not a positive finite number,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(prior: Prior) -> Result<Self> {
+        let (location, scale) = match prior {
+            Prior::Normal { mean, std_dev } => (mean, std_dev),
+            Prior::Cauchy { location, scale } => (location, scale),
+        };
+        track_assert!(location.is_finite(), ErrorKind::InvalidInput; location);
+        track_assert!(scale.is_finite() && scale > 0.0, ErrorKind::InvalidInput; scale);
+        Ok(Self { prior })
+    }
+
+    /// Returns the prior distribution of this domain.
+    pub fn prior(&self) -> Prior {
+        self.prior
+    }
+}
+impl Domain for UnboundedDomain {
+    type Point = f64;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        point.is_finite()
+    }
+}
+impl Distribution<f64> for UnboundedDomain {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        loop {
+            // `1.0 - gen()` is in `(0.0, 1.0]`, which keeps `ln` and `tan` finite.
+            let u = 1.0 - rng.gen::<f64>();
+            let x = match self.prior {
+                Prior::Normal { mean, std_dev } => {
+                    let v = rng.gen::<f64>();
+                    let z = (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos();
+                    mean + std_dev * z
+                }
+                Prior::Cauchy { location, scale } => {
+                    location + scale * (std::f64::consts::PI * (u - 0.5)).tan()
+                }
+            };
+            if x.is_finite() {
+                return x;
+            }
+        }
+    }
+}
+
+/// Numerical domain whose points are `low, low + step, low + 2 * step, ...` (up to `high`).
+///
+/// This is useful for quantized parameters such as batch sizes in multiples of 8 or thresholds in 0.05 steps.
+/// The `i`-th point is always computed as `low + step * i`, so points compare exactly equal to the ones returned by `point`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SteppedDomain {
+    low: f64,
+    step: f64,
+    len: NonZeroU64,
+}
+impl SteppedDomain {
+    // Tolerance for the rounding errors of `(high - low) / step` (e.g., `(1.0 - 0.0) / 0.05`).
+    const TOLERANCE: f64 = 1e-9;
+
+    /// Makes a new `SteppedDomain` instance.
+    ///
+    /// `high` is included in the domain if `high - low` is a multiple of `step`.
+    ///
+    /// # Errors
+    ///
+    /// If one of the following conditions is satisfied, this function returns an `ErrorKind::InvalidInput` error:
+    ///
+    /// - `low` or `high` is not a finite number
+    /// - `low > high`
+    /// - `step` is not a positive finite number
+    /// - the number of the points does not fit in `u64`
+    pub fn new(low: f64, high: f64, step: f64) -> Result<Self> {
+        if !(low.is_finite() && high.is_finite() && low <= high && (high - low).is_finite()) {
+            track_panic!(
+                ErrorKind::InvalidInput,
+                ErrorContext::new("Invalid domain bounds").bounds(low, high)
+            );
+        }
+        track_assert!(step.is_finite() && step > 0.0, ErrorKind::InvalidInput; step);
+
+        let n = ((high - low) / step + Self::TOLERANCE).floor();
+        track_assert!(n < u64::MAX as f64, ErrorKind::InvalidInput; low, high, step);
+        let len = track_assert_some!(NonZeroU64::new(n as u64 + 1), ErrorKind::Bug);
+        Ok(Self { low, step, len })
+    }
+
+    /// Returns the smallest point of this domain.
+    pub fn low(&self) -> f64 {
+        self.low
+    }
+
+    /// Returns the largest point of this domain.
+    pub fn high(&self) -> f64 {
+        self.point(self.len.get() - 1)
+    }
+
+    /// Returns the step between adjacent points.
+    pub fn step(&self) -> f64 {
+        self.step
+    }
+
+    /// Returns the number of the points in this domain.
+    pub const fn len(&self) -> NonZeroU64 {
+        self.len
+    }
+
+    /// Returns the `i`-th point of this domain.
+    ///
+    /// `i` is clamped to the range of this domain.
+    pub fn point(&self, i: u64) -> f64 {
+        self.low + self.step * i.min(self.len.get() - 1) as f64
+    }
+
+    /// Returns the index of the given point if it is contained in this domain.
+    pub fn index(&self, x: f64) -> Option<u64> {
+        let i = self.nearest_index(x);
+        if self.point(i) == x {
+            Some(i)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the point of this domain nearest to `x`.
+    ///
+    /// If `x` is NaN, the lowest point is returned.
+    pub fn snap(&self, x: f64) -> f64 {
+        self.point(self.nearest_index(x))
+    }
+
+    fn nearest_index(&self, x: f64) -> u64 {
+        let i = ((x - self.low) / self.step).round();
+        if i.is_nan() || i < 0.0 {
+            0
+        } else {
+            (i as u64).min(self.len.get() - 1)
+        }
+    }
+}
+impl Domain for SteppedDomain {
+    type Point = f64;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        self.index(*point).is_some()
+    }
+}
+impl SteppedDomain {
+    /// Returns an iterator over all the points of this domain.
+    pub fn iter_points(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..self.len.get()).map(move |i| self.point(i))
+    }
+}
+impl GridDomain for SteppedDomain {
+    fn grid(&self, resolution: usize) -> Vec<f64> {
+        grid_indices(self.len.get(), resolution)
+            .into_iter()
+            .map(|i| self.point(i))
+            .collect()
+    }
+}
+impl Distribution<f64> for SteppedDomain {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> f64 {
+        self.point(rng.gen_range(0..self.len.get()))
+    }
+}
+
+/// Bit string domain.
+///
+/// A point of this domain is a vector of booleans that has the specified number of bits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitStringDomain {
+    bits: usize,
+}
+impl BitStringDomain {
+    /// Makes a new `BitStringDomain` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `bits` is `0`, this function returns an `ErrorKind::InvalidInput` error.
+    pub fn new(bits: usize) -> Result<Self> {
+        track_assert!(bits > 0, ErrorKind::InvalidInput);
+        Ok(Self { bits })
+    }
+
+    /// Returns the number of the bits of this domain.
+    pub const fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// Encodes the given integer into the Gray code representation.
+    ///
+    /// The first element of the resulting vector is the most significant bit.
+    /// With Gray codes, adjacent integers differ only in a single bit.
+    ///
+    /// # Errors
+    ///
+    /// If `value` cannot be represented by the bits of this domain,
+    /// this function returns an `ErrorKind::InvalidInput` error.
+    pub fn encode_gray(&self, value: u64) -> Result<Vec<bool>> {
+        track_assert!(
+            self.bits >= 64 || value >> self.bits == 0,
+            ErrorKind::InvalidInput; value, self.bits
+        );
+        let gray = value ^ (value >> 1);
+        Ok((0..self.bits)
+            .rev()
+            .map(|i| i < 64 && (gray >> i) & 1 == 1)
+            .collect())
+    }
+
+    /// Decodes the given Gray code into the integer.
+    ///
+    /// # Errors
+    ///
+    /// If `bits` is not contained in this domain or the decoded value does not fit in `u64`,
+    /// this function returns an `ErrorKind::InvalidInput` error.
+    pub fn decode_gray(&self, bits: &[bool]) -> Result<u64> {
+        track_assert_eq!(bits.len(), self.bits, ErrorKind::InvalidInput);
+        let mut value = 0u64;
+        let mut prev = false;
+        for &b in bits {
+            track_assert!(value >> 63 == 0, ErrorKind::InvalidInput; self.bits);
+            prev ^= b;
+            value = (value << 1) | prev as u64;
+        }
+        Ok(value)
+    }
+}
+impl Domain for BitStringDomain {
+    type Point = Vec<bool>;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        point.len() == self.bits
+    }
+}
+impl Distribution<Vec<bool>> for BitStringDomain {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<bool> {
+        (0..self.bits).map(|_| rng.gen()).collect()
+    }
+}
+
+/// Permutation domain.
+///
+/// A point of this domain is a permutation of `0..n`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PermutationDomain {
+    n: usize,
+}
+impl PermutationDomain {
+    /// Makes a new `PermutationDomain` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is `0`, this function returns an `ErrorKind::InvalidInput` error.
+    pub fn new(n: usize) -> Result<Self> {
+        track_assert!(n > 0, ErrorKind::InvalidInput);
+        Ok(Self { n })
+    }
+
+    /// Returns the number of the elements of a permutation.
+    pub const fn elements(&self) -> usize {
+        self.n
+    }
+}
+impl Domain for PermutationDomain {
+    type Point = Vec<usize>;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        if point.len() != self.n {
+            return false;
+        }
+        let mut seen = vec![false; self.n];
+        for &x in point {
+            if x >= self.n || seen[x] {
+                return false;
+            }
+            seen[x] = true;
+        }
+        true
+    }
+}
+impl Distribution<Vec<usize>> for PermutationDomain {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<usize> {
+        let mut point = (0..self.n).collect::<Vec<_>>();
+        point.shuffle(rng);
+        point
+    }
+}
+
+/// A node of a tree (e.g., an expression tree or a neural network architecture).
+///
+/// `kind` is the index of the node type in the associated `TreeDomain`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tree {
+    /// The node type of this node.
+    pub kind: usize,
+
+    /// The children of this node.
+    pub children: Vec<Tree>,
+}
+impl Tree {
+    /// Makes a new `Tree` instance.
+    pub const fn new(kind: usize, children: Vec<Tree>) -> Self {
+        Self { kind, children }
+    }
+
+    /// Returns the depth of this tree (a leaf has the depth `1`).
+    pub fn depth(&self) -> usize {
+        1 + self.children.iter().map(Tree::depth).max().unwrap_or(0)
+    }
+
+    /// Returns the number of the nodes of this tree.
+    pub fn size(&self) -> usize {
+        1 + self.children.iter().map(Tree::size).sum::<usize>()
+    }
+
+    /// Returns the `index`-th node in pre-order.
+    pub fn get(&self, mut index: usize) -> Option<&Tree> {
+        if index == 0 {
+            return Some(self);
+        }
+        index -= 1;
+        for child in &self.children {
+            let size = child.size();
+            if index < size {
+                return child.get(index);
+            }
+            index -= size;
+        }
+        None
+    }
+
+    /// Returns a mutable reference to the `index`-th node in pre-order.
+    pub fn get_mut(&mut self, mut index: usize) -> Option<&mut Tree> {
+        if index == 0 {
+            return Some(self);
+        }
+        index -= 1;
+        for child in &mut self.children {
+            let size = child.size();
+            if index < size {
+                return child.get_mut(index);
+            }
+            index -= size;
+        }
+        None
+    }
+}
+
+/// Tree-structured domain.
+///
+/// A point of this domain is a `Tree` whose nodes are typed by user-specified node types.
+/// Each node type has a fixed arity (i.e., the number of children), and
+/// node types of which the arity is `0` are terminals.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TreeDomain {
+    arities: Vec<usize>,
+    max_depth: usize,
+}
+impl TreeDomain {
+    /// Makes a new `TreeDomain` instance.
+    ///
+    /// `arities[i]` is the arity of the `i`-th node type.
+    ///
+    /// # Errors
+    ///
+    /// If `arities` has no terminal node type or `max_depth` is `0`,
+    /// this function returns an `ErrorKind::InvalidInput` error.
+    pub fn new(arities: Vec<usize>, max_depth: usize) -> Result<Self> {
+        track_assert!(arities.contains(&0), ErrorKind::InvalidInput; arities);
+        track_assert!(max_depth > 0, ErrorKind::InvalidInput);
+        Ok(Self { arities, max_depth })
+    }
+
+    /// Returns the arities of the node types.
+    pub fn arities(&self) -> &[usize] {
+        &self.arities
+    }
+
+    /// Returns the maximum depth of a tree.
+    pub const fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    fn grow<R: Rng + ?Sized>(&self, rng: &mut R, depth: usize) -> Tree {
+        let kinds = (0..self.arities.len())
+            .filter(|&k| depth < self.max_depth || self.arities[k] == 0)
+            .collect::<Vec<_>>();
+        let kind = kinds[rng.gen_range(0..kinds.len())];
+        let children = (0..self.arities[kind])
+            .map(|_| self.grow(rng, depth + 1))
+            .collect();
+        Tree::new(kind, children)
+    }
+
+    fn contains_node(&self, node: &Tree, depth: usize) -> bool {
+        depth <= self.max_depth
+            && self.arities.get(node.kind) == Some(&node.children.len())
+            && node
+                .children
+                .iter()
+                .all(|c| self.contains_node(c, depth + 1))
+    }
+}
+impl Domain for TreeDomain {
+    type Point = Tree;
+
+    fn contains(&self, point: &Self::Point) -> bool {
+        self.contains_node(point, 1)
+    }
+}
+impl Distribution<Tree> for TreeDomain {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Tree {
+        self.grow(rng, 1)
+    }
+}
+
+/// Returns the Hamming distance between the given bit strings.
+///
+/// If the lengths differ, the extra bits are counted as different.
+pub fn hamming_distance(a: &[bool], b: &[bool]) -> usize {
+    let diff = a.iter().zip(b.iter()).filter(|(a, b)| a != b).count();
+    diff + cmp::max(a.len(), b.len()) - cmp::min(a.len(), b.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn named_domain_works() -> TestResult {
+        let domain = VecDomain(vec![
+            NamedDomain::new("lr", track!(ContinuousDomain::new(0.0, 1.0))?),
+            NamedDomain::new("timeout", track!(ContinuousDomain::new(0.0, 10.0))?).with_unit("s"),
+        ]);
+        assert_eq!(domain.labels(), ["lr", "timeout [s]"]);
+        assert_eq!(domain.position("timeout"), Some(1));
+        assert_eq!(
+            track!(domain.zip_names(vec![0.3, 0.7]))?,
+            [("lr", 0.3), ("timeout", 0.7)]
+        );
+
+        let point = domain.sample(&mut rand::thread_rng());
+        assert!(domain.contains(&point));
+        track!(domain.check(&point))?;
+
+        let e = domain.check(&[0.5, 20.0]).err();
+        assert_eq!(e.as_ref().and_then(|e| e.param_index()), Some(1));
+        assert_eq!(e.as_ref().and_then(|e| e.param_name()), Some("timeout"));
+        Ok(())
+    }
+
+    #[test]
+    fn continuous_domain_works() -> TestResult {
+        let domain = track!(ContinuousDomain::new(-1.0, 3.0))?;
+        assert_eq!(domain.center(), 1.0);
+
+        assert_eq!(domain.clamp(-2.0), -1.0);
+        assert_eq!(domain.clamp(f64::NAN), -1.0);
+        assert_eq!(domain.clamp(2.0), 2.0);
+        assert!(domain.contains(&domain.clamp(3.0)));
+        assert!(domain.contains(&domain.clamp(100.0)));
+
+        let large = track!(ContinuousDomain::new(0.0, 1e10))?;
+        assert!(large.contains(&large.clamp(1e10)));
+
+        assert_eq!(domain.linspace(4), vec![-1.0, 0.0, 1.0, 2.0]);
+        assert!(domain.linspace(0).is_empty());
+
+        let expanded = track!(domain.expand(2.0))?;
+        assert_eq!((expanded.low(), expanded.high()), (-3.0, 5.0));
+        assert!(domain.expand(0.0).is_err());
+
+        let closed = track!(domain.with_bounds(Bound::Inclusive, Bound::Inclusive))?;
+        assert!(closed.contains(&3.0));
+        assert_eq!(closed.clamp(100.0), 3.0);
+        let open = track!(domain.with_bounds(Bound::Exclusive, Bound::Exclusive))?;
+        assert!(!open.contains(&-1.0));
+        assert!(!open.contains(&3.0));
+        assert_eq!(open.clamp(-2.0), (-1f64).next_up());
+        let expanded = track!(open.expand(2.0))?;
+        assert_eq!(expanded.low_bound(), Bound::Exclusive);
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            assert!(closed.contains(&closed.sample(&mut rng)));
+            assert!(open.contains(&open.sample(&mut rng)));
+        }
+
+        let tiny = track!(ContinuousDomain::new(1.0, 1f64.next_up()))?;
+        assert!(tiny
+            .with_bounds(Bound::Exclusive, Bound::Exclusive)
+            .is_err());
+        assert!(tiny.with_bounds(Bound::Exclusive, Bound::Inclusive).is_ok());
+
+        let unbounded = track!(UnboundedDomain::new(Prior::Cauchy {
+            location: 0.0,
+            scale: 1.0
+        }))?;
+        assert!(unbounded.contains(&unbounded.sample(&mut rng)));
+        assert!(!unbounded.contains(&f64::INFINITY));
+        assert!(UnboundedDomain::new(Prior::Normal {
+            mean: 0.0,
+            std_dev: 0.0
+        })
+        .is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn continuous_domain_deserialization_is_validated() -> TestResult {
+        use trackable::error::ErrorKindExt;
+
+        let domain = track!(ContinuousDomain::new(0.0, 1.0))?;
+        let json = track!(serde_json::to_string(&domain).map_err(|e| ErrorKind::Other.cause(e)))?;
+        let restored: ContinuousDomain =
+            track!(serde_json::from_str(&json).map_err(|e| ErrorKind::Other.cause(e)))?;
+        assert_eq!(restored, domain);
+
+        let invalid = r#"{"low":1.0,"high":0.0,"low_bound":"Inclusive","high_bound":"Exclusive"}"#;
+        assert!(serde_json::from_str::<ContinuousDomain>(invalid).is_err());
+        let empty = r#"{"low":0.0,"high":5e-324,"low_bound":"Exclusive","high_bound":"Exclusive"}"#;
+        assert!(serde_json::from_str::<ContinuousDomain>(empty).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn stepped_domain_works() -> TestResult {
+        let domain = track!(SteppedDomain::new(0.0, 1.0, 0.05))?;
+        assert_eq!(domain.len().get(), 21);
+        assert_eq!(domain.high(), 1.0);
+        assert_eq!(domain.snap(0.12), domain.point(2));
+        assert_eq!(domain.snap(-3.0), 0.0);
+        assert_eq!(domain.snap(3.0), 1.0);
+        assert!(domain.contains(&domain.point(7)));
+        assert!(!domain.contains(&0.12));
+
+        let batch_sizes = track!(SteppedDomain::new(8.0, 100.0, 8.0))?;
+        assert_eq!(batch_sizes.high(), 96.0);
+        assert_eq!(batch_sizes.index(32.0), Some(3));
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let x = batch_sizes.sample(&mut rng);
+            assert!(batch_sizes.contains(&x));
+            assert_eq!(x % 8.0, 0.0);
+        }
+
+        assert!(SteppedDomain::new(1.0, 0.0, 0.1).is_err());
+        assert!(SteppedDomain::new(0.0, 1.0, 0.0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn grid_works() -> TestResult {
+        let categorical = track!(CategoricalDomain::new(3))?;
+        assert_eq!(categorical.iter_points().collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(categorical.grid(10), [0, 1, 2]);
+        let discrete = track!(DiscreteDomain::new(11))?;
+        assert_eq!(discrete.grid(3), [0, 5, 10]);
+        assert_eq!(discrete.grid(1), [5]);
+        assert!(discrete.grid(0).is_empty());
+
+        let continuous = track!(ContinuousDomain::new(0.0, 1.0))?;
+        let closed = track!(continuous.with_bounds(Bound::Inclusive, Bound::Inclusive))?;
+        assert_eq!(closed.grid(3), [0.0, 0.5, 1.0]);
+        assert!(continuous.grid(5).iter().all(|x| continuous.contains(x)));
+
+        let stepped = track!(SteppedDomain::new(8.0, 32.0, 8.0))?;
+        assert_eq!(
+            stepped.iter_points().collect::<Vec<_>>(),
+            [8.0, 16.0, 24.0, 32.0]
+        );
+
+        let domain = VecDomain(vec![closed.clone(), closed]);
+        let grid = domain.grid(2);
+        assert_eq!(
+            grid,
+            [
+                vec![0.0, 0.0],
+                vec![0.0, 1.0],
+                vec![1.0, 0.0],
+                vec![1.0, 1.0]
+            ]
+        );
+        assert_eq!(domain.grid_iter(3).total(), Some(9));
+        assert_eq!(domain.grid_iter(3).count(), 9);
+        assert_eq!(CartesianProduct::new(vec![vec![1], vec![]]).count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn bit_string_domain_works() -> TestResult {
+        let domain = track!(BitStringDomain::new(4))?;
+        assert!(BitStringDomain::new(0).is_err());
+        assert!(domain.contains(&domain.sample(&mut rand::thread_rng())));
+
+        assert_eq!(
+            track!(domain.encode_gray(5))?,
+            vec![false, true, true, true]
+        );
+        assert!(domain.encode_gray(16).is_err());
+        for v in 0..16 {
+            let bits = track!(domain.encode_gray(v))?;
+            assert_eq!(track!(domain.decode_gray(&bits))?, v);
+
+            let next = track!(domain.encode_gray((v + 1) % 16))?;
+            assert_eq!(hamming_distance(&bits, &next), 1);
+        }
+        assert_eq!(hamming_distance(&[true], &[false, false]), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn permutation_domain_works() -> TestResult {
+        let domain = track!(PermutationDomain::new(5))?;
+        assert!(domain.contains(&domain.sample(&mut rand::thread_rng())));
+        assert!(domain.contains(&vec![4, 3, 2, 1, 0]));
+        assert!(!domain.contains(&vec![0, 1, 2, 3, 3]));
+        assert!(!domain.contains(&vec![0, 1, 2, 3, 5]));
+        assert!(!domain.contains(&vec![0, 1, 2, 3]));
+        Ok(())
+    }
+
+    #[test]
+    fn tree_domain_works() -> TestResult {
+        assert!(TreeDomain::new(vec![1, 2], 3).is_err());
+
+        let domain = track!(TreeDomain::new(vec![0, 0, 1, 2], 3))?;
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let tree = domain.sample(&mut rng);
+            assert!(domain.contains(&tree));
+            assert!(tree.depth() <= 3);
+        }
+
+        let tree = Tree::new(
+            3,
+            vec![
+                Tree::new(2, vec![Tree::new(0, vec![])]),
+                Tree::new(1, vec![]),
+            ],
+        );
+        assert!(domain.contains(&tree));
+        assert_eq!(tree.depth(), 3);
+        assert_eq!(tree.size(), 4);
+        assert_eq!(tree.get(2).map(|t| t.kind), Some(0));
+        assert_eq!(tree.get(3).map(|t| t.kind), Some(1));
+        assert!(tree.get(4).is_none());
+
+        assert!(!domain.contains(&Tree::new(3, vec![Tree::new(0, vec![])])));
+        assert!(!domain.contains(&Tree::new(
+            2,
+            vec![Tree::new(2, vec![Tree::new(2, vec![Tree::new(0, vec![])])])]
+        )));
+        Ok(())
     }
 }
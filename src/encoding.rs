@@ -0,0 +1,260 @@
+//! Encoding of structured parameters into continuous vectors.
+//!
+//! Optimizers that inherently work on `R^n` (e.g., `NelderMeadOptimizer`) can handle
+//! integer, categorical, log-scaled and boolean parameters via `Encoder`
+//! (see also `optimizers::encoded::EncodedOptimizer`).
+//!
+//! Every encoded coordinate is in the range `[0.0, 1.0)`:
+//!
+//! - continuous parameters are linearly (or logarithmically) normalized
+//! - the `i`-th value of a discrete parameter of size `n` is encoded as `(i + 0.5) / n` and decoded by flooring
+//! - a categorical parameter is one-hot encoded (`0.75` for the chosen category and `0.25` for the others)
+//!   and decoded by argmax
+//! - a boolean parameter is encoded as `0.25` or `0.75` and decoded by thresholding at `0.5`
+use crate::domains::{CategoricalDomain, ContinuousDomain, DiscreteDomain};
+use crate::{Domain, ErrorKind, Result};
+
+const LOW: f64 = 0.25;
+const HIGH: f64 = 0.75;
+
+/// A structured parameter value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    /// A real number (for `Encoding::Continuous` and `Encoding::LogScale`).
+    Float(f64),
+
+    /// An integer (for `Encoding::Discrete`).
+    Int(u64),
+
+    /// A category index (for `Encoding::Categorical`).
+    Category(u64),
+
+    /// A boolean (for `Encoding::Boolean`).
+    Bool(bool),
+}
+
+/// Encoding of a structured parameter.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// A real number in the given domain.
+    Continuous(ContinuousDomain),
+
+    /// A positive real number in the given domain that is searched in the logarithmic scale.
+    LogScale(ContinuousDomain),
+
+    /// An integer in the given domain.
+    Discrete(DiscreteDomain),
+
+    /// A category of the given domain.
+    Categorical(CategoricalDomain),
+
+    /// A boolean.
+    Boolean,
+}
+impl Encoding {
+    /// Makes a new `Encoding::LogScale` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `low` is not positive or the bounds are invalid, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn log_scale(low: f64, high: f64) -> Result<Self> {
+        track_assert!(low > 0.0, ErrorKind::InvalidInput; low);
+        track!(ContinuousDomain::new(low, high)).map(Encoding::LogScale)
+    }
+
+    /// Returns the number of the coordinates of an encoded value.
+    pub fn dimensions(&self) -> usize {
+        match self {
+            Encoding::Categorical(d) => d.cardinality().get() as usize,
+            _ => 1,
+        }
+    }
+
+    /// Appends the encoded coordinates of `value` to `encoded`.
+    ///
+    /// # Errors
+    ///
+    /// If `value` does not match this encoding or is out of the domain,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn encode(&self, value: ParamValue, encoded: &mut Vec<f64>) -> Result<()> {
+        match (self, value) {
+            (Encoding::Continuous(d), ParamValue::Float(x)) => {
+                track_assert!(d.contains(&x), ErrorKind::InvalidInput; x);
+                encoded.push(unit().clamp((x - d.low()) / d.size()));
+            }
+            (Encoding::LogScale(d), ParamValue::Float(x)) => {
+                track_assert!(d.contains(&x), ErrorKind::InvalidInput; x);
+                let (low, high) = (d.low().ln(), d.high().ln());
+                encoded.push(unit().clamp((x.ln() - low) / (high - low)));
+            }
+            (Encoding::Discrete(d), ParamValue::Int(i)) => {
+                track_assert!(d.contains(&i), ErrorKind::InvalidInput; i);
+                encoded.push((i as f64 + 0.5) / d.size().get() as f64);
+            }
+            (Encoding::Categorical(d), ParamValue::Category(c)) => {
+                track_assert!(d.contains(&c), ErrorKind::InvalidInput; c);
+                encoded.extend((0..d.cardinality().get()).map(|i| if i == c { HIGH } else { LOW }));
+            }
+            (Encoding::Boolean, ParamValue::Bool(b)) => {
+                encoded.push(if b { HIGH } else { LOW });
+            }
+            _ => track_panic!(ErrorKind::InvalidInput, "Mismatched value: {:?}", value),
+        }
+        Ok(())
+    }
+
+    /// Decodes the given coordinates.
+    ///
+    /// Out of range coordinates are clamped into `[0.0, 1.0)` before decoding.
+    ///
+    /// # Errors
+    ///
+    /// If the length of `encoded` is not equal to `self.dimensions()`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn decode(&self, encoded: &[f64]) -> Result<ParamValue> {
+        track_assert_eq!(encoded.len(), self.dimensions(), ErrorKind::InvalidInput);
+        let x = unit().clamp(encoded[0]);
+        Ok(match self {
+            Encoding::Continuous(d) => ParamValue::Float(d.clamp(d.low() + x * d.size())),
+            Encoding::LogScale(d) => {
+                let (low, high) = (d.low().ln(), d.high().ln());
+                ParamValue::Float(d.clamp((low + x * (high - low)).exp()))
+            }
+            Encoding::Discrete(d) => {
+                let n = d.size().get();
+                ParamValue::Int(((x * n as f64) as u64).min(n - 1))
+            }
+            Encoding::Categorical(_) => {
+                let mut best = 0;
+                for (i, &x) in encoded.iter().enumerate() {
+                    if unit().clamp(x) > unit().clamp(encoded[best]) {
+                        best = i;
+                    }
+                }
+                ParamValue::Category(best as u64)
+            }
+            Encoding::Boolean => ParamValue::Bool(x >= 0.5),
+        })
+    }
+}
+
+/// Encoder of structured parameter vectors.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Encoder {
+    encodings: Vec<Encoding>,
+}
+impl Encoder {
+    /// Makes a new `Encoder` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `encodings` is empty, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(encodings: Vec<Encoding>) -> Result<Self> {
+        track_assert!(!encodings.is_empty(), ErrorKind::InvalidInput);
+        Ok(Self { encodings })
+    }
+
+    /// Returns the encodings of the parameter elements.
+    pub fn encodings(&self) -> &[Encoding] {
+        &self.encodings
+    }
+
+    /// Returns the number of the coordinates of an encoded vector.
+    pub fn dimensions(&self) -> usize {
+        self.encodings.iter().map(Encoding::dimensions).sum()
+    }
+
+    /// Returns the domains of the coordinates of an encoded vector (i.e., `[0.0, 1.0)` for every coordinate).
+    pub fn domains(&self) -> Vec<ContinuousDomain> {
+        vec![unit(); self.dimensions()]
+    }
+
+    /// Encodes the given parameter vector.
+    ///
+    /// # Errors
+    ///
+    /// If the length of `values` is not equal to the number of the encodings or
+    /// a value does not match the corresponding encoding, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn encode(&self, values: &[ParamValue]) -> Result<Vec<f64>> {
+        track_assert_eq!(values.len(), self.encodings.len(), ErrorKind::InvalidInput);
+        let mut encoded = Vec::with_capacity(self.dimensions());
+        for (e, &v) in self.encodings.iter().zip(values.iter()) {
+            track!(e.encode(v, &mut encoded))?;
+        }
+        Ok(encoded)
+    }
+
+    /// Decodes the given vector.
+    ///
+    /// # Errors
+    ///
+    /// If the length of `encoded` is not equal to `self.dimensions()`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn decode(&self, encoded: &[f64]) -> Result<Vec<ParamValue>> {
+        track_assert_eq!(encoded.len(), self.dimensions(), ErrorKind::InvalidInput);
+        let mut offset = 0;
+        let mut values = Vec::with_capacity(self.encodings.len());
+        for e in &self.encodings {
+            let n = e.dimensions();
+            values.push(track!(e.decode(&encoded[offset..offset + n]))?);
+            offset += n;
+        }
+        Ok(values)
+    }
+}
+
+fn unit() -> ContinuousDomain {
+    ContinuousDomain::new(0.0, 1.0).unwrap_or_else(|e| unreachable!("{}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn encoder_works() -> TestResult {
+        let encoder = track!(Encoder::new(vec![
+            Encoding::Continuous(track!(ContinuousDomain::new(-1.0, 1.0))?),
+            track!(Encoding::log_scale(1e-5, 1e-1))?,
+            Encoding::Discrete(track!(DiscreteDomain::new(4))?),
+            Encoding::Categorical(track!(CategoricalDomain::new(3))?),
+            Encoding::Boolean,
+        ]))?;
+        assert_eq!(encoder.dimensions(), 7);
+
+        let values = vec![
+            ParamValue::Float(0.5),
+            ParamValue::Float(1e-3),
+            ParamValue::Int(2),
+            ParamValue::Category(1),
+            ParamValue::Bool(true),
+        ];
+        let encoded = track!(encoder.encode(&values))?;
+        assert_eq!(&encoded[2..], &[0.625, 0.25, 0.75, 0.25, 0.75]);
+        assert!((encoded[1] - 0.5).abs() < 1e-9);
+
+        let decoded = track!(encoder.decode(&encoded))?;
+        assert_eq!(decoded[0], ParamValue::Float(0.5));
+        assert_eq!(&decoded[2..], &values[2..]);
+        match decoded[1] {
+            ParamValue::Float(x) => assert!((x - 1e-3).abs() < 1e-12),
+            _ => panic!(),
+        }
+
+        let decoded = track!(encoder.decode(&[1.5, -1.0, 0.74, 0.1, 0.2, 0.3, 0.49]))?;
+        assert!(matches!(decoded[0], ParamValue::Float(x) if 0.99 < x && x < 1.0));
+        assert_eq!(
+            &decoded[2..],
+            &[
+                ParamValue::Int(2),
+                ParamValue::Category(2),
+                ParamValue::Bool(false)
+            ]
+        );
+
+        assert!(encoder.encode(&values[..4]).is_err());
+        assert!(encoder.encode(&[ParamValue::Int(0); 5]).is_err());
+        Ok(())
+    }
+}
@@ -1,9 +1,39 @@
+use crate::ObsId;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use trackable::error::{ErrorKind as TrackableErrorKind, ErrorKindExt};
 use trackable::error::{Failure, TrackableError};
 
 /// This crate specific `Error` type.
 #[derive(Debug, Clone, TrackableError)]
 pub struct Error(TrackableError<ErrorKind>);
+impl Error {
+    /// Returns the structured context of this error if it has one.
+    pub fn context(&self) -> Option<&ErrorContext> {
+        self.0.concrete_cause()
+    }
+
+    /// Returns the identifier of the offending observation if it is known.
+    pub fn obs_id(&self) -> Option<ObsId> {
+        self.context().and_then(|c| c.obs_id)
+    }
+
+    /// Returns the index of the offending parameter element if it is known.
+    pub fn param_index(&self) -> Option<usize> {
+        self.context().and_then(|c| c.param_index)
+    }
+
+    /// Returns the name of the offending parameter element if it is known.
+    pub fn param_name(&self) -> Option<&str> {
+        self.context().and_then(|c| c.param_name.as_deref())
+    }
+
+    /// Returns the bounds `(low, high)` of the offending domain if they are known.
+    pub fn bounds(&self) -> Option<(f64, f64)> {
+        self.context().and_then(|c| c.bounds)
+    }
+}
 impl From<Failure> for Error {
     fn from(f: Failure) -> Self {
         ErrorKind::Other.takes_over(f).into()
@@ -17,6 +47,7 @@ impl From<std::io::Error> for Error {
 
 /// Possible error kinds.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ErrorKind {
     /// Invalid input was given.
     InvalidInput,
@@ -34,3 +65,110 @@ pub enum ErrorKind {
     Other,
 }
 impl TrackableErrorKind for ErrorKind {}
+
+/// Structured context of an error.
+///
+/// This is attached to an `Error` as its cause
+/// (e.g., `track_panic!(ErrorKind::InvalidInput, ErrorContext::new("...").obs_id(id))`)
+/// and can be retrieved by `Error::context`.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ErrorContext {
+    /// Error message.
+    pub message: String,
+
+    /// The identifier of the offending observation.
+    pub obs_id: Option<ObsId>,
+
+    /// The index of the offending parameter element.
+    pub param_index: Option<usize>,
+
+    /// The name of the offending parameter element.
+    pub param_name: Option<String>,
+
+    /// The bounds `(low, high)` of the offending domain.
+    pub bounds: Option<(f64, f64)>,
+}
+impl ErrorContext {
+    /// Makes a new `ErrorContext` instance with the given message.
+    pub fn new<T: Into<String>>(message: T) -> Self {
+        Self {
+            message: message.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Sets the identifier of the offending observation.
+    pub fn obs_id(mut self, id: ObsId) -> Self {
+        self.obs_id = Some(id);
+        self
+    }
+
+    /// Sets the index of the offending parameter element.
+    pub fn param_index(mut self, index: usize) -> Self {
+        self.param_index = Some(index);
+        self
+    }
+
+    /// Sets the name of the offending parameter element.
+    pub fn param_name<T: Into<String>>(mut self, name: T) -> Self {
+        self.param_name = Some(name.into());
+        self
+    }
+
+    /// Sets the bounds of the offending domain.
+    pub fn bounds(mut self, low: f64, high: f64) -> Self {
+        self.bounds = Some((low, high));
+        self
+    }
+}
+impl fmt::Display for ErrorContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(id) = self.obs_id {
+            write!(f, "; obs_id={}", id.get())?;
+        }
+        if let Some(i) = self.param_index {
+            write!(f, "; param_index={}", i)?;
+        }
+        if let Some(name) = &self.param_name {
+            write!(f, "; param_name={}", name)?;
+        }
+        if let Some((low, high)) = self.bounds {
+            write!(f, "; low={}; high={}", low, high)?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for ErrorContext {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Result;
+
+    fn fail() -> Result<()> {
+        track_panic!(
+            ErrorKind::InvalidInput,
+            ErrorContext::new("Out of the domain parameter")
+                .obs_id(ObsId::new(3))
+                .param_index(1)
+                .bounds(0.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn error_context_works() {
+        let e = fail().err().unwrap_or_else(|| panic!());
+        assert_eq!(*e.kind(), ErrorKind::InvalidInput);
+        assert_eq!(e.obs_id(), Some(ObsId::new(3)));
+        assert_eq!(e.param_index(), Some(1));
+        assert_eq!(e.bounds(), Some((0.0, 1.0)));
+        assert!(e
+            .to_string()
+            .contains("obs_id=3; param_index=1; low=0; high=1"));
+
+        let e: Error = ErrorKind::Other.error().into();
+        assert!(e.context().is_none());
+    }
+}
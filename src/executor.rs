@@ -0,0 +1,371 @@
+//! Parallel evaluation of objective functions.
+//!
+//! `ParallelExecutor` drives an ordinary `Optimizer` while evaluating the asked parameters on the rayon thread pool.
+//! Asks and tells are issued only from the calling thread, so the optimizer needs neither a `Mutex` nor `Sync`.
+//! Each result is told as soon as its evaluation completes, and a new parameter is asked to fill the freed slot.
+//!
+//! The wall-clock time of each evaluation and of the optimizer itself is recorded into `RunStats`,
+//! which tells whether the optimizer or the objective function dominates the runtime.
+use crate::{ErrorKind, IdGen, ObsId, Optimizer, Result, StudyBudget, StudyConfig};
+use rand::Rng;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use trackable::error::ErrorKindExt;
+
+/// Wall-clock timing of an evaluation.
+///
+/// The times are measured from the start of the run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EvalTiming {
+    /// The identifier of the evaluated observation.
+    pub id: ObsId,
+
+    /// The time when the evaluation started.
+    pub start: Duration,
+
+    /// The time when the evaluation finished.
+    pub end: Duration,
+}
+impl EvalTiming {
+    /// Returns the duration of the evaluation.
+    pub fn duration(&self) -> Duration {
+        self.end.saturating_sub(self.start)
+    }
+}
+
+/// Statistics of a run of `ParallelExecutor`.
+#[derive(Debug, Clone, Default)]
+pub struct RunStats {
+    timings: Vec<EvalTiming>,
+    optimizer_overhead: Duration,
+    elapsed: Duration,
+}
+impl RunStats {
+    /// Returns the timings of the evaluations in the order they were told.
+    pub fn timings(&self) -> &[EvalTiming] {
+        &self.timings
+    }
+
+    /// Returns the median of the durations of the evaluations (or `None` if there are no evaluations).
+    ///
+    /// If the number of the evaluations is even, the lower median is returned.
+    pub fn median_eval_time(&self) -> Option<Duration> {
+        let mut durations = self
+            .timings
+            .iter()
+            .map(EvalTiming::duration)
+            .collect::<Vec<_>>();
+        durations.sort();
+        durations.get(durations.len().checked_sub(1)? / 2).copied()
+    }
+
+    /// Returns the number of the evaluations finished per second.
+    pub fn throughput(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs == 0.0 {
+            0.0
+        } else {
+            self.timings.len() as f64 / secs
+        }
+    }
+
+    /// Returns the total time spent by the optimizer (i.e., in asks and tells).
+    pub fn optimizer_overhead(&self) -> Duration {
+        self.optimizer_overhead
+    }
+
+    /// Returns the wall-clock time of the whole run.
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// An executor evaluating the parameters asked by an optimizer in parallel.
+#[derive(Debug, Clone)]
+pub struct ParallelExecutor {
+    max_outstanding: usize,
+}
+impl ParallelExecutor {
+    /// Makes a new `ParallelExecutor` instance.
+    ///
+    /// At most `max_outstanding` parameters are asked but not yet told at the same time.
+    ///
+    /// # Errors
+    ///
+    /// If `max_outstanding` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(max_outstanding: usize) -> Result<Self> {
+        track_assert!(max_outstanding > 0, ErrorKind::InvalidInput);
+        Ok(Self { max_outstanding })
+    }
+
+    /// Returns the maximum number of the outstanding evaluations.
+    pub fn max_outstanding(&self) -> usize {
+        self.max_outstanding
+    }
+
+    /// Runs the optimization until `budget` is exhausted.
+    ///
+    /// No new parameter is asked once the evaluations already started would exhaust the evaluation limit of `budget`,
+    /// and the evaluations running when the time limit is reached are told before this method returns.
+    ///
+    /// This method blocks the calling thread, so it should not be called from within the rayon thread pool.
+    /// The timings of the evaluations and of the optimizer are returned as `RunStats`.
+    ///
+    /// # Errors
+    ///
+    /// If `objective` panics, an `ErrorKind::Other` error will be returned
+    /// after the other running evaluations finished and their results were told.
+    /// Errors returned by `optimizer` are also propagated in the same way.
+    pub fn run<O, F, R, G>(
+        &self,
+        optimizer: &mut O,
+        objective: F,
+        budget: &mut StudyBudget,
+        mut rng: R,
+        mut idg: G,
+    ) -> Result<RunStats>
+    where
+        O: Optimizer,
+        O::Param: Send,
+        O::Value: Send,
+        F: Fn(&O::Param) -> O::Value + Sync,
+        R: Rng,
+        G: IdGen,
+    {
+        let objective = &objective;
+        let started = Instant::now();
+        let mut stats = RunStats::default();
+        let (tx, rx) = mpsc::channel();
+        rayon::in_place_scope(|scope| {
+            let mut outstanding = 0;
+            let mut error = None;
+            loop {
+                while error.is_none()
+                    && outstanding < self.max_outstanding
+                    && can_start(budget, outstanding)
+                {
+                    let asking = Instant::now();
+                    let obs = match track!(optimizer.ask(&mut rng, &mut idg)) {
+                        Ok(obs) => obs,
+                        Err(e) => {
+                            error = Some(e);
+                            break;
+                        }
+                    };
+                    stats.optimizer_overhead += asking.elapsed();
+
+                    let tx = tx.clone();
+                    scope.spawn(move |_| {
+                        let start = started.elapsed();
+                        let result =
+                            panic::catch_unwind(AssertUnwindSafe(|| objective(&obs.param)));
+                        let timing = EvalTiming {
+                            id: obs.id,
+                            start,
+                            end: started.elapsed(),
+                        };
+                        let _ = tx.send(result.map(|value| (obs.map_value(|()| value), timing)));
+                    });
+                    outstanding += 1;
+                }
+                if outstanding == 0 {
+                    stats.elapsed = started.elapsed();
+                    return error.map_or(Ok(stats), Err);
+                }
+
+                // After an error, the remaining results are still told so that the optimizer has no dangling asks.
+                let result = track!(rx.recv().map_err(|e| ErrorKind::Other.cause(e)))?;
+                outstanding -= 1;
+                let (obs, timing) = match result {
+                    Ok(x) => x,
+                    Err(_) => {
+                        let e = track!(ErrorKind::Other.cause("The objective panicked"));
+                        error = error.or(Some(e.into()));
+                        continue;
+                    }
+                };
+                let telling = Instant::now();
+                if let Err(e) = track!(optimizer.tell(obs)) {
+                    error = error.or(Some(e));
+                    continue;
+                }
+                stats.optimizer_overhead += telling.elapsed();
+                stats.timings.push(timing);
+                budget.record_evaluation();
+            }
+        })
+    }
+
+    /// Runs the study configured by `config`.
+    ///
+    /// This is equivalent to `run` with the budget and the random number generator made by `config`.
+    /// The number of the evaluations told to `optimizer` is added to `config.completed_evaluations`
+    /// (even if an error occurs), so a study resumed with the updated `config` and a restored `optimizer`
+    /// only runs the remaining evaluations.
+    ///
+    /// # Errors
+    ///
+    /// The same as `run`.
+    pub fn run_study<O, F, G>(
+        &self,
+        config: &mut StudyConfig,
+        optimizer: &mut O,
+        objective: F,
+        idg: G,
+    ) -> Result<RunStats>
+    where
+        O: Optimizer,
+        O::Param: Send,
+        O::Value: Send,
+        F: Fn(&O::Param) -> O::Value + Sync,
+        G: IdGen,
+    {
+        let mut budget = config.budget();
+        let result = track!(self.run(optimizer, objective, &mut budget, config.rng(), idg));
+        config.completed_evaluations += budget.evaluations();
+        result
+    }
+}
+impl Default for ParallelExecutor {
+    /// Makes a `ParallelExecutor` that runs as many evaluations as the threads of the current rayon thread pool.
+    fn default() -> Self {
+        Self {
+            max_outstanding: rayon::current_num_threads(),
+        }
+    }
+}
+
+fn can_start(budget: &StudyBudget, outstanding: usize) -> bool {
+    !budget.is_exhausted()
+        && budget
+            .remaining_evaluations()
+            .is_none_or(|n| n > outstanding as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::ContinuousDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::random::RandomOptimizer;
+    use crate::Obs;
+    use std::collections::HashSet;
+    use std::sync::atomic::{self, AtomicUsize};
+    use trackable::result::TestResult;
+
+    #[derive(Debug)]
+    struct Recorder {
+        inner: RandomOptimizer<ContinuousDomain, f64>,
+        pending: HashSet<u64>,
+        max_pending: usize,
+        told: usize,
+    }
+    impl Optimizer for Recorder {
+        type Param = f64;
+        type Value = f64;
+
+        fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<f64>> {
+            let obs = track!(self.inner.ask(rng, idg))?;
+            self.pending.insert(obs.id.get());
+            self.max_pending = self.max_pending.max(self.pending.len());
+            Ok(obs)
+        }
+
+        fn tell(&mut self, obs: Obs<f64, f64>) -> Result<()> {
+            track_assert!(
+                self.pending.remove(&obs.id.get()),
+                ErrorKind::UnknownObservation
+            );
+            track_assert_eq!(obs.param * 2.0, obs.value, ErrorKind::InvalidInput);
+            self.told += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn parallel_executor_works() -> TestResult {
+        assert!(ParallelExecutor::new(0).is_err());
+
+        let mut optimizer = Recorder {
+            inner: RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?),
+            pending: HashSet::new(),
+            max_pending: 0,
+            told: 0,
+        };
+        let executor = track!(ParallelExecutor::new(3))?;
+        let mut budget = StudyBudget::new().max_evaluations(10);
+        let stats = track!(executor.run(
+            &mut optimizer,
+            |x: &f64| x * 2.0,
+            &mut budget,
+            rand::thread_rng(),
+            SerialIdGenerator::new()
+        ))?;
+        assert_eq!(optimizer.told, 10);
+        assert_eq!(stats.timings().len(), 10);
+        assert!(stats.timings().iter().all(|t| t.end <= stats.elapsed()));
+        assert!(stats.median_eval_time().is_some());
+        assert!(stats.optimizer_overhead() <= stats.elapsed());
+        assert!(stats.throughput() > 0.0);
+
+        let timing = |id, start, end| EvalTiming {
+            id: ObsId::new(id),
+            start: Duration::from_secs(start),
+            end: Duration::from_secs(end),
+        };
+        let stats = RunStats {
+            timings: vec![timing(0, 0, 3), timing(1, 1, 2), timing(2, 2, 7)],
+            optimizer_overhead: Duration::from_secs(1),
+            elapsed: Duration::from_secs(10),
+        };
+        assert_eq!(stats.median_eval_time(), Some(Duration::from_secs(3)));
+        assert_eq!(stats.throughput(), 0.3);
+        assert_eq!(RunStats::default().median_eval_time(), None);
+        assert!(optimizer.pending.is_empty());
+        assert!(optimizer.max_pending <= 3);
+        assert!(budget.is_exhausted());
+
+        let mut budget = StudyBudget::new().max_evaluations(10);
+        let calls = AtomicUsize::new(0);
+        optimizer.told = 0;
+        let result = executor.run(
+            &mut optimizer,
+            |x: &f64| {
+                if calls.fetch_add(1, atomic::Ordering::SeqCst) == 0 {
+                    panic!("{}", x);
+                }
+                x * 2.0
+            },
+            &mut budget,
+            rand::thread_rng(),
+            SerialIdGenerator::new(),
+        );
+        assert!(result.is_err());
+        assert_eq!(optimizer.told, 2);
+        assert_eq!(optimizer.pending.len(), 1);
+        optimizer.pending.clear();
+
+        let mut config = StudyConfig::new("double");
+        config.max_evaluations = Some(5);
+        optimizer.told = 0;
+        track!(executor.run_study(
+            &mut config,
+            &mut optimizer,
+            |x: &f64| x * 2.0,
+            SerialIdGenerator::new()
+        ))?;
+        assert_eq!(optimizer.told, 5);
+        assert_eq!(config.completed_evaluations, 5);
+
+        // Resuming a finished study runs nothing.
+        track!(executor.run_study(
+            &mut config,
+            &mut optimizer,
+            |x: &f64| x * 2.0,
+            SerialIdGenerator::new()
+        ))?;
+        assert_eq!(optimizer.told, 5);
+        Ok(())
+    }
+}
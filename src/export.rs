@@ -0,0 +1,235 @@
+//! Export of observations for external analysis.
+use crate::{ErrorKind, MfObs, Obs, Result};
+use std::io::Write;
+
+/// This trait allows converting a parameter or a value into CSV fields.
+pub trait CsvFields {
+    /// Appends the fields of this item to `fields`.
+    fn csv_fields(&self, fields: &mut Vec<String>);
+}
+
+macro_rules! impl_csv_fields {
+    ($($t:ty),*) => {
+        $(impl CsvFields for $t {
+            fn csv_fields(&self, fields: &mut Vec<String>) {
+                fields.push(self.to_string());
+            }
+        })*
+    };
+}
+impl_csv_fields!(f32, f64, i8, i16, i32, i64, u8, u16, u32, u64, usize, bool, String);
+
+impl CsvFields for () {
+    fn csv_fields(&self, _fields: &mut Vec<String>) {}
+}
+
+impl<T: CsvFields> CsvFields for [T] {
+    fn csv_fields(&self, fields: &mut Vec<String>) {
+        for x in self {
+            x.csv_fields(fields);
+        }
+    }
+}
+
+impl<T: CsvFields> CsvFields for Vec<T> {
+    fn csv_fields(&self, fields: &mut Vec<String>) {
+        self[..].csv_fields(fields);
+    }
+}
+
+impl<T: CsvFields> CsvFields for Option<T> {
+    fn csv_fields(&self, fields: &mut Vec<String>) {
+        if let Some(x) = self {
+            x.csv_fields(fields);
+        }
+    }
+}
+
+/// CSV writer of observations.
+///
+/// The header consists of `id`, (`budget_amount` and `budget_consumption` for `MfObs`),
+/// the parameter column names and the value column names.
+#[derive(Debug)]
+pub struct CsvWriter<W> {
+    writer: W,
+    param_columns: Vec<String>,
+    value_columns: Vec<String>,
+    multi_fidelity: bool,
+    header_written: bool,
+}
+impl<W: Write> CsvWriter<W> {
+    /// Makes a new `CsvWriter` instance for `Obs`.
+    pub fn new(writer: W, param_columns: Vec<String>, value_columns: Vec<String>) -> Self {
+        Self {
+            writer,
+            param_columns,
+            value_columns,
+            multi_fidelity: false,
+            header_written: false,
+        }
+    }
+
+    /// Makes a new `CsvWriter` instance for `MfObs`.
+    pub fn new_multi_fidelity(
+        writer: W,
+        param_columns: Vec<String>,
+        value_columns: Vec<String>,
+    ) -> Self {
+        Self {
+            multi_fidelity: true,
+            ..Self::new(writer, param_columns, value_columns)
+        }
+    }
+
+    /// Writes an observation.
+    ///
+    /// # Errors
+    ///
+    /// If this writer was made by `CsvWriter::new_multi_fidelity` or
+    /// the number of the fields does not match the number of the columns,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn write_obs<P, V>(&mut self, obs: &Obs<P, V>) -> Result<()>
+    where
+        P: CsvFields,
+        V: CsvFields,
+    {
+        track_assert!(!self.multi_fidelity, ErrorKind::InvalidInput);
+        let mut fields = vec![obs.id.get().to_string()];
+        track!(self.push_fields(&mut fields, &obs.param, &obs.value))?;
+        track!(self.write_record(&fields))
+    }
+
+    /// Writes a multi-fidelity observation.
+    ///
+    /// # Errors
+    ///
+    /// If this writer was made by `CsvWriter::new` or
+    /// the number of the fields does not match the number of the columns,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn write_mf_obs<P, V>(&mut self, obs: &MfObs<P, V>) -> Result<()>
+    where
+        P: CsvFields,
+        V: CsvFields,
+    {
+        track_assert!(self.multi_fidelity, ErrorKind::InvalidInput);
+        let mut fields = vec![
+            obs.id.get().to_string(),
+            obs.budget.amount.to_string(),
+            obs.budget.consumption.to_string(),
+        ];
+        track!(self.push_fields(&mut fields, &obs.param, &obs.value))?;
+        track!(self.write_record(&fields))
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<()> {
+        track!(self.writer.flush().map_err(Into::into))
+    }
+
+    /// Consumes the `CsvWriter`, returning the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn push_fields<P, V>(&self, fields: &mut Vec<String>, param: &P, value: &V) -> Result<()>
+    where
+        P: CsvFields,
+        V: CsvFields,
+    {
+        let offset = fields.len();
+        param.csv_fields(fields);
+        track_assert_eq!(
+            fields.len() - offset,
+            self.param_columns.len(),
+            ErrorKind::InvalidInput
+        );
+
+        let offset = fields.len();
+        value.csv_fields(fields);
+        track_assert_eq!(
+            fields.len() - offset,
+            self.value_columns.len(),
+            ErrorKind::InvalidInput
+        );
+        Ok(())
+    }
+
+    fn write_record(&mut self, fields: &[String]) -> Result<()> {
+        if !self.header_written {
+            let mut header = vec!["id".to_owned()];
+            if self.multi_fidelity {
+                header.push("budget_amount".to_owned());
+                header.push("budget_consumption".to_owned());
+            }
+            header.extend(self.param_columns.iter().cloned());
+            header.extend(self.value_columns.iter().cloned());
+            track!(write_line(&mut self.writer, &header))?;
+            self.header_written = true;
+        }
+        track!(write_line(&mut self.writer, fields))
+    }
+}
+
+fn write_line<W: Write>(mut writer: W, fields: &[String]) -> Result<()> {
+    let line = fields
+        .iter()
+        .map(|f| escape(f))
+        .collect::<Vec<_>>()
+        .join(",");
+    track!(writeln!(writer, "{}", line).map_err(Into::into))
+}
+
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Budget, ObsId};
+    use trackable::result::TestResult;
+
+    #[test]
+    fn csv_writer_works() -> TestResult {
+        let mut writer = CsvWriter::new(
+            Vec::new(),
+            vec!["x".to_owned(), "y".to_owned()],
+            vec!["value".to_owned()],
+        );
+        let obs = Obs {
+            id: ObsId::new(3),
+            param: vec![0.5, 1.0],
+            value: "a,\"b\"".to_owned(),
+        };
+        track!(writer.write_obs(&obs))?;
+        assert!(writer.write_obs(&obs.map_param(|_| vec![1.0])).is_err());
+
+        let csv = String::from_utf8(writer.into_inner()).unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(csv, "id,x,y,value\n3,0.5,1,\"a,\"\"b\"\"\"\n");
+        Ok(())
+    }
+
+    #[test]
+    fn csv_writer_for_mf_obs_works() -> TestResult {
+        let mut writer =
+            CsvWriter::new_multi_fidelity(Vec::new(), vec!["x".to_owned()], vec!["v".to_owned()]);
+        let obs = MfObs {
+            id: ObsId::new(0),
+            budget: Budget::new(10),
+            param: 2u64,
+            value: 0.25,
+        };
+        track!(writer.write_mf_obs(&obs))?;
+
+        let csv = String::from_utf8(writer.into_inner()).unwrap_or_else(|e| panic!("{}", e));
+        assert_eq!(
+            csv,
+            "id,budget_amount,budget_consumption,x,v\n0,10,0,2,0.25\n"
+        );
+        Ok(())
+    }
+}
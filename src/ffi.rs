@@ -0,0 +1,318 @@
+//! C FFI layer for driving optimizers from non-Rust evaluators.
+//!
+//! This module is enabled by the `ffi` feature.
+//!
+//! The parameters of the optimizers are vectors of `f64`s and
+//! the values are vectors of `f64`s (one element per objective) to be minimized.
+//! An optimizer is created by one of the `yamakan_*_new` functions and
+//! must be released by `yamakan_free`.
+//!
+//! Functions returning `c_int` return `0` on success and `-1` on failure.
+//! Panics are caught at the boundary and reported as failures (or null pointers).
+//!
+//! The declarations of the functions are in `include/yamakan.h`, and
+//! `cargo build --release --features ffi` builds `libyamakan.so` (or `.dylib`/`.dll`) and `libyamakan.a` to link.
+//!
+//! ```c
+//! double lows[2] = {0.0, 0.0}, highs[2] = {1.0, 1.0};
+//! yamakan_optimizer *opt = yamakan_nsga2_new(lows, highs, 2, 2, 10, 0);
+//! for (int i = 0; i < 100; i++) {
+//!     uint64_t id;
+//!     double x[2], v[2];
+//!     yamakan_ask(opt, &id, x, 2);
+//!     v[0] = x[0]; v[1] = 1.0 - x[0] * x[1];
+//!     yamakan_tell(opt, id, v, 2);
+//! }
+//! yamakan_free(opt);
+//! ```
+use crate::domains::{ContinuousDomain, VecDomain};
+use crate::generators::SerialIdGenerator;
+use crate::optimizers::nsga2::{
+    Nsga2Optimizer, Nsga2Strategy, RandomGenerator, ReplaceVec, TournamentSelector,
+    UniformCrossOver,
+};
+use crate::optimizers::random::RandomOptimizer;
+use crate::{ErrorKind, Obs, ObsId, Optimizer, Result};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::os::raw::c_int;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+type Nsga2 = Nsga2Optimizer<
+    VecDomain<ContinuousDomain>,
+    Nsga2Strategy<
+        VecDomain<ContinuousDomain>,
+        RandomGenerator,
+        TournamentSelector,
+        UniformCrossOver,
+        ReplaceVec,
+    >,
+>;
+
+/// An opaque handle of an optimizer.
+#[derive(Debug)]
+#[allow(non_camel_case_types)]
+pub struct yamakan_optimizer {
+    inner: Inner,
+    dim: usize,
+    objectives: usize,
+    rng: StdRng,
+    idg: SerialIdGenerator,
+    asked: HashMap<ObsId, Vec<f64>>,
+}
+impl yamakan_optimizer {
+    fn new(inner: Inner, dim: usize, objectives: usize, seed: u64) -> Self {
+        Self {
+            inner,
+            dim,
+            objectives,
+            rng: StdRng::seed_from_u64(seed),
+            idg: SerialIdGenerator::new(),
+            asked: HashMap::new(),
+        }
+    }
+
+    fn ask(&mut self) -> Result<Obs<Vec<f64>>> {
+        let obs = match &mut self.inner {
+            Inner::Random(o) => track!(o.ask(&mut self.rng, &mut self.idg))?,
+            Inner::Nsga2(o) => track!(o.ask(&mut self.rng, &mut self.idg))?,
+        };
+        self.asked.insert(obs.id, obs.param.clone());
+        Ok(obs)
+    }
+
+    fn tell(&mut self, id: ObsId, value: Vec<f64>) -> Result<()> {
+        track_assert_eq!(value.len(), self.objectives, ErrorKind::InvalidInput);
+        let param = track_assert_some!(
+            self.asked.remove(&id),
+            ErrorKind::UnknownObservation; id
+        );
+        let obs = Obs { id, param, value };
+        match &mut self.inner {
+            Inner::Random(o) => track!(o.tell(obs)),
+            Inner::Nsga2(o) => track!(o.tell(obs)),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Inner {
+    Random(RandomOptimizer<VecDomain<ContinuousDomain>, Vec<f64>>),
+    Nsga2(Box<Nsga2>),
+}
+
+/// Makes a new random optimizer.
+///
+/// The `i`-th element of a parameter is sampled from the range `lows[i]..highs[i]`.
+/// If the arguments are invalid, this function returns a null pointer.
+///
+/// # Safety
+///
+/// `lows` and `highs` must point to `dim` readable `f64`s.
+#[no_mangle]
+pub unsafe extern "C" fn yamakan_random_new(
+    lows: *const f64,
+    highs: *const f64,
+    dim: usize,
+    objectives: usize,
+    seed: u64,
+) -> *mut yamakan_optimizer {
+    ffi_guard(std::ptr::null_mut(), || {
+        let domain = match params_domain(lows, highs, dim) {
+            Ok(domain) => domain,
+            Err(_) => return std::ptr::null_mut(),
+        };
+        if objectives == 0 {
+            return std::ptr::null_mut();
+        }
+        let inner = Inner::Random(RandomOptimizer::new(domain));
+        Box::into_raw(Box::new(yamakan_optimizer::new(
+            inner, dim, objectives, seed,
+        )))
+    })
+}
+
+/// Makes a new NSGA-II optimizer.
+///
+/// The `i`-th element of a parameter is searched in the range `lows[i]..highs[i]`.
+/// If the arguments are invalid, this function returns a null pointer.
+///
+/// # Safety
+///
+/// `lows` and `highs` must point to `dim` readable `f64`s.
+#[no_mangle]
+pub unsafe extern "C" fn yamakan_nsga2_new(
+    lows: *const f64,
+    highs: *const f64,
+    dim: usize,
+    objectives: usize,
+    population_size: usize,
+    seed: u64,
+) -> *mut yamakan_optimizer {
+    ffi_guard(std::ptr::null_mut(), || {
+        let domain = match params_domain(lows, highs, dim) {
+            Ok(domain) => domain,
+            Err(_) => return std::ptr::null_mut(),
+        };
+        if objectives == 0 {
+            return std::ptr::null_mut();
+        }
+        let strategy = Nsga2Strategy::new(
+            RandomGenerator,
+            TournamentSelector::default(),
+            UniformCrossOver::default(),
+            ReplaceVec::default(),
+        );
+        match Nsga2Optimizer::new(domain, population_size, strategy) {
+            Ok(o) => Box::into_raw(Box::new(yamakan_optimizer::new(
+                Inner::Nsga2(Box::new(o)),
+                dim,
+                objectives,
+                seed,
+            ))),
+            Err(_) => std::ptr::null_mut(),
+        }
+    })
+}
+
+/// Releases the given optimizer.
+///
+/// # Safety
+///
+/// `opt` must be a pointer returned by one of the `yamakan_*_new` functions (or null),
+/// and must not be used after calling this function.
+#[no_mangle]
+pub unsafe extern "C" fn yamakan_free(opt: *mut yamakan_optimizer) {
+    ffi_guard((), || {
+        if !opt.is_null() {
+            drop(Box::from_raw(opt));
+        }
+    })
+}
+
+/// Asks the next parameter to be evaluated.
+///
+/// The identifier of the observation is written to `id` and the parameter is written to `param`.
+/// If `dim` differs from the dimension of the optimizer, this function fails.
+///
+/// # Safety
+///
+/// `opt` must be a valid optimizer, `id` must point to a writable `u64` and
+/// `param` must point to `dim` writable `f64`s.
+#[no_mangle]
+pub unsafe extern "C" fn yamakan_ask(
+    opt: *mut yamakan_optimizer,
+    id: *mut u64,
+    param: *mut f64,
+    dim: usize,
+) -> c_int {
+    ffi_guard(-1, || {
+        let opt = match opt.as_mut() {
+            Some(opt) => opt,
+            None => return -1,
+        };
+        if id.is_null() || param.is_null() || dim != opt.dim {
+            return -1;
+        }
+        match opt.ask() {
+            Ok(obs) => {
+                *id = obs.id.get();
+                slice::from_raw_parts_mut(param, dim).copy_from_slice(&obs.param);
+                0
+            }
+            Err(_) => -1,
+        }
+    })
+}
+
+/// Tells the evaluated values of the observation identified by `id`.
+///
+/// If `objectives` differs from the number of the objectives of the optimizer, this function fails.
+///
+/// # Safety
+///
+/// `opt` must be a valid optimizer and `values` must point to `objectives` readable `f64`s.
+#[no_mangle]
+pub unsafe extern "C" fn yamakan_tell(
+    opt: *mut yamakan_optimizer,
+    id: u64,
+    values: *const f64,
+    objectives: usize,
+) -> c_int {
+    ffi_guard(-1, || {
+        let opt = match opt.as_mut() {
+            Some(opt) => opt,
+            None => return -1,
+        };
+        if values.is_null() {
+            return -1;
+        }
+        let values = slice::from_raw_parts(values, objectives).to_vec();
+        match opt.tell(ObsId::new(id), values) {
+            Ok(()) => 0,
+            Err(_) => -1,
+        }
+    })
+}
+
+/// Calls `f`, and returns `default` instead of unwinding across the FFI boundary if `f` panics.
+fn ffi_guard<T, F: FnOnce() -> T>(default: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+unsafe fn params_domain(
+    lows: *const f64,
+    highs: *const f64,
+    dim: usize,
+) -> Result<VecDomain<ContinuousDomain>> {
+    track_assert!(!lows.is_null() && !highs.is_null(), ErrorKind::InvalidInput);
+    track_assert!(dim > 0, ErrorKind::InvalidInput);
+    let lows = slice::from_raw_parts(lows, dim);
+    let highs = slice::from_raw_parts(highs, dim);
+    lows.iter()
+        .zip(highs.iter())
+        .map(|(&low, &high)| track!(ContinuousDomain::new(low, high)))
+        .collect::<Result<_>>()
+        .map(VecDomain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ffi_works() {
+        let lows = [0.0, 0.0];
+        let highs = [1.0, 1.0];
+        unsafe {
+            assert!(yamakan_random_new(highs.as_ptr(), lows.as_ptr(), 2, 1, 0).is_null());
+            assert!(yamakan_random_new(lows.as_ptr(), highs.as_ptr(), 2, 0, 0).is_null());
+            assert_eq!(
+                yamakan_ask(std::ptr::null_mut(), &mut 0, [0.0].as_mut_ptr(), 1),
+                -1
+            );
+            assert_eq!(ffi_guard(-1, || -> c_int { panic!("boom") }), -1);
+
+            for opt in [
+                yamakan_random_new(lows.as_ptr(), highs.as_ptr(), 2, 2, 0),
+                yamakan_nsga2_new(lows.as_ptr(), highs.as_ptr(), 2, 2, 10, 0),
+            ] {
+                assert!(!opt.is_null());
+                for _ in 0..30 {
+                    let mut id = 0;
+                    let mut x = [0.0; 2];
+                    assert_eq!(yamakan_ask(opt, &mut id, x.as_mut_ptr(), 2), 0);
+                    assert!(x.iter().all(|&x| (0.0..1.0).contains(&x)));
+
+                    let v = [x[0], 1.0 - x[0] * x[1]];
+                    assert_eq!(yamakan_tell(opt, id, v.as_ptr(), 1), -1);
+                    assert_eq!(yamakan_tell(opt, id, v.as_ptr(), 2), 0);
+                    assert_eq!(yamakan_tell(opt, id, v.as_ptr(), 2), -1);
+                }
+                yamakan_free(opt);
+            }
+        }
+    }
+}
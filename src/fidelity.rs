@@ -0,0 +1,186 @@
+//! Conversion of multi-fidelity observations for single-fidelity optimizers.
+//!
+//! Multi-fidelity studies produce several observations of the same configuration evaluated with different budgets.
+//! Single-fidelity optimizers (and analyses) expect one value per configuration,
+//! so the observations need to be reduced in a fidelity-aware way before they are told.
+//!
+//! `rank_by_fidelity` applies the same mapping as `ShaOptimizer` and `AshaOptimizer` use internally,
+//! and `to_single_fidelity` reduces the observations of each configuration into one value by a `FidelityPolicy`.
+use crate::curve::Curve;
+use crate::{ErrorKind, MfObs, Obs, ObsId, Ranked, Result};
+use std::collections::BTreeMap;
+
+/// How to reduce the multi-fidelity observations of a configuration into a single value.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FidelityPolicy {
+    /// Only the observations whose consumption reached the maximum budget are kept.
+    ///
+    /// Configurations that have not been fully evaluated are dropped.
+    #[default]
+    MaxFidelityOnly,
+
+    /// The observation having the largest consumption is kept for each configuration.
+    ///
+    /// Note that values measured with different budgets are not comparable in general.
+    HighestFidelity,
+
+    /// The value at the maximum budget is extrapolated from the observations of each configuration.
+    ///
+    /// The observations are regarded as a learning curve over the consumption,
+    /// and a power law is fitted to it (see `Curve::fit_power_law`).
+    /// Configurations having fewer than three observations with positive consumption,
+    /// or which have already been fully evaluated, keep their highest-fidelity values.
+    Extrapolate,
+}
+
+/// Converts multi-fidelity observations into single-fidelity ones having ranked values.
+///
+/// The rank of an observation is `max_budget - consumption` (saturated at `0`),
+/// so any value measured with a larger budget is regarded as better than values measured with smaller budgets.
+/// This is the mapping `ShaOptimizer` and `AshaOptimizer` apply before telling observations to their inner optimizers.
+pub fn rank_by_fidelity<P, V, I>(obss: I, max_budget: u64) -> Vec<Obs<P, Ranked<V>>>
+where
+    I: IntoIterator<Item = MfObs<P, V>>,
+{
+    obss.into_iter()
+        .map(|obs| {
+            let rank = max_budget.saturating_sub(obs.budget.consumption);
+            Obs::from(obs).map_value(|value| Ranked { rank, value })
+        })
+        .collect()
+}
+
+/// Reduces multi-fidelity observations into one observation per configuration by the given policy.
+///
+/// Observations sharing the same identifier are regarded as the evaluations of the same configuration,
+/// and the parameter of the highest-fidelity one is used.
+/// The resulting observations are sorted by their identifiers.
+///
+/// # Errors
+///
+/// If a value is not finite, an `ErrorKind::InvalidInput` error will be returned.
+pub fn to_single_fidelity<P: Clone>(
+    obss: &[MfObs<P, f64>],
+    max_budget: u64,
+    policy: FidelityPolicy,
+) -> Result<Vec<Obs<P, f64>>> {
+    let mut configs: BTreeMap<ObsId, Vec<&MfObs<P, f64>>> = BTreeMap::new();
+    for obs in obss {
+        track_assert!(obs.value.is_finite(), ErrorKind::InvalidInput; obs.id, obs.value);
+        configs.entry(obs.id).or_default().push(obs);
+    }
+
+    let mut converted = Vec::with_capacity(configs.len());
+    for (id, mut history) in configs {
+        // Stable, so later observations win ties.
+        history.sort_by_key(|o| o.budget.consumption);
+        let highest = history[history.len() - 1];
+        let value = match policy {
+            FidelityPolicy::MaxFidelityOnly => {
+                if highest.budget.consumption < max_budget {
+                    continue;
+                }
+                highest.value
+            }
+            FidelityPolicy::HighestFidelity => highest.value,
+            FidelityPolicy::Extrapolate => track!(extrapolate(&history, max_budget))?,
+        };
+        converted.push(Obs {
+            id,
+            param: highest.param.clone(),
+            value,
+        });
+    }
+    Ok(converted)
+}
+
+fn extrapolate<P>(history: &[&MfObs<P, f64>], max_budget: u64) -> Result<f64> {
+    let highest = history[history.len() - 1];
+    if highest.budget.consumption >= max_budget {
+        return Ok(highest.value);
+    }
+
+    let mut curve = Curve::new();
+    for (i, obs) in history.iter().enumerate() {
+        let step = obs.budget.consumption;
+        let superseded = history
+            .get(i + 1)
+            .is_some_and(|o| o.budget.consumption == step);
+        if step > 0 && !superseded {
+            track!(curve.push(step, obs.value))?;
+        }
+    }
+    if curve.len() < 3 {
+        return Ok(highest.value);
+    }
+    let law = track!(curve.fit_power_law())?;
+    Ok(law.predict(max_budget))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Budget;
+    use trackable::result::TestResult;
+
+    fn mf_obs(id: u64, consumption: u64, value: f64) -> MfObs<u64, f64> {
+        MfObs {
+            id: ObsId::new(id),
+            budget: Budget {
+                amount: consumption,
+                consumption,
+            },
+            param: id * 10,
+            value,
+        }
+    }
+
+    #[test]
+    fn to_single_fidelity_works() -> TestResult {
+        let obss = vec![
+            mf_obs(1, 1, 5.0),
+            mf_obs(0, 1, 3.0),
+            mf_obs(0, 9, 1.0),
+            mf_obs(2, 1, 10.0),
+            mf_obs(2, 2, 10.0 / 2f64.powf(0.5)),
+            mf_obs(2, 4, 5.0),
+        ];
+
+        let converted = track!(to_single_fidelity(
+            &obss,
+            9,
+            FidelityPolicy::MaxFidelityOnly
+        ))?;
+        assert_eq!(converted.len(), 1);
+        assert_eq!((converted[0].id, converted[0].param), (ObsId::new(0), 0));
+        assert_eq!(converted[0].value, 1.0);
+
+        let converted = track!(to_single_fidelity(
+            &obss,
+            9,
+            FidelityPolicy::HighestFidelity
+        ))?;
+        let values = converted.iter().map(|o| o.value).collect::<Vec<_>>();
+        assert_eq!(values, [1.0, 5.0, 5.0]);
+
+        let converted = track!(to_single_fidelity(&obss, 9, FidelityPolicy::Extrapolate))?;
+        assert_eq!(converted[0].value, 1.0);
+        assert_eq!(converted[1].value, 5.0);
+        assert!(converted[2].value < 5.0, "{}", converted[2].value);
+
+        assert!(
+            to_single_fidelity(&[mf_obs(0, 1, f64::NAN)], 9, FidelityPolicy::default()).is_err()
+        );
+
+        let ranked = rank_by_fidelity(obss, 9);
+        assert_eq!(
+            ranked[1].value,
+            Ranked {
+                rank: 8,
+                value: 3.0
+            }
+        );
+        assert!(ranked[2].value < ranked[1].value);
+        Ok(())
+    }
+}
@@ -1,5 +1,11 @@
 //! Observation identifier generators.
-use crate::{IdGen, ObsId, Result};
+use crate::{ErrorKind, IdGen, ObsId, Result};
+use rand::Rng;
+use std::cmp;
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
 /// An implementation of `IdGen` that generates serial identifiers starting from zero.
 #[derive(Debug, Default)]
@@ -20,6 +26,41 @@ impl IdGen for SerialIdGenerator {
     }
 }
 
+/// An implementation of `IdGen` that generates identifiers packing a worker identifier and serial sequence numbers.
+///
+/// Generators having different worker identifiers never generate the same identifier without any coordination,
+/// and the worker that proposed an observation can be recovered by `ObsId::worker`.
+#[derive(Debug)]
+pub struct PartitionedIdGenerator {
+    worker: u16,
+    next_sequence: u64,
+}
+impl PartitionedIdGenerator {
+    /// Makes a new `PartitionedIdGenerator` instance whose sequence numbers start from zero.
+    pub const fn new(worker: u16) -> Self {
+        Self {
+            worker,
+            next_sequence: 0,
+        }
+    }
+
+    /// Returns the worker identifier of this generator.
+    pub const fn worker(&self) -> u16 {
+        self.worker
+    }
+}
+impl IdGen for PartitionedIdGenerator {
+    /// # Errors
+    ///
+    /// If the sequence numbers are exhausted (see `ObsId::MAX_SEQUENCE`),
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    fn generate(&mut self) -> Result<ObsId> {
+        let id = track!(ObsId::with_worker(self.worker, self.next_sequence))?;
+        self.next_sequence += 1;
+        Ok(id)
+    }
+}
+
 /// An implementation of `IdGen` that always returns the same identifier.
 #[derive(Debug)]
 pub struct ConstIdGenerator {
@@ -38,3 +79,214 @@ impl IdGen for ConstIdGenerator {
         Ok(self.id)
     }
 }
+
+/// This trait allows leasing blocks of identifiers from a coordinator.
+///
+/// Implementations must not hand out overlapping ranges (even to different processes).
+pub trait IdLeaser {
+    /// Leases a new block of `count` identifiers.
+    fn lease(&mut self, count: u64) -> Result<Range<u64>>;
+}
+impl<T: IdLeaser + ?Sized> IdLeaser for &mut T {
+    fn lease(&mut self, count: u64) -> Result<Range<u64>> {
+        (**self).lease(count)
+    }
+}
+
+/// An implementation of `IdLeaser` backed by a shared atomic counter.
+///
+/// Cloned instances share the same counter, so they can be used by multiple threads.
+#[derive(Debug, Default, Clone)]
+pub struct AtomicIdLeaser {
+    next_id: Arc<AtomicU64>,
+}
+impl AtomicIdLeaser {
+    /// Makes a new `AtomicIdLeaser` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+impl IdLeaser for AtomicIdLeaser {
+    fn lease(&mut self, count: u64) -> Result<Range<u64>> {
+        let start = self
+            .next_id
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_add(count));
+        let start = track_assert_some!(start.ok(), ErrorKind::Other; count);
+        Ok(start..start + count)
+    }
+}
+
+/// An implementation of `IdGen` that generates identifiers from blocks leased by an `IdLeaser`.
+///
+/// This is useful for distributed setups where each worker has its own generator.
+#[derive(Debug)]
+pub struct LeaseIdGenerator<L> {
+    leaser: L,
+    block_size: u64,
+    block: Range<u64>,
+}
+impl<L: IdLeaser> LeaseIdGenerator<L> {
+    /// Makes a new `LeaseIdGenerator` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `block_size` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(leaser: L, block_size: u64) -> Result<Self> {
+        track_assert!(block_size > 0, ErrorKind::InvalidInput);
+        Ok(Self {
+            leaser,
+            block_size,
+            block: 0..0,
+        })
+    }
+
+    /// Returns a reference to the underlying leaser.
+    pub fn leaser(&self) -> &L {
+        &self.leaser
+    }
+}
+impl<L: IdLeaser> IdGen for LeaseIdGenerator<L> {
+    fn generate(&mut self) -> Result<ObsId> {
+        if self.block.start == self.block.end {
+            self.block = track!(self.leaser.lease(self.block_size))?;
+            track_assert!(self.block.start < self.block.end, ErrorKind::Other; self.block);
+        }
+        let id = self.block.start;
+        self.block.start += 1;
+        Ok(ObsId::new(id))
+    }
+}
+
+/// An implementation of `IdGen` that retries failed generations of the inner generator.
+///
+/// Between retries, it sleeps with exponential backoff and random jitter
+/// (the sleep duration is uniformly sampled from `[0, backoff)`).
+#[derive(Debug)]
+pub struct RetryIdGenerator<G> {
+    inner: G,
+    max_retries: usize,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+impl<G: IdGen> RetryIdGenerator<G> {
+    /// Makes a new `RetryIdGenerator` instance.
+    ///
+    /// By default, it retries up to `3` times with the initial backoff of `10` milliseconds
+    /// (and the maximum backoff of `1` second).
+    pub fn new(inner: G) -> Self {
+        Self {
+            inner,
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(1),
+        }
+    }
+
+    /// Sets the maximum number of retries.
+    pub fn max_retries(mut self, n: usize) -> Self {
+        self.max_retries = n;
+        self
+    }
+
+    /// Sets the initial and the maximum backoff durations.
+    pub fn backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Returns a reference to the underlying generator.
+    pub fn inner(&self) -> &G {
+        &self.inner
+    }
+
+    /// Consumes the `RetryIdGenerator`, returning the underlying generator.
+    pub fn into_inner(self) -> G {
+        self.inner
+    }
+}
+impl<G: IdGen> IdGen for RetryIdGenerator<G> {
+    fn generate(&mut self) -> Result<ObsId> {
+        let mut backoff = self.initial_backoff;
+        let mut retries = 0;
+        loop {
+            match self.inner.generate() {
+                Ok(id) => return Ok(id),
+                Err(e) if retries >= self.max_retries => return Err(track!(e)),
+                Err(_) => {
+                    let jitter = rand::thread_rng().gen_range(0.0..1.0);
+                    std::thread::sleep(backoff.mul_f64(jitter));
+                    backoff = cmp::min(backoff * 2, self.max_backoff);
+                    retries += 1;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trackable::result::TestResult;
+
+    #[derive(Debug)]
+    struct FlakyIdGenerator {
+        failures: usize,
+        inner: SerialIdGenerator,
+    }
+    impl IdGen for FlakyIdGenerator {
+        fn generate(&mut self) -> Result<ObsId> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                track_panic!(ErrorKind::Other);
+            }
+            self.inner.generate()
+        }
+    }
+
+    #[test]
+    fn generators_work() -> TestResult {
+        let leaser = AtomicIdLeaser::new();
+        let mut idg0 = track!(LeaseIdGenerator::new(leaser.clone(), 2))?;
+        let mut idg1 = track!(LeaseIdGenerator::new(leaser, 2))?;
+        let ids = [
+            track!(idg0.generate())?,
+            track!(idg1.generate())?,
+            track!(idg0.generate())?,
+            track!(idg0.generate())?,
+        ];
+        assert_eq!(
+            ids.iter().map(|id| id.get()).collect::<Vec<_>>(),
+            [0, 2, 1, 4]
+        );
+
+        let mut leaser = AtomicIdLeaser::new();
+        track!(leaser.lease(u64::MAX - 1))?;
+        assert!(leaser.lease(2).is_err());
+        assert_eq!(track!(leaser.lease(1))?, u64::MAX - 1..u64::MAX);
+
+        let flaky = FlakyIdGenerator {
+            failures: 2,
+            inner: SerialIdGenerator::new(),
+        };
+        let mut idg = RetryIdGenerator::new(flaky)
+            .backoff(Duration::from_millis(1), Duration::from_millis(1));
+        assert_eq!(track!(idg.generate())?.get(), 0);
+
+        let flaky = FlakyIdGenerator {
+            failures: 2,
+            inner: SerialIdGenerator::new(),
+        };
+        let mut idg = RetryIdGenerator::new(flaky).max_retries(1);
+        assert!(idg.generate().is_err());
+
+        let mut idg0 = PartitionedIdGenerator::new(0);
+        let mut idg1 = PartitionedIdGenerator::new(1);
+        let id0 = track!(idg0.generate())?;
+        let id1 = track!(idg1.generate())?;
+        assert_ne!(id0, id1);
+        assert_eq!((id1.worker(), id1.sequence()), (1, 0));
+        assert_eq!(track!(idg1.generate())?.sequence(), 1);
+        Ok(())
+    }
+}
@@ -0,0 +1,251 @@
+//! Generation of initial parameters.
+//!
+//! `Generate` implementations are used by optimizers to sample their initial (or exploratory) parameters,
+//! e.g., the initial population of `Nsga2Optimizer` and the samples of `RandomOptimizer`.
+//!
+//! Besides plain random sampling, this module provides space-filling designs for continuous vectors
+//! (Latin hypercube sampling and Sobol sequences), which cover the search space more evenly than random samples.
+//!
+//! # References
+//!
+//! - [A Comparison of Three Methods for Selecting Values of Input Variables in the Analysis of Output from a Computer Code](https://doi.org/10.2307/1268522)
+//! - [Constructing Sobol sequences with better two-dimensional projections](https://doi.org/10.1137/070709359)
+use crate::domains::{ContinuousDomain, VecDomain};
+use crate::{Domain, ErrorKind, Result};
+use rand::distributions::Distribution;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+/// This trait allows generating new parameters (individuals).
+pub trait Generate<D: Domain> {
+    /// Generates a new individual.
+    fn generate<R: Rng>(&mut self, rng: R, domain: &D) -> Result<D::Point>;
+}
+
+/// Random generator.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RandomGenerator;
+
+impl<D> Generate<D> for RandomGenerator
+where
+    D: Domain + Distribution<<D as Domain>::Point>,
+{
+    fn generate<R: Rng>(&mut self, mut rng: R, domain: &D) -> Result<D::Point> {
+        Ok(domain.sample(&mut rng))
+    }
+}
+
+/// Latin hypercube sampling (LHS) generator.
+///
+/// Points are generated in batches of `batch_size`.
+/// In each batch, the range of every dimension is divided into `batch_size` equal strata and
+/// each stratum is sampled exactly once.
+#[derive(Debug, Clone)]
+pub struct LatinHypercube {
+    batch_size: usize,
+    batch: Vec<Vec<f64>>,
+}
+impl LatinHypercube {
+    /// Makes a new `LatinHypercube` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `batch_size` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(batch_size: usize) -> Result<Self> {
+        track_assert!(batch_size > 0, ErrorKind::InvalidInput);
+        Ok(Self {
+            batch_size,
+            batch: Vec::new(),
+        })
+    }
+
+    /// Returns the batch size.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    fn refill<R: Rng>(&mut self, mut rng: R, dimensions: usize) {
+        let n = self.batch_size;
+        let mut batch = vec![Vec::with_capacity(dimensions); n];
+        let mut strata = (0..n).collect::<Vec<_>>();
+        for _ in 0..dimensions {
+            strata.shuffle(&mut rng);
+            for (point, &s) in batch.iter_mut().zip(strata.iter()) {
+                point.push((s as f64 + rng.gen::<f64>()) / n as f64);
+            }
+        }
+        self.batch = batch;
+    }
+}
+impl Generate<VecDomain<ContinuousDomain>> for LatinHypercube {
+    fn generate<R: Rng>(
+        &mut self,
+        rng: R,
+        domain: &VecDomain<ContinuousDomain>,
+    ) -> Result<Vec<f64>> {
+        if self.batch.last().is_none_or(|p| p.len() != domain.0.len()) {
+            self.refill(rng, domain.0.len());
+        }
+        let point = track_assert_some!(self.batch.pop(), ErrorKind::Bug);
+        Ok(scale(domain, &point))
+    }
+}
+
+// (s, a, m_1..m_s) of the dimensions 2, 3, ... taken from the `new-joe-kuo-6.21201` table.
+const SOBOL_PARAMS: [(u32, u32, &[u32]); 15] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+];
+
+const SOBOL_BITS: usize = 32;
+
+/// Sobol sequence generator.
+///
+/// The sequence is randomized by a digital shift (i.e., XOR with random bits drawn at the first generation).
+/// Up to `Sobol::MAX_DIMENSIONS` dimensions are supported.
+#[derive(Debug, Clone, Default)]
+pub struct Sobol {
+    directions: Vec<[u32; SOBOL_BITS]>,
+    shift: Vec<u32>,
+    current: Vec<u32>,
+    index: u32,
+}
+impl Sobol {
+    /// The maximum number of dimensions supported by this generator.
+    pub const MAX_DIMENSIONS: usize = SOBOL_PARAMS.len() + 1;
+
+    /// Makes a new `Sobol` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn init<R: Rng>(&mut self, mut rng: R, dimensions: usize) -> Result<()> {
+        track_assert!(
+            dimensions <= Self::MAX_DIMENSIONS,
+            ErrorKind::InvalidInput; dimensions
+        );
+        let mut directions = Vec::with_capacity(dimensions);
+        for d in 0..dimensions {
+            let mut v = [0; SOBOL_BITS];
+            if d == 0 {
+                for (k, v) in v.iter_mut().enumerate() {
+                    *v = 1 << (SOBOL_BITS - 1 - k);
+                }
+            } else {
+                let (s, a, m) = SOBOL_PARAMS[d - 1];
+                let s = s as usize;
+                for k in 0..SOBOL_BITS {
+                    v[k] = if k < s {
+                        m[k] << (SOBOL_BITS - 1 - k)
+                    } else {
+                        let mut x = v[k - s] ^ (v[k - s] >> s);
+                        for l in 1..s {
+                            x ^= ((a >> (s - 1 - l)) & 1) * v[k - l];
+                        }
+                        x
+                    };
+                }
+            }
+            directions.push(v);
+        }
+        self.directions = directions;
+        self.shift = (0..dimensions).map(|_| rng.gen()).collect();
+        self.current = vec![0; dimensions];
+        self.index = 0;
+        Ok(())
+    }
+}
+impl Generate<VecDomain<ContinuousDomain>> for Sobol {
+    fn generate<R: Rng>(
+        &mut self,
+        rng: R,
+        domain: &VecDomain<ContinuousDomain>,
+    ) -> Result<Vec<f64>> {
+        if self.current.len() != domain.0.len() {
+            track!(self.init(rng, domain.0.len()))?;
+        }
+        let point = self
+            .current
+            .iter()
+            .zip(self.shift.iter())
+            .map(|(&x, &s)| f64::from(x ^ s) / 2f64.powi(SOBOL_BITS as i32))
+            .collect::<Vec<_>>();
+
+        // Gray code construction: flip the direction number of the rightmost zero bit of the index.
+        let c = self.index.trailing_ones() as usize;
+        track_assert!(c < SOBOL_BITS, ErrorKind::Other; self.index);
+        for (x, v) in self.current.iter_mut().zip(self.directions.iter()) {
+            *x ^= v[c];
+        }
+        self.index += 1;
+        Ok(scale(domain, &point))
+    }
+}
+
+fn scale(domain: &VecDomain<ContinuousDomain>, unit: &[f64]) -> Vec<f64> {
+    domain
+        .0
+        .iter()
+        .zip(unit.iter())
+        .map(|(d, &u)| d.clamp(d.low() + u * d.size()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn generators_work() -> TestResult {
+        let mut rng = rand::thread_rng();
+        let domain = VecDomain(vec![track!(ContinuousDomain::new(-1.0, 1.0))?; 3]);
+
+        let mut lhs = track!(LatinHypercube::new(10))?;
+        let points = (0..10)
+            .map(|_| track!(lhs.generate(&mut rng, &domain)))
+            .collect::<Result<Vec<_>>>()?;
+        for i in 0..3 {
+            let mut strata = points
+                .iter()
+                .map(|p| ((p[i] + 1.0) / 2.0 * 10.0) as usize)
+                .collect::<Vec<_>>();
+            strata.sort_unstable();
+            assert_eq!(strata, (0..10).collect::<Vec<_>>());
+        }
+
+        let mut sobol = Sobol::new();
+        let points = (0..16)
+            .map(|_| track!(sobol.generate(&mut rng, &domain)))
+            .collect::<Result<Vec<_>>>()?;
+        for i in 0..3 {
+            let mut strata = points
+                .iter()
+                .map(|p| ((p[i] + 1.0) / 2.0 * 16.0) as usize)
+                .collect::<Vec<_>>();
+            strata.sort_unstable();
+            assert_eq!(strata, (0..16).collect::<Vec<_>>());
+        }
+        assert!(points.iter().all(|p| domain.contains(p)));
+
+        let domain = VecDomain(vec![
+            track!(ContinuousDomain::new(0.0, 1.0))?;
+            Sobol::MAX_DIMENSIONS + 1
+        ]);
+        assert!(Sobol::new().generate(&mut rng, &domain).is_err());
+        Ok(())
+    }
+}
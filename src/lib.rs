@@ -6,21 +6,54 @@
 #[macro_use]
 extern crate trackable;
 
-use rand::Rng;
+use rand::{Rng, RngCore};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, MutexGuard};
+use trackable::error::ErrorKindExt;
 
-pub use self::budget::Budget;
-pub use self::error::{Error, ErrorKind};
-pub use self::observation::{MfObs, Obs, ObsId};
+pub use self::budget::{Budget, BudgetUnit, LinearUnit, StudyBudget};
+pub use self::error::{Error, ErrorContext, ErrorKind};
+pub use self::observation::{MfObs, Obs, ObsId, ObsState, SeedStrategy, SplitMixSeed};
+pub use self::study::StudyConfig;
+pub use self::value::{FiniteF64, MeasuredValue, NanPolicy};
 
+pub mod analysis;
+#[cfg(feature = "async")]
+pub mod async_;
+pub mod curve;
 pub mod domains;
+pub mod encoding;
+#[cfg(feature = "parallel")]
+pub mod executor;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fidelity;
 pub mod generators;
+pub mod init;
+pub mod lint;
 pub mod optimizers;
+#[cfg(feature = "optuna")]
+pub mod optuna;
+pub mod pareto;
+pub mod presets;
+pub mod provenance;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod scalarize;
+pub mod significance;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod worker;
 
 mod budget;
 mod error;
 mod observation;
+mod study;
+mod value;
 
 /// This crate specific `Result` type.
 pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -48,6 +81,137 @@ pub trait Optimizer {
     /// Some implementations may return an `ErrorKind::UnknownObservation` error
     /// if this optimizer does not known (or has not generated) the specified observation.
     fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()>;
+
+    /// Tells the results of multiple observations to this optimizer.
+    ///
+    /// This is useful for bulk-loading historical observations.
+    /// The default implementation simply calls `tell` for each observation,
+    /// but some implementations override it to avoid the per-observation overhead.
+    ///
+    /// # Errors
+    ///
+    /// Errors are the same as `tell`.
+    /// Observations before the failed one may have already been told.
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        for obs in obss {
+            track!(self.tell(obs))?;
+        }
+        Ok(())
+    }
+}
+
+/// Object-safe version of `Optimizer`.
+///
+/// Since `Optimizer::ask` is generic, `Box<dyn Optimizer>` is not allowed.
+/// This trait takes the random number generator and the identifier generator as trait objects instead,
+/// and is implemented for every `Optimizer`.
+/// Conversely, `Box<dyn DynOptimizer<..>>` implements `Optimizer`,
+/// so heterogeneous optimizers can be combined (e.g., by `PortfolioOptimizer`) without enum wrappers.
+pub trait DynOptimizer {
+    /// The parameter to be optimized.
+    type Param;
+
+    /// The value obtained as a result of a parameter evaluation.
+    type Value;
+
+    /// Asks the next parameter to be evaluated.
+    fn dyn_ask(&mut self, rng: &mut dyn RngCore, idg: &mut dyn IdGen) -> Result<Obs<Self::Param>>;
+
+    /// Tells the result of an observation to this optimizer.
+    fn dyn_tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()>;
+
+    /// Tells the results of multiple observations to this optimizer.
+    fn dyn_tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()>;
+}
+impl<O: Optimizer> DynOptimizer for O {
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn dyn_ask(&mut self, rng: &mut dyn RngCore, idg: &mut dyn IdGen) -> Result<Obs<Self::Param>> {
+        track!(self.ask(rng, idg))
+    }
+
+    fn dyn_tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        track!(self.tell(obs))
+    }
+
+    fn dyn_tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        track!(self.tell_batch(obss))
+    }
+}
+macro_rules! impl_boxed_dyn_optimizer {
+    ($($bound:tt)*) => {
+        impl<'a, P, V> Optimizer for Box<dyn DynOptimizer<Param = P, Value = V> $($bound)* + 'a> {
+            type Param = P;
+            type Value = V;
+
+            fn ask<R: Rng, G: IdGen>(&mut self, mut rng: R, mut idg: G) -> Result<Obs<Self::Param>> {
+                track!((**self).dyn_ask(&mut rng, &mut idg))
+            }
+
+            fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+                track!((**self).dyn_tell(obs))
+            }
+
+            fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+                track!((**self).dyn_tell_batch(obss))
+            }
+        }
+    };
+}
+impl_boxed_dyn_optimizer!();
+impl_boxed_dyn_optimizer!(+ Send);
+
+/// This trait provides ask-and-tell interface through shared references.
+///
+/// Implementations synchronize their internal states by themselves,
+/// so that they can be shared between threads (e.g., via `Arc` in a server) without a lock around the whole optimizer.
+///
+/// Any `Optimizer` can be used as a `SharedOptimizer` by wrapping it in a `Mutex`,
+/// though all asks and tells are serialized in that case.
+pub trait SharedOptimizer {
+    /// The parameter to be optimized.
+    type Param;
+
+    /// The value obtained as a result of a parameter evaluation.
+    type Value;
+
+    /// Asks the next parameter to be evaluated.
+    ///
+    /// The evaluation result should be told to this optimizer.
+    fn ask<R: Rng, G: IdGen>(&self, rng: R, idg: G) -> Result<Obs<Self::Param>>;
+
+    /// Tells the result of an observation to this optimizer.
+    ///
+    /// # Errors
+    ///
+    /// Some implementations may return an `ErrorKind::UnknownObservation` error
+    /// if this optimizer does not known (or has not generated) the specified observation.
+    fn tell(&self, obs: Obs<Self::Param, Self::Value>) -> Result<()>;
+}
+impl<O: Optimizer> SharedOptimizer for Mutex<O> {
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        track!(track!(lock(self))?.ask(rng, idg))
+    }
+
+    fn tell(&self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        track!(track!(lock(self))?.tell(obs))
+    }
+}
+impl<O: SharedOptimizer + ?Sized> SharedOptimizer for Arc<O> {
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        (**self).ask(rng, idg)
+    }
+
+    fn tell(&self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        (**self).tell(obs)
+    }
 }
 
 /// This trait provides ask-and-tell interface for multi-fidelity black-box optimization.
@@ -75,10 +239,49 @@ pub trait MultiFidelityOptimizer {
     fn tell(&mut self, obs: MfObs<Self::Param, Self::Value>) -> Result<()>;
 }
 
+/// This trait provides ask-and-tell interface for black-box optimization conditioned on contexts.
+///
+/// A context is a feature vector describing the situation of an evaluation (e.g., the workload or the hardware),
+/// and the asked parameter is expected to be good for the given context.
+pub trait ContextualOptimizer {
+    /// The parameter to be optimized.
+    type Param;
+
+    /// The value obtained as a result of a parameter evaluation.
+    type Value;
+
+    /// Asks the next parameter to be evaluated under the given context.
+    ///
+    /// The context is stored with the observation, so the evaluation result should be told to this optimizer.
+    fn ask<R: Rng, G: IdGen>(
+        &mut self,
+        context: &[f64],
+        rng: R,
+        idg: G,
+    ) -> Result<Obs<Self::Param>>;
+
+    /// Tells the result of an observation to this optimizer.
+    ///
+    /// # Errors
+    ///
+    /// Some implementations may return an `ErrorKind::UnknownObservation` error
+    /// if this optimizer does not known (or has not generated) the specified observation.
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()>;
+}
+
 /// Parameter search domain.
 pub trait Domain {
     /// A specific point in this domain.
     type Point;
+
+    /// Returns `true` if the given point is contained in this domain, otherwise `false`.
+    ///
+    /// Optimizers use this to reject out-of-domain parameters on tell.
+    /// The default implementation accepts every point, so domains that cannot check their points need not override it.
+    fn contains(&self, point: &Self::Point) -> bool {
+        let _ = point;
+        true
+    }
 }
 
 /// Observation ID generator.
@@ -86,7 +289,7 @@ pub trait IdGen {
     /// Generates a new identifier.
     fn generate(&mut self) -> Result<ObsId>;
 }
-impl<'a, T: IdGen + ?Sized> IdGen for &'a mut T {
+impl<T: IdGen + ?Sized> IdGen for &mut T {
     fn generate(&mut self) -> Result<ObsId> {
         (**self).generate()
     }
@@ -107,3 +310,9 @@ pub struct Ranked<T> {
     /// Value.
     pub value: T,
 }
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<'_, T>> {
+    mutex
+        .lock()
+        .map_err(|e| ErrorKind::Other.cause(e.to_string()).into())
+}
@@ -0,0 +1,232 @@
+//! Linting of parameter search spaces.
+//!
+//! `lint` detects suspicious setups of `Encoding`s (and their observed samples),
+//! which are common causes of poor optimization results.
+use crate::encoding::{Encoding, ParamValue};
+use crate::{ErrorKind, Obs, Result};
+use std::collections::BTreeMap;
+
+const LOG_SCALE_RATIO: f64 = 1e3;
+const BOUNDARY_FRACTION: f64 = 0.1;
+const MIN_SAMPLES: usize = 10;
+
+/// A warning about a parameter search space.
+///
+/// `index` is the index of the suspicious parameter element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LintWarning {
+    /// A continuous range spans many orders of magnitude but is not log-scaled.
+    ConsiderLogScale {
+        /// Parameter index.
+        index: usize,
+
+        /// The ratio of the upper bound to the lower bound.
+        ratio: f64,
+    },
+
+    /// A discrete or categorical domain has only one element, so the parameter is a constant.
+    SingleElement {
+        /// Parameter index.
+        index: usize,
+    },
+
+    /// The observed values of a discrete parameter have no monotonic trend,
+    /// so the parameter would be better treated as a categorical one.
+    ConsiderCategorical {
+        /// Parameter index.
+        index: usize,
+    },
+
+    /// The best observed values are concentrated near a bound,
+    /// so the optimum may lie outside of the range.
+    BestNearBound {
+        /// Parameter index.
+        index: usize,
+
+        /// `true` if the bound is the upper one, otherwise `false`.
+        upper: bool,
+    },
+}
+
+/// Detects suspicious setups of the given parameter encodings.
+///
+/// The following checks only use `encodings`:
+///
+/// - `LintWarning::ConsiderLogScale`: a positive continuous range whose upper bound is `1000` times (or more) its lower bound
+/// - `LintWarning::SingleElement`: a discrete or categorical domain of size `1`
+///
+/// The following checks use `obss` (and are skipped if there are less than `10` observations):
+///
+/// - `LintWarning::ConsiderCategorical`: the mean values of the levels of a discrete parameter go up and down
+///   more than half as many times as there are observed levels (at least four levels are required)
+/// - `LintWarning::BestNearBound`: all of the best 10% observations lie within 10% of the range from the same bound
+///
+/// # Errors
+///
+/// If the parameter of an observation does not match `encodings`,
+/// an `ErrorKind::InvalidInput` error will be returned.
+pub fn lint(
+    encodings: &[Encoding],
+    obss: &[Obs<Vec<ParamValue>, f64>],
+) -> Result<Vec<LintWarning>> {
+    for obs in obss {
+        track_assert_eq!(obs.param.len(), encodings.len(), ErrorKind::InvalidInput; obs.id);
+        for (e, &v) in encodings.iter().zip(obs.param.iter()) {
+            track!(e.encode(v, &mut Vec::new()); obs.id)?;
+        }
+    }
+
+    let mut best = obss
+        .iter()
+        .filter(|o| !o.value.is_nan())
+        .collect::<Vec<_>>();
+    best.sort_by(|a, b| a.value.total_cmp(&b.value));
+    best.truncate(best.len().div_ceil(10));
+
+    let mut warnings = Vec::new();
+    for (index, e) in encodings.iter().enumerate() {
+        match e {
+            Encoding::Continuous(d) if d.low() > 0.0 && d.high() / d.low() >= LOG_SCALE_RATIO => {
+                warnings.push(LintWarning::ConsiderLogScale {
+                    index,
+                    ratio: d.high() / d.low(),
+                });
+            }
+            Encoding::Discrete(d) if d.size().get() == 1 => {
+                warnings.push(LintWarning::SingleElement { index });
+            }
+            Encoding::Categorical(d) if d.cardinality().get() == 1 => {
+                warnings.push(LintWarning::SingleElement { index });
+            }
+            Encoding::Discrete(_)
+                if obss.len() >= MIN_SAMPLES && !is_monotonic_trend(obss, index) =>
+            {
+                warnings.push(LintWarning::ConsiderCategorical { index });
+            }
+            _ => {}
+        }
+        if obss.len() >= MIN_SAMPLES {
+            if let Some(upper) = best_near_bound(e, &best, index) {
+                warnings.push(LintWarning::BestNearBound { index, upper });
+            }
+        }
+    }
+    Ok(warnings)
+}
+
+fn is_monotonic_trend(obss: &[Obs<Vec<ParamValue>, f64>], index: usize) -> bool {
+    let mut levels = BTreeMap::new();
+    for obs in obss.iter().filter(|o| !o.value.is_nan()) {
+        if let ParamValue::Int(i) = obs.param[index] {
+            let e = levels.entry(i).or_insert((0.0, 0));
+            e.0 += obs.value;
+            e.1 += 1;
+        }
+    }
+    if levels.len() < 4 {
+        return true;
+    }
+
+    let means = levels
+        .values()
+        .map(|&(sum, n)| sum / n as f64)
+        .collect::<Vec<_>>();
+    let signs = means
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|d| *d != 0.0)
+        .map(|d| d > 0.0)
+        .collect::<Vec<_>>();
+    let changes = signs.windows(2).filter(|w| w[0] != w[1]).count();
+    changes * 2 <= levels.len()
+}
+
+fn best_near_bound(
+    encoding: &Encoding,
+    best: &[&Obs<Vec<ParamValue>, f64>],
+    index: usize,
+) -> Option<bool> {
+    if !matches!(encoding, Encoding::Continuous(_) | Encoding::LogScale(_)) {
+        return None;
+    }
+    let mut encoded = Vec::new();
+    for obs in best {
+        encoding.encode(obs.param[index], &mut encoded).ok()?;
+    }
+    if encoded.iter().all(|&x| x < BOUNDARY_FRACTION) {
+        Some(false)
+    } else if encoded.iter().all(|&x| x >= 1.0 - BOUNDARY_FRACTION) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::{CategoricalDomain, ContinuousDomain, DiscreteDomain};
+    use crate::ObsId;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn lint_works() -> TestResult {
+        let encodings = vec![
+            Encoding::Continuous(track!(ContinuousDomain::new(0.001, 10.0))?),
+            Encoding::Categorical(track!(CategoricalDomain::new(1))?),
+            Encoding::Discrete(track!(DiscreteDomain::new(6))?),
+            Encoding::Continuous(track!(ContinuousDomain::new(0.0, 1.0))?),
+        ];
+        let zigzag = [0.0, 5.0, 1.0, 6.0, 2.0, 7.0];
+        let obss = (0..60)
+            .map(|i| {
+                let level = i % 6;
+                let x = (i as f64 / 60.0).min(0.999);
+                Obs {
+                    id: ObsId::new(i),
+                    param: vec![
+                        ParamValue::Float(5.0),
+                        ParamValue::Category(0),
+                        ParamValue::Int(level),
+                        ParamValue::Float(x),
+                    ],
+                    value: zigzag[level as usize] + 10.0 * x,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let warnings = track!(lint(&encodings, &obss))?;
+        assert_eq!(
+            warnings,
+            [
+                LintWarning::ConsiderLogScale {
+                    index: 0,
+                    ratio: 10000.0
+                },
+                LintWarning::SingleElement { index: 1 },
+                LintWarning::ConsiderCategorical { index: 2 },
+            ]
+        );
+
+        let warnings = track!(lint(&encodings[3..], &[]))?;
+        assert!(warnings.is_empty());
+
+        let obss = (0..50)
+            .map(|i| Obs {
+                id: ObsId::new(i),
+                param: vec![ParamValue::Float(i as f64 / 50.0)],
+                value: i as f64,
+            })
+            .collect::<Vec<_>>();
+        let warnings = track!(lint(&encodings[3..], &obss))?;
+        assert_eq!(
+            warnings,
+            [LintWarning::BestNearBound {
+                index: 0,
+                upper: false
+            }]
+        );
+        assert!(lint(&encodings[..1], &obss).is_err());
+        Ok(())
+    }
+}
@@ -1,22 +1,135 @@
 //! Observation and its identifier.
-use crate::{Budget, IdGen, Result};
+use crate::{Budget, ErrorKind, IdGen, Result};
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use trackable::error::ErrorKindExt;
 
 /// Observation Identifier.
+///
+/// In distributed setups, an identifier can be packed from a worker identifier and a per-worker sequence number
+/// (see `ObsId::with_worker` and `PartitionedIdGenerator`).
+/// The upper `ObsId::WORKER_BITS` bits hold the worker identifier, and the remaining bits hold the sequence number.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ObsId(u64);
 impl ObsId {
+    /// The number of the bits used for worker identifiers by `ObsId::with_worker`.
+    pub const WORKER_BITS: u32 = 16;
+
+    /// The number of the bits used for sequence numbers by `ObsId::with_worker`.
+    pub const SEQUENCE_BITS: u32 = 64 - Self::WORKER_BITS;
+
+    /// The largest sequence number that can be packed by `ObsId::with_worker`.
+    pub const MAX_SEQUENCE: u64 = (1 << Self::SEQUENCE_BITS) - 1;
+
     /// Makes a new observation identifier.
     pub const fn new(id: u64) -> Self {
         Self(id)
     }
 
+    /// Makes a new observation identifier by packing the given worker identifier and sequence number.
+    ///
+    /// # Errors
+    ///
+    /// If `sequence` is greater than `ObsId::MAX_SEQUENCE`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn with_worker(worker: u16, sequence: u64) -> Result<Self> {
+        track_assert!(sequence <= Self::MAX_SEQUENCE, ErrorKind::InvalidInput; worker, sequence);
+        Ok(Self((u64::from(worker) << Self::SEQUENCE_BITS) | sequence))
+    }
+
+    /// Returns the worker identifier packed in this identifier.
+    ///
+    /// This is meaningful only if the identifier has been made by `ObsId::with_worker`
+    /// (identifiers of other generators are usually regarded as the ones of the worker `0`).
+    pub const fn worker(self) -> u16 {
+        (self.0 >> Self::SEQUENCE_BITS) as u16
+    }
+
+    /// Returns the sequence number packed in this identifier.
+    pub const fn sequence(self) -> u64 {
+        self.0 & Self::MAX_SEQUENCE
+    }
+
+    /// Returns the `"{worker}:{sequence}"` representation of this identifier.
+    ///
+    /// The result can be parsed by `ObsId::from_str`.
+    pub fn to_packed_string(self) -> String {
+        format!("{}:{}", self.worker(), self.sequence())
+    }
+
     /// Returns the value of this identifier.
     pub const fn get(self) -> u64 {
         self.0
     }
+
+    /// Returns a deterministic seed for evaluating the observation having this identifier.
+    ///
+    /// This is equivalent to `SplitMixSeed::new(base_seed).seed(self)`.
+    pub fn seed(self, base_seed: u64) -> u64 {
+        SplitMixSeed::new(base_seed).seed(self)
+    }
+}
+
+/// Parses either a plain integer (e.g., `"42"`) or a `"{worker}:{sequence}"` pair (e.g., `"3:17"`).
+impl FromStr for ObsId {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = |s: &str| {
+            s.parse::<u64>()
+                .map_err(|e| ErrorKind::InvalidInput.cause(e))
+        };
+        match s.split_once(':') {
+            None => Ok(Self(track!(parse(s); s)?)),
+            Some((worker, sequence)) => {
+                let worker = track!(worker
+                    .parse::<u16>()
+                    .map_err(|e| ErrorKind::InvalidInput.cause(e)); s)?;
+                let sequence = track!(parse(sequence); s)?;
+                track!(Self::with_worker(worker, sequence))
+            }
+        }
+    }
+}
+
+/// This trait allows deriving per-observation seeds.
+///
+/// Evaluations of stochastic objectives become reproducible
+/// by seeding their random number generators with the derived seeds.
+pub trait SeedStrategy {
+    /// Returns the seed for the observation having the given identifier.
+    fn seed(&self, id: ObsId) -> u64;
+}
+
+/// A `SeedStrategy` implementation that mixes a base seed and identifiers by the [SplitMix64] finalizer.
+///
+/// [SplitMix64]: https://prng.di.unimi.it/splitmix64.c
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SplitMixSeed {
+    base_seed: u64,
+}
+impl SplitMixSeed {
+    /// Makes a new `SplitMixSeed` instance.
+    pub const fn new(base_seed: u64) -> Self {
+        Self { base_seed }
+    }
+
+    /// Returns the base seed.
+    pub const fn base_seed(&self) -> u64 {
+        self.base_seed
+    }
+}
+impl SeedStrategy for SplitMixSeed {
+    fn seed(&self, id: ObsId) -> u64 {
+        let mut z = self
+            .base_seed
+            .wrapping_add(id.get().wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
 }
 
 /// Observation.
@@ -92,6 +205,11 @@ impl<P, V> Obs<P, V> {
         })
     }
 
+    /// Returns a deterministic seed for evaluating this observation (see `ObsId::seed`).
+    pub fn seed(&self, base_seed: u64) -> u64 {
+        self.id.seed(base_seed)
+    }
+
     /// Takes the value of this observation.
     pub fn take_value(self) -> (Obs<P>, V) {
         let Obs { id, param, value } = self;
@@ -115,6 +233,28 @@ impl<P, V> From<MfObs<P, V>> for Obs<P, V> {
     }
 }
 
+/// State of a multi-fidelity observation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ObsState {
+    /// The observation has been asked and is being evaluated.
+    Running,
+
+    /// The evaluation has been paused and can be resumed with an extended budget.
+    Suspended,
+
+    /// The evaluation has reached the maximum budget.
+    Completed,
+
+    /// The evaluation has been canceled before consuming the allocated budget.
+    ///
+    /// Canceled observations can be resumed like suspended ones.
+    Canceled,
+
+    /// The evaluation has failed.
+    Failed,
+}
+
 /// Multi-Fidelity Observation.
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -207,6 +347,14 @@ impl<P, V> MfObs<P, V> {
         })
     }
 
+    /// Returns a deterministic seed for evaluating this observation (see `ObsId::seed`).
+    ///
+    /// The seed does not depend on the budget, so every evaluation of the same configuration
+    /// (e.g., before and after a promotion) gets the same seed.
+    pub fn seed(&self, base_seed: u64) -> u64 {
+        self.id.seed(base_seed)
+    }
+
     /// Takes the value of this observation.
     pub fn take_value(self) -> (MfObs<P>, V) {
         let MfObs {
@@ -226,3 +374,44 @@ impl<P, V> MfObs<P, V> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::SerialIdGenerator;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn seed_works() -> TestResult {
+        let mut idg = SerialIdGenerator::new();
+        let obs0 = track!(Obs::new(&mut idg, ()))?;
+        let obs1 = track!(Obs::new(&mut idg, ()))?;
+        assert_eq!(obs0.seed(7), obs0.seed(7));
+        assert_ne!(obs0.seed(7), obs1.seed(7));
+        assert_ne!(obs0.seed(7), obs0.seed(8));
+        assert_eq!(obs1.seed(7), SplitMixSeed::new(7).seed(obs1.id));
+
+        let obs = MfObs::from_obs(obs1, Budget::new(10));
+        assert_eq!(obs.seed(7), obs1.seed(7));
+        Ok(())
+    }
+
+    #[test]
+    fn packed_id_works() -> TestResult {
+        let id = track!(ObsId::with_worker(3, 17))?;
+        assert_eq!((id.worker(), id.sequence()), (3, 17));
+        assert_eq!(id.get(), (3 << 48) | 17);
+        assert_eq!(id.to_packed_string(), "3:17");
+        assert_eq!(track!("3:17".parse::<ObsId>())?, id);
+        assert_eq!(track!("42".parse::<ObsId>())?, ObsId::new(42));
+        assert_eq!(ObsId::new(42).worker(), 0);
+
+        let id = track!(ObsId::with_worker(u16::MAX, ObsId::MAX_SEQUENCE))?;
+        assert_eq!(id.get(), u64::MAX);
+        assert!(ObsId::with_worker(0, ObsId::MAX_SEQUENCE + 1).is_err());
+        assert!("3:".parse::<ObsId>().is_err());
+        assert!("65536:1".parse::<ObsId>().is_err());
+        assert!("-1".parse::<ObsId>().is_err());
+        Ok(())
+    }
+}
@@ -1,5 +1,25 @@
 //! Black-box optimizers.
+pub mod aggregating;
 pub mod asha;
+pub mod budgeted;
+pub mod coordinate_descent;
+pub mod dedup;
+pub mod encoded;
+pub mod failure_aware;
+pub mod frozen;
+pub mod harmonica;
+pub mod island;
+pub mod lin_ucb;
+pub mod map_value;
 pub mod nelder_mead;
 pub mod nsga2;
+pub mod phased;
+pub mod portfolio;
+pub mod racing;
 pub mod random;
+pub mod refining;
+pub mod replay;
+pub mod sha;
+pub mod turbo;
+
+mod rungs;
@@ -0,0 +1,206 @@
+//! Adapter for aggregating repeated measurements.
+use crate::{ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use ordered_float::OrderedFloat;
+use rand::Rng;
+use std::collections::{HashMap, VecDeque};
+
+/// How to aggregate repeated measurements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Aggregation {
+    /// Arithmetic mean.
+    Mean,
+
+    /// Median.
+    Median,
+
+    /// Minimum.
+    Min,
+}
+impl Aggregation {
+    /// Aggregates the given values.
+    ///
+    /// # Errors
+    ///
+    /// If `values` is empty, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn aggregate(self, values: &[f64]) -> Result<f64> {
+        track_assert!(!values.is_empty(), ErrorKind::InvalidInput);
+        match self {
+            Aggregation::Mean => Ok(values.iter().sum::<f64>() / values.len() as f64),
+            Aggregation::Median => {
+                let mut values = values.to_vec();
+                values.sort_by_key(|&v| OrderedFloat(v));
+                let n = values.len();
+                if n % 2 == 1 {
+                    Ok(values[n / 2])
+                } else {
+                    Ok((values[n / 2 - 1] + values[n / 2]) / 2.0)
+                }
+            }
+            Aggregation::Min => Ok(values.iter().copied().fold(f64::INFINITY, f64::min)),
+        }
+    }
+}
+
+/// An optimizer adapter that aggregates multiple measurements of the same observation.
+///
+/// An asked observation is asked again until it has been asked `sample_size` times.
+/// Told values are accumulated per observation identifier,
+/// and once the number of measurements reaches `sample_size`,
+/// the aggregated value is told to the inner optimizer.
+/// Measurements told after that update the aggregated value and are forwarded again
+/// (i.e., the previous state of the observation in the inner optimizer is overwritten).
+#[derive(Debug)]
+pub struct AggregatingOptimizer<O: Optimizer> {
+    inner: O,
+    aggregation: Aggregation,
+    sample_size: usize,
+    samples: HashMap<ObsId, Samples<O::Param>>,
+    queue: VecDeque<ObsId>,
+}
+impl<O> AggregatingOptimizer<O>
+where
+    O: Optimizer<Value = f64>,
+    O::Param: Clone,
+{
+    /// Makes a new `AggregatingOptimizer` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `sample_size` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(inner: O, aggregation: Aggregation, sample_size: usize) -> Result<Self> {
+        track_assert!(sample_size > 0, ErrorKind::InvalidInput; sample_size);
+        Ok(Self {
+            inner,
+            aggregation,
+            sample_size,
+            samples: HashMap::new(),
+            queue: VecDeque::new(),
+        })
+    }
+
+    /// Returns the measurements told for the given observation.
+    pub fn samples(&self, id: ObsId) -> Option<&[f64]> {
+        self.samples.get(&id).map(|s| &s.values[..])
+    }
+
+    /// Returns the aggregated value of the given observation.
+    pub fn aggregated_value(&self, id: ObsId) -> Option<f64> {
+        self.samples
+            .get(&id)
+            .and_then(|s| self.aggregation.aggregate(&s.values).ok())
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `AggregatingOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+impl<O> Optimizer for AggregatingOptimizer<O>
+where
+    O: Optimizer<Value = f64>,
+    O::Param: Clone,
+{
+    type Param = O::Param;
+    type Value = f64;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        while let Some(id) = self.queue.front().copied() {
+            let s = track_assert_some!(self.samples.get_mut(&id), ErrorKind::Bug);
+            if s.asked < self.sample_size {
+                s.asked += 1;
+                return Ok(Obs {
+                    id,
+                    param: s.param.clone(),
+                    value: (),
+                });
+            }
+            self.queue.pop_front();
+        }
+
+        let obs = track!(self.inner.ask(rng, idg))?;
+        self.samples.insert(
+            obs.id,
+            Samples {
+                param: obs.param.clone(),
+                asked: 1,
+                values: Vec::new(),
+            },
+        );
+        if self.sample_size > 1 {
+            self.queue.push_back(obs.id);
+        }
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let s = track_assert_some!(
+            self.samples.get_mut(&obs.id),
+            ErrorKind::UnknownObservation; obs.id
+        );
+        s.values.push(obs.value);
+        if s.values.len() < self.sample_size {
+            return Ok(());
+        }
+
+        let value = track!(self.aggregation.aggregate(&s.values))?;
+        track!(self.inner.tell(obs.map_value(|_| value)))
+    }
+}
+
+#[derive(Debug)]
+struct Samples<P> {
+    param: P,
+    asked: usize,
+    values: Vec<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::ContinuousDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::random::RandomOptimizer;
+    use rand;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn aggregation_works() -> TestResult {
+        let values = [3.0, 1.0, 4.0, 2.0];
+        assert_eq!(track!(Aggregation::Mean.aggregate(&values))?, 2.5);
+        assert_eq!(track!(Aggregation::Median.aggregate(&values))?, 2.5);
+        assert_eq!(track!(Aggregation::Median.aggregate(&values[..3]))?, 3.0);
+        assert_eq!(track!(Aggregation::Min.aggregate(&values))?, 1.0);
+        assert!(Aggregation::Mean.aggregate(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn aggregating_optimizer_works() -> TestResult {
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut opt = track!(AggregatingOptimizer::new(inner, Aggregation::Mean, 3))?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        for i in 0..3 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            assert_eq!(obs.id.get(), 0);
+            track!(opt.tell(obs.map_value(|()| f64::from(i))))?;
+        }
+        assert_eq!(opt.aggregated_value(ObsId::new(0)), Some(1.0));
+
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 1);
+
+        Ok(())
+    }
+}
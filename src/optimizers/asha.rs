@@ -3,12 +3,244 @@
 //! # References
 //!
 //! - [Massively Parallel Hyperparameter Tuning](https://arxiv.org/abs/1810.05934)
+use super::rungs::{Candidate, Rungs};
+use crate::provenance::ProvenanceRegistry;
 use crate::{
-    Budget, ErrorKind, IdGen, MfObs, MultiFidelityOptimizer, Obs, ObsId, Optimizer, Ranked, Result,
+    Budget, BudgetUnit, ErrorKind, IdGen, MeasuredValue, MfObs, MultiFidelityOptimizer, Obs, ObsId,
+    ObsState, Optimizer, Ranked, Result,
 };
 use rand::Rng;
-use std::cmp;
-use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// A finished observation in the top rung that can be thawed.
+#[derive(Debug)]
+pub struct Thawable<'a, V> {
+    /// Observation identifier.
+    pub id: ObsId,
+
+    /// Evaluation budget consumed so far.
+    pub budget: Budget,
+
+    /// Observed value.
+    pub value: &'a V,
+}
+
+/// This trait allows deciding which finished observation should be evaluated again with an extended budget.
+pub trait ThawPolicy<V> {
+    /// Selects an observation to be thawed.
+    ///
+    /// `candidates` are the finished observations in the top rung sorted by their values (best first).
+    ///
+    /// If this returns `None`, a new configuration is asked to the inner optimizer instead.
+    fn select(&mut self, candidates: &[Thawable<V>]) -> Option<ObsId>;
+}
+
+/// A `ThawPolicy` implementation that never thaws observations.
+///
+/// This is the default policy of `AshaOptimizer`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NeverThaw;
+
+impl<V> ThawPolicy<V> for NeverThaw {
+    fn select(&mut self, _candidates: &[Thawable<V>]) -> Option<ObsId> {
+        None
+    }
+}
+
+/// A `ThawPolicy` implementation that thaws the best observation until its budget reaches the given limit.
+#[derive(Debug, Clone, Copy)]
+pub struct ThawBest {
+    budget_limit: u64,
+}
+
+impl ThawBest {
+    /// Makes a new `ThawBest` instance.
+    ///
+    /// Observations that have consumed `budget_limit` or more are not thawed.
+    pub const fn new(budget_limit: u64) -> Self {
+        Self { budget_limit }
+    }
+}
+
+impl<V> ThawPolicy<V> for ThawBest {
+    fn select(&mut self, candidates: &[Thawable<V>]) -> Option<ObsId> {
+        candidates
+            .iter()
+            .find(|c| c.budget.consumption < self.budget_limit)
+            .map(|c| c.id)
+    }
+}
+
+/// This trait allows deciding whether a candidate can be promoted to the next rung.
+///
+/// A candidate is an observation among the top `1 / reduction_factor` configurations in a rung.
+pub trait PromotionRule<V> {
+    /// Returns `true` if the candidate having the value `candidate` can be promoted.
+    ///
+    /// `rival` is the best value among the configurations that are not in the top of the rung (if any).
+    fn is_promotable(&mut self, candidate: &V, rival: Option<&V>) -> bool;
+}
+
+/// A `PromotionRule` implementation that promotes candidates only by their ranks.
+///
+/// This is the default rule of `AshaOptimizer`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RankPromotion;
+
+impl<V> PromotionRule<V> for RankPromotion {
+    fn is_promotable(&mut self, _candidate: &V, _rival: Option<&V>) -> bool {
+        true
+    }
+}
+
+/// A `PromotionRule` implementation that requires a confidence margin for promotions.
+///
+/// A candidate is promoted only if `candidate.mean() + margin * candidate.sem()`
+/// is less than `rival.mean() - margin * rival.sem()`.
+/// This prevents promotions driven by noise when intermediate values are jittery.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfidencePromotion {
+    margin: f64,
+}
+impl ConfidencePromotion {
+    /// Makes a new `ConfidencePromotion` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `margin` is not a non-negative finite number, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(margin: f64) -> Result<Self> {
+        track_assert!(margin.is_finite() && margin >= 0.0, ErrorKind::InvalidInput; margin);
+        Ok(Self { margin })
+    }
+}
+impl PromotionRule<MeasuredValue> for ConfidencePromotion {
+    fn is_promotable(&mut self, candidate: &MeasuredValue, rival: Option<&MeasuredValue>) -> bool {
+        rival.is_none_or(|rival| {
+            candidate.mean() + self.margin * candidate.sem()
+                < rival.mean() - self.margin * rival.sem()
+        })
+    }
+}
+
+/// Summary of a rung of `AshaOptimizer`.
+#[derive(Debug, Clone)]
+pub struct RungStats<'a, V> {
+    /// The budget that observations in this rung have consumed at least.
+    pub budget: u64,
+
+    /// The number of configurations in this rung.
+    pub configs: usize,
+
+    /// The best value observed in this rung.
+    pub best_value: Option<&'a V>,
+}
+
+/// Occupancy of a rung of `AshaOptimizer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RungOccupancy {
+    /// The budget that observations in this rung have consumed at least.
+    pub budget: u64,
+
+    /// The number of configurations that are waiting for promotions (or thaws) in this rung.
+    pub pending: usize,
+
+    /// The number of configurations that have been promoted from this rung.
+    pub promoted: usize,
+}
+
+/// A promotion (or thaw) of an observation of `AshaOptimizer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PromotionEvent {
+    /// The identifier of the promoted observation.
+    pub id: ObsId,
+
+    /// The budget consumed before the promotion.
+    pub from_budget: u64,
+
+    /// The budget allocated by the promotion.
+    pub to_budget: u64,
+
+    /// The number of asks made before the promotion (i.e., the time of the promotion).
+    pub asks: u64,
+}
+
+/// A promotion decision made by `AshaOptimizer`.
+///
+/// A decision is recorded for each candidate examined by the promotion rule (and for each thaw),
+/// including the candidates that were not promoted.
+/// If the `serde` feature is enabled, this can be serialized into any format supported by serde (e.g., JSON).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PromotionDecision<V> {
+    /// The identifier of the candidate observation.
+    pub id: ObsId,
+
+    /// The index of the rung the candidate belongs to.
+    pub from_rung: usize,
+
+    /// The index of the rung the candidate would be promoted to.
+    ///
+    /// This is equal to `from_rung` in the case of thaws.
+    pub to_rung: usize,
+
+    /// The budget consumed by the candidate before the decision.
+    pub from_budget: u64,
+
+    /// The budget the candidate is (or would be) promoted with.
+    pub to_budget: u64,
+
+    /// The value of the candidate.
+    pub value: V,
+
+    /// The best value among the configurations that are not in the top `1 / reduction_factor` of the rung.
+    pub rival: Option<V>,
+
+    /// The competitors considered in the decision.
+    ///
+    /// For promotions, these are the top `1 / reduction_factor` configurations of the rung
+    /// (including the candidate and the configurations already promoted).
+    /// For thaws, these are the configurations that could be thawed.
+    pub competitors: Vec<(ObsId, V)>,
+
+    /// Whether the candidate was promoted.
+    pub promoted: bool,
+
+    /// The number of asks made before the decision (i.e., the time of the decision).
+    pub asks: u64,
+}
+
+/// Progress of `AshaOptimizer`.
+///
+/// This contains enough information to render successive halving diagrams
+/// (e.g., on a dashboard monitoring a long multi-fidelity run).
+/// If the `serde` feature is enabled, this can be serialized into any format supported by serde (e.g., JSON).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AshaProgress {
+    /// The minimum budget.
+    pub min_budget: u64,
+
+    /// The maximum budget.
+    pub max_budget: u64,
+
+    /// The reduction factor.
+    pub reduction_factor: usize,
+
+    /// The number of asks made so far.
+    pub asks: u64,
+
+    /// The occupancies of the rungs (from the lowest budget to the highest).
+    pub rungs: Vec<RungOccupancy>,
+
+    /// The promotions (and thaws) in the order they occurred.
+    ///
+    /// These are the promoted entries of `AshaOptimizer::promotion_log`.
+    pub promotions: Vec<PromotionEvent>,
+}
 
 /// Builder of `AshaOptimizer`.
 #[derive(Debug, Clone)]
@@ -53,16 +285,136 @@ impl AshaOptimizerBuilder {
         V: Ord,
         O: Optimizer<Value = Ranked<V>>,
     {
+        track!(self.finish_with_thaw_policy(inner, min_budget, max_budget, NeverThaw))
+    }
+
+    /// Builds a new `AshaOptimizer` instance whose minimum and maximum budgets are given as quantities of `unit`
+    /// (e.g., `AshaOptimizerBuilder::new().finish_with_unit(inner, 0.5, 8.0, &gpu_hours)`).
+    ///
+    /// # Errors
+    ///
+    /// If `unit` fails to convert the quantities, the error is propagated.
+    pub fn finish_with_unit<V, O, U>(
+        &self,
+        inner: O,
+        min_quantity: f64,
+        max_quantity: f64,
+        unit: &U,
+    ) -> Result<AshaOptimizer<V, O>>
+    where
+        V: Ord,
+        O: Optimizer<Value = Ranked<V>>,
+        U: BudgetUnit,
+    {
+        let min_budget = track!(unit.to_budget(min_quantity))?;
+        let max_budget = track!(unit.to_budget(max_quantity))?;
+        track!(self.finish(inner, min_budget, max_budget))
+    }
+
+    /// Returns the budgets of the rungs (from the lowest to the highest) that would be built by this builder.
+    ///
+    /// Combined with `BudgetUnit::to_quantity`, this shows the rung thresholds in real-world quantities.
+    ///
+    /// # Errors
+    ///
+    /// If `min_budget` is `0` or greater than `max_budget`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn rung_budgets(&self, min_budget: u64, max_budget: u64) -> Result<Vec<u64>> {
         track_assert!(min_budget <= max_budget, ErrorKind::InvalidInput; min_budget, max_budget);
         track_assert!(0 < min_budget, ErrorKind::InvalidInput; min_budget, max_budget);
+        let rungs = Rungs::<(), ()>::new(min_budget, max_budget, self.reduction_factor);
+        Ok(rungs.iter().map(|r| r.curr_budget()).collect())
+    }
 
-        let rungs = Rungs::new(min_budget, max_budget, self);
+    /// Returns the minimum budget such that a trial with `max_budget` fits `total` budget
+    /// together with the lower rungs' evaluations of the configurations needed to promote it.
+    ///
+    /// This is a helper for deriving rung thresholds from a total (e.g., wall-clock) budget:
+    /// with the reduction factor `η` and `k` rungs, promoting one configuration to the top rung requires
+    /// `η^(k-1)` configurations at the bottom rung, and the sum of their budgets should not exceed `total`.
+    ///
+    /// # Errors
+    ///
+    /// If `max_budget` is `0` or `total` is less than `max_budget`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn min_budget_for_total(&self, total: u64, max_budget: u64) -> Result<u64> {
+        track_assert!(0 < max_budget, ErrorKind::InvalidInput; max_budget);
+        track_assert!(max_budget <= total, ErrorKind::InvalidInput; total, max_budget);
+
+        let eta = self.reduction_factor as u64;
+        let mut best = max_budget;
+        let mut min_budget = max_budget;
+        while min_budget > 1 {
+            min_budget = min_budget.div_ceil(eta);
+            let budgets = track!(self.rung_budgets(min_budget, max_budget))?;
+            let mut configs = 1u64;
+            let mut cost = 0u64;
+            for &b in budgets.iter().rev() {
+                cost = cost.saturating_add(configs.saturating_mul(b));
+                configs = configs.saturating_mul(eta);
+            }
+            if cost > total {
+                break;
+            }
+            best = min_budget;
+        }
+        Ok(best)
+    }
+
+    /// Builds a new `AshaOptimizer` instance that uses the given policy to thaw finished observations.
+    ///
+    /// When there are no promotable observations, the resulting optimizer consults `thaw_policy`
+    /// and re-asks the selected observation in the top rung with a budget extended by the reduction factor
+    /// (the extended budget can exceed `max_budget`).
+    pub fn finish_with_thaw_policy<V, O, T>(
+        &self,
+        inner: O,
+        min_budget: u64,
+        max_budget: u64,
+        thaw_policy: T,
+    ) -> Result<AshaOptimizer<V, O, T>>
+    where
+        V: Ord,
+        O: Optimizer<Value = Ranked<V>>,
+        T: ThawPolicy<V>,
+    {
+        track!(self.finish_with_policies(inner, min_budget, max_budget, thaw_policy, RankPromotion))
+    }
+
+    /// Builds a new `AshaOptimizer` instance that uses the given thaw policy and promotion rule.
+    ///
+    /// See `finish_with_thaw_policy` for the thaw policy.
+    pub fn finish_with_policies<V, O, T, P>(
+        &self,
+        inner: O,
+        min_budget: u64,
+        max_budget: u64,
+        thaw_policy: T,
+        promotion_rule: P,
+    ) -> Result<AshaOptimizer<V, O, T, P>>
+    where
+        V: Ord,
+        O: Optimizer<Value = Ranked<V>>,
+        T: ThawPolicy<V>,
+        P: PromotionRule<V>,
+    {
+        track_assert!(min_budget <= max_budget, ErrorKind::InvalidInput; min_budget, max_budget);
+        track_assert!(0 < min_budget, ErrorKind::InvalidInput; min_budget, max_budget);
+
+        let rungs = Rungs::new(min_budget, max_budget, self.reduction_factor);
         Ok(AshaOptimizer {
             inner,
             rungs,
             initial_budget: Budget::new(min_budget),
             without_checkpoint: self.without_checkpoint,
+            reduction_factor: self.reduction_factor,
             max_budget,
+            thaw_policy,
+            promotion_rule,
+            thawed: HashSet::new(),
+            canceled: HashMap::new(),
+            states: HashMap::new(),
+            asks: 0,
+            promotion_log: PromotionLog::new(),
+            provenance: None,
         })
     }
 }
@@ -76,12 +428,21 @@ impl Default for AshaOptimizerBuilder {
 ///
 /// [ASHA]: https://arxiv.org/abs/1810.05934
 #[derive(Debug)]
-pub struct AshaOptimizer<V, O: Optimizer> {
+pub struct AshaOptimizer<V, O: Optimizer, T = NeverThaw, P = RankPromotion> {
     inner: O,
     rungs: Rungs<O::Param, V>,
     initial_budget: Budget,
     without_checkpoint: bool,
+    reduction_factor: usize,
     max_budget: u64,
+    thaw_policy: T,
+    promotion_rule: P,
+    thawed: HashSet<ObsId>,
+    canceled: HashMap<ObsId, MfObs<O::Param>>,
+    states: HashMap<ObsId, ObsState>,
+    asks: u64,
+    promotion_log: PromotionLog<V>,
+    provenance: Option<(ProvenanceRegistry, String)>,
 }
 impl<V, O> AshaOptimizer<V, O>
 where
@@ -92,6 +453,161 @@ where
     pub fn new(inner: O, min_budget: u64, max_budget: u64) -> Result<Self> {
         track!(AshaOptimizerBuilder::new().finish(inner, min_budget, max_budget))
     }
+}
+impl<V, O, T, P> AshaOptimizer<V, O, T, P>
+where
+    V: Ord,
+    O: Optimizer<Value = Ranked<V>>,
+    T: ThawPolicy<V>,
+    P: PromotionRule<V>,
+{
+    /// Returns an iterator over the summaries of the rungs (from the lowest budget to the highest).
+    pub fn rungs(&self) -> impl Iterator<Item = RungStats<'_, V>> {
+        self.rungs.iter().map(|r| RungStats {
+            budget: r.curr_budget(),
+            configs: r.len(),
+            best_value: r.best_value(),
+        })
+    }
+
+    /// Returns the progress of this optimizer.
+    pub fn progress(&self) -> AshaProgress {
+        AshaProgress {
+            min_budget: self.initial_budget.amount,
+            max_budget: self.max_budget,
+            reduction_factor: self.reduction_factor,
+            asks: self.asks,
+            rungs: self
+                .rungs
+                .iter()
+                .map(|r| RungOccupancy {
+                    budget: r.curr_budget(),
+                    pending: r.pending_len(),
+                    promoted: r.len() - r.pending_len(),
+                })
+                .collect(),
+            promotions: self
+                .promotion_log
+                .decisions
+                .iter()
+                .filter(|d| d.promoted)
+                .map(|d| PromotionEvent {
+                    id: d.id,
+                    from_budget: d.from_budget,
+                    to_budget: d.to_budget,
+                    asks: d.asks,
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the promotion decisions made so far in the order they occurred.
+    ///
+    /// Consecutive identical rejections of the same candidate are recorded only once.
+    pub fn promotion_log(&self) -> &[PromotionDecision<V>] {
+        &self.promotion_log.decisions
+    }
+
+    /// Returns the best observation in the top rung.
+    pub fn best_obs(&self) -> Option<&MfObs<O::Param, V>> {
+        self.rungs.last().pending().into_iter().next()
+    }
+
+    /// Returns a reference to the thaw policy.
+    pub fn thaw_policy(&self) -> &T {
+        &self.thaw_policy
+    }
+
+    /// Returns a mutable reference to the thaw policy.
+    pub fn thaw_policy_mut(&mut self) -> &mut T {
+        &mut self.thaw_policy
+    }
+
+    /// Returns the state of the given observation.
+    pub fn obs_state(&self, id: ObsId) -> Option<ObsState> {
+        self.states.get(&id).copied()
+    }
+
+    /// Returns the identifiers of the suspended observations (i.e., observations waiting for promotions).
+    pub fn suspended(&self) -> Vec<ObsId> {
+        let mut ids = self
+            .states
+            .iter()
+            .filter(|(_, s)| **s == ObsState::Suspended)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        ids
+    }
+
+    /// Resumes the given suspended, canceled or completed observation with the given budget.
+    ///
+    /// The resumed observation is removed from its rung and is placed again into the rung
+    /// that corresponds to the consumed budget when it is told.
+    ///
+    /// # Errors
+    ///
+    /// If the observation is neither suspended, canceled nor completed, or
+    /// `budget_amount` is not greater than the consumed budget,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn resume(&mut self, id: ObsId, budget_amount: u64) -> Result<MfObs<O::Param>> {
+        let state = self.obs_state(id);
+        let mut obs = match state {
+            Some(ObsState::Suspended) | Some(ObsState::Completed) => {
+                track_assert_some!(self.rungs.take_pending(id), ErrorKind::InvalidInput; id)
+            }
+            Some(ObsState::Canceled) => {
+                let consumption = self.canceled.get(&id).map(|obs| obs.budget.consumption);
+                let consumption = track_assert_some!(consumption, ErrorKind::Bug; id);
+                track_assert!(
+                    budget_amount > consumption,
+                    ErrorKind::InvalidInput; id, budget_amount, consumption
+                );
+                track_assert_some!(self.canceled.remove(&id), ErrorKind::Bug; id)
+            }
+            _ => track_panic!(ErrorKind::InvalidInput; id, state),
+        };
+        track_assert!(
+            budget_amount > obs.budget.consumption,
+            ErrorKind::InvalidInput; id, budget_amount, obs.budget
+        );
+
+        obs.budget.amount = budget_amount;
+        if budget_amount > self.max_budget {
+            self.thawed.insert(id);
+        }
+        self.states.insert(id, ObsState::Running);
+        Ok(obs)
+    }
+
+    /// Marks the given running observation as failed.
+    ///
+    /// Failed observations are never promoted nor resumed, and are not told to the inner optimizer.
+    /// Use `tell` with a consumption less than the budget amount for evaluations that were just canceled.
+    ///
+    /// # Errors
+    ///
+    /// If the observation is not running, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn fail(&mut self, id: ObsId) -> Result<()> {
+        let state = self.obs_state(id);
+        track_assert_eq!(state, Some(ObsState::Running), ErrorKind::InvalidInput; id);
+        self.thawed.remove(&id);
+        self.states.insert(id, ObsState::Failed);
+        Ok(())
+    }
+
+    /// Returns a reference to the promotion rule.
+    pub fn promotion_rule(&self) -> &P {
+        &self.promotion_rule
+    }
+
+    /// Makes the optimizer record the observations generated by the underlying optimizer into `registry`.
+    ///
+    /// The label of such observations is `name`.
+    /// If checkpoints are disabled, promoted observations inherit the labels of their original observations.
+    pub fn set_provenance(&mut self, registry: ProvenanceRegistry, name: &str) {
+        self.provenance = Some((registry, name.to_owned()));
+    }
 
     /// Returns a references to the underlying optimizer.
     pub fn inner(&self) -> &O {
@@ -107,43 +623,130 @@ where
     pub fn into_inner(self) -> O {
         self.inner
     }
+
+    fn restart_promoted<G: IdGen>(&mut self, obs: &mut MfObs<O::Param>, mut idg: G) -> Result<()> {
+        let id = track!(idg.generate())?;
+        if let Some((registry, _)) = &self.provenance {
+            track!(registry.inherit(obs.id, id))?;
+        }
+        // The original observation is superseded by the restarted one, so it can no longer be resumed.
+        self.states.remove(&obs.id);
+        obs.id = id;
+        obs.budget.consumption = 0;
+        Ok(())
+    }
+
+    fn ask_thawable(&mut self) -> Option<MfObs<O::Param>>
+    where
+        V: Clone,
+    {
+        let level = self.rungs.len() - 1;
+        let top = self.rungs.last();
+        let candidates = top
+            .pending()
+            .into_iter()
+            .map(|obs| Thawable {
+                id: obs.id,
+                budget: obs.budget,
+                value: &obs.value,
+            })
+            .collect::<Vec<_>>();
+        let id = self.thaw_policy.select(&candidates)?;
+        let thawed = candidates.iter().find(|c| c.id == id)?;
+        let from_budget = thawed.budget.consumption;
+        let to_budget = from_budget.saturating_mul(self.reduction_factor as u64);
+        let value = thawed.value.clone();
+        let competitors = candidates.iter().map(|c| (c.id, c.value.clone())).collect();
+        self.promotion_log.record(PromotionDecision {
+            id,
+            from_rung: level,
+            to_rung: level,
+            from_budget,
+            to_budget,
+            value,
+            rival: None,
+            competitors,
+            promoted: true,
+            asks: self.asks,
+        });
+
+        let mut obs = self.rungs.last_mut().take_pending(id)?;
+        obs.budget.amount = to_budget;
+        Some(obs)
+    }
 }
-impl<V, O> MultiFidelityOptimizer for AshaOptimizer<V, O>
+impl<V, O, T, P> MultiFidelityOptimizer for AshaOptimizer<V, O, T, P>
 where
     V: Ord + Clone,
     O: Optimizer<Value = Ranked<V>>,
     O::Param: Clone,
+    T: ThawPolicy<V>,
+    P: PromotionRule<V>,
 {
     type Param = O::Param;
     type Value = V;
 
     fn ask<R: Rng, G: IdGen>(&mut self, rng: R, mut idg: G) -> Result<MfObs<Self::Param>> {
-        if let Some(mut obs) = self.rungs.ask_promotable() {
+        let promotion_rule = &mut self.promotion_rule;
+        let promotion_log = &mut self.promotion_log;
+        let asks = self.asks;
+        let obs = if let Some(mut obs) = self.rungs.ask_promotable(|c| {
+            let promoted = promotion_rule.is_promotable(c.value, c.rival);
+            promotion_log.record_candidate(c, promoted, asks);
+            promoted
+        }) {
+            if self.without_checkpoint {
+                track!(self.restart_promoted(&mut obs, &mut idg))?;
+            }
+            obs
+        } else if let Some(mut obs) = self.ask_thawable() {
             if self.without_checkpoint {
-                obs.id = track!(idg.generate())?;
-                obs.budget.consumption = 0;
+                track!(self.restart_promoted(&mut obs, &mut idg))?;
             }
-            Ok(obs)
+            self.thawed.insert(obs.id);
+            obs
         } else {
             let obs = track!(self.inner.ask(rng, idg))?;
-            let obs = MfObs::from_obs(obs, self.initial_budget);
-            Ok(obs)
-        }
+            if let Some((registry, name)) = &self.provenance {
+                track!(registry.record(obs.id, name.clone()))?;
+            }
+            MfObs::from_obs(obs, self.initial_budget)
+        };
+        self.states.insert(obs.id, ObsState::Running);
+        self.asks += 1;
+        Ok(obs)
     }
 
     fn tell(&mut self, obs: MfObs<Self::Param, Self::Value>) -> Result<()> {
+        let thawed = self.thawed.remove(&obs.id);
         track_assert!(
-            obs.budget.consumption <= self.max_budget,
+            thawed || obs.budget.consumption <= self.max_budget,
             ErrorKind::InvalidInput; obs.id, obs.budget, self.max_budget
         );
 
-        if obs.budget.consumption < obs.budget.amount {
+        let state = if obs.budget.consumption < obs.budget.amount {
             // The evaluation of this observation was canceled.
+            if thawed && obs.budget.consumption >= self.max_budget {
+                // Puts the thawed observation back to the top rung.
+                track!(self.rungs.tell(obs.clone()))?;
+                ObsState::Completed
+            } else {
+                // Keeps the observation (including thawed ones canceled before reaching `max_budget`)
+                // so that it can be resumed later.
+                self.canceled.insert(obs.id, obs.clone().take_value().0);
+                ObsState::Canceled
+            }
         } else {
             track!(self.rungs.tell(obs.clone()))?;
-        }
+            if obs.budget.consumption >= self.max_budget {
+                ObsState::Completed
+            } else {
+                ObsState::Suspended
+            }
+        };
+        self.states.insert(obs.id, state);
 
-        let rank = self.max_budget - obs.budget.consumption;
+        let rank = self.max_budget.saturating_sub(obs.budget.consumption);
         let obs = Obs::from(obs).map_value(|value| Ranked { rank, value });
         track!(self.inner.tell(obs))?;
 
@@ -152,125 +755,53 @@ where
 }
 
 #[derive(Debug)]
-struct Rungs<P, V>(Vec<Rung<P, V>>);
-impl<P, V> Rungs<P, V>
-where
-    V: Ord,
-{
-    fn new(min_budget: u64, max_budget: u64, builder: &AshaOptimizerBuilder) -> Self {
-        let mut rungs = Vec::new();
-        let mut budget = min_budget;
-        while budget < max_budget {
-            let next_budget = cmp::min(
-                max_budget,
-                budget.saturating_mul(builder.reduction_factor as u64),
-            );
-            rungs.push(Rung::new(budget, Some(next_budget), builder));
-            budget = next_budget;
-        }
-        rungs.push(Rung::new(max_budget, None, builder));
-        Self(rungs)
-    }
-
-    fn ask_promotable(&mut self) -> Option<MfObs<P>> {
-        for rung in self.0.iter_mut().rev() {
-            if let Some(obs) = rung.ask_promotable() {
-                return Some(obs);
-            }
-        }
-        None
-    }
-
-    fn tell(&mut self, obs: MfObs<P, V>) -> Result<()> {
-        use std::u64;
-
-        for rung in self.0.iter_mut().rev() {
-            let p = obs.budget.consumption;
-            if rung.curr_budget <= p && p < rung.next_budget.unwrap_or(u64::MAX) {
-                track!(rung.tell(obs))?;
-                return Ok(());
-            }
-        }
-        track_panic!(ErrorKind::InvalidInput; obs.id);
-    }
-}
-
-#[derive(Debug)]
-struct Rung<P, V> {
-    obss: HashMap<ObsId, Config<P, V>>,
-    curr_budget: u64,
-    next_budget: Option<u64>,
-    reduction_factor: usize,
+struct PromotionLog<V> {
+    decisions: Vec<PromotionDecision<V>>,
+    latest: HashMap<ObsId, usize>,
 }
-impl<P, V> Rung<P, V>
-where
-    V: Ord,
-{
-    fn new(curr_budget: u64, next_budget: Option<u64>, builder: &AshaOptimizerBuilder) -> Self {
+impl<V> PromotionLog<V> {
+    fn new() -> Self {
         Self {
-            obss: HashMap::new(),
-            curr_budget,
-            next_budget,
-            reduction_factor: builder.reduction_factor,
+            decisions: Vec::new(),
+            latest: HashMap::new(),
         }
     }
-
-    fn ask_promotable(&mut self) -> Option<MfObs<P>> {
-        let next_budget = self.next_budget?;
-
-        // FIXME: optimize
-        let mut configs = self.obss.values().collect::<Vec<_>>();
-        configs.sort_by_key(|c| c.value());
-
-        let mut found = None;
-        let promotables = self.obss.len() / self.reduction_factor;
-        for c in configs.iter().take(promotables) {
-            if let Config::Pending { obs } = c {
-                found = Some(obs.id);
-                break;
-            }
-        }
-
-        if let Some(id) = found {
-            let (mut obs, value) = if let Config::Pending { obs } =
-                self.obss.remove(&id).unwrap_or_else(|| unreachable!())
-            {
-                obs.take_value()
-            } else {
-                unreachable!()
-            };
-
-            self.obss.insert(id, Config::Finished { value });
-
-            obs.budget.amount = next_budget;
-            Some(obs)
-        } else {
-            None
-        }
-    }
-
-    fn tell(&mut self, obs: MfObs<P, V>) -> Result<()> {
-        track_assert!(!self.obss.contains_key(&obs.id), ErrorKind::Bug);
-        track_assert!(
-            self.curr_budget <= obs.budget.consumption,
-            ErrorKind::InvalidInput; self.curr_budget, obs.budget
-        );
-        self.obss.insert(obs.id, Config::Pending { obs });
-        Ok(())
-    }
 }
+impl<V: Clone + PartialEq> PromotionLog<V> {
+    fn record_candidate(&mut self, candidate: &Candidate<V>, promoted: bool, asks: u64) {
+        self.record(PromotionDecision {
+            id: candidate.id,
+            from_rung: candidate.level,
+            to_rung: candidate.level + 1,
+            from_budget: candidate.budget.consumption,
+            to_budget: candidate.budget.amount,
+            value: candidate.value.clone(),
+            rival: candidate.rival.cloned(),
+            competitors: candidate
+                .top
+                .iter()
+                .map(|&(id, v)| (id, v.clone()))
+                .collect(),
+            promoted,
+            asks,
+        });
+    }
 
-#[derive(Debug)]
-enum Config<P, V> {
-    Pending { obs: MfObs<P, V> },
-    Finished { value: V },
-}
-impl<P, V> Config<P, V> {
-    fn value(&self) -> &V {
-        match self {
-            Config::Pending { obs } => &obs.value,
-            Config::Finished { value } => value,
+    fn record(&mut self, decision: PromotionDecision<V>) {
+        let latest = self.latest.get(&decision.id).map(|&i| &self.decisions[i]);
+        if let Some(latest) = latest {
+            let repeated = !decision.promoted
+                && !latest.promoted
+                && latest.from_rung == decision.from_rung
+                && latest.value == decision.value
+                && latest.rival == decision.rival
+                && latest.competitors == decision.competitors;
+            if repeated {
+                return;
+            }
         }
+        self.latest.insert(decision.id, self.decisions.len());
+        self.decisions.push(decision);
     }
 }
 
@@ -280,6 +811,7 @@ mod tests {
     use crate::domains::ContinuousDomain;
     use crate::generators::SerialIdGenerator;
     use crate::optimizers::random::RandomOptimizer;
+    use crate::LinearUnit;
     use rand;
     use trackable::result::TestResult;
 
@@ -315,6 +847,307 @@ mod tests {
         obs.budget.consumption += 10;
         track!(optimizer.tell(obs))?;
 
+        let rungs = optimizer.rungs().collect::<Vec<_>>();
+        assert_eq!(rungs.len(), 2);
+        assert_eq!((rungs[0].budget, rungs[0].configs), (10, 2));
+        assert_eq!((rungs[1].budget, rungs[1].configs), (20, 1));
+        assert_eq!(rungs[1].best_value, Some(&1));
+        assert_eq!(optimizer.best_obs().map(|obs| obs.id.get()), Some(0));
+
+        let progress = optimizer.progress();
+        assert_eq!(progress.asks, 3);
+        assert_eq!(
+            progress.rungs,
+            [
+                RungOccupancy {
+                    budget: 10,
+                    pending: 1,
+                    promoted: 1
+                },
+                RungOccupancy {
+                    budget: 20,
+                    pending: 1,
+                    promoted: 0
+                }
+            ]
+        );
+        assert_eq!(
+            progress.promotions,
+            [PromotionEvent {
+                id: ObsId::new(0),
+                from_budget: 10,
+                to_budget: 20,
+                asks: 2
+            }]
+        );
+        assert_eq!(
+            optimizer.promotion_log(),
+            [PromotionDecision {
+                id: ObsId::new(0),
+                from_rung: 0,
+                to_rung: 1,
+                from_budget: 10,
+                to_budget: 20,
+                value: 1,
+                rival: Some(2),
+                competitors: vec![(ObsId::new(0), 1)],
+                promoted: true,
+                asks: 2
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn asha_budget_unit_works() -> TestResult {
+        let builder = AshaOptimizerBuilder::new();
+        assert_eq!(track!(builder.rung_budgets(1, 8))?, [1, 2, 4, 8]);
+        assert_eq!(track!(builder.min_budget_for_total(30, 8))?, 2);
+        assert_eq!(track!(builder.min_budget_for_total(32, 8))?, 1);
+        assert!(builder.min_budget_for_total(4, 8).is_err());
+
+        let hours = track!(LinearUnit::new(0.5))?;
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let optimizer: AshaOptimizer<usize, _> =
+            track!(builder.finish_with_unit(inner, 0.5, 4.0, &hours))?;
+        let progress = optimizer.progress();
+        assert_eq!((progress.min_budget, progress.max_budget), (1, 8));
+        assert_eq!(hours.to_quantity(progress.max_budget), 4.0);
+
+        let mut budget = Budget::new(8);
+        track!(budget.consume(&hours, 1.2))?;
+        assert_eq!(budget.consumption, 3);
+        assert!(budget.consume(&hours, -1.0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn asha_thaw_works() -> TestResult {
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut optimizer = track!(AshaOptimizerBuilder::new().finish_with_thaw_policy(
+            inner,
+            10,
+            10,
+            ThawBest::new(40)
+        ))?;
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 0);
+        let mut obs = obs.map_value(|_| 1);
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+
+        // thawed (10 -> 20)
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 0);
+        assert_eq!(obs.budget.amount, 20);
+        let mut obs = obs.map_value(|_| 1);
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+
+        // thawed (20 -> 40)
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 0);
+        assert_eq!(obs.budget.amount, 40);
+        let mut obs = obs.map_value(|_| 1);
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+
+        // reached the limit
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 1);
+        assert_eq!(obs.budget.amount, 10);
+        let budgets = optimizer
+            .progress()
+            .promotions
+            .iter()
+            .map(|p| (p.from_budget, p.to_budget))
+            .collect::<Vec<_>>();
+        assert_eq!(budgets, [(10, 20), (20, 40)]);
+
+        // thawed without checkpoints, but canceled before reaching the maximum budget
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut optimizer = track!(AshaOptimizerBuilder::new()
+            .without_checkpoint()
+            .finish_with_thaw_policy(inner, 10, 10, ThawBest::new(40)))?;
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        let mut obs = obs.map_value(|_| 1);
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.budget.amount, 20);
+        let mut obs = obs.map_value(|_| 1);
+        obs.budget.consumption = 5;
+        let id = obs.id;
+        track!(optimizer.tell(obs))?;
+        assert_eq!(optimizer.obs_state(id), Some(ObsState::Canceled));
+        let obs = track!(optimizer.resume(id, 20))?;
+        let mut obs = obs.map_value(|_| 1);
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+        assert_eq!(optimizer.obs_state(id), Some(ObsState::Completed));
+
+        Ok(())
+    }
+
+    #[test]
+    fn asha_resume_works() -> TestResult {
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut optimizer = track!(AshaOptimizer::<usize, _>::new(inner, 10, 20))?;
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(optimizer.obs_state(obs.id), Some(ObsState::Running));
+        let mut obs = obs.map_value(|_| 1);
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+        assert_eq!(optimizer.suspended(), [ObsId::new(0)]);
+
+        // canceled
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 1);
+        let mut obs = obs.map_value(|_| 2);
+        obs.budget.consumption = 5;
+        track!(optimizer.tell(obs))?;
+        assert_eq!(optimizer.obs_state(ObsId::new(1)), Some(ObsState::Canceled));
+        assert!(optimizer.resume(ObsId::new(1), 5).is_err());
+        let obs = track!(optimizer.resume(ObsId::new(1), 10))?;
+        assert_eq!(obs.budget.consumption, 5);
+        let mut obs = obs.map_value(|_| 2);
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+        assert_eq!(optimizer.suspended(), [ObsId::new(0), ObsId::new(1)]);
+
+        let obs = track!(optimizer.resume(ObsId::new(0), 20))?;
+        assert_eq!(obs.budget.amount, 20);
+        assert_eq!(optimizer.suspended(), [ObsId::new(1)]);
+        let mut obs = obs.map_value(|_| 1);
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+        assert_eq!(
+            optimizer.obs_state(ObsId::new(0)),
+            Some(ObsState::Completed)
+        );
+
+        // failed
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 2);
+        track!(optimizer.fail(obs.id))?;
+        assert_eq!(optimizer.obs_state(obs.id), Some(ObsState::Failed));
+        assert!(optimizer.fail(obs.id).is_err());
+        assert!(optimizer.resume(obs.id, 20).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn asha_without_checkpoint_resume_works() -> TestResult {
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut optimizer = track!(AshaOptimizerBuilder::new()
+            .without_checkpoint()
+            .finish::<u64, _>(inner, 1, 4))?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let mut promoted = 0;
+        for _ in 0..8 {
+            let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+            if obs.budget.amount > 1 {
+                promoted += 1;
+            }
+            let value = obs.id.get();
+            let mut obs = obs.map_value(|_| value);
+            obs.budget.consumption = obs.budget.amount;
+            track!(optimizer.tell(obs))?;
+        }
+        assert!(promoted > 0);
+
+        // Promoted observations are superseded by their restarted ones.
+        let suspended = optimizer.suspended();
+        assert!(!suspended.is_empty());
+        for id in suspended {
+            track!(optimizer.resume(id, 4))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn asha_ties_are_broken_by_id() -> TestResult {
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut optimizer = track!(AshaOptimizerBuilder::new()
+            .reduction_factor(2)?
+            .finish::<usize, _>(inner, 10, 20))?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let mut obss = (0..8)
+            .map(|_| track!(optimizer.ask(&mut rng, &mut idg)))
+            .collect::<Result<Vec<_>>>()?;
+        obss.reverse();
+        for obs in obss {
+            let mut obs = obs.map_value(|_| 1);
+            obs.budget.consumption = obs.budget.amount;
+            track!(optimizer.tell(obs))?;
+        }
+        for id in 0..4 {
+            let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+            assert_eq!(obs.id.get(), id);
+            assert_eq!(obs.budget.amount, 20);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn asha_confidence_promotion_works() -> TestResult {
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut optimizer = track!(AshaOptimizerBuilder::new().finish_with_policies(
+            inner,
+            10,
+            20,
+            NeverThaw,
+            track!(ConfidencePromotion::new(1.0))?
+        ))?;
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let values = [
+            track!(MeasuredValue::new(1.0, 0.01, 10))?,
+            track!(MeasuredValue::from_samples(&[0.7, 1.7]))?,
+        ];
+        for &value in &values {
+            let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+            let mut obs = obs.map_value(|_| value);
+            obs.budget.consumption = obs.budget.amount;
+            track!(optimizer.tell(obs))?;
+        }
+
+        // The difference between the two is within the margin.
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 2);
+        let mut obs = obs
+            .map_value(|_| MeasuredValue::new(0.5, 0.01, 10).unwrap_or_else(|e| panic!("{}", e)));
+        obs.budget.consumption = obs.budget.amount;
+        track!(optimizer.tell(obs))?;
+
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.id.get(), 2);
+        assert_eq!(obs.budget.amount, 20);
+
+        let log = optimizer.promotion_log();
+        assert_eq!(log.len(), 2);
+        assert_eq!((log[0].id.get(), log[0].promoted), (0, false));
+        assert_eq!(log[0].rival, Some(values[1]));
+        assert_eq!((log[1].id.get(), log[1].promoted), (2, true));
+        assert_eq!(log[1].competitors.len(), 1);
+
         Ok(())
     }
 }
@@ -0,0 +1,128 @@
+//! Adapter for stopping a study when its budget is exhausted.
+use crate::{ErrorContext, ErrorKind, IdGen, Obs, ObsId, Optimizer, Result, StudyBudget};
+use rand::Rng;
+use std::collections::HashSet;
+
+/// An optimizer adapter that refuses asks after the study budget has been exhausted.
+///
+/// If the budget has been exhausted, `ask` returns an `ErrorKind::Other` error.
+///
+/// Each told observation is recorded as an evaluation of the budget.
+/// Asked but not yet told observations are also taken into account,
+/// so the number of evaluations never exceeds the limit even if they are run in parallel.
+#[derive(Debug)]
+pub struct BudgetedOptimizer<O> {
+    inner: O,
+    budget: StudyBudget,
+    pending: HashSet<ObsId>,
+}
+impl<O: Optimizer> BudgetedOptimizer<O> {
+    /// Makes a new `BudgetedOptimizer` instance.
+    pub fn new(inner: O, budget: StudyBudget) -> Self {
+        Self {
+            inner,
+            budget,
+            pending: HashSet::new(),
+        }
+    }
+
+    /// Returns a reference to the budget.
+    pub fn budget(&self) -> &StudyBudget {
+        &self.budget
+    }
+
+    /// Returns `true` if no more asks are allowed, otherwise `false`.
+    pub fn is_exhausted(&self) -> bool {
+        self.budget.is_exhausted()
+            || self
+                .budget
+                .remaining_evaluations()
+                .is_some_and(|n| n <= self.pending.len() as u64)
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `BudgetedOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+impl<O: Optimizer> Optimizer for BudgetedOptimizer<O> {
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        if self.is_exhausted() {
+            track_panic!(
+                ErrorKind::Other,
+                ErrorContext::new("Study budget exhausted")
+            );
+        }
+        let obs = track!(self.inner.ask(rng, idg))?;
+        self.pending.insert(obs.id);
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        if self.pending.remove(&obs.id) {
+            self.budget.record_evaluation();
+        }
+        track!(self.inner.tell(obs))
+    }
+
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        for obs in &obss {
+            if self.pending.remove(&obs.id) {
+                self.budget.record_evaluation();
+            }
+        }
+        track!(self.inner.tell_batch(obss))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::random::RandomOptimizer;
+    use std::time::Duration;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn budgeted_works() -> TestResult {
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let inner = RandomOptimizer::new(track!(DiscreteDomain::new(10))?);
+        let budget = StudyBudget::new()
+            .max_evaluations(4)
+            .max_duration(Duration::from_secs(3600));
+        let mut opt = BudgetedOptimizer::new(inner, budget);
+
+        let obs0 = track!(opt.ask(&mut rng, &mut idg))?;
+        let obs1 = track!(opt.ask(&mut rng, &mut idg))?;
+        track!(opt.tell(obs0.map_value(|()| 1.0)))?;
+        assert_eq!(opt.budget().remaining_evaluations(), Some(3));
+        assert_eq!(opt.budget().remaining_fraction(), 0.75);
+
+        let obs2 = track!(opt.ask(&mut rng, &mut idg))?;
+        let obs3 = track!(opt.ask(&mut rng, &mut idg))?;
+        assert!(opt.is_exhausted());
+        assert!(opt.ask(&mut rng, &mut idg).is_err());
+        for obs in [obs1, obs2, obs3] {
+            track!(opt.tell(obs.map_value(|()| 1.0)))?;
+        }
+        assert!(opt.budget().is_exhausted());
+        assert_eq!(opt.budget().remaining_fraction(), 0.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,323 @@
+//! Coordinate descent with golden-section line search.
+//!
+//! # References
+//!
+//! - [Coordinate descent (Wikipedia)](https://en.wikipedia.org/wiki/Coordinate_descent)
+//! - [Golden-section search (Wikipedia)](https://en.wikipedia.org/wiki/Golden-section_search)
+use crate::domains::ContinuousDomain;
+use crate::{Domain, ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use rand::distributions::Distribution;
+use rand::Rng;
+
+const INV_PHI: f64 = 0.618_033_988_749_895;
+
+/// Builder of `CoordinateDescentOptimizer`.
+#[derive(Debug, Clone)]
+pub struct CoordinateDescentOptimizerBuilder {
+    max_sweeps: usize,
+    tolerance: f64,
+}
+impl CoordinateDescentOptimizerBuilder {
+    /// Makes a new `CoordinateDescentOptimizerBuilder` instance with the default settings.
+    pub const fn new() -> Self {
+        Self {
+            max_sweeps: 10,
+            tolerance: 0.001,
+        }
+    }
+
+    /// Sets the maximum number of sweeps (i.e., line searches over all of the dimensions).
+    ///
+    /// The default value is `10`.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn max_sweeps(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 0, ErrorKind::InvalidInput);
+        self.max_sweeps = n;
+        Ok(self)
+    }
+
+    /// Sets the tolerance of a line search relative to the size of the domain.
+    ///
+    /// A line search finishes when the width of its bracketing interval becomes smaller than
+    /// `tolerance * domain.size()`.
+    /// The default value is `0.001`.
+    ///
+    /// # Errors
+    ///
+    /// If `tolerance` is not in the range `(0.0, 1.0)`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn tolerance(&mut self, tolerance: f64) -> Result<&mut Self> {
+        track_assert!(
+            0.0 < tolerance && tolerance < 1.0,
+            ErrorKind::InvalidInput; tolerance
+        );
+        self.tolerance = tolerance;
+        Ok(self)
+    }
+
+    /// Builds a new `CoordinateDescentOptimizer` instance starting from a randomly sampled point.
+    ///
+    /// # Errors
+    ///
+    /// If `params_domain` is empty, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn finish<V, R: Rng>(
+        &self,
+        params_domain: Vec<ContinuousDomain>,
+        mut rng: R,
+    ) -> Result<CoordinateDescentOptimizer<V>> {
+        let point = params_domain
+            .iter()
+            .map(|p| p.sample(&mut rng))
+            .collect::<Vec<_>>();
+        track!(self.finish_with_initial_point(params_domain, &point))
+    }
+
+    /// Builds a new `CoordinateDescentOptimizer` instance starting from the given point.
+    ///
+    /// # Errors
+    ///
+    /// If `params_domain` is empty or `point` is not contained in `params_domain`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn finish_with_initial_point<V>(
+        &self,
+        params_domain: Vec<ContinuousDomain>,
+        point: &[f64],
+    ) -> Result<CoordinateDescentOptimizer<V>> {
+        track_assert!(!params_domain.is_empty(), ErrorKind::InvalidInput);
+        track_assert_eq!(params_domain.len(), point.len(), ErrorKind::InvalidInput);
+        for (d, &x) in params_domain.iter().zip(point.iter()) {
+            track_assert!(d.contains(&x), ErrorKind::InvalidInput; x);
+        }
+
+        Ok(CoordinateDescentOptimizer {
+            params_domain,
+            builder: self.clone(),
+            current: point.to_vec(),
+            current_value: None,
+            line: None,
+            dim: 0,
+            sweeps: 0,
+            improved: false,
+            evaluating: None,
+            finished: false,
+        })
+    }
+}
+impl Default for CoordinateDescentOptimizerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Coordinate descent optimizer.
+///
+/// This optimizer cycles through the dimensions of the search space and
+/// minimizes the objective along each of them by golden-section search
+/// while keeping the other coordinates fixed.
+///
+/// Evaluations are sequential: `ask` fails while the previously asked observation has not been told.
+/// The optimizer finishes when a sweep makes no improvement or the maximum number of sweeps is reached,
+/// after that `ask` always fails.
+#[derive(Debug)]
+pub struct CoordinateDescentOptimizer<V> {
+    params_domain: Vec<ContinuousDomain>,
+    builder: CoordinateDescentOptimizerBuilder,
+    current: Vec<f64>,
+    current_value: Option<V>,
+    line: Option<LineSearch<V>>,
+    dim: usize,
+    sweeps: usize,
+    improved: bool,
+    evaluating: Option<(ObsId, Probe)>,
+    finished: bool,
+}
+impl<V> CoordinateDescentOptimizer<V>
+where
+    V: Ord + Clone,
+{
+    /// Makes a new `CoordinateDescentOptimizer` instance with the default settings.
+    pub fn new<R: Rng>(params_domain: Vec<ContinuousDomain>, rng: R) -> Result<Self> {
+        track!(CoordinateDescentOptimizerBuilder::new().finish(params_domain, rng))
+    }
+
+    /// Returns the best parameter found so far.
+    pub fn best_param(&self) -> &[f64] {
+        &self.current
+    }
+
+    /// Returns the value of the best parameter found so far.
+    pub fn best_value(&self) -> Option<&V> {
+        self.current_value.as_ref()
+    }
+
+    /// Returns the number of the completed sweeps.
+    pub fn sweeps(&self) -> usize {
+        self.sweeps
+    }
+
+    /// Returns `true` if this optimizer has finished.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    fn next_line(&mut self) {
+        self.line = None;
+        self.dim += 1;
+        if self.dim == self.params_domain.len() {
+            self.dim = 0;
+            self.sweeps += 1;
+            if !self.improved || self.sweeps >= self.builder.max_sweeps {
+                self.finished = true;
+            }
+            self.improved = false;
+        }
+    }
+}
+impl<V> Optimizer for CoordinateDescentOptimizer<V>
+where
+    V: Ord + Clone,
+{
+    type Param = Vec<f64>;
+    type Value = V;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, _rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        track_assert!(self.evaluating.is_none(), ErrorKind::Other);
+        track_assert!(!self.finished, ErrorKind::Other, "Already finished");
+
+        let (probe, x) = if self.current_value.is_none() {
+            (Probe::Initial, self.current.clone())
+        } else {
+            let domain = &self.params_domain[self.dim];
+            let line = self
+                .line
+                .get_or_insert_with(|| LineSearch::new(domain.low(), domain.high()));
+            let (probe, v) = line.next_probe();
+            let mut x = self.current.clone();
+            x[self.dim] = domain.clamp(v);
+            (probe, x)
+        };
+
+        let obs = track!(Obs::new(idg, x))?;
+        self.evaluating = Some((obs.id, probe));
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let (id, probe) = track_assert_some!(self.evaluating, ErrorKind::UnknownObservation);
+        track_assert_eq!(id, obs.id, ErrorKind::UnknownObservation);
+        self.evaluating = None;
+
+        if probe == Probe::Initial {
+            self.current_value = Some(obs.value);
+            return Ok(());
+        }
+
+        if self.current_value.as_ref().is_none_or(|v| obs.value < *v) {
+            self.current = obs.param;
+            self.current_value = Some(obs.value.clone());
+            self.improved = true;
+        }
+
+        let line = track_assert_some!(self.line.as_mut(), ErrorKind::Bug);
+        line.tell(probe, obs.value);
+        if line.width() < self.builder.tolerance * self.params_domain[self.dim].size() {
+            self.next_line();
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Probe {
+    Initial,
+    Left,
+    Right,
+}
+
+#[derive(Debug)]
+struct LineSearch<V> {
+    low: f64,
+    high: f64,
+    left: (f64, Option<V>),
+    right: (f64, Option<V>),
+}
+impl<V: Ord> LineSearch<V> {
+    fn new(low: f64, high: f64) -> Self {
+        let d = INV_PHI * (high - low);
+        Self {
+            low,
+            high,
+            left: (high - d, None),
+            right: (low + d, None),
+        }
+    }
+
+    fn width(&self) -> f64 {
+        self.high - self.low
+    }
+
+    fn next_probe(&self) -> (Probe, f64) {
+        if self.left.1.is_none() {
+            (Probe::Left, self.left.0)
+        } else {
+            (Probe::Right, self.right.0)
+        }
+    }
+
+    fn tell(&mut self, probe: Probe, value: V) {
+        match probe {
+            Probe::Left => self.left.1 = Some(value),
+            Probe::Right => self.right.1 = Some(value),
+            Probe::Initial => unreachable!(),
+        }
+
+        let left_is_better = match (&self.left.1, &self.right.1) {
+            (Some(l), Some(r)) => l < r,
+            _ => return,
+        };
+        if left_is_better {
+            self.high = self.right.0;
+            self.right = std::mem::replace(&mut self.left, (0.0, None));
+            self.left.0 = self.high - INV_PHI * self.width();
+        } else {
+            self.low = self.left.0;
+            self.left = std::mem::replace(&mut self.right, (0.0, None));
+            self.right.0 = self.low + INV_PHI * self.width();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::SerialIdGenerator;
+    use ordered_float::NotNan;
+    use rand;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn coordinate_descent_works() -> TestResult {
+        let params_domain = vec![
+            track!(ContinuousDomain::new(-5.0, 5.0))?,
+            track!(ContinuousDomain::new(-5.0, 5.0))?,
+        ];
+        let mut opt = track!(CoordinateDescentOptimizerBuilder::new()
+            .tolerance(0.0001)?
+            .finish_with_initial_point(params_domain, &[4.0, 4.0]))?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        while !opt.is_finished() {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let value = (obs.param[0] - 0.3).powi(2) + (obs.param[1] + 1.0).powi(2);
+            track!(opt
+                .tell(obs.map_value(|()| NotNan::new(value).unwrap_or_else(|e| panic!("{}", e)))))?;
+        }
+        assert!(opt.ask(&mut rng, &mut idg).is_err());
+        assert!((opt.best_param()[0] - 0.3).abs() < 0.01);
+        assert!((opt.best_param()[1] + 1.0).abs() < 0.01);
+        Ok(())
+    }
+}
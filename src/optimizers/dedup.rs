@@ -0,0 +1,375 @@
+//! Adapter for avoiding duplicate evaluations.
+//!
+//! On small discrete domains, optimizers (e.g., `RandomOptimizer`) often ask parameters that have already
+//! been evaluated (or are being evaluated). `DedupOptimizer` detects such duplicates by
+//! hashing the parameters and handles them according to the given `DedupPolicy`.
+//!
+//! `EvaluationCache` works on the evaluation side instead:
+//! it wraps an objective function and reuses the values of already evaluated parameters.
+use crate::optimizers::nsga2::Mutate;
+use crate::{Domain, IdGen, Obs, Optimizer, Result};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// This trait allows perturbing a duplicate parameter.
+pub trait Perturb<P> {
+    /// Perturbs the given parameter.
+    fn perturb<R: Rng>(&mut self, rng: R, param: &mut P) -> Result<()>;
+}
+
+/// A `Perturb` implementation that does nothing.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoPerturbation;
+impl<P> Perturb<P> for NoPerturbation {
+    fn perturb<R: Rng>(&mut self, _rng: R, _param: &mut P) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// A `Perturb` implementation based on a mutation operator of `Nsga2Optimizer`.
+#[derive(Debug)]
+pub struct MutatePerturbation<D, M> {
+    domain: D,
+    mutator: M,
+}
+impl<D, M> MutatePerturbation<D, M>
+where
+    D: Domain,
+    M: Mutate<D>,
+{
+    /// Makes a new `MutatePerturbation` instance.
+    pub fn new(domain: D, mutator: M) -> Self {
+        Self { domain, mutator }
+    }
+}
+impl<D, M> Perturb<D::Point> for MutatePerturbation<D, M>
+where
+    D: Domain,
+    M: Mutate<D>,
+{
+    fn perturb<R: Rng>(&mut self, rng: R, param: &mut D::Point) -> Result<()> {
+        track!(self.mutator.mutate(rng, &self.domain, param))
+    }
+}
+
+/// How to handle duplicate parameters.
+#[derive(Debug)]
+pub enum DedupPolicy<M> {
+    /// Duplicate observations are dropped (never told to the inner optimizer) and the inner optimizer is asked again.
+    ///
+    /// Note that some optimizers (e.g., `NelderMeadOptimizer`) cannot work with this policy
+    /// because they require that every asked observation is told.
+    Reask,
+
+    /// If the value of a duplicate parameter is already known,
+    /// the duplicate observation is told to the inner optimizer with the cached value and
+    /// the inner optimizer is asked again.
+    ///
+    /// Duplicates of parameters that are still being evaluated are dropped as `Reask` does.
+    TellCached,
+
+    /// Duplicate parameters are perturbed by the given `Perturb` implementation.
+    ///
+    /// If a perturbed parameter is still a duplicate, the observation is dropped as `Reask` does.
+    Perturb(M),
+}
+
+/// An optimizer adapter that avoids asking already evaluated (or being evaluated) parameters.
+///
+/// If no unique parameter is found within the maximum number of retries,
+/// the last (duplicate) observation is returned as is.
+#[derive(Debug)]
+pub struct DedupOptimizer<O: Optimizer, M = NoPerturbation> {
+    inner: O,
+    policy: DedupPolicy<M>,
+    max_retries: usize,
+    pending: HashSet<O::Param>,
+    evaluated: HashMap<O::Param, O::Value>,
+    duplicates: usize,
+}
+impl<O> DedupOptimizer<O, NoPerturbation>
+where
+    O: Optimizer,
+    O::Param: Clone + Eq + Hash,
+    O::Value: Clone,
+{
+    /// Makes a new `DedupOptimizer` instance.
+    ///
+    /// The default maximum number of retries is `10`.
+    pub fn new(inner: O, policy: DedupPolicy<NoPerturbation>) -> Self {
+        Self::with_policy(inner, policy)
+    }
+}
+impl<O, M> DedupOptimizer<O, M>
+where
+    O: Optimizer,
+    O::Param: Clone + Eq + Hash,
+    O::Value: Clone,
+    M: Perturb<O::Param>,
+{
+    /// Makes a new `DedupOptimizer` instance that perturbs duplicate parameters by `perturbation`.
+    pub fn with_perturbation(inner: O, perturbation: M) -> Self {
+        Self::with_policy(inner, DedupPolicy::Perturb(perturbation))
+    }
+
+    fn with_policy(inner: O, policy: DedupPolicy<M>) -> Self {
+        Self {
+            inner,
+            policy,
+            max_retries: 10,
+            pending: HashSet::new(),
+            evaluated: HashMap::new(),
+            duplicates: 0,
+        }
+    }
+
+    /// Sets the maximum number of retries per ask.
+    pub fn max_retries(mut self, n: usize) -> Self {
+        self.max_retries = n;
+        self
+    }
+
+    /// Returns the cached value of the given parameter if it has already been evaluated.
+    pub fn cached_value(&self, param: &O::Param) -> Option<&O::Value> {
+        self.evaluated.get(param)
+    }
+
+    /// Returns the number of the duplicate parameters asked by the inner optimizer so far.
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `DedupOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+
+    fn is_duplicate(&self, param: &O::Param) -> bool {
+        self.pending.contains(param) || self.evaluated.contains_key(param)
+    }
+
+    fn record(&mut self, obs: &Obs<O::Param, O::Value>) {
+        self.pending.remove(&obs.param);
+        self.evaluated.insert(obs.param.clone(), obs.value.clone());
+    }
+}
+impl<O, M> Optimizer for DedupOptimizer<O, M>
+where
+    O: Optimizer,
+    O::Param: Clone + Eq + Hash,
+    O::Value: Clone,
+    M: Perturb<O::Param>,
+{
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, mut rng: R, mut idg: G) -> Result<Obs<Self::Param>> {
+        let mut retries = 0;
+        loop {
+            let mut obs = track!(self.inner.ask(&mut rng, &mut idg))?;
+            if self.is_duplicate(&obs.param) {
+                self.duplicates += 1;
+                if retries < self.max_retries {
+                    retries += 1;
+                    match &mut self.policy {
+                        DedupPolicy::Reask => continue,
+                        DedupPolicy::TellCached => {
+                            if let Some(value) = self.evaluated.get(&obs.param).cloned() {
+                                track!(self.inner.tell(obs.map_value(|()| value)))?;
+                            }
+                            continue;
+                        }
+                        DedupPolicy::Perturb(m) => {
+                            track!(m.perturb(&mut rng, &mut obs.param))?;
+                            if self.is_duplicate(&obs.param) {
+                                continue;
+                            }
+                        }
+                    }
+                }
+            }
+            self.pending.insert(obs.param.clone());
+            return Ok(obs);
+        }
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        self.record(&obs);
+        track!(self.inner.tell(obs))
+    }
+
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        for obs in &obss {
+            self.record(obs);
+        }
+        track!(self.inner.tell_batch(obss))
+    }
+}
+
+/// How `EvaluationCache` handles already evaluated parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// The cached value is reused.
+    Reuse,
+
+    /// The parameter is evaluated again until the given number of values are cached for it
+    /// (e.g., to average a noisy objective), and then the last cached value is reused.
+    Resample(usize),
+}
+
+/// A cache of evaluations keyed by parameters.
+///
+/// This is intended to be consulted before evaluating an asked parameter,
+/// so that expensive objectives are not evaluated for duplicate parameters.
+#[derive(Debug)]
+pub struct EvaluationCache<P, V> {
+    policy: CachePolicy,
+    values: HashMap<P, Vec<V>>,
+    hits: usize,
+    misses: usize,
+}
+impl<P, V> EvaluationCache<P, V>
+where
+    P: Clone + Eq + Hash,
+    V: Clone,
+{
+    /// Makes a new `EvaluationCache` instance.
+    pub fn new(policy: CachePolicy) -> Self {
+        Self {
+            policy,
+            values: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the value of the given parameter.
+    ///
+    /// `objective` is invoked only if the value should not be reused according to the policy.
+    ///
+    /// # Errors
+    ///
+    /// Errors returned by `objective` are propagated (and nothing is cached).
+    pub fn evaluate<F>(&mut self, param: &P, objective: F) -> Result<V>
+    where
+        F: FnOnce(&P) -> Result<V>,
+    {
+        let limit = match self.policy {
+            CachePolicy::Reuse => 1,
+            CachePolicy::Resample(n) => n.max(1),
+        };
+        if let Some(value) = self
+            .values
+            .get(param)
+            .filter(|vs| vs.len() >= limit)
+            .and_then(|vs| vs.last())
+        {
+            self.hits += 1;
+            return Ok(value.clone());
+        }
+
+        let value = track!(objective(param))?;
+        self.misses += 1;
+        self.values
+            .entry(param.clone())
+            .or_default()
+            .push(value.clone());
+        Ok(value)
+    }
+
+    /// Evaluates the parameter of the given observation via `EvaluationCache::evaluate`.
+    pub fn evaluate_obs<F>(&mut self, obs: Obs<P>, objective: F) -> Result<Obs<P, V>>
+    where
+        F: FnOnce(&P) -> Result<V>,
+    {
+        let value = track!(self.evaluate(&obs.param, objective))?;
+        Ok(obs.map_value(|()| value))
+    }
+
+    /// Returns the cached values of the given parameter.
+    pub fn values(&self, param: &P) -> Option<&[V]> {
+        self.values.get(param).map(|vs| &vs[..])
+    }
+
+    /// Returns the number of the evaluations served from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns the number of the evaluations that invoked the objective function.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::nsga2::Replace;
+    use crate::optimizers::random::RandomOptimizer;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn dedup_works() -> TestResult {
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let inner = RandomOptimizer::new(track!(DiscreteDomain::new(5))?);
+        let mut opt = DedupOptimizer::new(inner, DedupPolicy::Reask).max_retries(1000);
+        let mut params = HashSet::new();
+        for _ in 0..5 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            assert!(params.insert(obs.param));
+            track!(opt.tell(obs.map_value(|()| 0.0)))?;
+        }
+        assert_eq!(opt.cached_value(&3), Some(&0.0));
+
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        assert!(params.contains(&obs.param));
+        assert!(opt.duplicates() > 1000);
+
+        let inner = RandomOptimizer::new(track!(DiscreteDomain::new(5))?);
+        let mut opt = DedupOptimizer::new(inner, DedupPolicy::TellCached).max_retries(1000);
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        track!(opt.tell(obs.map_value(|()| 1.0)))?;
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        assert_eq!(opt.cached_value(&obs.param), None);
+
+        let domain = track!(DiscreteDomain::new(5))?;
+        let perturbation = MutatePerturbation::new(domain.clone(), track!(Replace::new(1.0))?);
+        let inner = RandomOptimizer::<_, f64>::new(domain);
+        let mut opt = DedupOptimizer::with_perturbation(inner, perturbation).max_retries(1000);
+        let mut params = HashSet::new();
+        for _ in 0..5 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            assert!(params.insert(obs.param));
+        }
+
+        let mut cache = EvaluationCache::new(CachePolicy::Resample(2));
+        let mut count = 0;
+        for _ in 0..4 {
+            let obs = Obs::new(&mut idg, 3)?;
+            let obs = track!(cache.evaluate_obs(obs, |&x| {
+                count += 1;
+                Ok(x + count)
+            }))?;
+            assert!(obs.value == 4 || obs.value == 5);
+        }
+        assert_eq!(cache.values(&3), Some(&[4, 5][..]));
+        assert_eq!((cache.hits(), cache.misses()), (2, 2));
+        Ok(())
+    }
+}
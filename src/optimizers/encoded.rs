@@ -0,0 +1,135 @@
+//! Adapter for optimizing structured parameters by continuous optimizers.
+use crate::encoding::{Encoder, ParamValue};
+use crate::{ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// An optimizer adapter that optimizes structured parameters by using an optimizer for continuous vectors.
+///
+/// The inner optimizer should search the domains returned by `Encoder::domains`.
+/// Asked observations are decoded by the encoder, and
+/// told observations are associated with the original continuous vectors
+/// (or encoded, if the observations were not asked by this optimizer).
+#[derive(Debug)]
+pub struct EncodedOptimizer<O> {
+    inner: O,
+    encoder: Encoder,
+    asked: HashMap<ObsId, Vec<f64>>,
+}
+impl<O> EncodedOptimizer<O>
+where
+    O: Optimizer<Param = Vec<f64>>,
+{
+    /// Makes a new `EncodedOptimizer` instance.
+    pub fn new(inner: O, encoder: Encoder) -> Self {
+        Self {
+            inner,
+            encoder,
+            asked: HashMap::new(),
+        }
+    }
+
+    /// Returns a reference to the encoder.
+    pub fn encoder(&self) -> &Encoder {
+        &self.encoder
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `EncodedOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+
+    fn encode(&mut self, obs: Obs<Vec<ParamValue>, O::Value>) -> Result<Obs<Vec<f64>, O::Value>> {
+        let param = match self.asked.remove(&obs.id) {
+            Some(param) => param,
+            None => track!(self.encoder.encode(&obs.param))?,
+        };
+        track_assert_eq!(param.len(), self.encoder.dimensions(), ErrorKind::Bug);
+        Ok(Obs {
+            id: obs.id,
+            param,
+            value: obs.value,
+        })
+    }
+}
+impl<O> Optimizer for EncodedOptimizer<O>
+where
+    O: Optimizer<Param = Vec<f64>>,
+{
+    type Param = Vec<ParamValue>;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let obs = track!(self.inner.ask(rng, idg))?;
+        let param = track!(self.encoder.decode(&obs.param))?;
+        self.asked.insert(obs.id, obs.param);
+        Ok(Obs {
+            id: obs.id,
+            param,
+            value: (),
+        })
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let obs = track!(self.encode(obs))?;
+        track!(self.inner.tell(obs))
+    }
+
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        let obss = obss
+            .into_iter()
+            .map(|obs| track!(self.encode(obs)))
+            .collect::<Result<Vec<_>>>()?;
+        track!(self.inner.tell_batch(obss))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::{CategoricalDomain, DiscreteDomain};
+    use crate::encoding::Encoding;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::nelder_mead::NelderMeadOptimizer;
+    use ordered_float::NotNan;
+    use rand;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn encoded_optimizer_works() -> TestResult {
+        let encoder = track!(Encoder::new(vec![
+            Encoding::Discrete(track!(DiscreteDomain::new(10))?),
+            Encoding::Categorical(track!(CategoricalDomain::new(3))?),
+        ]))?;
+        let inner = track!(NelderMeadOptimizer::new(
+            encoder.domains(),
+            rand::thread_rng()
+        ))?;
+        let mut opt = EncodedOptimizer::new(inner, encoder);
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        for _ in 0..30 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let value = match (obs.param[0], obs.param[1]) {
+                (ParamValue::Int(i), ParamValue::Category(c)) => {
+                    (i as f64 - 3.0).abs() + if c == 2 { 0.0 } else { 1.0 }
+                }
+                _ => panic!(),
+            };
+            let value = NotNan::new(value).unwrap_or_else(|e| panic!("{}", e));
+            track!(opt.tell(obs.map_value(|()| value)))?;
+        }
+        Ok(())
+    }
+}
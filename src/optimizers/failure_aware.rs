@@ -0,0 +1,169 @@
+//! Adapter for handling failed evaluations.
+use crate::{IdGen, Obs, ObsId, Optimizer, Result};
+use rand::Rng;
+use std::fmt;
+
+/// An error that occurred while evaluating a parameter.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TrialError {
+    /// The reason of the failure.
+    pub reason: String,
+}
+impl TrialError {
+    /// Makes a new `TrialError` instance.
+    pub fn new<T: Into<String>>(reason: T) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+}
+impl fmt::Display for TrialError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Trial failed: {}", self.reason)
+    }
+}
+impl std::error::Error for TrialError {}
+
+/// How to handle failed evaluations.
+#[derive(Debug, Clone)]
+pub enum FailurePolicy<V> {
+    /// Failed observations are not told to the inner optimizer.
+    ///
+    /// Note that some optimizers (e.g., `NelderMeadOptimizer`) cannot work with this policy
+    /// because they require that every asked observation is told.
+    Discard,
+
+    /// Failed observations are told to the inner optimizer with the given (usually worst) value.
+    ///
+    /// For example, by using a vector of `f64::INFINITY` as the penalty,
+    /// failed individuals of `Nsga2Optimizer` are dominated by all successful ones.
+    Penalize(V),
+}
+
+/// An optimizer adapter that accepts failed evaluations.
+///
+/// The value type of this optimizer is `Result<V, TrialError>`,
+/// and failed observations are handled according to the given `FailurePolicy`.
+#[derive(Debug)]
+pub struct FailureAwareOptimizer<O: Optimizer> {
+    inner: O,
+    policy: FailurePolicy<O::Value>,
+    failures: Vec<(ObsId, TrialError)>,
+}
+impl<O> FailureAwareOptimizer<O>
+where
+    O: Optimizer,
+    O::Value: Clone,
+{
+    /// Makes a new `FailureAwareOptimizer` instance.
+    pub fn new(inner: O, policy: FailurePolicy<O::Value>) -> Self {
+        Self {
+            inner,
+            policy,
+            failures: Vec::new(),
+        }
+    }
+
+    /// Returns the failures told so far.
+    pub fn failures(&self) -> &[(ObsId, TrialError)] {
+        &self.failures
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `FailureAwareOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+
+    // Records a failure, and returns the observation to be told to the inner optimizer (if any).
+    fn resolve(
+        &mut self,
+        obs: Obs<O::Param, std::result::Result<O::Value, TrialError>>,
+    ) -> Option<Obs<O::Param, O::Value>> {
+        let (obs, value) = obs.take_value();
+        match value {
+            Ok(value) => Some(obs.map_value(|()| value)),
+            Err(e) => {
+                self.failures.push((obs.id, e));
+                match &self.policy {
+                    FailurePolicy::Discard => None,
+                    FailurePolicy::Penalize(penalty) => {
+                        let penalty = penalty.clone();
+                        Some(obs.map_value(|()| penalty))
+                    }
+                }
+            }
+        }
+    }
+}
+impl<O> Optimizer for FailureAwareOptimizer<O>
+where
+    O: Optimizer,
+    O::Value: Clone,
+{
+    type Param = O::Param;
+    type Value = std::result::Result<O::Value, TrialError>;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        track!(self.inner.ask(rng, idg))
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        if let Some(obs) = self.resolve(obs) {
+            track!(self.inner.tell(obs))?;
+        }
+        Ok(())
+    }
+
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        let obss = obss
+            .into_iter()
+            .filter_map(|obs| self.resolve(obs))
+            .collect();
+        track!(self.inner.tell_batch(obss))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::ContinuousDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::nelder_mead::NelderMeadOptimizer;
+    use ordered_float::NotNan;
+    use rand;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn failure_aware_works() -> TestResult {
+        let params_domain = vec![
+            track!(ContinuousDomain::new(0.0, 1.0))?,
+            track!(ContinuousDomain::new(0.0, 1.0))?,
+        ];
+        let mut rng = rand::thread_rng();
+        let inner = track!(NelderMeadOptimizer::new(params_domain, &mut rng))?;
+        let penalty = NotNan::new(f64::MAX).unwrap_or_else(|e| panic!("{}", e));
+        let mut opt = FailureAwareOptimizer::new(inner, FailurePolicy::Penalize(penalty));
+        let mut idg = SerialIdGenerator::new();
+
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        track!(opt.tell(obs.map_value(|()| Err(TrialError::new("crashed")))))?;
+        assert_eq!(opt.failures().len(), 1);
+
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        let value = NotNan::new(obs.param[0]).unwrap_or_else(|e| panic!("{}", e));
+        track!(opt.tell(obs.map_value(|()| Ok(value))))?;
+        assert_eq!(opt.failures().len(), 1);
+
+        Ok(())
+    }
+}
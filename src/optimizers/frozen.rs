@@ -0,0 +1,218 @@
+//! Adapter for re-optimizing a subset of the parameter dimensions.
+//!
+//! `FrozenOptimizer` fixes some dimensions of vector parameters to constant values and
+//! lets the inner optimizer search only the remaining (free) dimensions.
+//! This is useful for iterative refinement, where a follow-up study re-optimizes selected parameters
+//! while keeping the others as they are. The observations of the previous study can be reused
+//! via `FrozenOptimizer::tell_compatible`.
+use crate::domains::VecDomain;
+use crate::{ErrorContext, ErrorKind, IdGen, Obs, Optimizer, Result};
+use rand::Rng;
+
+/// Returns the domain of the free (i.e., `None`) dimensions of `frozen`.
+///
+/// The returned domain should be searched by the inner optimizer of `FrozenOptimizer`.
+///
+/// # Errors
+///
+/// If the dimensions of `domain` differ from `frozen`, an `ErrorKind::InvalidInput` error will be returned.
+pub fn free_domain<D: Clone, T>(
+    domain: &VecDomain<D>,
+    frozen: &[Option<T>],
+) -> Result<VecDomain<D>> {
+    track_assert_eq!(domain.0.len(), frozen.len(), ErrorKind::InvalidInput);
+    Ok(VecDomain(
+        domain
+            .0
+            .iter()
+            .zip(frozen.iter())
+            .filter(|(_, f)| f.is_none())
+            .map(|(d, _)| d.clone())
+            .collect(),
+    ))
+}
+
+/// An optimizer adapter that freezes some dimensions of vector parameters.
+///
+/// The inner optimizer should search the domain returned by `free_domain`.
+#[derive(Debug)]
+pub struct FrozenOptimizer<O, T> {
+    inner: O,
+    frozen: Vec<Option<T>>,
+}
+impl<O, T> FrozenOptimizer<O, T>
+where
+    O: Optimizer<Param = Vec<T>>,
+    T: Clone + PartialEq,
+{
+    /// Makes a new `FrozenOptimizer` instance.
+    ///
+    /// `frozen` has an element for each dimension: `Some(value)` freezes the dimension to `value`, and
+    /// `None` leaves the dimension free.
+    pub fn new(inner: O, frozen: Vec<Option<T>>) -> Self {
+        Self { inner, frozen }
+    }
+
+    /// Returns the frozen values of the dimensions.
+    pub fn frozen(&self) -> &[Option<T>] {
+        &self.frozen
+    }
+
+    /// Returns the indices of the free dimensions.
+    pub fn free_indices(&self) -> Vec<usize> {
+        self.frozen
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| v.is_none())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Tells the given observation to the inner optimizer if it is compatible with the frozen values.
+    ///
+    /// This is intended to reuse the observations of a previous study whose parameters cover all of the dimensions.
+    /// Returns `false` (and drops the observation) if some of the frozen dimensions have different values.
+    ///
+    /// # Errors
+    ///
+    /// If the dimensions of the parameter differ from the frozen ones, an `ErrorKind::InvalidInput` error will be returned.
+    /// And errors returned by the inner optimizer are propagated.
+    pub fn tell_compatible(&mut self, obs: Obs<Vec<T>, O::Value>) -> Result<bool> {
+        track_assert_eq!(obs.param.len(), self.frozen.len(), ErrorKind::InvalidInput; obs.id);
+        if !self.is_compatible(&obs.param) {
+            return Ok(false);
+        }
+        let obs = self.project(obs);
+        track!(self.inner.tell(obs))?;
+        Ok(true)
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `FrozenOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+
+    fn is_compatible(&self, param: &[T]) -> bool {
+        self.frozen
+            .iter()
+            .zip(param.iter())
+            .all(|(f, p)| f.as_ref().is_none_or(|f| f == p))
+    }
+
+    fn check_and_project<V>(&self, obs: Obs<Vec<T>, V>) -> Result<Obs<Vec<T>, V>> {
+        track_assert_eq!(obs.param.len(), self.frozen.len(), ErrorKind::InvalidInput; obs.id);
+        if !self.is_compatible(&obs.param) {
+            track_panic!(
+                ErrorKind::InvalidInput,
+                ErrorContext::new("Frozen dimensions have different values").obs_id(obs.id)
+            );
+        }
+        Ok(self.project(obs))
+    }
+
+    fn project<V>(&self, obs: Obs<Vec<T>, V>) -> Obs<Vec<T>, V> {
+        obs.map_param(|param| {
+            param
+                .into_iter()
+                .zip(self.frozen.iter())
+                .filter(|(_, f)| f.is_none())
+                .map(|(p, _)| p)
+                .collect()
+        })
+    }
+}
+impl<O, T> Optimizer for FrozenOptimizer<O, T>
+where
+    O: Optimizer<Param = Vec<T>>,
+    T: Clone + PartialEq,
+{
+    type Param = Vec<T>;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let obs = track!(self.inner.ask(rng, idg))?;
+        track_assert_eq!(
+            obs.param.len(),
+            self.frozen.iter().filter(|f| f.is_none()).count(),
+            ErrorKind::InvalidInput; obs.id
+        );
+        let mut free = obs.param.into_iter();
+        let param = self
+            .frozen
+            .iter()
+            .map(|f| f.clone().or_else(|| free.next()))
+            .collect::<Option<Vec<_>>>();
+        let param = track_assert_some!(param, ErrorKind::Bug; obs.id);
+        Ok(Obs {
+            id: obs.id,
+            param,
+            value: (),
+        })
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let obs = track!(self.check_and_project(obs))?;
+        track!(self.inner.tell(obs))
+    }
+
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        let obss = obss
+            .into_iter()
+            .map(|obs| track!(self.check_and_project(obs)))
+            .collect::<Result<Vec<_>>>()?;
+        track!(self.inner.tell_batch(obss))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::random::RandomOptimizer;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn frozen_works() -> TestResult {
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let domain = VecDomain(vec![
+            track!(DiscreteDomain::new(10))?,
+            track!(DiscreteDomain::new(20))?,
+            track!(DiscreteDomain::new(30))?,
+        ]);
+        let frozen = vec![Some(3), None, Some(7)];
+        let free_domain = track!(free_domain(&domain, &frozen))?;
+        assert_eq!(free_domain.0, [track!(DiscreteDomain::new(20))?]);
+        assert!(super::free_domain(&domain, &[None::<u64>]).is_err());
+
+        let mut opt = FrozenOptimizer::new(RandomOptimizer::<_, f64>::new(free_domain), frozen);
+        assert_eq!(opt.free_indices(), [1]);
+        for _ in 0..10 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            assert_eq!(obs.param[0], 3);
+            assert!(obs.param[1] < 20);
+            assert_eq!(obs.param[2], 7);
+            track!(opt.tell(obs.map_value(|()| 0.0)))?;
+        }
+
+        let obs = Obs::new(&mut idg, vec![3, 5, 7])?.map_value(|()| 1.0);
+        assert!(track!(opt.tell_compatible(obs))?);
+        let obs = Obs::new(&mut idg, vec![3, 5, 8])?.map_value(|()| 1.0);
+        assert!(!track!(opt.tell_compatible(obs))?);
+        let obs = Obs::new(&mut idg, vec![3, 5, 8])?.map_value(|()| 1.0);
+        assert!(opt.tell(obs).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+//! Harmonica optimizer for high-dimensional boolean spaces.
+//!
+//! # References
+//!
+//! - [Hyperparameter Optimization: A Spectral Approach](https://arxiv.org/abs/1706.00764)
+use crate::{ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use rand::Rng;
+use std::collections::{BTreeSet, HashMap};
+
+/// Builder of `HarmonicaOptimizer`.
+#[derive(Debug, Clone)]
+pub struct HarmonicaOptimizerBuilder {
+    degree: usize,
+    sparsity: usize,
+    samples_per_stage: usize,
+    stages: usize,
+    lambda: f64,
+}
+impl HarmonicaOptimizerBuilder {
+    /// Makes a new `HarmonicaOptimizerBuilder` instance with the default settings.
+    pub const fn new() -> Self {
+        Self {
+            degree: 2,
+            sparsity: 5,
+            samples_per_stage: 100,
+            stages: 3,
+            lambda: 0.01,
+        }
+    }
+
+    /// Sets the maximum degree of the monomials used as features of the surrogate.
+    ///
+    /// The default value is `2`.
+    ///
+    /// # Errors
+    ///
+    /// If `degree` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn degree(&mut self, degree: usize) -> Result<&mut Self> {
+        track_assert!(degree > 0, ErrorKind::InvalidInput);
+        self.degree = degree;
+        Ok(self)
+    }
+
+    /// Sets the number of the most important monomials whose variables are fixed at the end of a stage.
+    ///
+    /// The default value is `5`.
+    ///
+    /// # Errors
+    ///
+    /// If `sparsity` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn sparsity(&mut self, sparsity: usize) -> Result<&mut Self> {
+        track_assert!(sparsity > 0, ErrorKind::InvalidInput);
+        self.sparsity = sparsity;
+        Ok(self)
+    }
+
+    /// Sets the number of the samples evaluated in a stage.
+    ///
+    /// The default value is `100`.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is less than `2`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn samples_per_stage(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 1, ErrorKind::InvalidInput; n);
+        self.samples_per_stage = n;
+        Ok(self)
+    }
+
+    /// Sets the number of stages.
+    ///
+    /// After the last stage, the optimizer samples the remaining (free) variables uniformly at random.
+    /// The default value is `3`.
+    pub fn stages(&mut self, stages: usize) -> &mut Self {
+        self.stages = stages;
+        self
+    }
+
+    /// Sets the L1 regularization coefficient of the LASSO regression.
+    ///
+    /// The default value is `0.01`.
+    ///
+    /// # Errors
+    ///
+    /// If `lambda` is not a non-negative finite number, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn lambda(&mut self, lambda: f64) -> Result<&mut Self> {
+        track_assert!(lambda.is_finite() && lambda >= 0.0, ErrorKind::InvalidInput; lambda);
+        self.lambda = lambda;
+        Ok(self)
+    }
+
+    /// Builds a new `HarmonicaOptimizer` instance for `dim` boolean variables.
+    ///
+    /// # Errors
+    ///
+    /// If `dim` is `0` or `sparsity * degree` is greater than `20`
+    /// (the variables of the selected monomials are minimized by exhaustive search),
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn finish(&self, dim: usize) -> Result<HarmonicaOptimizer> {
+        track_assert!(dim > 0, ErrorKind::InvalidInput);
+        track_assert!(
+            self.sparsity * self.degree <= 20,
+            ErrorKind::InvalidInput; self.sparsity, self.degree
+        );
+        Ok(HarmonicaOptimizer {
+            builder: self.clone(),
+            fixed: vec![None; dim],
+            stage: 0,
+            asked: HashMap::new(),
+            samples: Vec::new(),
+        })
+    }
+}
+impl Default for HarmonicaOptimizerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Harmonica optimizer.
+///
+/// In each stage, this optimizer evaluates random samples of the free variables and
+/// fits a sparse polynomial surrogate (LASSO on low-degree Fourier basis, i.e., parity functions).
+/// Then, the variables appearing in the most important monomials are fixed to the values that minimize the surrogate,
+/// and the next stage explores the restricted subspace.
+#[derive(Debug)]
+pub struct HarmonicaOptimizer {
+    builder: HarmonicaOptimizerBuilder,
+    fixed: Vec<Option<bool>>,
+    stage: usize,
+    asked: HashMap<ObsId, usize>,
+    samples: Vec<(Vec<bool>, f64)>,
+}
+impl HarmonicaOptimizer {
+    /// Makes a new `HarmonicaOptimizer` instance with the default settings.
+    pub fn new(dim: usize) -> Result<Self> {
+        track!(HarmonicaOptimizerBuilder::new().finish(dim))
+    }
+
+    /// Returns the current restriction of the search space.
+    ///
+    /// `None` means that the corresponding variable is free.
+    pub fn fixed(&self) -> &[Option<bool>] {
+        &self.fixed
+    }
+
+    /// Returns the index of the current stage.
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+
+    fn finish_stage(&mut self) {
+        let free = (0..self.fixed.len())
+            .filter(|&i| self.fixed[i].is_none())
+            .collect::<Vec<_>>();
+        let monomials = monomials(&free, self.builder.degree);
+        let samples = std::mem::take(&mut self.samples);
+        let columns = monomials
+            .iter()
+            .map(|m| samples.iter().map(|(x, _)| parity(m, x)).collect())
+            .collect::<Vec<_>>();
+        let values = samples.iter().map(|&(_, v)| v).collect::<Vec<_>>();
+        let coefs = lasso(&columns, &values, self.builder.lambda);
+
+        let mut important = (0..monomials.len())
+            .filter(|&i| coefs[i] != 0.0)
+            .collect::<Vec<_>>();
+        important.sort_by(|&a, &b| {
+            coefs[b]
+                .abs()
+                .partial_cmp(&coefs[a].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        important.truncate(self.builder.sparsity);
+
+        let vars = important
+            .iter()
+            .flat_map(|&i| monomials[i].iter().copied())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        let mut best = (f64::INFINITY, 0);
+        for bits in 0..(1u32 << vars.len()) {
+            let mut x = vec![false; self.fixed.len()];
+            for (j, &v) in vars.iter().enumerate() {
+                x[v] = bits & (1 << j) != 0;
+            }
+            let g = important
+                .iter()
+                .map(|&i| coefs[i] * parity(&monomials[i], &x))
+                .sum::<f64>();
+            if g < best.0 {
+                best = (g, bits);
+            }
+        }
+        for (j, &v) in vars.iter().enumerate() {
+            self.fixed[v] = Some(best.1 & (1 << j) != 0);
+        }
+        self.stage += 1;
+    }
+}
+impl Optimizer for HarmonicaOptimizer {
+    type Param = Vec<bool>;
+    type Value = f64;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, mut rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let param = self
+            .fixed
+            .iter()
+            .map(|x| x.unwrap_or_else(|| rng.gen()))
+            .collect();
+        let obs = track!(Obs::new(idg, param))?;
+        self.asked.insert(obs.id, self.stage);
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let stage = track_assert_some!(
+            self.asked.remove(&obs.id),
+            ErrorKind::UnknownObservation; obs.id
+        );
+        track_assert_eq!(obs.param.len(), self.fixed.len(), ErrorKind::InvalidInput);
+        track_assert!(obs.value.is_finite(), ErrorKind::InvalidInput; obs.id, obs.value);
+        if stage != self.stage || self.stage >= self.builder.stages {
+            return Ok(());
+        }
+
+        self.samples.push((obs.param, obs.value));
+        if self.samples.len() >= self.builder.samples_per_stage {
+            self.finish_stage();
+        }
+        Ok(())
+    }
+}
+
+fn monomials(vars: &[usize], degree: usize) -> Vec<Vec<usize>> {
+    let mut result = Vec::new();
+    let mut stack = vec![(Vec::new(), 0)];
+    while let Some((m, start)) = stack.pop() {
+        if !m.is_empty() {
+            result.push(m.clone());
+        }
+        if m.len() == degree {
+            continue;
+        }
+        for (i, &v) in vars.iter().enumerate().skip(start) {
+            let mut m = m.clone();
+            m.push(v);
+            stack.push((m, i + 1));
+        }
+    }
+    result
+}
+
+fn parity(monomial: &[usize], x: &[bool]) -> f64 {
+    if monomial.iter().filter(|&&i| x[i]).count() % 2 == 0 {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+/// Solves LASSO regression by coordinate descent.
+///
+/// Each element of `columns` is a feature vector consisting of `1.0` and `-1.0`.
+fn lasso(columns: &[Vec<f64>], values: &[f64], lambda: f64) -> Vec<f64> {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let mut residuals = values.iter().map(|v| v - mean).collect::<Vec<_>>();
+    let mut coefs = vec![0.0; columns.len()];
+    for _ in 0..100 {
+        let mut max_delta: f64 = 0.0;
+        for (coef, column) in coefs.iter_mut().zip(columns.iter()) {
+            let rho = column
+                .iter()
+                .zip(residuals.iter())
+                .map(|(f, r)| f * (r + f * *coef))
+                .sum::<f64>()
+                / n;
+            let new = rho.signum() * (rho.abs() - lambda).max(0.0);
+            let delta = new - *coef;
+            if delta != 0.0 {
+                for (r, f) in residuals.iter_mut().zip(column.iter()) {
+                    *r -= f * delta;
+                }
+                *coef = new;
+                max_delta = max_delta.max(delta.abs());
+            }
+        }
+        if max_delta < 1e-6 {
+            break;
+        }
+    }
+    coefs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::SerialIdGenerator;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use trackable::result::TestResult;
+
+    fn s(x: bool) -> f64 {
+        if x {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    #[test]
+    fn harmonica_works() -> TestResult {
+        let mut opt = track!(HarmonicaOptimizerBuilder::new()
+            .samples_per_stage(60)?
+            .sparsity(3)?
+            .stages(1)
+            .finish(10))?;
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut idg = SerialIdGenerator::new();
+
+        for _ in 0..60 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let x = &obs.param;
+            let value = 3.0 * s(x[0]) + 2.0 * s(x[1]) - 1.5 * s(x[3]) * s(x[4]);
+            track!(opt.tell(obs.map_value(|()| value)))?;
+        }
+        assert_eq!(opt.stage(), 1);
+
+        let fixed = opt.fixed();
+        assert_eq!(fixed[0], Some(true));
+        assert_eq!(fixed[1], Some(true));
+        assert!(fixed[3].is_some());
+        assert_eq!(fixed[3], fixed[4]);
+
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        assert!(obs.param[0] && obs.param[1]);
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+//! Island model of evolutionary optimizers.
+//!
+//! # References
+//!
+//! - [Parallel Genetic Algorithms, Population Genetics and Combinatorial Optimization](https://link.springer.com/chapter/10.1007/3-540-55027-5_23)
+use crate::optimizers::nsga2::{Nsga2Optimizer, Strategy};
+use crate::pareto;
+use crate::{Domain, ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use rand::Rng;
+use std::collections::HashMap;
+
+/// This trait allows an evolutionary optimizer to be used as an island of `IslandOptimizer`.
+pub trait Island: Optimizer {
+    /// Returns the number of the generations so far.
+    fn generations(&self) -> u64;
+
+    /// Returns (at most) `n` of the best individuals to be sent to another island.
+    fn emigrants(&self, n: usize) -> Result<Vec<Obs<Self::Param, Self::Value>>>;
+
+    /// Accepts an individual sent from another island.
+    fn immigrate(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()>;
+}
+
+impl<P, S> Island for Nsga2Optimizer<P, S>
+where
+    P: Domain,
+    P::Point: Clone + PartialEq,
+    S: Strategy<P>,
+{
+    fn generations(&self) -> u64 {
+        self.generations()
+    }
+
+    fn emigrants(&self, n: usize) -> Result<Vec<Obs<Self::Param, Self::Value>>> {
+        let fronts = track!(pareto::non_dominated_sort(
+            self.parent_population().to_vec()
+        ))?;
+        Ok(fronts.into_iter().flatten().take(n).collect())
+    }
+
+    fn immigrate(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        track!(self.immigrate(obs))
+    }
+}
+
+/// Statistics of an island of `IslandOptimizer`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IslandStats {
+    /// The number of asks allocated to the island.
+    pub asks: usize,
+
+    /// The number of the generations of the island.
+    pub generations: u64,
+
+    /// The number of the individuals that have migrated to the island.
+    pub immigrants: usize,
+}
+
+/// An optimizer that runs several independent populations (islands) and
+/// periodically migrates the best individuals between them.
+///
+/// Asks are allocated to the islands in a round-robin manner, and
+/// each told observation is routed to the island that generated it.
+/// Every time an island has evolved `migration_interval` generations,
+/// its best `migration_size` individuals are copied to the next island (i.e., the islands form a ring).
+#[derive(Debug)]
+pub struct IslandOptimizer<O> {
+    islands: Vec<O>,
+    stats: Vec<IslandStats>,
+    last_migrations: Vec<u64>,
+    owners: HashMap<ObsId, usize>,
+    next: usize,
+    migration_interval: u64,
+    migration_size: usize,
+}
+impl<O> IslandOptimizer<O>
+where
+    O: Island,
+    O::Param: Clone,
+    O::Value: Clone,
+{
+    /// Makes a new `IslandOptimizer` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `islands` has less than two elements, or `migration_interval` or `migration_size` is `0`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(islands: Vec<O>, migration_interval: u64, migration_size: usize) -> Result<Self> {
+        track_assert!(islands.len() >= 2, ErrorKind::InvalidInput; islands.len());
+        track_assert!(migration_interval > 0, ErrorKind::InvalidInput);
+        track_assert!(migration_size > 0, ErrorKind::InvalidInput);
+        Ok(Self {
+            stats: islands
+                .iter()
+                .map(|island| IslandStats {
+                    asks: 0,
+                    generations: island.generations(),
+                    immigrants: 0,
+                })
+                .collect(),
+            last_migrations: islands.iter().map(|island| island.generations()).collect(),
+            islands,
+            owners: HashMap::new(),
+            next: 0,
+            migration_interval,
+            migration_size,
+        })
+    }
+
+    /// Returns the statistics of the islands.
+    pub fn stats(&self) -> &[IslandStats] {
+        &self.stats
+    }
+
+    /// Returns the islands.
+    pub fn islands(&self) -> &[O] {
+        &self.islands
+    }
+
+    /// Returns the index of the island that generated the given observation.
+    pub fn owner(&self, id: ObsId) -> Option<usize> {
+        self.owners.get(&id).copied()
+    }
+
+    /// Consumes the `IslandOptimizer`, returning the underlying islands.
+    pub fn into_inner(self) -> Vec<O> {
+        self.islands
+    }
+
+    fn migrate_if_needed(&mut self, i: usize) -> Result<()> {
+        let generations = self.islands[i].generations();
+        self.stats[i].generations = generations;
+        if generations < self.last_migrations[i] + self.migration_interval {
+            return Ok(());
+        }
+        self.last_migrations[i] = generations;
+
+        let emigrants = track!(self.islands[i].emigrants(self.migration_size))?;
+        let j = (i + 1) % self.islands.len();
+        for obs in emigrants {
+            track!(self.islands[j].immigrate(obs))?;
+            self.stats[j].immigrants += 1;
+        }
+        Ok(())
+    }
+}
+impl<O> Optimizer for IslandOptimizer<O>
+where
+    O: Island,
+    O::Param: Clone,
+    O::Value: Clone,
+{
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let i = self.next;
+        self.next = (self.next + 1) % self.islands.len();
+
+        let obs = track!(self.islands[i].ask(rng, idg))?;
+        self.stats[i].asks += 1;
+        self.owners.insert(obs.id, i);
+        track!(self.migrate_if_needed(i))?;
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let i = track_assert_some!(
+            self.owners.remove(&obs.id),
+            ErrorKind::UnknownObservation; obs.id
+        );
+        track!(self.islands[i].tell(obs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::nsga2::Nsga2Strategy;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn island_works() -> TestResult {
+        let islands = (0..3)
+            .map(|_| {
+                let domain = track!(DiscreteDomain::new(1000))?;
+                track!(Nsga2Optimizer::new(domain, 10, Nsga2Strategy::default()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut opt = track!(IslandOptimizer::new(islands, 2, 3))?;
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        for _ in 0..300 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let x = obs.param as f64;
+            track!(opt.tell(obs.map_value(|()| vec![x, 1000.0 - x])))?;
+        }
+
+        for stats in opt.stats() {
+            assert_eq!(stats.asks, 100);
+            assert!(stats.generations >= 5, "{:?}", stats);
+            assert!(stats.immigrants >= 6, "{:?}", stats);
+        }
+        assert!(opt
+            .tell(Obs::new(&mut idg, 0)?.map_value(|()| vec![0.0, 0.0]))
+            .is_err());
+
+        let islands = vec![track!(Nsga2Optimizer::new(
+            track!(DiscreteDomain::new(10))?,
+            10,
+            Nsga2Strategy::default()
+        ))?];
+        assert!(IslandOptimizer::new(islands, 1, 1).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,212 @@
+//! Contextual bandit based on linear models.
+//!
+//! # References
+//!
+//! - [A Contextual-Bandit Approach to Personalized News Article Recommendation](https://arxiv.org/abs/1003.0146)
+use crate::{ContextualOptimizer, ErrorContext, ErrorKind, IdGen, Obs, ObsId, Result};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// A contextual optimizer that selects a parameter from a finite set of candidates by the [LinUCB] algorithm.
+///
+/// The value of each candidate is modeled as a linear function of the context.
+/// The candidate that has the lowest lower confidence bound of the value is selected (lower values are better).
+/// If you want an intercept term, please append a constant feature (e.g., `1.0`) to the contexts.
+///
+/// [LinUCB]: https://arxiv.org/abs/1003.0146
+#[derive(Debug)]
+pub struct LinUcbOptimizer<P> {
+    arms: Vec<P>,
+    models: Vec<LinearModel>,
+    context_dim: usize,
+    alpha: f64,
+    pending: HashMap<ObsId, (usize, Vec<f64>)>,
+}
+impl<P: Clone> LinUcbOptimizer<P> {
+    /// Makes a new `LinUcbOptimizer` instance.
+    ///
+    /// `arms` are the candidate parameters and `context_dim` is the dimension of the contexts.
+    ///
+    /// # Errors
+    ///
+    /// If `arms` is empty or `context_dim` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(arms: Vec<P>, context_dim: usize) -> Result<Self> {
+        track_assert!(!arms.is_empty(), ErrorKind::InvalidInput);
+        track_assert!(context_dim > 0, ErrorKind::InvalidInput);
+        Ok(Self {
+            models: vec![LinearModel::new(context_dim); arms.len()],
+            arms,
+            context_dim,
+            alpha: 1.0,
+            pending: HashMap::new(),
+        })
+    }
+
+    /// Sets the width of the confidence bounds (the default value is `1.0`).
+    ///
+    /// Larger values lead to more exploration.
+    ///
+    /// # Errors
+    ///
+    /// If `alpha` is negative or not finite, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_alpha(&mut self, alpha: f64) -> Result<()> {
+        track_assert!(alpha.is_finite() && alpha >= 0.0, ErrorKind::InvalidInput; alpha);
+        self.alpha = alpha;
+        Ok(())
+    }
+
+    /// Returns the candidate parameters.
+    pub fn arms(&self) -> &[P] {
+        &self.arms
+    }
+
+    /// Returns the estimated value of the `arm`-th candidate under the given context.
+    ///
+    /// # Errors
+    ///
+    /// If `arm` is out of range or the dimension of `context` is wrong,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn estimate(&self, arm: usize, context: &[f64]) -> Result<f64> {
+        track!(self.check_context(context))?;
+        let model = track_assert_some!(self.models.get(arm), ErrorKind::InvalidInput; arm);
+        Ok(model.mean(context))
+    }
+
+    fn check_context(&self, context: &[f64]) -> Result<()> {
+        track_assert_eq!(context.len(), self.context_dim, ErrorKind::InvalidInput);
+        track_assert!(
+            context.iter().all(|x| x.is_finite()),
+            ErrorKind::InvalidInput; context
+        );
+        Ok(())
+    }
+}
+impl<P: Clone> ContextualOptimizer for LinUcbOptimizer<P> {
+    type Param = P;
+    type Value = f64;
+
+    fn ask<R: Rng, G: IdGen>(
+        &mut self,
+        context: &[f64],
+        mut rng: R,
+        idg: G,
+    ) -> Result<Obs<Self::Param>> {
+        track!(self.check_context(context))?;
+        let bounds = self
+            .models
+            .iter()
+            .map(|m| m.mean(context) - self.alpha * m.variance(context).max(0.0).sqrt())
+            .collect::<Vec<_>>();
+        let lowest = bounds.iter().copied().fold(f64::INFINITY, f64::min);
+        let candidates = (0..bounds.len())
+            .filter(|&i| bounds[i] == lowest)
+            .collect::<Vec<_>>();
+        let arm = *track_assert_some!(candidates.choose(&mut rng), ErrorKind::Bug);
+
+        let obs = track!(Obs::new(idg, self.arms[arm].clone()))?;
+        self.pending.insert(obs.id, (arm, context.to_vec()));
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let (arm, context) = track_assert_some!(
+            self.pending.remove(&obs.id),
+            ErrorKind::UnknownObservation; obs.id
+        );
+        if !obs.value.is_finite() {
+            self.pending.insert(obs.id, (arm, context));
+            track_panic!(
+                ErrorKind::InvalidInput,
+                ErrorContext::new("Non-finite value").obs_id(obs.id)
+            );
+        }
+        self.models[arm].update(&context, obs.value);
+        Ok(())
+    }
+}
+
+/// Ridge regression model updated incrementally by the Sherman-Morrison formula.
+#[derive(Debug, Clone)]
+struct LinearModel {
+    a_inv: Vec<Vec<f64>>,
+    b: Vec<f64>,
+}
+impl LinearModel {
+    fn new(dim: usize) -> Self {
+        let a_inv = (0..dim)
+            .map(|i| (0..dim).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+            .collect();
+        Self {
+            a_inv,
+            b: vec![0.0; dim],
+        }
+    }
+
+    fn mul_a_inv(&self, x: &[f64]) -> Vec<f64> {
+        self.a_inv
+            .iter()
+            .map(|row| row.iter().zip(x).map(|(a, x)| a * x).sum())
+            .collect()
+    }
+
+    fn mean(&self, x: &[f64]) -> f64 {
+        let theta = self.mul_a_inv(&self.b);
+        theta.iter().zip(x).map(|(t, x)| t * x).sum()
+    }
+
+    fn variance(&self, x: &[f64]) -> f64 {
+        self.mul_a_inv(x).iter().zip(x).map(|(a, x)| a * x).sum()
+    }
+
+    fn update(&mut self, x: &[f64], y: f64) {
+        let ax = self.mul_a_inv(x);
+        let denom = 1.0 + ax.iter().zip(x).map(|(a, x)| a * x).sum::<f64>();
+        for (row, &ai) in self.a_inv.iter_mut().zip(ax.iter()) {
+            for (a, &aj) in row.iter_mut().zip(ax.iter()) {
+                *a -= ai * aj / denom;
+            }
+        }
+        for (b, &x) in self.b.iter_mut().zip(x) {
+            *b += y * x;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::SerialIdGenerator;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn lin_ucb_works() -> TestResult {
+        let mut opt = track!(LinUcbOptimizer::new(vec!["small", "large"], 2))?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        // "small" is better for light workloads and "large" is better for heavy ones.
+        let cost = |arm: &str, load: f64| match arm {
+            "small" => load * 10.0,
+            _ => 3.0,
+        };
+        for i in 0..200 {
+            let load = (i % 10) as f64 / 10.0;
+            let context = [1.0, load];
+            let obs = track!(opt.ask(&context, &mut rng, &mut idg))?;
+            let value = cost(obs.param, load);
+            track!(opt.tell(obs.map_value(|()| value)))?;
+        }
+
+        let light = [1.0, 0.0];
+        let heavy = [1.0, 0.9];
+        assert!(track!(opt.estimate(0, &light))? < track!(opt.estimate(1, &light))?);
+        assert!(track!(opt.estimate(0, &heavy))? > track!(opt.estimate(1, &heavy))?);
+
+        track!(opt.set_alpha(0.0))?;
+        assert_eq!(track!(opt.ask(&light, &mut rng, &mut idg))?.param, "small");
+        assert_eq!(track!(opt.ask(&heavy, &mut rng, &mut idg))?.param, "large");
+        assert!(opt.ask(&[1.0], &mut rng, &mut idg).is_err());
+        Ok(())
+    }
+}
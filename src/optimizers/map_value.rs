@@ -0,0 +1,373 @@
+//! Adapter for transforming told values.
+use crate::{ErrorKind, IdGen, NanPolicy, Obs, Optimizer, Result};
+use rand::Rng;
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+
+/// This trait allows transforming values before they are told to an optimizer.
+pub trait ValueTransform<V> {
+    /// The type of transformed values.
+    type Output;
+
+    /// Transforms the given value.
+    fn transform(&mut self, value: V) -> Result<Self::Output>;
+}
+
+impl<V, U, F> ValueTransform<V> for F
+where
+    F: FnMut(V) -> U,
+{
+    type Output = U;
+
+    fn transform(&mut self, value: V) -> Result<Self::Output> {
+        Ok(self(value))
+    }
+}
+
+impl<V, A, B> ValueTransform<V> for (A, B)
+where
+    A: ValueTransform<V>,
+    B: ValueTransform<A::Output>,
+{
+    type Output = B::Output;
+
+    fn transform(&mut self, value: V) -> Result<Self::Output> {
+        let value = track!(self.0.transform(value))?;
+        track!(self.1.transform(value))
+    }
+}
+
+/// A transform that takes the natural logarithm of values.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LogTransform;
+
+impl ValueTransform<f64> for LogTransform {
+    type Output = f64;
+
+    fn transform(&mut self, value: f64) -> Result<Self::Output> {
+        track_assert!(value > 0.0, ErrorKind::InvalidInput; value);
+        Ok(value.ln())
+    }
+}
+
+/// A value annotated with its rank among the values transformed before it (see `RankTransform`).
+///
+/// The rank depends on when the value was transformed, so values are ordered by `value` only
+/// and `rank` is just informational.
+/// This keeps optimizers that compare the values told at different times consistent.
+#[derive(Debug, Clone, Copy)]
+pub struct RankedValue<V> {
+    /// The original value.
+    pub value: V,
+
+    /// The number of the previously transformed values less than `value`.
+    pub rank: u64,
+}
+impl<V: PartialEq> PartialEq for RankedValue<V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+impl<V: Eq> Eq for RankedValue<V> {}
+impl<V: PartialOrd> PartialOrd for RankedValue<V> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+impl<V: Ord> Ord for RankedValue<V> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+/// A transform that annotates values with their ranks among the values transformed so far.
+///
+/// The rank of a value is the number of the previous values less than it.
+/// Note that ranks of values transformed at different times are not comparable with each other
+/// (e.g., `2` told after `1` gets the rank `1`, while `3` told first gets the rank `0`),
+/// so the resulting `RankedValue`s are ordered by the original values.
+#[derive(Debug, Clone)]
+pub struct RankTransform<V> {
+    values: Vec<V>,
+}
+
+impl<V> RankTransform<V> {
+    /// Makes a new `RankTransform` instance.
+    pub const fn new() -> Self {
+        Self { values: Vec::new() }
+    }
+}
+
+impl<V> Default for RankTransform<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V: PartialOrd + Clone> ValueTransform<V> for RankTransform<V> {
+    type Output = RankedValue<V>;
+
+    fn transform(&mut self, value: V) -> Result<Self::Output> {
+        let rank = self.values.iter().take_while(|v| **v < value).count();
+        let i = self.values.iter().take_while(|v| **v <= value).count();
+        self.values.insert(i, value.clone());
+        Ok(RankedValue {
+            value,
+            rank: rank as u64,
+        })
+    }
+}
+
+/// A transform that compresses the scale of values by `sign(v) * ln(1 + |v|)`.
+///
+/// This is strictly increasing, so the order of values is preserved regardless of when they are told.
+/// Combined with `NanPolicy::Worst` (i.e., `(SignedLogTransform, NanPolicy::Worst)`),
+/// objectives having exploding scales or NaN-prone regions can be given to optimizers that compare `Ord` values
+/// such as `NelderMeadOptimizer`, without the failed evaluations destroying the search.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SignedLogTransform;
+
+impl ValueTransform<f64> for SignedLogTransform {
+    type Output = f64;
+
+    fn transform(&mut self, value: f64) -> Result<Self::Output> {
+        Ok(value.signum() * value.abs().ln_1p())
+    }
+}
+
+/// A transform that standardizes values (i.e., z-score) using the running mean and standard deviation.
+///
+/// Standardized values keep objectives with exploding scales in a reasonable range.
+///
+/// Note that the statistics change at every transformation, so the order of the values transformed at different times
+/// is not preserved (e.g., `6` told after `5`, `10` and `1000` is transformed to a value less than the one of `5`).
+/// Therefore, this should not be used with optimizers comparing the told values (e.g., `NelderMeadOptimizer`);
+/// use order-preserving transforms such as `SignedLogTransform` for them.
+#[derive(Debug, Default, Clone)]
+pub struct StandardizeTransform {
+    count: u64,
+    mean: f64,
+    m2: f64,
+    nan_policy: NanPolicy,
+}
+
+impl StandardizeTransform {
+    /// Makes a new `StandardizeTransform` instance.
+    pub const fn new() -> Self {
+        Self::with_nan_policy(NanPolicy::Error)
+    }
+
+    /// Makes a new `StandardizeTransform` instance with the given NaN policy.
+    ///
+    /// If the policy is `NanPolicy::Worst`, NaN and positive infinite values are transformed to `f64::INFINITY`
+    /// without updating the running statistics.
+    pub const fn with_nan_policy(nan_policy: NanPolicy) -> Self {
+        Self {
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+            nan_policy,
+        }
+    }
+
+    /// Returns the NaN policy.
+    pub fn nan_policy(&self) -> NanPolicy {
+        self.nan_policy
+    }
+
+    /// Returns the running mean of the values.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the running (sample) standard deviation of the values.
+    pub fn stddev(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            (self.m2 / (self.count - 1) as f64).sqrt()
+        }
+    }
+}
+
+impl ValueTransform<f64> for StandardizeTransform {
+    type Output = f64;
+
+    fn transform(&mut self, value: f64) -> Result<Self::Output> {
+        if self.nan_policy == NanPolicy::Worst && (value.is_nan() || value == f64::INFINITY) {
+            return Ok(f64::INFINITY);
+        }
+        track_assert!(value.is_finite(), ErrorKind::InvalidInput; value);
+
+        // Welford's online algorithm.
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+
+        let stddev = self.stddev();
+        if stddev == 0.0 {
+            Ok(0.0)
+        } else {
+            Ok((value - self.mean) / stddev)
+        }
+    }
+}
+
+/// An optimizer adapter that transforms told values before the inner optimizer sees them.
+#[derive(Debug)]
+pub struct MapValueOptimizer<O, T, V> {
+    inner: O,
+    transform: T,
+    _value: PhantomData<V>,
+}
+impl<O, T, V> MapValueOptimizer<O, T, V>
+where
+    O: Optimizer,
+    T: ValueTransform<V, Output = O::Value>,
+{
+    /// Makes a new `MapValueOptimizer` instance.
+    pub fn new(inner: O, transform: T) -> Self {
+        Self {
+            inner,
+            transform,
+            _value: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the transform.
+    pub fn transform(&self) -> &T {
+        &self.transform
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `MapValueOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+}
+impl<O, T, V> Optimizer for MapValueOptimizer<O, T, V>
+where
+    O: Optimizer,
+    T: ValueTransform<V, Output = O::Value>,
+{
+    type Param = O::Param;
+    type Value = V;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        track!(self.inner.ask(rng, idg))
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let transform = &mut self.transform;
+        let obs = track!(obs.try_map_value(|v| transform.transform(v)))?;
+        track!(self.inner.tell(obs))
+    }
+
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        let transform = &mut self.transform;
+        let obss = obss
+            .into_iter()
+            .map(|obs| track!(obs.try_map_value(|v| transform.transform(v))))
+            .collect::<Result<Vec<_>>>()?;
+        track!(self.inner.tell_batch(obss))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::ContinuousDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::nelder_mead::NelderMeadOptimizer;
+    use crate::optimizers::random::RandomOptimizer;
+    use rand;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn transforms_work() -> TestResult {
+        assert!(LogTransform.transform(0.0).is_err());
+        assert_eq!(track!(LogTransform.transform(1.0))?, 0.0);
+
+        let mut t = RankTransform::new();
+        let ranked = [3, 1, 2, 1]
+            .iter()
+            .map(|&v| t.transform(v))
+            .collect::<Result<Vec<_>>>()?;
+        let ranks = ranked.iter().map(|r| r.rank).collect::<Vec<_>>();
+        assert_eq!(ranks, [0, 0, 1, 0]);
+        assert!(ranked[2] < ranked[0]);
+        assert_eq!(ranked[1], ranked[3]);
+
+        let mut t = StandardizeTransform::new();
+        assert_eq!(track!(t.transform(1.0))?, 0.0);
+        assert!(track!(t.transform(3.0))? > 0.0);
+        assert_eq!(t.mean(), 2.0);
+        assert!(t.transform(f64::NAN).is_err());
+
+        let mut t = StandardizeTransform::with_nan_policy(NanPolicy::Worst);
+        assert_eq!(track!(t.transform(f64::NAN))?, f64::INFINITY);
+        assert_eq!(track!(t.transform(1e300))?, 0.0);
+        assert!(t.transform(f64::NEG_INFINITY).is_err());
+
+        let values = [5.0, 10.0, 1000.0, 6.0, -1e300]
+            .iter()
+            .map(|&v| SignedLogTransform.transform(v))
+            .collect::<Result<Vec<_>>>()?;
+        assert!(values[0] < values[3] && values[3] < values[1] && values[1] < values[2]);
+        assert!(values[4] < 0.0);
+
+        let mut t = (LogTransform, |v: f64| v * 2.0);
+        assert_eq!(track!(t.transform(1.0))?, 0.0);
+        Ok(())
+    }
+
+    #[test]
+    fn map_value_works() -> TestResult {
+        let inner =
+            RandomOptimizer::<_, RankedValue<f64>>::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut opt = MapValueOptimizer::new(inner, (LogTransform, RankTransform::new()));
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        track!(opt.tell(obs.map_value(|()| 10.0)))?;
+
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        assert!(opt.tell(obs.map_value(|()| -1.0)).is_err());
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let inner = track!(NelderMeadOptimizer::new(
+            vec![track!(ContinuousDomain::new(-10.0, 10.0))?; 2],
+            &mut rng
+        ))?;
+        let mut opt = MapValueOptimizer::new(inner, (SignedLogTransform, NanPolicy::Worst));
+        let mut best = f64::INFINITY;
+        for _ in 0..100 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let (x, y) = (obs.param[0], obs.param[1]);
+            let value = if x > 5.0 {
+                f64::NAN
+            } else {
+                ((x - 1.0).powi(2) + y.powi(2)).exp()
+            };
+            if value < best {
+                best = value;
+            }
+            track!(opt.tell(obs.map_value(|()| value)))?;
+        }
+        assert!(best < 1.1, "{}", best);
+        Ok(())
+    }
+}
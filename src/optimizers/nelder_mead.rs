@@ -8,10 +8,11 @@
 //!
 //! [ANMS]: https://link.springer.com/article/10.1007/s10589-010-9329-3
 use crate::domains::ContinuousDomain;
-use crate::{ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use crate::{ErrorContext, ErrorKind, IdGen, Obs, Optimizer, Result};
 use rand::distributions::Distribution;
 use rand::Rng;
-use std::f64::EPSILON;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// An optimizer based on [Adaptive Nelder-Mead Simplex (ANMS)][ANMS] algorithm.
 ///
@@ -26,8 +27,10 @@ pub struct NelderMeadOptimizer<V> {
     delta: f64,
     initial: Vec<Vec<f64>>,
     centroid: Vec<f64>,
-    evaluating: Option<ObsId>,
+    evaluating: Option<Obs<Vec<f64>>>,
     state: State<V>,
+    boundary_policy: BoundaryPolicy,
+    projections: usize,
 }
 impl<V> NelderMeadOptimizer<V>
 where
@@ -86,29 +89,174 @@ where
             centroid: Vec::new(),
             evaluating: None,
             state: State::Initialize,
+            boundary_policy: BoundaryPolicy::default(),
+            projections: 0,
         })
     }
 
+    /// Sets the policy for handling points outside of the domain (the default is `BoundaryPolicy::Project`).
+    ///
+    /// # Errors
+    ///
+    /// If the policy is `BoundaryPolicy::Restart(0)`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_boundary_policy(&mut self, policy: BoundaryPolicy) -> Result<()> {
+        if let BoundaryPolicy::Restart(n) = policy {
+            track_assert!(n > 0, ErrorKind::InvalidInput);
+        }
+        self.boundary_policy = policy;
+        Ok(())
+    }
+
+    /// Returns the policy for handling points outside of the domain.
+    pub fn boundary_policy(&self) -> BoundaryPolicy {
+        self.boundary_policy
+    }
+
+    /// Makes a new `NelderMeadOptimizer` which resumes from the given snapshot.
+    ///
+    /// If an observation was being evaluated when the snapshot was taken,
+    /// it can be obtained by `pending_obs` and its result can be told as usual.
+    ///
+    /// # Errors
+    ///
+    /// If the snapshot is inconsistent with `params_domain` or has an invalid boundary policy,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn from_snapshot(
+        params_domain: Vec<ContinuousDomain>,
+        snapshot: NelderMeadSnapshot<V>,
+    ) -> Result<Self> {
+        let dim = params_domain.len();
+        track_assert!(dim >= 2, ErrorKind::InvalidInput; dim);
+        if let State::Initialize = snapshot.state {
+            let evaluating = snapshot.evaluating.iter().count();
+            track_assert_eq!(
+                snapshot.simplex.len() + snapshot.initial.len() + evaluating,
+                dim + 1,
+                ErrorKind::InvalidInput
+            );
+        } else {
+            track_assert_eq!(snapshot.simplex.len(), dim + 1, ErrorKind::InvalidInput);
+        }
+        let params = snapshot
+            .simplex
+            .iter()
+            .map(|o| &o.param)
+            .chain(snapshot.initial.iter())
+            .chain(snapshot.evaluating.iter().map(|o| &o.param))
+            .chain(snapshot.state.prev().map(|o| &o.param));
+        for param in params {
+            track_assert_eq!(param.len(), dim, ErrorKind::InvalidInput);
+        }
+        if let State::Shrink { index } = snapshot.state {
+            track_assert!(0 < index && index <= dim, ErrorKind::InvalidInput; index);
+        }
+
+        let mut this = track!(Self::with_initial_simplex(
+            params_domain,
+            vec![Vec::new(); dim + 1]
+        ))?;
+        this.simplex = snapshot.simplex;
+        this.initial = snapshot.initial;
+        this.evaluating = snapshot.evaluating;
+        this.state = snapshot.state;
+        track!(this.set_boundary_policy(snapshot.boundary_policy))?;
+        this.projections = snapshot.projections;
+        if this.simplex.len() == dim + 1 {
+            this.update_centroid();
+        }
+        Ok(this)
+    }
+
+    /// Returns the snapshot of the current state of this optimizer.
+    ///
+    /// The snapshot can be serialized (if the `serde` feature is enabled) to resume the optimization later.
+    pub fn snapshot(&self) -> NelderMeadSnapshot<V>
+    where
+        V: Clone,
+    {
+        NelderMeadSnapshot {
+            simplex: self.simplex.clone(),
+            initial: self.initial.clone(),
+            evaluating: self.evaluating.clone(),
+            state: self.state.clone(),
+            boundary_policy: self.boundary_policy,
+            projections: self.projections,
+        }
+    }
+
+    /// Returns the observation being evaluated (i.e., asked but not told yet).
+    ///
+    /// Since this optimizer evaluates one observation at a time,
+    /// the result of this observation must be told before the next ask.
+    pub fn pending_obs(&self) -> Option<&Obs<Vec<f64>>> {
+        self.evaluating.as_ref()
+    }
+
     fn dim(&self) -> usize {
         self.params_domain.len()
     }
 
-    fn adjust(&self, x: Vec<f64>) -> Vec<f64> {
-        self.params_domain
+    fn adjust(&mut self, x: Vec<f64>) -> Vec<f64> {
+        let reflect = self.boundary_policy == BoundaryPolicy::Reflect;
+        let mut projected = false;
+        let x = self
+            .params_domain
             .iter()
-            .zip(x.into_iter())
+            .zip(x)
             .map(|(p, v)| {
-                let v = p.low().max(v);
-                let mut v = (p.high() - std::f64::EPSILON).min(v);
-                for i in 2.. {
-                    if (v - p.high()).abs() > EPSILON {
-                        break;
-                    }
-                    v -= EPSILON * f64::from(i);
-                }
-                v
+                let v = if !reflect {
+                    v
+                } else if v < p.low() {
+                    p.low() + (p.low() - v)
+                } else if v > p.high() {
+                    p.high() - (v - p.high())
+                } else {
+                    v
+                };
+                let clamped = p.clamp(v);
+                projected |= clamped != v;
+                clamped
             })
-            .collect()
+            .collect();
+        if projected {
+            self.projections += 1;
+        } else {
+            self.projections = 0;
+        }
+        x
+    }
+
+    fn restart_if_stalled(&mut self) {
+        let n = match self.boundary_policy {
+            BoundaryPolicy::Restart(n) => n,
+            _ => return,
+        };
+        if self.projections < n || !matches!(self.state, State::Reflect) {
+            return;
+        }
+
+        // Rebuilds the simplex around the best point, stepping away from the nearest bounds.
+        let best = self.simplex.swap_remove(0);
+        self.simplex.clear();
+        self.initial = self
+            .params_domain
+            .iter()
+            .enumerate()
+            .map(|(i, d)| {
+                let step = d.size() * 0.05;
+                let mut x = best.param.clone();
+                x[i] = if best.param[i] - d.low() < d.high() - best.param[i] {
+                    d.clamp(x[i] + step)
+                } else {
+                    d.clamp(x[i] - step)
+                };
+                x
+            })
+            .collect();
+        self.simplex.push(best);
+        self.centroid.clear();
+        self.projections = 0;
+        self.state = State::Initialize;
     }
 
     fn initial_ask(&mut self) -> Vec<f64> {
@@ -265,8 +413,14 @@ where
     type Value = V;
 
     fn ask<R: Rng, G: IdGen>(&mut self, _rng: R, idg: G) -> Result<Obs<Self::Param>> {
-        track_assert!(self.evaluating.is_none(), ErrorKind::Other);
+        if let Some(obs) = &self.evaluating {
+            track_panic!(
+                ErrorKind::Other,
+                ErrorContext::new("The pending observation has not been told").obs_id(obs.id)
+            );
+        }
 
+        self.restart_if_stalled();
         let x = match &self.state {
             State::Initialize => self.initial_ask(),
             State::Reflect => self.reflect_ask(),
@@ -290,13 +444,17 @@ where
 
         let x = self.adjust(x);
         let obs = track!(Obs::new(idg, x))?;
-        self.evaluating = Some(obs.id);
+        self.evaluating = Some(obs.clone());
 
         Ok(obs)
     }
 
     fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
-        track_assert_eq!(self.evaluating, Some(obs.id), ErrorKind::UnknownObservation);
+        track_assert_eq!(
+            self.evaluating.as_ref().map(|o| o.id),
+            Some(obs.id),
+            ErrorKind::UnknownObservation
+        );
         self.evaluating = None;
 
         match std::mem::replace(&mut self.state, State::Initialize) {
@@ -324,7 +482,44 @@ where
     }
 }
 
+/// Policy of `NelderMeadOptimizer` for handling points outside of the domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BoundaryPolicy {
+    /// Projects the points onto the bounds of the domain.
+    ///
+    /// This may collapse the simplex along the dimensions whose bounds are hit.
+    #[default]
+    Project,
+
+    /// Reflects the points at the bounds of the domain (and then projects them if they are still outside).
+    Reflect,
+
+    /// Projects the points, and restarts the simplex around the best point
+    /// when `n` consecutive asked points have been projected.
+    Restart(usize),
+}
+
+/// A snapshot of the state of `NelderMeadOptimizer`.
+///
+/// It includes the in-flight step of the simplex algorithm, the observation being evaluated,
+/// and the boundary policy along with the count of the consecutive projections towards a restart.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NelderMeadSnapshot<V> {
+    simplex: Vec<Obs<Vec<f64>, V>>,
+    initial: Vec<Vec<f64>>,
+    evaluating: Option<Obs<Vec<f64>>>,
+    state: State<V>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    boundary_policy: BoundaryPolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    projections: usize,
+}
+
+/// A step of the simplex algorithm.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 enum State<V> {
     Initialize,
     Reflect,
@@ -333,13 +528,24 @@ enum State<V> {
     ContractInside(Obs<Vec<f64>, V>),
     Shrink { index: usize },
 }
+impl<V> State<V> {
+    fn prev(&self) -> Option<&Obs<Vec<f64>, V>> {
+        match self {
+            State::Expand(prev) | State::ContractOutside(prev) | State::ContractInside(prev) => {
+                Some(prev)
+            }
+            _ => None,
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::domains::ContinuousDomain;
     use crate::generators::SerialIdGenerator;
-    use ordered_float::NotNan;
+    use crate::Domain;
+    use ordered_float::{NotNan, OrderedFloat};
     use rand;
     use trackable::result::TopLevelResult;
 
@@ -368,4 +574,102 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn nelder_mead_boundary_policy_works() -> TopLevelResult {
+        let params_domain = vec![
+            ContinuousDomain::new(0.0, 100.0)?,
+            ContinuousDomain::new(0.0, 100.0)?,
+        ];
+        let objective = |x: &[f64]| OrderedFloat((x[0] + 10.0).powi(2) + (x[1] - 3.0).powi(2));
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        for policy in [
+            BoundaryPolicy::Project,
+            BoundaryPolicy::Reflect,
+            BoundaryPolicy::Restart(3),
+        ] {
+            let mut optimizer =
+                NelderMeadOptimizer::with_initial_point(params_domain.clone(), &[50.0, 50.0])?;
+            optimizer.set_boundary_policy(policy)?;
+            let mut best = objective(&[50.0, 50.0]);
+            let mut restarts = 0;
+            for _ in 0..200 {
+                let initialized = !matches!(optimizer.state, State::Initialize);
+                let obs = optimizer.ask(&mut rng, &mut idg)?;
+                if initialized && matches!(optimizer.state, State::Initialize) {
+                    // The simplex has been rebuilt around the best point.
+                    assert_eq!(optimizer.simplex.len(), 1);
+                    assert_eq!(optimizer.projections, 0);
+                    restarts += 1;
+                }
+                assert!(params_domain
+                    .iter()
+                    .zip(obs.param.iter())
+                    .all(|(d, x)| d.contains(x)));
+                let value = objective(&obs.param);
+                best = best.min(value);
+                optimizer.tell(obs.map_value(|_| value))?;
+            }
+            assert!(best < objective(&[1.0, 3.0]), "{:?}: {}", policy, best);
+            if let BoundaryPolicy::Restart(_) = policy {
+                assert!(restarts > 0);
+            } else {
+                assert_eq!(restarts, 0);
+            }
+        }
+
+        let mut optimizer = NelderMeadOptimizer::<OrderedFloat<f64>>::with_initial_point(
+            params_domain,
+            &[1.0, 1.0],
+        )?;
+        assert!(optimizer
+            .set_boundary_policy(BoundaryPolicy::Restart(0))
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn nelder_mead_snapshot_works() -> TopLevelResult {
+        let params_domain = vec![
+            ContinuousDomain::new(0.0, 100.0)?,
+            ContinuousDomain::new(0.0, 100.0)?,
+        ];
+        let mut optimizer =
+            NelderMeadOptimizer::with_initial_point(params_domain.clone(), &[10.0, 20.0])?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        for _ in 0..10 {
+            let obs = optimizer.ask(&mut rng, &mut idg)?;
+            let value = OrderedFloat(objective(&obs.param));
+            optimizer.tell(obs.map_value(|_| value))?;
+        }
+
+        optimizer.set_boundary_policy(BoundaryPolicy::Restart(5))?;
+        optimizer.projections = 2;
+        let obs = optimizer.ask(&mut rng, &mut idg)?;
+        let projections = optimizer.projections;
+        let snapshot = optimizer.snapshot();
+        drop(optimizer);
+
+        let mut resumed = NelderMeadOptimizer::from_snapshot(params_domain.clone(), snapshot)?;
+        assert_eq!(resumed.boundary_policy(), BoundaryPolicy::Restart(5));
+        assert_eq!(resumed.projections, projections);
+        assert!(resumed.ask(&mut rng, &mut idg).is_err());
+        let pending = resumed.pending_obs().cloned();
+        assert_eq!(pending.as_ref().map(|o| o.id), Some(obs.id));
+        assert_eq!(pending.map(|o| o.param), Some(obs.param.clone()));
+
+        let value = OrderedFloat(objective(&obs.param));
+        resumed.tell(obs.map_value(|_| value))?;
+        for _ in 0..10 {
+            let obs = resumed.ask(&mut rng, &mut idg)?;
+            let value = OrderedFloat(objective(&obs.param));
+            resumed.tell(obs.map_value(|_| value))?;
+        }
+
+        let snapshot = resumed.snapshot();
+        assert!(NelderMeadOptimizer::from_snapshot(params_domain[..1].to_vec(), snapshot).is_err());
+        Ok(())
+    }
 }
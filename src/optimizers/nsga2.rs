@@ -5,34 +5,23 @@
 //! - [A fast and elitist multiobjective genetic algorithm: NSGA-II][NSGA-II]
 //!
 //! [NSGA-II]: https://ieeexplore.ieee.org/document/996017
-use crate::domains::VecDomain;
-use crate::{Domain, ErrorKind, IdGen, Obs, Optimizer, Result};
+use crate::domains::{
+    BitStringDomain, ContinuousDomain, PermutationDomain, SteppedDomain, Tree, TreeDomain,
+    VecDomain,
+};
+use crate::pareto::{self, Dominance};
+use crate::{Domain, ErrorContext, ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
 use ordered_float::OrderedFloat;
 use rand::distributions::Distribution;
 use rand::seq::SliceRandom;
 use rand::Rng;
+use std::cmp::{self, Reverse};
 use std::collections::{HashMap, HashSet, VecDeque};
-use std::f64::INFINITY;
 use std::marker::PhantomData;
 
-/// This trait allows generating new individuals.
-pub trait Generate<D: Domain> {
-    /// Generates a new individual.
-    fn generate<R: Rng>(&mut self, rng: R, domain: &D) -> Result<D::Point>;
-}
-
-/// Random generator.
-#[derive(Debug, Default)]
-pub struct RandomGenerator;
+pub use crate::init::{Generate, RandomGenerator};
 
-impl<D> Generate<D> for RandomGenerator
-where
-    D: Domain + Distribution<<D as Domain>::Point>,
-{
-    fn generate<R: Rng>(&mut self, mut rng: R, domain: &D) -> Result<D::Point> {
-        Ok(domain.sample(&mut rng))
-    }
-}
+const MAX_DEDUP_ATTEMPTS: usize = 10;
 
 /// This trait allows selecting parents from a population.
 pub trait Select<D: Domain> {
@@ -78,7 +67,7 @@ impl<D: Domain> Select<D> for TournamentSelector {
         for _ in 1..self.tournament_size {
             let candidate =
                 track_assert_some!(population.choose(&mut rng), ErrorKind::InvalidInput);
-            if track!(dominates(candidate, winner))? {
+            if track!(candidate.dominates(winner))? {
                 winner = candidate;
             }
         }
@@ -90,12 +79,218 @@ impl<D: Domain> Select<D> for TournamentSelector {
 pub trait CrossOver<D: Domain> {
     /// Applies crossover operator.
     fn cross_over<R: Rng>(&mut self, rng: R, p0: &mut D::Point, p1: &mut D::Point) -> Result<()>;
+
+    /// Notifies that the offspring having the given identifiers have been created
+    /// by the preceding invocations of this operator.
+    ///
+    /// The default implementation does nothing.
+    fn offspring_created(&mut self, _ids: &[ObsId]) {}
+
+    /// Notifies whether the given offspring has improved on its parents (i.e., dominates at least one of them).
+    ///
+    /// The default implementation does nothing.
+    fn offspring_evaluated(&mut self, _id: ObsId, _improved: bool) {}
 }
 
 /// This trait allows applying mutation operator.
 pub trait Mutate<D: Domain> {
     /// Mutates an individual.
     fn mutate<R: Rng>(&mut self, rng: R, domain: &D, p: &mut D::Point) -> Result<()>;
+
+    /// Notifies that the offspring having the given identifiers have been created
+    /// by the preceding invocations of this operator.
+    ///
+    /// The default implementation does nothing.
+    fn offspring_created(&mut self, _ids: &[ObsId]) {}
+
+    /// Notifies whether the given offspring has improved on its parents (i.e., dominates at least one of them).
+    ///
+    /// The default implementation does nothing.
+    fn offspring_evaluated(&mut self, _id: ObsId, _improved: bool) {}
+}
+
+/// A set of operators whose invocation probabilities are adapted online by [adaptive pursuit].
+///
+/// Each invocation applies an operator chosen according to the probabilities.
+/// An operator is rewarded when an offspring produced by it improves on its parents,
+/// and the probability of the operator having the highest estimated reward approaches `1 - (K - 1) * min_probability`
+/// (where `K` is the number of the operators) while the others approach `min_probability`.
+///
+/// This implements both `CrossOver` and `Mutate`, so it can be used as
+/// the crossover operator and/or the mutator of `Nsga2Strategy`.
+///
+/// [adaptive pursuit]: https://doi.org/10.1145/1068009.1068251
+#[derive(Debug)]
+pub struct AdaptiveOperators<T> {
+    operators: Vec<T>,
+    probabilities: Vec<f64>,
+    qualities: Vec<f64>,
+    min_probability: f64,
+    adaptation_rate: f64,
+    learning_rate: f64,
+    unassigned: Vec<usize>,
+    assigned: HashMap<ObsId, usize>,
+}
+
+impl<T> AdaptiveOperators<T> {
+    /// Makes a new `AdaptiveOperators` instance.
+    ///
+    /// Initially, all operators are chosen with the same probability.
+    /// The default minimum probability is `0.2 / K`, and both the adaptation rate (of the reward estimates)
+    /// and the learning rate (of the probabilities) are `0.3`.
+    ///
+    /// # Errors
+    ///
+    /// If `operators` is empty, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(operators: Vec<T>) -> Result<Self> {
+        track_assert!(!operators.is_empty(), ErrorKind::InvalidInput);
+        let k = operators.len() as f64;
+        Ok(Self {
+            probabilities: vec![1.0 / k; operators.len()],
+            qualities: vec![1.0; operators.len()],
+            operators,
+            min_probability: 0.2 / k,
+            adaptation_rate: 0.3,
+            learning_rate: 0.3,
+            unassigned: Vec::new(),
+            assigned: HashMap::new(),
+        })
+    }
+
+    /// Sets the minimum invocation probability of each operator.
+    ///
+    /// # Errors
+    ///
+    /// If `p` is negative or `p * K` is greater than `1`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_min_probability(&mut self, p: f64) -> Result<()> {
+        track_assert!(
+            0.0 <= p && p * self.operators.len() as f64 <= 1.0,
+            ErrorKind::InvalidInput; p
+        );
+        self.min_probability = p;
+        Ok(())
+    }
+
+    /// Sets the adaptation rate of the reward estimates and the learning rate of the probabilities.
+    ///
+    /// # Errors
+    ///
+    /// If a rate is not in the range `(0.0, 1.0]`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_rates(&mut self, adaptation_rate: f64, learning_rate: f64) -> Result<()> {
+        track_assert!(
+            0.0 < adaptation_rate && adaptation_rate <= 1.0,
+            ErrorKind::InvalidInput; adaptation_rate
+        );
+        track_assert!(
+            0.0 < learning_rate && learning_rate <= 1.0,
+            ErrorKind::InvalidInput; learning_rate
+        );
+        self.adaptation_rate = adaptation_rate;
+        self.learning_rate = learning_rate;
+        Ok(())
+    }
+
+    /// Returns the operators.
+    pub fn operators(&self) -> &[T] {
+        &self.operators
+    }
+
+    /// Returns the current invocation probabilities of the operators.
+    pub fn probabilities(&self) -> &[f64] {
+        &self.probabilities
+    }
+
+    fn choose<R: Rng>(&mut self, mut rng: R) -> usize {
+        let mut x = rng.gen_range(0.0..1.0);
+        for (i, p) in self.probabilities.iter().enumerate() {
+            if x < *p {
+                self.unassigned.push(i);
+                return i;
+            }
+            x -= p;
+        }
+        let i = self.operators.len() - 1;
+        self.unassigned.push(i);
+        i
+    }
+
+    fn assign(&mut self, ids: &[ObsId]) {
+        if let Some(&last) = self.unassigned.last() {
+            for (k, id) in ids.iter().enumerate() {
+                let i = self.unassigned.get(k).copied().unwrap_or(last);
+                self.assigned.insert(*id, i);
+            }
+        }
+        self.unassigned.clear();
+    }
+
+    fn reward(&mut self, id: ObsId, improved: bool) {
+        let i = match self.assigned.remove(&id) {
+            Some(i) => i,
+            None => return,
+        };
+        let r = if improved { 1.0 } else { 0.0 };
+        self.qualities[i] += self.adaptation_rate * (r - self.qualities[i]);
+
+        let mut best = 0;
+        for (j, q) in self.qualities.iter().enumerate() {
+            if *q > self.qualities[best] {
+                best = j;
+            }
+        }
+        let max_probability = 1.0 - (self.operators.len() - 1) as f64 * self.min_probability;
+        for (j, p) in self.probabilities.iter_mut().enumerate() {
+            let target = if j == best {
+                max_probability
+            } else {
+                self.min_probability
+            };
+            *p += self.learning_rate * (target - *p);
+        }
+    }
+}
+
+impl<D, T> CrossOver<D> for AdaptiveOperators<T>
+where
+    D: Domain,
+    T: CrossOver<D>,
+{
+    fn cross_over<R: Rng>(
+        &mut self,
+        mut rng: R,
+        p0: &mut D::Point,
+        p1: &mut D::Point,
+    ) -> Result<()> {
+        let i = self.choose(&mut rng);
+        track!(self.operators[i].cross_over(rng, p0, p1))
+    }
+
+    fn offspring_created(&mut self, ids: &[ObsId]) {
+        self.assign(ids);
+    }
+
+    fn offspring_evaluated(&mut self, id: ObsId, improved: bool) {
+        self.reward(id, improved);
+    }
+}
+
+impl<D, T> Mutate<D> for AdaptiveOperators<T>
+where
+    D: Domain,
+    T: Mutate<D>,
+{
+    fn mutate<R: Rng>(&mut self, mut rng: R, domain: &D, p: &mut D::Point) -> Result<()> {
+        let i = self.choose(&mut rng);
+        track!(self.operators[i].mutate(rng, domain, p))
+    }
+
+    fn offspring_created(&mut self, ids: &[ObsId]) {
+        self.assign(ids);
+    }
+
+    fn offspring_evaluated(&mut self, id: ObsId, improved: bool) {
+        self.reward(id, improved);
+    }
 }
 
 /// A crossover operator that stochastically exchanges two individuals.
@@ -175,60 +370,902 @@ impl Replace {
     }
 }
 
-impl Default for Replace {
-    fn default() -> Self {
-        Self { probability: 0.3 }
+impl Default for Replace {
+    fn default() -> Self {
+        Self { probability: 0.3 }
+    }
+}
+
+impl<D> Mutate<D> for Replace
+where
+    D: Domain + Distribution<<D as Domain>::Point>,
+{
+    fn mutate<R: Rng>(&mut self, mut rng: R, domain: &D, p: &mut D::Point) -> Result<()> {
+        if rng.gen_bool(self.probability) {
+            *p = domain.sample(&mut rng);
+        }
+        Ok(())
+    }
+}
+
+/// A mutation operator that stochastically moves a point of `SteppedDomain` to one of its neighbors.
+///
+/// The point is moved by at most `max_steps` steps (clamped to the domain).
+#[derive(Debug)]
+pub struct StepMutation {
+    probability: f64,
+    max_steps: u64,
+}
+
+impl StepMutation {
+    /// Makes a new `StepMutation` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `probability` is not in `0.0..=1.0` or `max_steps` is `0`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(probability: f64, max_steps: u64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        track_assert!(max_steps > 0, ErrorKind::InvalidInput);
+        Ok(Self {
+            probability,
+            max_steps,
+        })
+    }
+}
+
+impl Default for StepMutation {
+    fn default() -> Self {
+        Self {
+            probability: 0.3,
+            max_steps: 1,
+        }
+    }
+}
+
+impl Mutate<SteppedDomain> for StepMutation {
+    fn mutate<R: Rng>(&mut self, mut rng: R, domain: &SteppedDomain, p: &mut f64) -> Result<()> {
+        if rng.gen_bool(self.probability) {
+            let i = track_assert_some!(domain.index(*p), ErrorKind::InvalidInput; p);
+            let steps = rng.gen_range(1..=self.max_steps);
+            *p = if rng.gen() {
+                domain.point(i.saturating_add(steps))
+            } else {
+                domain.point(i.saturating_sub(steps))
+            };
+        }
+        Ok(())
+    }
+}
+
+/// Vector version of `Replace` operator.
+#[derive(Debug, Default)]
+pub struct ReplaceVec(Replace);
+
+impl ReplaceVec {
+    /// Makes a new `ReplaceVec` instance.
+    pub fn new(probability: f64) -> Result<Self> {
+        track!(Replace::new(probability)).map(Self)
+    }
+}
+
+impl<D> Mutate<VecDomain<D>> for ReplaceVec
+where
+    D: Domain + Distribution<<D as Domain>::Point>,
+    Replace: Mutate<D>,
+{
+    fn mutate<R: Rng>(
+        &mut self,
+        mut rng: R,
+        domain: &VecDomain<D>,
+        ps: &mut Vec<D::Point>,
+    ) -> Result<()> {
+        for (d, p) in domain.0.iter().zip(ps.iter_mut()) {
+            track!(self.0.mutate(&mut rng, d, p))?;
+        }
+        Ok(())
+    }
+}
+
+/// A mutation operator that adds Gaussian noise to a point of `ContinuousDomain`.
+///
+/// The standard deviation (sigma) is relative to the size of the domain,
+/// and mutated points are clamped to the domain.
+/// Sigma self-adapts by the 1/5th success rule: it is multiplied by `exp(1/3)` when a mutated offspring
+/// improves on its parents and by `exp(-1/12)` otherwise,
+/// so it stays unchanged when one in five mutations is successful.
+///
+/// # References
+///
+/// - [Learning probability distributions in continuous evolutionary algorithms](https://doi.org/10.1023/B:NACO.0000023416.59689.4e)
+#[derive(Debug)]
+pub struct GaussianMutation {
+    probability: f64,
+    sigma: f64,
+    rule: SuccessRule,
+    feedback: Feedback<bool>,
+}
+
+impl GaussianMutation {
+    /// Makes a new `GaussianMutation` instance.
+    ///
+    /// The default range of sigma is `[1e-6, 1.0]`.
+    ///
+    /// # Errors
+    ///
+    /// If `probability` is not in `0.0..=1.0` or `sigma` is not in the range of sigma,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(probability: f64, sigma: f64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        let rule = SuccessRule::default();
+        track!(rule.check(sigma))?;
+        Ok(Self {
+            probability,
+            sigma,
+            rule,
+            feedback: Feedback::default(),
+        })
+    }
+
+    /// Sets the range of sigma.
+    ///
+    /// The current sigma is clamped to the range.
+    ///
+    /// # Errors
+    ///
+    /// If `0 < min <= max` is not satisfied, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_sigma_range(&mut self, min: f64, max: f64) -> Result<()> {
+        self.rule = track!(SuccessRule::new(min, max))?;
+        self.sigma = self.sigma.clamp(min, max);
+        Ok(())
+    }
+
+    /// Returns the current sigma (relative to the size of the domain).
+    pub fn sigma(&self) -> f64 {
+        self.sigma
+    }
+}
+
+impl Default for GaussianMutation {
+    fn default() -> Self {
+        Self {
+            probability: 1.0,
+            sigma: 0.1,
+            rule: SuccessRule::default(),
+            feedback: Feedback::default(),
+        }
+    }
+}
+
+impl Mutate<ContinuousDomain> for GaussianMutation {
+    fn mutate<R: Rng>(&mut self, mut rng: R, domain: &ContinuousDomain, p: &mut f64) -> Result<()> {
+        let mutated = rng.gen_bool(self.probability);
+        if mutated {
+            *p = domain.clamp(*p + self.sigma * domain.size() * standard_normal(&mut rng));
+        }
+        self.feedback.push(mutated);
+        Ok(())
+    }
+
+    fn offspring_created(&mut self, ids: &[ObsId]) {
+        self.feedback.assign(ids);
+    }
+
+    fn offspring_evaluated(&mut self, id: ObsId, improved: bool) {
+        if self.feedback.take(id) == Some(true) {
+            self.sigma = self.rule.update(self.sigma, improved);
+        }
+    }
+}
+
+/// Vector version of `GaussianMutation` operator.
+///
+/// Each element is mutated with the given probability, and each dimension has its own sigma.
+/// When an offspring is evaluated, only the sigmas of the dimensions mutated in the offspring are adapted.
+#[derive(Debug)]
+pub struct GaussianMutationVec {
+    probability: f64,
+    initial_sigma: f64,
+    sigmas: Vec<f64>,
+    rule: SuccessRule,
+    feedback: Feedback<Vec<bool>>,
+}
+
+impl GaussianMutationVec {
+    /// Makes a new `GaussianMutationVec` instance.
+    ///
+    /// The sigmas of all dimensions start from `sigma`.
+    ///
+    /// # Errors
+    ///
+    /// See `GaussianMutation::new`.
+    pub fn new(probability: f64, sigma: f64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        let rule = SuccessRule::default();
+        track!(rule.check(sigma))?;
+        Ok(Self {
+            probability,
+            initial_sigma: sigma,
+            sigmas: Vec::new(),
+            rule,
+            feedback: Feedback::default(),
+        })
+    }
+
+    /// Sets the range of sigmas.
+    ///
+    /// # Errors
+    ///
+    /// See `GaussianMutation::set_sigma_range`.
+    pub fn set_sigma_range(&mut self, min: f64, max: f64) -> Result<()> {
+        self.rule = track!(SuccessRule::new(min, max))?;
+        self.initial_sigma = self.initial_sigma.clamp(min, max);
+        for sigma in &mut self.sigmas {
+            *sigma = sigma.clamp(min, max);
+        }
+        Ok(())
+    }
+
+    /// Returns the current sigmas of the dimensions.
+    ///
+    /// This is empty until the operator is applied for the first time.
+    pub fn sigmas(&self) -> &[f64] {
+        &self.sigmas
+    }
+}
+
+impl Default for GaussianMutationVec {
+    fn default() -> Self {
+        Self {
+            probability: 0.3,
+            initial_sigma: 0.1,
+            sigmas: Vec::new(),
+            rule: SuccessRule::default(),
+            feedback: Feedback::default(),
+        }
+    }
+}
+
+impl Mutate<VecDomain<ContinuousDomain>> for GaussianMutationVec {
+    fn mutate<R: Rng>(
+        &mut self,
+        mut rng: R,
+        domain: &VecDomain<ContinuousDomain>,
+        ps: &mut Vec<f64>,
+    ) -> Result<()> {
+        track_assert_eq!(ps.len(), domain.0.len(), ErrorKind::InvalidInput);
+        if self.sigmas.len() != domain.0.len() {
+            self.sigmas = vec![self.initial_sigma; domain.0.len()];
+        }
+
+        let mut mutated = vec![false; ps.len()];
+        for (i, (d, p)) in domain.0.iter().zip(ps.iter_mut()).enumerate() {
+            if rng.gen_bool(self.probability) {
+                *p = d.clamp(*p + self.sigmas[i] * d.size() * standard_normal(&mut rng));
+                mutated[i] = true;
+            }
+        }
+        self.feedback.push(mutated);
+        Ok(())
+    }
+
+    fn offspring_created(&mut self, ids: &[ObsId]) {
+        self.feedback.assign(ids);
+    }
+
+    fn offspring_evaluated(&mut self, id: ObsId, improved: bool) {
+        if let Some(mutated) = self.feedback.take(id) {
+            for (sigma, mutated) in self.sigmas.iter_mut().zip(mutated) {
+                if mutated {
+                    *sigma = self.rule.update(*sigma, improved);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SuccessRule {
+    min: f64,
+    max: f64,
+}
+
+impl SuccessRule {
+    fn new(min: f64, max: f64) -> Result<Self> {
+        track_assert!(
+            0.0 < min && min <= max && max.is_finite(),
+            ErrorKind::InvalidInput; min, max
+        );
+        Ok(Self { min, max })
+    }
+
+    fn check(&self, sigma: f64) -> Result<()> {
+        track_assert!(
+            self.min <= sigma && sigma <= self.max,
+            ErrorKind::InvalidInput; sigma, self.min, self.max
+        );
+        Ok(())
+    }
+
+    fn update(&self, sigma: f64, improved: bool) -> f64 {
+        let factor = if improved { 1.0 / 3.0 } else { -1.0 / 12.0 };
+        (sigma * f64::exp(factor)).clamp(self.min, self.max)
+    }
+}
+
+impl Default for SuccessRule {
+    fn default() -> Self {
+        Self {
+            min: 1e-6,
+            max: 1.0,
+        }
+    }
+}
+
+/// Records made by the invocations of an operator, waiting for the identifiers of the resulting offspring.
+#[derive(Debug)]
+struct Feedback<T> {
+    unassigned: Vec<T>,
+    assigned: HashMap<ObsId, T>,
+}
+
+impl<T> Feedback<T> {
+    fn push(&mut self, record: T) {
+        self.unassigned.push(record);
+    }
+
+    fn assign(&mut self, ids: &[ObsId]) {
+        for (id, record) in ids.iter().zip(self.unassigned.drain(..)) {
+            self.assigned.insert(*id, record);
+        }
+        self.unassigned.clear();
+    }
+
+    fn take(&mut self, id: ObsId) -> Option<T> {
+        self.assigned.remove(&id)
+    }
+}
+
+impl<T> Default for Feedback<T> {
+    fn default() -> Self {
+        Self {
+            unassigned: Vec::new(),
+            assigned: HashMap::new(),
+        }
+    }
+}
+
+fn standard_normal<R: Rng>(mut rng: R) -> f64 {
+    let u = 1.0 - rng.gen::<f64>();
+    let v = rng.gen::<f64>();
+    (-2.0 * u.ln()).sqrt() * (2.0 * std::f64::consts::PI * v).cos()
+}
+
+/// A crossover operator that exchanges each element of two vectors with the given probability.
+#[derive(Debug)]
+pub struct UniformCrossOver {
+    probability: f64,
+}
+
+impl UniformCrossOver {
+    /// Makes a new `UniformCrossOver` instance.
+    pub fn new(probability: f64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        Ok(Self { probability })
+    }
+}
+
+impl Default for UniformCrossOver {
+    fn default() -> Self {
+        Self { probability: 0.5 }
+    }
+}
+
+impl<D, T> CrossOver<D> for UniformCrossOver
+where
+    D: Domain<Point = Vec<T>>,
+{
+    fn cross_over<R: Rng>(&mut self, mut rng: R, p0: &mut Vec<T>, p1: &mut Vec<T>) -> Result<()> {
+        track_assert_eq!(p0.len(), p1.len(), ErrorKind::InvalidInput);
+        for (x0, x1) in p0.iter_mut().zip(p1.iter_mut()) {
+            if rng.gen_bool(self.probability) {
+                std::mem::swap(x0, x1);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A crossover operator that exchanges the tails of two vectors after a randomly chosen point.
+#[derive(Debug, Default)]
+pub struct OnePointCrossOver;
+
+impl<D, T> CrossOver<D> for OnePointCrossOver
+where
+    D: Domain<Point = Vec<T>>,
+{
+    fn cross_over<R: Rng>(&mut self, mut rng: R, p0: &mut Vec<T>, p1: &mut Vec<T>) -> Result<()> {
+        track_assert_eq!(p0.len(), p1.len(), ErrorKind::InvalidInput);
+        if p0.len() < 2 {
+            return Ok(());
+        }
+        let point = rng.gen_range(1..p0.len());
+        p0[point..].swap_with_slice(&mut p1[point..]);
+        Ok(())
+    }
+}
+
+/// A mutation operator that flips each bit of a bit string with the given probability.
+#[derive(Debug, Default)]
+pub struct BitFlip {
+    probability: Option<f64>,
+}
+
+impl BitFlip {
+    /// Makes a new `BitFlip` instance.
+    ///
+    /// If this is created by `BitFlip::default()`, the probability is `1 / domain.bits()`.
+    pub fn new(probability: f64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        Ok(Self {
+            probability: Some(probability),
+        })
+    }
+}
+
+impl Mutate<BitStringDomain> for BitFlip {
+    fn mutate<R: Rng>(
+        &mut self,
+        mut rng: R,
+        domain: &BitStringDomain,
+        p: &mut Vec<bool>,
+    ) -> Result<()> {
+        let probability = self
+            .probability
+            .unwrap_or_else(|| 1.0 / domain.bits() as f64);
+        for b in p.iter_mut() {
+            if rng.gen_bool(probability) {
+                *b = !*b;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Partially mapped crossover (PMX) operator for permutations.
+///
+/// Both parents must be permutations of `0..n` with the same `n`.
+#[derive(Debug, Default)]
+pub struct PartiallyMappedCrossOver;
+
+impl CrossOver<PermutationDomain> for PartiallyMappedCrossOver {
+    fn cross_over<R: Rng>(
+        &mut self,
+        rng: R,
+        p0: &mut Vec<usize>,
+        p1: &mut Vec<usize>,
+    ) -> Result<()> {
+        track_assert_eq!(p0.len(), p1.len(), ErrorKind::InvalidInput);
+        // Mapping chains over duplicated elements never terminate.
+        track_assert!(is_permutation(p0), ErrorKind::InvalidInput; p0);
+        track_assert!(is_permutation(p1), ErrorKind::InvalidInput; p1);
+        let (lo, hi) = random_segment(rng, p0.len());
+
+        let pmx = |a: &[usize], b: &[usize]| {
+            let mut position = vec![0; b.len()];
+            for (i, &x) in b.iter().enumerate() {
+                position[x] = i;
+            }
+
+            let mut child = vec![None; a.len()];
+            for (c, &x) in child[lo..hi].iter_mut().zip(a[lo..hi].iter()) {
+                *c = Some(x);
+            }
+            for (i, &v) in b.iter().enumerate().take(hi).skip(lo) {
+                if a[lo..hi].contains(&v) {
+                    continue;
+                }
+                let mut j = i;
+                while lo <= j && j < hi {
+                    j = position[a[j]];
+                }
+                child[j] = Some(v);
+            }
+            child
+                .into_iter()
+                .zip(b.iter())
+                .map(|(c, &x)| c.unwrap_or(x))
+                .collect::<Vec<_>>()
+        };
+        let c0 = pmx(p0, p1);
+        let c1 = pmx(p1, p0);
+        *p0 = c0;
+        *p1 = c1;
+        Ok(())
+    }
+}
+
+fn is_permutation(p: &[usize]) -> bool {
+    let mut sorted = p.to_vec();
+    sorted.sort_unstable();
+    sorted.into_iter().eq(0..p.len())
+}
+
+/// Order crossover (OX) operator for permutations.
+#[derive(Debug, Default)]
+pub struct OrderCrossOver;
+
+impl CrossOver<PermutationDomain> for OrderCrossOver {
+    fn cross_over<R: Rng>(
+        &mut self,
+        rng: R,
+        p0: &mut Vec<usize>,
+        p1: &mut Vec<usize>,
+    ) -> Result<()> {
+        track_assert_eq!(p0.len(), p1.len(), ErrorKind::InvalidInput);
+        let n = p0.len();
+        let (lo, hi) = random_segment(rng, n);
+
+        let ox = |a: &[usize], b: &[usize]| {
+            let mut child = a.to_vec();
+            let mut rest = (0..n)
+                .map(|i| b[(hi + i) % n])
+                .filter(|x| !a[lo..hi].contains(x));
+            for i in 0..n - (hi - lo) {
+                if let Some(x) = rest.next() {
+                    child[(hi + i) % n] = x;
+                }
+            }
+            child
+        };
+        let c0 = ox(p0, p1);
+        let c1 = ox(p1, p0);
+        *p0 = c0;
+        *p1 = c1;
+        Ok(())
+    }
+}
+
+/// A mutation operator that stochastically swaps two elements of a permutation.
+#[derive(Debug)]
+pub struct SwapMutation {
+    probability: f64,
+}
+
+impl SwapMutation {
+    /// Makes a new `SwapMutation` instance.
+    pub fn new(probability: f64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        Ok(Self { probability })
+    }
+}
+
+impl Default for SwapMutation {
+    fn default() -> Self {
+        Self { probability: 0.3 }
+    }
+}
+
+impl Mutate<PermutationDomain> for SwapMutation {
+    fn mutate<R: Rng>(
+        &mut self,
+        mut rng: R,
+        _domain: &PermutationDomain,
+        p: &mut Vec<usize>,
+    ) -> Result<()> {
+        if !p.is_empty() && rng.gen_bool(self.probability) {
+            let i = rng.gen_range(0..p.len());
+            let j = rng.gen_range(0..p.len());
+            p.swap(i, j);
+        }
+        Ok(())
+    }
+}
+
+/// A mutation operator that stochastically moves an element of a permutation to another position.
+#[derive(Debug)]
+pub struct InsertionMutation {
+    probability: f64,
+}
+
+impl InsertionMutation {
+    /// Makes a new `InsertionMutation` instance.
+    pub fn new(probability: f64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        Ok(Self { probability })
+    }
+}
+
+impl Default for InsertionMutation {
+    fn default() -> Self {
+        Self { probability: 0.3 }
+    }
+}
+
+impl Mutate<PermutationDomain> for InsertionMutation {
+    fn mutate<R: Rng>(
+        &mut self,
+        mut rng: R,
+        _domain: &PermutationDomain,
+        p: &mut Vec<usize>,
+    ) -> Result<()> {
+        if !p.is_empty() && rng.gen_bool(self.probability) {
+            let x = p.remove(rng.gen_range(0..p.len()));
+            p.insert(rng.gen_range(0..=p.len()), x);
+        }
+        Ok(())
+    }
+}
+
+/// A mutation operator that stochastically reverses a random segment of a permutation.
+#[derive(Debug)]
+pub struct InversionMutation {
+    probability: f64,
+}
+
+impl InversionMutation {
+    /// Makes a new `InversionMutation` instance.
+    pub fn new(probability: f64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        Ok(Self { probability })
+    }
+}
+
+impl Default for InversionMutation {
+    fn default() -> Self {
+        Self { probability: 0.3 }
+    }
+}
+
+impl Mutate<PermutationDomain> for InversionMutation {
+    fn mutate<R: Rng>(
+        &mut self,
+        mut rng: R,
+        _domain: &PermutationDomain,
+        p: &mut Vec<usize>,
+    ) -> Result<()> {
+        if rng.gen_bool(self.probability) {
+            let (lo, hi) = random_segment(&mut rng, p.len());
+            p[lo..hi].reverse();
+        }
+        Ok(())
+    }
+}
+
+fn random_segment<R: Rng>(mut rng: R, n: usize) -> (usize, usize) {
+    let a = rng.gen_range(0..=n);
+    let b = rng.gen_range(0..=n);
+    (cmp::min(a, b), cmp::max(a, b))
+}
+
+/// Subtree crossover operator for trees.
+///
+/// This operator exchanges randomly chosen subtrees of the parents.
+/// If the resulting trees exceed the maximum depth, the parents are left unchanged.
+#[derive(Debug)]
+pub struct SubtreeCrossOver {
+    max_depth: usize,
+}
+
+impl SubtreeCrossOver {
+    /// Makes a new `SubtreeCrossOver` instance for trees of the given domain.
+    pub const fn new(domain: &TreeDomain) -> Self {
+        Self {
+            max_depth: domain.max_depth(),
+        }
+    }
+}
+
+impl CrossOver<TreeDomain> for SubtreeCrossOver {
+    fn cross_over<R: Rng>(&mut self, mut rng: R, p0: &mut Tree, p1: &mut Tree) -> Result<()> {
+        let i = rng.gen_range(0..p0.size());
+        let j = rng.gen_range(0..p1.size());
+        let swap = |p0: &mut Tree, p1: &mut Tree| -> Result<()> {
+            let s0 = track_assert_some!(p0.get_mut(i), ErrorKind::Bug);
+            let s1 = track_assert_some!(p1.get_mut(j), ErrorKind::Bug);
+            std::mem::swap(s0, s1);
+            Ok(())
+        };
+        track!(swap(p0, p1))?;
+        if p0.depth() > self.max_depth || p1.depth() > self.max_depth {
+            track!(swap(p0, p1))?;
+        }
+        Ok(())
+    }
+}
+
+/// A mutation operator that replaces the type of each node of a tree with the given probability.
+///
+/// The new node type is randomly chosen from the node types which have the same arity as the old one.
+#[derive(Debug, Default)]
+pub struct PointMutation {
+    probability: Option<f64>,
+}
+
+impl PointMutation {
+    /// Makes a new `PointMutation` instance.
+    ///
+    /// If this is created by `PointMutation::default()`, the probability is `1 / tree.size()`.
+    pub fn new(probability: f64) -> Result<Self> {
+        track_assert!((0.0..=1.0).contains(&probability), ErrorKind::InvalidInput; probability);
+        Ok(Self {
+            probability: Some(probability),
+        })
+    }
+}
+
+impl Mutate<TreeDomain> for PointMutation {
+    fn mutate<R: Rng>(&mut self, mut rng: R, domain: &TreeDomain, p: &mut Tree) -> Result<()> {
+        fn walk<R: Rng>(rng: &mut R, arities: &[usize], probability: f64, node: &mut Tree) {
+            if rng.gen_bool(probability) {
+                let arity = node.children.len();
+                let kinds = (0..arities.len())
+                    .filter(|&k| arities[k] == arity)
+                    .collect::<Vec<_>>();
+                if let Some(&kind) = kinds.choose(rng) {
+                    node.kind = kind;
+                }
+            }
+            for child in &mut node.children {
+                walk(rng, arities, probability, child);
+            }
+        }
+
+        let probability = self.probability.unwrap_or_else(|| 1.0 / p.size() as f64);
+        walk(&mut rng, domain.arities(), probability, p);
+        Ok(())
+    }
+}
+
+/// This trait allows deciding which individuals survive when a front does not fit into the next parent population.
+pub trait Replacement<D: Domain> {
+    /// Sorts `front` so that the individuals to be kept come first.
+    ///
+    /// All individuals in `front` have the same non-dominated rank.
+    fn prioritize<R: Rng>(&mut self, rng: R, front: &mut [Obs<D::Point, Vec<f64>>]) -> Result<()>;
+}
+
+/// A replacement policy that prefers individuals with larger crowding distances.
+///
+/// This is the policy of the original NSGA-II.
+/// Individuals having the same crowding distance are ordered by their identifiers.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CrowdingDistance;
+
+impl<D: Domain> Replacement<D> for CrowdingDistance {
+    fn prioritize<R: Rng>(&mut self, _rng: R, front: &mut [Obs<D::Point, Vec<f64>>]) -> Result<()> {
+        sort_by_crowding_distance(front);
+        Ok(())
+    }
+}
+
+/// A replacement policy based on epsilon-dominance archiving.
+///
+/// The objective space is divided into boxes whose sizes are given by `epsilons`,
+/// and at most one individual (the one closest to the lower corner of the box) per box is preferred.
+/// Representatives are then ordered by crowding distance, followed by the other individuals.
+///
+/// # References
+///
+/// - [Combining Convergence and Diversity in Evolutionary Multiobjective Optimization](https://doi.org/10.1162/106365602760234108)
+#[derive(Debug, Clone)]
+pub struct EpsilonDominance {
+    epsilons: Vec<f64>,
+}
+
+impl EpsilonDominance {
+    /// Makes a new `EpsilonDominance` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `epsilons` is empty or contains a non-positive or non-finite number,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(epsilons: Vec<f64>) -> Result<Self> {
+        track_assert!(!epsilons.is_empty(), ErrorKind::InvalidInput);
+        for &e in &epsilons {
+            track_assert!(e.is_finite() && e > 0.0, ErrorKind::InvalidInput; e);
+        }
+        Ok(Self { epsilons })
+    }
+}
+
+impl<D: Domain> Replacement<D> for EpsilonDominance {
+    fn prioritize<R: Rng>(&mut self, _rng: R, front: &mut [Obs<D::Point, Vec<f64>>]) -> Result<()> {
+        let mut representatives = HashMap::<Vec<i64>, (f64, ObsId)>::new();
+        for x in front.iter() {
+            track_assert_eq!(x.value.len(), self.epsilons.len(), ErrorKind::InvalidInput; x.id);
+            let mut cell = Vec::with_capacity(self.epsilons.len());
+            let mut distance = 0.0;
+            for (v, e) in x.value.iter().zip(self.epsilons.iter()) {
+                let b = (v / e).floor();
+                cell.push(b as i64);
+                distance += (v - b * e).powi(2);
+            }
+            let entry = representatives.entry(cell).or_insert((distance, x.id));
+            if (distance, x.id) < *entry {
+                *entry = (distance, x.id);
+            }
+        }
+        let representatives = representatives
+            .values()
+            .map(|&(_, id)| id)
+            .collect::<HashSet<_>>();
+
+        sort_by_crowding_distance(front);
+        front.sort_by_key(|x| !representatives.contains(&x.id));
+        Ok(())
     }
 }
 
-impl<D> Mutate<D> for Replace
-where
-    D: Domain + Distribution<<D as Domain>::Point>,
-{
-    fn mutate<R: Rng>(&mut self, mut rng: R, domain: &D, p: &mut D::Point) -> Result<()> {
-        if rng.gen_bool(self.probability) {
-            *p = domain.sample(&mut rng);
-        }
+/// A replacement policy that prefers younger individuals.
+///
+/// The age of an individual is determined by its identifier,
+/// so this assumes that identifiers are generated in ascending order (e.g., by `SerialIdGenerator`).
+/// Individuals of the same age are ordered by crowding distance.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AgeBased;
+
+impl<D: Domain> Replacement<D> for AgeBased {
+    fn prioritize<R: Rng>(&mut self, _rng: R, front: &mut [Obs<D::Point, Vec<f64>>]) -> Result<()> {
+        sort_by_crowding_distance(front);
+        front.sort_by_key(|x| Reverse(x.id));
         Ok(())
     }
 }
 
-/// Vector version of `Replace` operator.
-#[derive(Debug, Default)]
-pub struct ReplaceVec(Replace);
+fn sort_by_crowding_distance<P>(front: &mut [Obs<P, Vec<f64>>]) {
+    let distances = crowding_distances(front);
+    front.sort_by_key(|x| {
+        let distance = distances.get(&x.id).copied().unwrap_or(0.0);
+        (Reverse(OrderedFloat(distance)), x.id)
+    });
+}
 
-impl ReplaceVec {
-    /// Makes a new `ReplaceVec` instance.
-    pub fn new(probability: f64) -> Result<Self> {
-        track!(Replace::new(probability)).map(Self)
+fn crowding_distances<P>(front: &mut [Obs<P, Vec<f64>>]) -> HashMap<ObsId, f64> {
+    let l = front.len();
+    let mut distances = HashMap::new();
+    if l == 0 {
+        return distances;
     }
-}
 
-impl<D> Mutate<VecDomain<D>> for ReplaceVec
-where
-    D: Domain + Distribution<<D as Domain>::Point>,
-    Replace: Mutate<D>,
-{
-    fn mutate<R: Rng>(
-        &mut self,
-        mut rng: R,
-        domain: &VecDomain<D>,
-        ps: &mut Vec<D::Point>,
-    ) -> Result<()> {
-        for (d, p) in domain.0.iter().zip(ps.iter_mut()) {
-            track!(self.0.mutate(&mut rng, d, p))?;
+    for i in 0..front[0].value.len() {
+        front.sort_by_key(|x| (OrderedFloat(x.value[i]), x.id));
+
+        distances.insert(front[0].id, f64::INFINITY);
+        distances.insert(front[l - 1].id, f64::INFINITY);
+        let width = front[l - 1].value[i] - front[0].value[i];
+        if width == 0.0 {
+            // All individuals have the same value for this objective.
+            continue;
         }
-        Ok(())
+
+        for xs in front.windows(3) {
+            let d = distances.entry(xs[1].id).or_insert(0.0);
+            *d += (xs[2].value[i] - xs[0].value[i]) / width;
+        }
+    }
+    for x in front.iter() {
+        distances.entry(x.id).or_insert(0.0);
     }
+    distances
 }
 
-fn dominates<P>(a: &Obs<P, Vec<f64>>, b: &Obs<P, Vec<f64>>) -> Result<bool> {
-    track_assert_eq!(a.value.len(), b.value.len(), ErrorKind::InvalidInput);
-    if a.value.iter().zip(b.value.iter()).any(|(a, b)| a > b) {
-        Ok(false)
-    } else {
-        Ok(a.value.iter().zip(b.value.iter()).any(|(a, b)| a < b))
-    }
+/// Statistics of a generation of `Nsga2Optimizer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationStats {
+    /// The generation number (starting from `1`).
+    pub generation: u64,
+
+    /// The sizes of the non-dominated fronts of the parent population (best first).
+    pub front_sizes: Vec<usize>,
+
+    /// The hypervolume of the parent population (if a reference point is set).
+    pub hypervolume: Option<f64>,
+
+    /// The crowding distances of the individuals in the first front (in descending order).
+    ///
+    /// The distances of the boundary individuals are `f64::INFINITY`.
+    pub crowding_distances: Vec<f64>,
 }
 
 /// This trait allows providing operators used by the NSGA-II algorithm.
@@ -245,6 +1282,9 @@ pub trait Strategy<D: Domain> {
     /// Mutator.
     type Mutator: Mutate<D>;
 
+    /// Replacement policy.
+    type Replacement: Replacement<D>;
+
     /// Returns a reference to the generator.
     fn generator(&self) -> &Self::Generator;
 
@@ -268,15 +1308,39 @@ pub trait Strategy<D: Domain> {
 
     /// Returns a mutable reference to the mutator.
     fn mutator_mut(&mut self) -> &mut Self::Mutator;
+
+    /// Returns a reference to the replacement policy.
+    fn replacement(&self) -> &Self::Replacement;
+
+    /// Returns a mutable reference to the replacement policy.
+    fn replacement_mut(&mut self) -> &mut Self::Replacement;
+
+    /// Notifies that the offspring having the given identifiers have been created
+    /// by the preceding invocations of the crossover operator and the mutator.
+    ///
+    /// The default implementation forwards the notification to the operators.
+    fn offspring_created(&mut self, ids: &[ObsId]) {
+        self.cross_over_mut().offspring_created(ids);
+        self.mutator_mut().offspring_created(ids);
+    }
+
+    /// Notifies whether the given offspring has improved on its parents (i.e., dominates at least one of them).
+    ///
+    /// The default implementation forwards the notification to the operators.
+    fn offspring_evaluated(&mut self, id: ObsId, improved: bool) {
+        self.cross_over_mut().offspring_evaluated(id, improved);
+        self.mutator_mut().offspring_evaluated(id, improved);
+    }
 }
 
 /// NSGA-II strategy.
 #[derive(Debug)]
-pub struct Nsga2Strategy<D, G, S, C, M> {
+pub struct Nsga2Strategy<D, G, S, C, M, T = CrowdingDistance> {
     generator: G,
     selector: S,
     cross_over: C,
     mutator: M,
+    replacement: T,
     _param_domain: PhantomData<D>,
 }
 
@@ -304,28 +1368,52 @@ where
 {
     /// Makes a new `Nsga2Strategy` instance.
     pub fn new(generator: G, selector: S, cross_over: C, mutator: M) -> Self {
+        Self::with_replacement(generator, selector, cross_over, mutator, CrowdingDistance)
+    }
+}
+
+impl<D, G, S, C, M, T> Nsga2Strategy<D, G, S, C, M, T>
+where
+    D: Domain,
+    G: Generate<D>,
+    S: Select<D>,
+    C: CrossOver<D>,
+    M: Mutate<D>,
+    T: Replacement<D>,
+{
+    /// Makes a new `Nsga2Strategy` instance with the given replacement policy.
+    pub fn with_replacement(
+        generator: G,
+        selector: S,
+        cross_over: C,
+        mutator: M,
+        replacement: T,
+    ) -> Self {
         Self {
             generator,
             selector,
             cross_over,
             mutator,
+            replacement,
             _param_domain: PhantomData,
         }
     }
 }
 
-impl<D, G, S, C, M> Strategy<D> for Nsga2Strategy<D, G, S, C, M>
+impl<D, G, S, C, M, T> Strategy<D> for Nsga2Strategy<D, G, S, C, M, T>
 where
     D: Domain,
     G: Generate<D>,
     S: Select<D>,
     C: CrossOver<D>,
     M: Mutate<D>,
+    T: Replacement<D>,
 {
     type Generator = G;
     type Selector = S;
     type CrossOver = C;
     type Mutator = M;
+    type Replacement = T;
 
     fn generator(&self) -> &Self::Generator {
         &self.generator
@@ -358,6 +1446,14 @@ where
     fn mutator_mut(&mut self) -> &mut Self::Mutator {
         &mut self.mutator
     }
+
+    fn replacement(&self) -> &Self::Replacement {
+        &self.replacement
+    }
+
+    fn replacement_mut(&mut self) -> &mut Self::Replacement {
+        &mut self.replacement
+    }
 }
 
 /// [NSGA-II] based optimizer.
@@ -374,12 +1470,18 @@ where
     strategy: S,
     param_domain: P,
     eval_queue: VecDeque<Obs<P::Point>>,
+    elite_count: usize,
+    deduplicate: bool,
+    parent_values: HashMap<ObsId, [Vec<f64>; 2]>,
+    generations: u64,
+    reference_point: Option<Vec<f64>>,
+    generation_stats: Option<GenerationStats>,
 }
 
 impl<P, S> Nsga2Optimizer<P, S>
 where
     P: Domain,
-    P::Point: Clone,
+    P::Point: Clone + PartialEq,
     S: Strategy<P>,
 {
     /// Makes a new `Nsga2Optimizer` instance.
@@ -392,9 +1494,205 @@ where
             strategy,
             param_domain,
             eval_queue: VecDeque::new(),
+            elite_count: population_size,
+            deduplicate: false,
+            parent_values: HashMap::new(),
+            generations: 0,
+            reference_point: None,
+            generation_stats: None,
         })
     }
 
+    /// Sets the number of individuals carried into the next generation by the elitist selection
+    /// (i.e., non-dominated rank and crowding distance).
+    ///
+    /// The remaining slots of the next parent population are filled with individuals
+    /// randomly chosen from the rest of the population, which helps to keep diversity.
+    /// The default value is the population size (i.e., the standard NSGA-II).
+    ///
+    /// # Errors
+    ///
+    /// If `n` is `0` or greater than the population size, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_elite_count(&mut self, n: usize) -> Result<()> {
+        track_assert!(
+            0 < n && n <= self.population_size,
+            ErrorKind::InvalidInput; n, self.population_size
+        );
+        self.elite_count = n;
+        Ok(())
+    }
+
+    /// Enables or disables the duplicate-parameter detection.
+    ///
+    /// If enabled, an offspring that has the same parameter as an individual in the population
+    /// (or an individual waiting to be evaluated) is replaced by a freshly generated one.
+    /// The default value is `false`.
+    pub fn set_deduplicate(&mut self, enabled: bool) {
+        self.deduplicate = enabled;
+    }
+
+    /// Returns the number of the generations (i.e., survivor selections) so far.
+    pub fn generations(&self) -> u64 {
+        self.generations
+    }
+
+    /// Sets the reference point used to compute the hypervolume of each generation.
+    ///
+    /// By default, no reference point is set and the hypervolume is not computed.
+    pub fn set_reference_point(&mut self, point: Vec<f64>) {
+        self.reference_point = Some(point);
+    }
+
+    /// Returns the statistics of the latest generation.
+    ///
+    /// The statistics are updated each time a new parent population is selected inside `ask`.
+    pub fn generation_stats(&self) -> Option<&GenerationStats> {
+        self.generation_stats.as_ref()
+    }
+
+    /// Returns the current parent population.
+    pub fn parent_population(&self) -> &[Obs<P::Point, Vec<f64>>] {
+        &self.parent_population
+    }
+
+    /// Adds an evaluated individual coming from outside (e.g., another population) to the current population.
+    ///
+    /// # Errors
+    ///
+    /// If the parameter of `obs` is out of the domain or the number of its objectives differs from
+    /// that of the other individuals, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn immigrate(&mut self, obs: Obs<P::Point, Vec<f64>>) -> Result<()> {
+        track!(self.check_told(&obs, self.objectives()))?;
+        self.current_population.push(obs);
+        Ok(())
+    }
+
+    fn select_survivors(&mut self, mut rng: impl Rng) -> Result<()> {
+        self.generations += 1;
+        let population = self
+            .parent_population
+            .drain(..)
+            .chain(self.current_population.drain(..))
+            .collect::<Vec<_>>();
+        let population_per_rank = track!(pareto::non_dominated_sort(population))?;
+
+        let mut rest = Vec::new();
+        for mut population in population_per_rank {
+            // Makes the result independent of the order in which the observations were told.
+            population.sort_by_key(|x| x.id);
+            let n = self.elite_count - cmp::min(self.elite_count, self.parent_population.len());
+            if n == 0 {
+                rest.extend(population);
+            } else if population.len() <= n {
+                self.parent_population.extend(population);
+            } else {
+                track!(self
+                    .strategy
+                    .replacement_mut()
+                    .prioritize(&mut rng, &mut population[..]))?;
+                rest.extend(population.split_off(n));
+                self.parent_population.extend(population);
+            }
+        }
+
+        let n = self.population_size - cmp::min(self.population_size, self.parent_population.len());
+        rest.shuffle(&mut rng);
+        self.parent_population.extend(rest.into_iter().take(n));
+        track!(self.update_generation_stats())?;
+        Ok(())
+    }
+
+    fn update_generation_stats(&mut self) -> Result<()> {
+        let mut fronts = track!(pareto::non_dominated_sort(self.parent_population.clone()))?;
+        let hypervolume = if let Some(reference) = &self.reference_point {
+            let points = self
+                .parent_population
+                .iter()
+                .map(|x| x.value.clone())
+                .collect::<Vec<_>>();
+            Some(track!(pareto::hypervolume(&points, reference))?)
+        } else {
+            None
+        };
+        let mut crowding_distances = fronts
+            .first_mut()
+            .map(|front| crowding_distances(front).into_values().collect::<Vec<_>>())
+            .unwrap_or_default();
+        crowding_distances.sort_by(|a, b| b.total_cmp(a));
+        self.generation_stats = Some(GenerationStats {
+            generation: self.generations,
+            front_sizes: fronts.iter().map(|f| f.len()).collect(),
+            hypervolume,
+            crowding_distances,
+        });
+        Ok(())
+    }
+
+    fn is_duplicate(&self, param: &P::Point) -> bool {
+        self.parent_population
+            .iter()
+            .chain(self.current_population.iter())
+            .any(|x| x.param == *param)
+            || self.eval_queue.iter().any(|x| x.param == *param)
+    }
+
+    fn dedup(&mut self, mut rng: impl Rng, mut param: P::Point) -> Result<P::Point> {
+        if !self.deduplicate {
+            return Ok(param);
+        }
+        for _ in 0..MAX_DEDUP_ATTEMPTS {
+            if !self.is_duplicate(&param) {
+                break;
+            }
+            param = track!(self
+                .strategy
+                .generator_mut()
+                .generate(&mut rng, &self.param_domain))?;
+        }
+        Ok(param)
+    }
+
+    // Returns the number of the objectives known from the observations told so far.
+    fn objectives(&self) -> Option<usize> {
+        self.parent_population
+            .iter()
+            .chain(self.current_population.iter())
+            .map(|x| x.value.len())
+            .chain(self.parent_values.values().map(|vs| vs[0].len()))
+            .next()
+    }
+
+    // Checks everything that `record_offspring` and the later survivor selection rely on,
+    // so that a successful check guarantees the observation can be inserted without errors.
+    fn check_told(&self, obs: &Obs<P::Point, Vec<f64>>, objectives: Option<usize>) -> Result<()> {
+        if !self.param_domain.contains(&obs.param) {
+            track_panic!(
+                ErrorKind::InvalidInput,
+                ErrorContext::new("Out of the domain parameter").obs_id(obs.id)
+            );
+        }
+        if let Some(n) = objectives {
+            track_assert_eq!(obs.value.len(), n, ErrorKind::InvalidInput; obs.id);
+        }
+        if let Some(parent_values) = self.parent_values.get(&obs.id) {
+            for v in parent_values {
+                track_assert_eq!(obs.value.len(), v.len(), ErrorKind::InvalidInput; obs.id);
+            }
+        }
+        Ok(())
+    }
+
+    fn record_offspring(&mut self, obs: &Obs<P::Point, Vec<f64>>) -> Result<()> {
+        if let Some(parent_values) = self.parent_values.remove(&obs.id) {
+            let mut improved = false;
+            for v in &parent_values {
+                improved |= track!(obs.value.dominates(v); obs.id)?;
+            }
+            self.strategy.offspring_evaluated(obs.id, improved);
+        }
+        Ok(())
+    }
+
     fn create_root_individual(&mut self, mut rng: impl Rng, mut idg: impl IdGen) -> Result<()> {
         let params = track!(self
             .strategy
@@ -413,6 +1711,7 @@ where
         let selector = self.strategy.selector_mut();
         let p0 = track!(selector.select(&mut rng, &self.parent_population))?;
         let p1 = track!(selector.select(&mut rng, &self.parent_population))?;
+        let parent_values = [p0.value.clone(), p1.value.clone()];
 
         let cross_over = self.strategy.cross_over_mut();
         let mut c0 = p0.param.clone();
@@ -423,116 +1722,37 @@ where
         track!(mutator.mutate(&mut rng, &self.param_domain, &mut c0))?;
         track!(mutator.mutate(&mut rng, &self.param_domain, &mut c1))?;
 
-        self.eval_queue.push_back(track!(Obs::new(&mut idg, c0))?);
-        self.eval_queue.push_back(track!(Obs::new(&mut idg, c1))?);
-        Ok(())
-    }
-
-    #[allow(clippy::type_complexity)]
-    fn fast_non_dominated_sort(
-        &self,
-        mut population: Vec<Obs<P::Point, Vec<f64>>>,
-    ) -> Result<Vec<Vec<Obs<P::Point, Vec<f64>>>>> {
-        let mut dominated_count = HashMap::new();
-        let mut dominates_list = HashMap::new();
-
-        for p in population.iter() {
-            let mut sp = HashSet::new();
-            let mut np = 0;
-
-            for q in population.iter() {
-                if track!(dominates(p, q))? {
-                    sp.insert(q.id);
-                } else if track!(dominates(q, p))? {
-                    np += 1;
-                }
-            }
-            dominated_count.insert(p.id, np);
-            dominates_list.insert(p.id, sp);
-        }
-
-        let mut population_per_rank = Vec::new();
-        while !population.is_empty() {
-            let mut non_dominated_population = Vec::new();
-            let mut i = 0;
-            while i < population.len() {
-                if dominated_count[&population[i].id] == 0 {
-                    non_dominated_population.push(population.swap_remove(i));
-                } else {
-                    i += 1;
-                }
-            }
-
-            for p in &non_dominated_population {
-                for q in &dominates_list[&p.id] {
-                    let nq = track_assert_some!(dominated_count.get_mut(q), ErrorKind::Bug);
-                    *nq -= 1;
-                }
-            }
-
-            track_assert!(!non_dominated_population.is_empty(), ErrorKind::Bug);
-            population_per_rank.push(non_dominated_population);
-        }
-
-        Ok(population_per_rank)
-    }
-
-    fn crowding_distance_sort(&self, population: &mut [Obs<P::Point, Vec<f64>>]) {
-        let l = population.len();
-        let mut distances = HashMap::new();
-        for i in 0..population[0].value.len() {
-            population.sort_by_key(|x| OrderedFloat(x.value[i]));
-
-            distances.insert(population[0].id, INFINITY);
-            distances.insert(population[l - 1].id, INFINITY);
-            let min = population[0].value[i];
-            let max = population[l - 1].value[i];
-            let width = max - min;
-
-            for xs in population.windows(3) {
-                let d = distances.entry(xs[1].id).or_insert(0.0);
-                *d += (xs[2].value[i] - xs[0].value[i]) / width;
-            }
+        let c0 = track!(self.dedup(&mut rng, c0))?;
+        let c0 = track!(Obs::new(&mut idg, c0))?;
+        let c1 = track!(self.dedup(&mut rng, c1))?;
+        let c1 = track!(Obs::new(&mut idg, c1))?;
+        let ids = [c0.id, c1.id];
+        self.strategy.offspring_created(&ids);
+        for id in ids {
+            self.parent_values.insert(id, parent_values.clone());
         }
-
-        population.sort_by_key(|x| OrderedFloat(distances[&x.id]));
-        population.reverse();
+        self.eval_queue.push_back(c0);
+        self.eval_queue.push_back(c1);
+        Ok(())
     }
 }
 
 impl<P, S> Optimizer for Nsga2Optimizer<P, S>
 where
     P: Domain,
-    P::Point: Clone,
+    P::Point: Clone + PartialEq,
     S: Strategy<P>,
 {
     type Param = P::Point;
     type Value = Vec<f64>;
 
-    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+    fn ask<R: Rng, G: IdGen>(&mut self, mut rng: R, idg: G) -> Result<Obs<Self::Param>> {
         if let Some(obs) = self.eval_queue.pop_front() {
             return Ok(obs);
         }
 
         if self.current_population.len() >= self.population_size {
-            let population = self
-                .parent_population
-                .drain(..)
-                .chain(self.current_population.drain(..))
-                .collect::<Vec<_>>();
-            let population_per_rank = track!(self.fast_non_dominated_sort(population))?;
-
-            for mut population in population_per_rank {
-                if self.parent_population.len() + population.len() < self.population_size {
-                    self.parent_population.extend(population);
-                } else {
-                    let n = self.population_size - self.parent_population.len();
-                    self.crowding_distance_sort(&mut population[..]);
-                    self.parent_population
-                        .extend(population.into_iter().take(n));
-                    break;
-                }
-            }
+            track!(self.select_survivors(&mut rng))?;
         }
 
         if self.parent_population.is_empty() {
@@ -547,9 +1767,29 @@ where
     }
 
     fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        track!(self.check_told(&obs, self.objectives()))?;
+        track!(self.record_offspring(&obs))?;
         self.current_population.push(obs);
         Ok(())
     }
+
+    /// Inserts all the observations into the current population at once.
+    ///
+    /// Unlike the default implementation, the parameters and the numbers of the objectives of all the observations
+    /// are validated before any of them is inserted, so the optimizer is left unchanged if an error is returned.
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        let objectives = self
+            .objectives()
+            .or_else(|| obss.first().map(|obs| obs.value.len()));
+        for obs in &obss {
+            track!(self.check_told(obs, objectives))?;
+        }
+        for obs in &obss {
+            track!(self.record_offspring(obs); obs.id)?;
+        }
+        self.current_population.extend(obss);
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -558,6 +1798,8 @@ mod tests {
     use crate::domains::DiscreteDomain;
     use crate::generators::SerialIdGenerator;
     use rand;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
     use trackable::result::TestResult;
 
     #[test]
@@ -572,6 +1814,374 @@ mod tests {
         let obs = track!(opt.ask(&mut rng, &mut idg))?;
         track!(opt.tell(obs.map_value(|()| vec![1.0])))?;
 
+        let domain = track!(SteppedDomain::new(0.0, 1.0, 0.25))?;
+        let mut mutation = track!(StepMutation::new(1.0, 1))?;
+        let mut p = 0.5;
+        track!(mutation.mutate(&mut rng, &domain, &mut p))?;
+        assert!(p == 0.25 || p == 0.75);
+        let mut p = 0.3;
+        assert!(mutation.mutate(&mut rng, &domain, &mut p).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn gaussian_mutation_works() -> TestResult {
+        assert!(GaussianMutation::new(1.1, 0.1).is_err());
+        assert!(GaussianMutation::new(1.0, 0.0).is_err());
+        assert!(GaussianMutation::new(1.0, 2.0).is_err());
+
+        let mut rng = rand::thread_rng();
+        let domain = track!(ContinuousDomain::new(0.0, 10.0))?;
+        let mut mutation = track!(GaussianMutation::new(1.0, 0.1))?;
+        let mut p0 = 5.0;
+        let mut p1 = 9.999;
+        track!(mutation.mutate(&mut rng, &domain, &mut p0))?;
+        track!(mutation.mutate(&mut rng, &domain, &mut p1))?;
+        assert_ne!(p0, 5.0);
+        assert!(domain.contains(&p1));
+
+        let ids = [ObsId::new(0), ObsId::new(1)];
+        Mutate::<ContinuousDomain>::offspring_created(&mut mutation, &ids);
+        Mutate::<ContinuousDomain>::offspring_evaluated(&mut mutation, ids[0], true);
+        assert!((mutation.sigma() - 0.1 * f64::exp(1.0 / 3.0)).abs() < 1e-12);
+        Mutate::<ContinuousDomain>::offspring_evaluated(&mut mutation, ids[1], false);
+        assert!((mutation.sigma() - 0.1 * f64::exp(0.25)).abs() < 1e-12);
+
+        track!(mutation.set_sigma_range(0.01, 0.05))?;
+        assert_eq!(mutation.sigma(), 0.05);
+
+        let domain = VecDomain(vec![domain.clone(), domain.clone(), domain]);
+        let mut mutation = track!(GaussianMutationVec::new(0.5, 0.1))?;
+        let mut mutated = Vec::new();
+        for i in 0..10 {
+            let mut ps = vec![5.0; 3];
+            track!(mutation.mutate(&mut rng, &domain, &mut ps))?;
+            mutated.push(ps.iter().map(|&p| p != 5.0).collect::<Vec<_>>());
+            Mutate::<VecDomain<_>>::offspring_created(&mut mutation, &[ObsId::new(i)]);
+            Mutate::<VecDomain<_>>::offspring_evaluated(&mut mutation, ObsId::new(i), false);
+        }
+        for (d, &sigma) in mutation.sigmas().iter().enumerate() {
+            let failures = mutated.iter().filter(|m| m[d]).count() as i32;
+            let expected = 0.1 * f64::exp(-1.0 / 12.0).powi(failures);
+            assert!((sigma - expected).abs() < 1e-12, "{} {}", sigma, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn nsga2_is_independent_of_tell_order() -> TestResult {
+        let run = |reverse: bool| -> Result<Vec<(ObsId, u64)>> {
+            let param_domain = track!(DiscreteDomain::new(20))?;
+            let mut opt = track!(Nsga2Optimizer::new(
+                param_domain,
+                6,
+                Nsga2Strategy::default()
+            ))?;
+            let mut rng = StdRng::seed_from_u64(0);
+            let mut idg = SerialIdGenerator::new();
+            for _ in 0..5 {
+                let mut batch = (0..6)
+                    .map(|_| track!(opt.ask(&mut rng, &mut idg)))
+                    .collect::<Result<Vec<_>>>()?;
+                if reverse {
+                    batch.reverse();
+                }
+                for obs in batch {
+                    // Many ties on purpose.
+                    let x = (obs.param / 5) as f64;
+                    track!(opt.tell(obs.map_value(|()| vec![x, 4.0 - x])))?;
+                }
+            }
+            Ok(opt
+                .parent_population()
+                .iter()
+                .map(|x| (x.id, x.param))
+                .collect())
+        };
+        assert_eq!(track!(run(false))?, track!(run(true))?);
+        Ok(())
+    }
+
+    #[test]
+    fn nsga2_tell_batch_works() -> TestResult {
+        let param_domain = track!(DiscreteDomain::new(100))?;
+        let mut opt = track!(Nsga2Optimizer::new(
+            param_domain,
+            10,
+            Nsga2Strategy::default()
+        ))?;
+        let mut idg = SerialIdGenerator::new();
+        let history = (0..50)
+            .map(|x| {
+                let obs = track!(Obs::new(&mut idg, x))?;
+                Ok(obs.map_value(|()| vec![x as f64, 100.0 - x as f64]))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut invalid = history.clone();
+        invalid.push(track!(Obs::new(&mut idg, 100))?.map_value(|()| vec![0.0, 0.0]));
+        assert!(opt.tell_batch(invalid).is_err());
+        assert!(opt.current_population.is_empty());
+
+        let mut invalid = history.clone();
+        invalid.push(track!(Obs::new(&mut idg, 0))?.map_value(|()| vec![0.0]));
+        assert!(opt.tell_batch(invalid).is_err());
+        assert!(opt.current_population.is_empty());
+
+        track!(opt.tell_batch(history))?;
+        assert_eq!(opt.current_population.len(), 50);
+
+        let mut rng = rand::thread_rng();
+        track!(opt.ask(&mut rng, &mut idg))?;
+        assert_eq!(opt.generations(), 1);
+        assert_eq!(opt.parent_population().len(), 10);
+        Ok(())
+    }
+
+    #[test]
+    fn nsga2_elitism_and_dedup_works() -> TestResult {
+        let param_domain = track!(DiscreteDomain::new(1000))?;
+        let mut opt = track!(Nsga2Optimizer::new(
+            param_domain,
+            10,
+            Nsga2Strategy::default()
+        ))?;
+        track!(opt.set_elite_count(5))?;
+        assert!(opt.set_elite_count(11).is_err());
+        opt.set_deduplicate(true);
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        for _ in 0..200 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            assert!(!opt.parent_population.iter().any(|x| x.param == obs.param));
+
+            let x = obs.param as f64;
+            track!(opt.tell(obs.map_value(|()| vec![x, 1000.0 - x])))?;
+        }
+        assert_eq!(opt.parent_population.len(), 10);
+
+        Ok(())
+    }
+
+    #[test]
+    fn nsga2_generation_stats_works() -> TestResult {
+        let param_domain = track!(DiscreteDomain::new(1000))?;
+        let mut opt = track!(Nsga2Optimizer::new(
+            param_domain,
+            10,
+            Nsga2Strategy::default()
+        ))?;
+        opt.set_reference_point(vec![1000.0, 1000.0]);
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        assert!(opt.generation_stats().is_none());
+        for _ in 0..21 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let x = obs.param as f64;
+            track!(opt.tell(obs.map_value(|()| vec![x, 1000.0 - x])))?;
+        }
+
+        let stats = track_assert_some!(opt.generation_stats(), ErrorKind::Other);
+        assert_eq!(stats.generation, 2);
+        assert_eq!(stats.front_sizes, [10]);
+        assert!(stats.hypervolume.is_some_and(|hv| hv > 0.0));
+        assert_eq!(stats.crowding_distances.len(), 10);
+        assert_eq!(stats.crowding_distances[0], f64::INFINITY);
+        Ok(())
+    }
+
+    #[test]
+    fn adaptive_operators_work() -> TestResult {
+        let param_domain = track!(DiscreteDomain::new(1000))?;
+        let mutator = track!(AdaptiveOperators::new(vec![
+            track!(Replace::new(0.0))?,
+            track!(Replace::new(1.0))?
+        ]))?;
+        let strategy = Nsga2Strategy::new(
+            RandomGenerator,
+            TournamentSelector::default(),
+            Exchange::default(),
+            mutator,
+        );
+        let mut opt = track!(Nsga2Optimizer::new(param_domain, 10, strategy))?;
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut idg = SerialIdGenerator::new();
+        for _ in 0..100 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let x = obs.param as f64;
+            track!(opt.tell(obs.map_value(|()| vec![x])))?;
+        }
+
+        let probabilities = opt.strategy.mutator().probabilities();
+        assert_ne!(probabilities[0], 0.5);
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(opt.strategy.mutator().assigned.is_empty());
+        assert!(opt.parent_values.is_empty());
+
+        // Only the offspring mutated by the second operator improve on their parents.
+        let domain = track!(DiscreteDomain::new(10))?;
+        let mut mutator = track!(AdaptiveOperators::new(vec![
+            track!(Replace::new(0.0))?,
+            track!(Replace::new(1.0))?
+        ]))?;
+        for i in 0..100 {
+            let mut p = 0;
+            track!(Mutate::mutate(&mut mutator, &mut rng, &domain, &mut p))?;
+            let improved = mutator.unassigned == [1];
+            let id = ObsId::new(i);
+            Mutate::<DiscreteDomain>::offspring_created(&mut mutator, &[id]);
+            Mutate::<DiscreteDomain>::offspring_evaluated(&mut mutator, id, improved);
+        }
+        assert!((mutator.probabilities()[1] - 0.9).abs() < 1e-3);
+        assert!(mutator.assigned.is_empty());
+
+        assert!(AdaptiveOperators::<Replace>::new(Vec::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn nsga2_bit_string_works() -> TestResult {
+        let param_domain = track!(BitStringDomain::new(16))?;
+        let strategy = Nsga2Strategy::new(
+            RandomGenerator,
+            TournamentSelector::default(),
+            OnePointCrossOver,
+            BitFlip::default(),
+        );
+        let mut opt = track!(Nsga2Optimizer::new(param_domain, 10, strategy))?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        for _ in 0..100 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let ones = obs.param.iter().filter(|b| **b).count() as f64;
+            track!(opt.tell(obs.map_value(|()| vec![ones, 16.0 - ones])))?;
+        }
+
+        let mut p0 = vec![0, 1, 2, 3];
+        let mut p1 = vec![4, 5, 6, 7];
+        track!(CrossOver::<VecDomain<DiscreteDomain>>::cross_over(
+            &mut UniformCrossOver::default(),
+            &mut rng,
+            &mut p0,
+            &mut p1
+        ))?;
+        for (i, (a, b)) in p0.iter().zip(p1.iter()).enumerate() {
+            let i = i as u64;
+            assert_eq!(a % 4, i);
+            assert_eq!(a + b, 2 * i + 4);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn permutation_operators_work() -> TestResult {
+        let domain = track!(PermutationDomain::new(8))?;
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let mut p0 = domain.sample(&mut rng);
+            let mut p1 = domain.sample(&mut rng);
+            track!(PartiallyMappedCrossOver.cross_over(&mut rng, &mut p0, &mut p1))?;
+            assert!(domain.contains(&p0) && domain.contains(&p1));
+
+            track!(OrderCrossOver.cross_over(&mut rng, &mut p0, &mut p1))?;
+            assert!(domain.contains(&p0) && domain.contains(&p1));
+
+            track!(SwapMutation::new(1.0)?.mutate(&mut rng, &domain, &mut p0))?;
+            track!(InsertionMutation::new(1.0)?.mutate(&mut rng, &domain, &mut p0))?;
+            track!(InversionMutation::new(1.0)?.mutate(&mut rng, &domain, &mut p0))?;
+            assert!(domain.contains(&p0));
+        }
+
+        for (mut p0, mut p1) in [
+            (vec![0, 0], vec![1, 0]),
+            (vec![1, 0], vec![0, 0]),
+            (vec![0, 1, 2], vec![1, 0]),
+        ] {
+            assert!(PartiallyMappedCrossOver
+                .cross_over(&mut rng, &mut p0, &mut p1)
+                .is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn tree_operators_work() -> TestResult {
+        let domain = track!(TreeDomain::new(vec![0, 0, 1, 2, 2], 4))?;
+        let mut crossover = SubtreeCrossOver::new(&domain);
+        let mut mutation = track!(PointMutation::new(0.5))?;
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let mut p0 = domain.sample(&mut rng);
+            let mut p1 = domain.sample(&mut rng);
+            let size = p0.size() + p1.size();
+            track!(crossover.cross_over(&mut rng, &mut p0, &mut p1))?;
+            assert!(domain.contains(&p0) && domain.contains(&p1));
+            assert_eq!(p0.size() + p1.size(), size);
+
+            let shape = p0.depth();
+            track!(mutation.mutate(&mut rng, &domain, &mut p0))?;
+            assert!(domain.contains(&p0));
+            assert_eq!(p0.depth(), shape);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn replacements_work() -> TestResult {
+        let mut rng = rand::thread_rng();
+        let front = || {
+            [(0, 0.0, 3.0), (1, 1.0, 2.0), (2, 1.1, 1.9), (3, 3.0, 0.0)]
+                .iter()
+                .map(|&(id, a, b)| Obs {
+                    id: ObsId::new(id),
+                    param: 0,
+                    value: vec![a, b],
+                })
+                .collect::<Vec<_>>()
+        };
+        let ids =
+            |front: &[Obs<u64, Vec<f64>>]| front.iter().map(|x| x.id.get()).collect::<Vec<_>>();
+
+        let mut xs = front();
+        track!(Replacement::<DiscreteDomain>::prioritize(
+            &mut CrowdingDistance,
+            &mut rng,
+            &mut xs
+        ))?;
+        assert_eq!(&ids(&xs)[2..], [2, 1]);
+
+        let mut xs = front();
+        let mut r = track!(EpsilonDominance::new(vec![2.0, 2.0]))?;
+        track!(Replacement::<DiscreteDomain>::prioritize(
+            &mut r, &mut rng, &mut xs
+        ))?;
+        assert_eq!(ids(&xs)[3], 1);
+
+        let mut xs = front();
+        track!(Replacement::<DiscreteDomain>::prioritize(
+            &mut AgeBased,
+            &mut rng,
+            &mut xs
+        ))?;
+        assert_eq!(ids(&xs), [3, 2, 1, 0]);
+
+        // Degenerated front (the width of the first objective is zero).
+        let mut xs = front();
+        for x in &mut xs {
+            x.value[0] = 1.0;
+        }
+        track!(Replacement::<DiscreteDomain>::prioritize(
+            &mut CrowdingDistance,
+            &mut rng,
+            &mut xs
+        ))?;
+        assert!(xs.iter().all(|x| x.value[0] == 1.0));
+
         Ok(())
     }
 }
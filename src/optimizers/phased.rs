@@ -0,0 +1,182 @@
+//! Combinator for switching optimizers sequentially.
+//!
+//! A common pattern is to explore the search space by one optimizer (e.g., `RandomOptimizer` with `Sobol`)
+//! and then exploit the results by another one (e.g., `NelderMeadOptimizer` starting from the best parameter).
+//! `PhasedOptimizer` automates the switching and the transfer of the observations.
+use crate::provenance::ProvenanceRegistry;
+use crate::{IdGen, Obs, ObsId, Optimizer, Result};
+use rand::Rng;
+use std::collections::HashSet;
+use std::fmt;
+
+/// An optimizer that uses the first optimizer for the first `n` observations and then switches to the second one.
+///
+/// The second optimizer is built by the given factory function from the observations of the first phase
+/// (e.g., by selecting the best parameter as the starting point, or by telling them to the new optimizer).
+///
+/// The first optimizer is used until `n` of its observations have been told.
+/// So, if evaluations are run in parallel, it can be asked more than `n` times.
+/// Observations of the first optimizer told after switching are still routed to it.
+pub struct PhasedOptimizer<A: Optimizer, B, F> {
+    first: A,
+    second: Option<B>,
+    factory: Option<F>,
+    switch_after: usize,
+    history: Vec<Obs<A::Param, A::Value>>,
+    first_ids: HashSet<ObsId>,
+    provenance: Option<(ProvenanceRegistry, String)>,
+}
+impl<A, B, F> PhasedOptimizer<A, B, F>
+where
+    A: Optimizer,
+    A::Param: Clone,
+    A::Value: Clone,
+    B: Optimizer<Param = A::Param, Value = A::Value>,
+    F: FnOnce(&[Obs<A::Param, A::Value>]) -> Result<B>,
+{
+    /// Makes a new `PhasedOptimizer` instance.
+    ///
+    /// `factory` is invoked with the observations of the first phase after `switch_after` observations have been told.
+    pub fn new(first: A, switch_after: usize, factory: F) -> Self {
+        Self {
+            first,
+            second: None,
+            factory: Some(factory),
+            switch_after,
+            history: Vec::new(),
+            first_ids: HashSet::new(),
+            provenance: None,
+        }
+    }
+
+    /// Makes the optimizer record the phase of each asked observation into `registry`.
+    ///
+    /// The label of an observation is `"{name}.first"` or `"{name}.second"`.
+    pub fn set_provenance(&mut self, registry: ProvenanceRegistry, name: &str) {
+        self.provenance = Some((registry, name.to_owned()));
+    }
+
+    fn record_provenance(&self, id: ObsId, phase: &str) -> Result<()> {
+        if let Some((registry, name)) = &self.provenance {
+            track!(registry.record(id, format!("{}.{}", name, phase)))?;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the optimizer has switched to the second phase, otherwise `false`.
+    pub fn is_switched(&self) -> bool {
+        self.second.is_some()
+    }
+
+    /// Returns the observations told in the first phase.
+    pub fn history(&self) -> &[Obs<A::Param, A::Value>] {
+        &self.history
+    }
+
+    /// Returns a reference to the first optimizer.
+    pub fn first(&self) -> &A {
+        &self.first
+    }
+
+    /// Returns a reference to the second optimizer if the optimizer has switched to the second phase.
+    pub fn second(&self) -> Option<&B> {
+        self.second.as_ref()
+    }
+
+    fn switch_if_needed(&mut self) -> Result<()> {
+        if self.history.len() < self.switch_after {
+            return Ok(());
+        }
+        if let Some(factory) = self.factory.take() {
+            self.second = Some(track!(factory(&self.history))?);
+        }
+        Ok(())
+    }
+}
+impl<A, B, F> Optimizer for PhasedOptimizer<A, B, F>
+where
+    A: Optimizer,
+    A::Param: Clone,
+    A::Value: Clone,
+    B: Optimizer<Param = A::Param, Value = A::Value>,
+    F: FnOnce(&[Obs<A::Param, A::Value>]) -> Result<B>,
+{
+    type Param = A::Param;
+    type Value = A::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        track!(self.switch_if_needed())?;
+        if let Some(second) = &mut self.second {
+            let obs = track!(second.ask(rng, idg))?;
+            track!(self.record_provenance(obs.id, "second"))?;
+            return Ok(obs);
+        }
+        let obs = track!(self.first.ask(rng, idg))?;
+        self.first_ids.insert(obs.id);
+        track!(self.record_provenance(obs.id, "first"))?;
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        if self.first_ids.remove(&obs.id) {
+            if self.second.is_none() {
+                self.history.push(obs.clone());
+            }
+            return track!(self.first.tell(obs));
+        }
+        match &mut self.second {
+            Some(second) => track!(second.tell(obs)),
+            None => track!(self.first.tell(obs)),
+        }
+    }
+}
+impl<A, B, F> fmt::Debug for PhasedOptimizer<A, B, F>
+where
+    A: Optimizer + fmt::Debug,
+    A::Param: fmt::Debug,
+    A::Value: fmt::Debug,
+    B: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PhasedOptimizer")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .field("switch_after", &self.switch_after)
+            .field("history", &self.history)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::{ContinuousDomain, VecDomain};
+    use crate::generators::SerialIdGenerator;
+    use crate::init::Sobol;
+    use crate::optimizers::nelder_mead::NelderMeadOptimizer;
+    use crate::optimizers::random::RandomOptimizer;
+    use ordered_float::OrderedFloat;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn phased_works() -> TestResult {
+        let domain = vec![track!(ContinuousDomain::new(-5.0, 5.0))?; 2];
+        let first = RandomOptimizer::with_generator(VecDomain(domain.clone()), Sobol::new());
+        let mut opt = PhasedOptimizer::new(first, 8, |history: &[Obs<_, _>]| {
+            let best = history.iter().min_by_key(|o| o.value);
+            let best = track_assert_some!(best, crate::ErrorKind::Bug);
+            track!(NelderMeadOptimizer::with_initial_point(domain, &best.param))
+        });
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        for i in 0..30 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            assert_eq!(opt.is_switched(), i >= 8);
+            let value = obs.param[0].powi(2) + (obs.param[1] - 1.0).powi(2);
+            track!(opt.tell(obs.map_value(|()| OrderedFloat(value))))?;
+        }
+        assert_eq!(opt.history().len(), 8);
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+//! Portfolio of optimizers.
+//!
+//! # References
+//!
+//! - [Finite-time Analysis of the Multiarmed Bandit Problem](https://link.springer.com/article/10.1023/A:1013689704352)
+use crate::provenance::ProvenanceRegistry;
+use crate::{ErrorKind, IdGen, Obs, ObsId, Optimizer, Result, SharedOptimizer};
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// An optimizer that allocates asks among several optimizers.
+///
+/// The optimizer used for each ask is selected by the [UCB1] rule,
+/// where the reward of an optimizer is the number of its observations that improved the best value so far.
+///
+/// Each told observation is routed to the optimizer that generated it.
+///
+/// Note that all the optimizers must have the same type.
+/// If you want to combine heterogeneous optimizers, please box them as `Box<dyn DynOptimizer<..>>`.
+///
+/// [UCB1]: https://link.springer.com/article/10.1023/A:1013689704352
+#[derive(Debug)]
+pub struct PortfolioOptimizer<O: Optimizer> {
+    arms: Vec<Arm<O>>,
+    owners: HashMap<ObsId, usize>,
+    best: Option<O::Value>,
+    exploration_factor: f64,
+    provenance: Option<(ProvenanceRegistry, String)>,
+}
+impl<O> PortfolioOptimizer<O>
+where
+    O: Optimizer,
+    O::Value: Ord + Clone,
+{
+    /// Makes a new `PortfolioOptimizer` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `optimizers` is empty, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(optimizers: Vec<O>) -> Result<Self> {
+        track_assert!(!optimizers.is_empty(), ErrorKind::InvalidInput);
+        Ok(Self {
+            arms: optimizers.into_iter().map(Arm::new).collect(),
+            owners: HashMap::new(),
+            best: None,
+            exploration_factor: 2f64.sqrt(),
+            provenance: None,
+        })
+    }
+
+    /// Makes the optimizer record the generator of each asked observation into `registry`.
+    ///
+    /// The label of an observation generated by the `i`-th optimizer is `"{name}[{i}]"`.
+    pub fn set_provenance(&mut self, registry: ProvenanceRegistry, name: &str) {
+        self.provenance = Some((registry, name.to_owned()));
+    }
+
+    /// Sets the exploration factor of the UCB1 rule (the default value is `sqrt(2)`).
+    ///
+    /// # Errors
+    ///
+    /// If `factor` is negative or not finite, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_exploration_factor(&mut self, factor: f64) -> Result<()> {
+        track_assert!(
+            factor.is_finite() && factor >= 0.0,
+            ErrorKind::InvalidInput; factor
+        );
+        self.exploration_factor = factor;
+        Ok(())
+    }
+
+    /// Returns the number of asks allocated to each optimizer.
+    pub fn asks(&self) -> Vec<usize> {
+        self.arms.iter().map(|a| a.asks).collect()
+    }
+
+    /// Returns the number of improvements observed by each optimizer.
+    pub fn improvements(&self) -> Vec<usize> {
+        self.arms.iter().map(|a| a.improvements).collect()
+    }
+
+    /// Returns the index of the optimizer that generated the given observation.
+    pub fn owner(&self, id: ObsId) -> Option<usize> {
+        self.owners.get(&id).copied()
+    }
+
+    /// Returns a reference to the `index`-th optimizer.
+    pub fn get(&self, index: usize) -> Option<&O> {
+        self.arms.get(index).map(|a| &a.optimizer)
+    }
+
+    /// Returns a mutable reference to the `index`-th optimizer.
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut O> {
+        self.arms.get_mut(index).map(|a| &mut a.optimizer)
+    }
+
+    /// Consumes the `PortfolioOptimizer`, returning the underlying optimizers.
+    pub fn into_inner(self) -> Vec<O> {
+        self.arms.into_iter().map(|a| a.optimizer).collect()
+    }
+
+    fn select_arm(&self) -> usize {
+        let stats = self
+            .arms
+            .iter()
+            .map(|a| (a.asks, a.improvements))
+            .collect::<Vec<_>>();
+        select_arm(&stats, self.exploration_factor)
+    }
+}
+impl<O> Optimizer for PortfolioOptimizer<O>
+where
+    O: Optimizer,
+    O::Value: Ord + Clone,
+{
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let i = self.select_arm();
+        let arm = &mut self.arms[i];
+        let obs = track!(arm.optimizer.ask(rng, idg))?;
+        arm.asks += 1;
+        self.owners.insert(obs.id, i);
+        if let Some((registry, name)) = &self.provenance {
+            track!(registry.record(obs.id, format!("{}[{}]", name, i)))?;
+        }
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let i = track_assert_some!(
+            self.owners.get(&obs.id).copied(),
+            ErrorKind::UnknownObservation; obs.id
+        );
+
+        if self.best.as_ref().is_none_or(|best| obs.value < *best) {
+            self.best = Some(obs.value.clone());
+            self.arms[i].improvements += 1;
+        }
+        track!(self.arms[i].optimizer.tell(obs))
+    }
+}
+
+/// A `PortfolioOptimizer` variant that implements `SharedOptimizer`.
+///
+/// The underlying optimizers must implement `SharedOptimizer` (e.g., `RandomOptimizer` or `Mutex<O>`),
+/// and the bandit statistics are guarded by a lock held only while selecting an optimizer or updating the statistics.
+/// So asks and tells to different optimizers can run concurrently.
+#[derive(Debug)]
+pub struct SharedPortfolioOptimizer<O: SharedOptimizer> {
+    optimizers: Vec<O>,
+    state: Mutex<SharedState<O::Value>>,
+    exploration_factor: f64,
+}
+impl<O> SharedPortfolioOptimizer<O>
+where
+    O: SharedOptimizer,
+    O::Value: Ord + Clone,
+{
+    /// Makes a new `SharedPortfolioOptimizer` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `optimizers` is empty, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(optimizers: Vec<O>) -> Result<Self> {
+        track_assert!(!optimizers.is_empty(), ErrorKind::InvalidInput);
+        Ok(Self {
+            state: Mutex::new(SharedState {
+                stats: vec![(0, 0); optimizers.len()],
+                owners: HashMap::new(),
+                best: None,
+            }),
+            optimizers,
+            exploration_factor: 2f64.sqrt(),
+        })
+    }
+
+    /// Sets the exploration factor of the UCB1 rule (the default value is `sqrt(2)`).
+    ///
+    /// # Errors
+    ///
+    /// If `factor` is negative or not finite, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_exploration_factor(&mut self, factor: f64) -> Result<()> {
+        track_assert!(
+            factor.is_finite() && factor >= 0.0,
+            ErrorKind::InvalidInput; factor
+        );
+        self.exploration_factor = factor;
+        Ok(())
+    }
+
+    /// Returns the number of asks allocated to each optimizer.
+    pub fn asks(&self) -> Result<Vec<usize>> {
+        let state = track!(crate::lock(&self.state))?;
+        Ok(state.stats.iter().map(|s| s.0).collect())
+    }
+
+    /// Returns the number of improvements observed by each optimizer.
+    pub fn improvements(&self) -> Result<Vec<usize>> {
+        let state = track!(crate::lock(&self.state))?;
+        Ok(state.stats.iter().map(|s| s.1).collect())
+    }
+
+    /// Returns the underlying optimizers.
+    pub fn optimizers(&self) -> &[O] {
+        &self.optimizers
+    }
+
+    /// Consumes the `SharedPortfolioOptimizer`, returning the underlying optimizers.
+    pub fn into_inner(self) -> Vec<O> {
+        self.optimizers
+    }
+}
+impl<O> SharedOptimizer for SharedPortfolioOptimizer<O>
+where
+    O: SharedOptimizer,
+    O::Value: Ord + Clone,
+{
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let i = {
+            let mut state = track!(crate::lock(&self.state))?;
+            let i = select_arm(&state.stats, self.exploration_factor);
+            state.stats[i].0 += 1;
+            i
+        };
+        let obs = track!(self.optimizers[i].ask(rng, idg))?;
+        track!(crate::lock(&self.state))?.owners.insert(obs.id, i);
+        Ok(obs)
+    }
+
+    fn tell(&self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let i = {
+            let mut state = track!(crate::lock(&self.state))?;
+            let i = track_assert_some!(
+                state.owners.get(&obs.id).copied(),
+                ErrorKind::UnknownObservation; obs.id
+            );
+            if state.best.as_ref().is_none_or(|best| obs.value < *best) {
+                state.best = Some(obs.value.clone());
+                state.stats[i].1 += 1;
+            }
+            i
+        };
+        track!(self.optimizers[i].tell(obs))
+    }
+}
+
+#[derive(Debug)]
+struct SharedState<V> {
+    stats: Vec<(usize, usize)>,
+    owners: HashMap<ObsId, usize>,
+    best: Option<V>,
+}
+
+/// Selects an arm by the UCB1 rule from the `(asks, improvements)` pairs of the arms.
+fn select_arm(stats: &[(usize, usize)], exploration_factor: f64) -> usize {
+    if let Some(i) = stats.iter().position(|s| s.0 == 0) {
+        return i;
+    }
+
+    let total = stats.iter().map(|s| s.0).sum::<usize>() as f64;
+    let mut best = (0, f64::NEG_INFINITY);
+    for (i, &(asks, improvements)) in stats.iter().enumerate() {
+        let n = asks as f64;
+        let score = improvements as f64 / n + exploration_factor * (total.ln() / n).sqrt();
+        if score > best.1 {
+            best = (i, score);
+        }
+    }
+    best.0
+}
+
+#[derive(Debug)]
+struct Arm<O> {
+    optimizer: O,
+    asks: usize,
+    improvements: usize,
+}
+impl<O> Arm<O> {
+    fn new(optimizer: O) -> Self {
+        Self {
+            optimizer,
+            asks: 0,
+            improvements: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::{ContinuousDomain, DiscreteDomain, VecDomain};
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::nelder_mead::NelderMeadOptimizer;
+    use crate::optimizers::random::RandomOptimizer;
+    use crate::DynOptimizer;
+    use ordered_float::OrderedFloat;
+    use rand;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn portfolio_works() -> TestResult {
+        let optimizers = vec![
+            RandomOptimizer::new(track!(DiscreteDomain::new(10))?),
+            RandomOptimizer::new(track!(DiscreteDomain::new(10))?),
+        ];
+        let mut opt = track!(PortfolioOptimizer::new(optimizers))?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        for _ in 0..10 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            assert!(opt.owner(obs.id).is_some());
+            track!(opt.tell(obs.map_value(|()| 1)))?;
+        }
+        assert_eq!(opt.asks().iter().sum::<usize>(), 10);
+        assert_eq!(opt.improvements().iter().sum::<usize>(), 1);
+
+        let unknown = Obs {
+            id: ObsId::new(100),
+            param: 0,
+            value: 0,
+        };
+        assert!(opt.tell(unknown).is_err());
+
+        let optimizers = vec![
+            Mutex::new(RandomOptimizer::new(track!(DiscreteDomain::new(10))?)),
+            Mutex::new(RandomOptimizer::new(track!(DiscreteDomain::new(10))?)),
+        ];
+        let opt = std::sync::Arc::new(track!(SharedPortfolioOptimizer::new(optimizers))?);
+        let idg = Mutex::new(SerialIdGenerator::new());
+        std::thread::scope(|s| -> Result<()> {
+            let handles = (0..4)
+                .map(|_| {
+                    s.spawn(|| -> Result<()> {
+                        for _ in 0..5 {
+                            let obs = {
+                                let mut idg = track!(crate::lock(&idg))?;
+                                track!(opt.ask(rand::thread_rng(), &mut *idg))?
+                            };
+                            track!(opt.tell(obs.map_value(|()| 1)))?;
+                        }
+                        Ok(())
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                let result = handle.join().unwrap_or_else(|_| panic!("thread panicked"));
+                track!(result)?;
+            }
+            Ok(())
+        })?;
+        assert_eq!(track!(opt.asks())?.iter().sum::<usize>(), 20);
+        assert_eq!(track!(opt.improvements())?.iter().sum::<usize>(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn portfolio_dyn_works() -> TestResult {
+        type BoxOptimizer = Box<dyn DynOptimizer<Param = Vec<f64>, Value = OrderedFloat<f64>>>;
+
+        let mut rng = rand::thread_rng();
+        let domain = vec![track!(ContinuousDomain::new(-5.0, 5.0))?; 2];
+        let optimizers: Vec<BoxOptimizer> = vec![
+            Box::new(RandomOptimizer::new(VecDomain(domain.clone()))),
+            Box::new(track!(NelderMeadOptimizer::new(domain, &mut rng))?),
+        ];
+        let mut opt = track!(PortfolioOptimizer::new(optimizers))?;
+        let mut idg = SerialIdGenerator::new();
+        for _ in 0..20 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let value = obs.param.iter().map(|x| x * x).sum::<f64>();
+            track!(opt.tell(obs.map_value(|()| OrderedFloat(value))))?;
+        }
+        assert!(opt.asks().iter().all(|&n| n > 0));
+        Ok(())
+    }
+}
@@ -0,0 +1,381 @@
+//! Racing optimizer for noisy objectives.
+//!
+//! # References
+//!
+//! - [The irace package: Iterated racing for automatic algorithm configuration](https://doi.org/10.1016/j.orp.2016.09.002)
+//! - [A Racing Algorithm for Configuring Metaheuristics](https://dl.acm.org/doi/10.5555/2955491.2955494)
+use crate::{ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use rand::Rng;
+use std::collections::HashSet;
+
+/// A parameter asked by `RacingOptimizer`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trial<P> {
+    /// The configuration to be evaluated.
+    pub config: P,
+
+    /// The index of the instance (e.g., seed or cross-validation fold) on which the configuration is evaluated.
+    ///
+    /// Instances are numbered from `0` in each race.
+    pub instance: usize,
+}
+
+/// Builder of `RacingOptimizer`.
+#[derive(Debug, Clone)]
+pub struct RacingOptimizerBuilder {
+    race_size: usize,
+    min_instances: usize,
+    max_instances: usize,
+    critical_value: f64,
+}
+impl RacingOptimizerBuilder {
+    /// Makes a new `RacingOptimizerBuilder` instance with the default settings.
+    pub const fn new() -> Self {
+        Self {
+            race_size: 8,
+            min_instances: 5,
+            max_instances: 20,
+            critical_value: 2.0,
+        }
+    }
+
+    /// Sets the number of candidates in a race (including the elite of the previous race).
+    ///
+    /// # Errors
+    ///
+    /// If `size` is less than `2`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn race_size(&mut self, size: usize) -> Result<&mut Self> {
+        track_assert!(size > 1, ErrorKind::InvalidInput; size);
+        self.race_size = size;
+        Ok(self)
+    }
+
+    /// Sets the number of instances evaluated before the first statistical test.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is less than `2`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn min_instances(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 1, ErrorKind::InvalidInput; n);
+        self.min_instances = n;
+        Ok(self)
+    }
+
+    /// Sets the maximum number of instances evaluated in a race.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn max_instances(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 0, ErrorKind::InvalidInput; n);
+        self.max_instances = n;
+        Ok(self)
+    }
+
+    /// Sets the critical value of the paired t-test used to eliminate candidates.
+    ///
+    /// The default value is `2.0` which roughly corresponds to the 95% significance level.
+    ///
+    /// # Errors
+    ///
+    /// If `value` is not a positive finite number, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn critical_value(&mut self, value: f64) -> Result<&mut Self> {
+        track_assert!(value.is_finite() && value > 0.0, ErrorKind::InvalidInput; value);
+        self.critical_value = value;
+        Ok(self)
+    }
+
+    /// Builds a new `RacingOptimizer` instance.
+    ///
+    /// # Errors
+    ///
+    /// If the minimum number of instances is greater than the maximum,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn finish<O>(&self, inner: O) -> Result<RacingOptimizer<O>>
+    where
+        O: Optimizer<Value = f64>,
+    {
+        track_assert!(
+            self.min_instances <= self.max_instances,
+            ErrorKind::InvalidInput; self.min_instances, self.max_instances
+        );
+        Ok(RacingOptimizer {
+            inner,
+            candidates: Vec::new(),
+            retired: HashSet::new(),
+            elite: None,
+            builder: self.clone(),
+        })
+    }
+}
+impl Default for RacingOptimizerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Racing optimizer.
+///
+/// This optimizer repeatedly evaluates a set of candidate configurations (generated by the inner optimizer)
+/// on multiple instances, and eliminates candidates that are significantly worse than the best one
+/// according to a paired t-test.
+/// When a race is over, its winner (elite) is carried over to the next race.
+///
+/// The same observation identifier is asked (and told) once per instance.
+/// The mean value of a candidate is told to the inner optimizer when the candidate leaves a race.
+///
+/// Tells for candidates that have been eliminated are ignored.
+#[derive(Debug)]
+pub struct RacingOptimizer<O: Optimizer> {
+    inner: O,
+    candidates: Vec<Candidate<O::Param>>,
+    retired: HashSet<ObsId>,
+    elite: Option<Obs<O::Param, f64>>,
+    builder: RacingOptimizerBuilder,
+}
+impl<O> RacingOptimizer<O>
+where
+    O: Optimizer<Value = f64>,
+    O::Param: Clone,
+{
+    /// Makes a new `RacingOptimizer` instance with the default settings.
+    pub fn new(inner: O) -> Result<Self> {
+        track!(RacingOptimizerBuilder::new().finish(inner))
+    }
+
+    /// Returns the winner of the last finished race and its mean value.
+    pub fn elite(&self) -> Option<&Obs<O::Param, f64>> {
+        self.elite.as_ref()
+    }
+
+    /// Returns the number of candidates that are alive in the current race.
+    pub fn alive_candidates(&self) -> usize {
+        self.candidates.iter().filter(|c| c.alive).count()
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `RacingOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+
+    fn start_race<R: Rng, G: IdGen>(&mut self, mut rng: R, mut idg: G) -> Result<()> {
+        if let Some(elite) = self.elite.clone() {
+            self.candidates.push(Candidate::new(elite.id, elite.param));
+        }
+        while self.candidates.len() < self.builder.race_size {
+            let obs = track!(self.inner.ask(&mut rng, &mut idg))?;
+            self.candidates.push(Candidate::new(obs.id, obs.param));
+        }
+        Ok(())
+    }
+
+    fn complete_instances(&self) -> usize {
+        self.candidates
+            .iter()
+            .filter(|c| c.alive)
+            .map(|c| c.results.iter().take_while(|r| r.is_some()).count())
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn race(&mut self) -> Result<()> {
+        let n = self.complete_instances();
+        if n < self.builder.min_instances {
+            return Ok(());
+        }
+
+        let alives = (0..self.candidates.len())
+            .filter(|&i| self.candidates[i].alive)
+            .collect::<Vec<_>>();
+        let best = track_assert_some!(
+            alives.iter().copied().min_by(|&a, &b| {
+                let a = self.candidates[a].mean(n);
+                let b = self.candidates[b].mean(n);
+                a.partial_cmp(&b).unwrap_or(std::cmp::Ordering::Equal)
+            }),
+            ErrorKind::Bug
+        );
+
+        for &i in &alives {
+            if i != best && self.is_significantly_worse(i, best, n) {
+                self.candidates[i].alive = false;
+                let value = self.candidates[i].mean(n);
+                let c = &self.candidates[i];
+                track!(self.inner.tell(Obs {
+                    id: c.id,
+                    param: c.config.clone(),
+                    value,
+                }))?;
+            }
+        }
+
+        if self.alive_candidates() == 1 || n >= self.builder.max_instances {
+            for c in self.candidates.iter().filter(|c| c.alive) {
+                track!(self.inner.tell(Obs {
+                    id: c.id,
+                    param: c.config.clone(),
+                    value: c.mean(n),
+                }))?;
+            }
+
+            let winner = &self.candidates[best];
+            self.elite = Some(Obs {
+                id: winner.id,
+                param: winner.config.clone(),
+                value: winner.mean(n),
+            });
+            self.retired = self.candidates.drain(..).map(|c| c.id).collect();
+        }
+        Ok(())
+    }
+
+    fn is_significantly_worse(&self, i: usize, best: usize, n: usize) -> bool {
+        let diffs = self.candidates[i].results[..n]
+            .iter()
+            .zip(self.candidates[best].results[..n].iter())
+            .map(|(a, b)| a.unwrap_or(0.0) - b.unwrap_or(0.0))
+            .collect::<Vec<_>>();
+
+        let k = diffs.len() as f64;
+        let mean = diffs.iter().sum::<f64>() / k;
+        let var = diffs.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (k - 1.0);
+        if var == 0.0 {
+            mean > 0.0
+        } else {
+            mean / (var / k).sqrt() > self.builder.critical_value
+        }
+    }
+}
+impl<O> Optimizer for RacingOptimizer<O>
+where
+    O: Optimizer<Value = f64>,
+    O::Param: Clone,
+{
+    type Param = Trial<O::Param>;
+    type Value = f64;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        if self.candidates.is_empty() {
+            track!(self.start_race(rng, idg))?;
+        }
+
+        let max_instances = self.builder.max_instances;
+        let c = track_assert_some!(
+            self.candidates
+                .iter_mut()
+                .filter(|c| c.alive && c.asked < max_instances)
+                .min_by_key(|c| c.asked),
+            ErrorKind::Other,
+            "Waiting for the evaluations of the current race"
+        );
+        c.asked += 1;
+        Ok(Obs {
+            id: c.id,
+            param: Trial {
+                config: c.config.clone(),
+                instance: c.asked - 1,
+            },
+            value: (),
+        })
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let c = if let Some(c) = self.candidates.iter_mut().find(|c| c.id == obs.id) {
+            c
+        } else {
+            track_assert!(
+                self.retired.contains(&obs.id),
+                ErrorKind::UnknownObservation; obs.id
+            );
+            return Ok(());
+        };
+        track_assert!(
+            obs.param.instance < c.asked,
+            ErrorKind::InvalidInput; obs.id, obs.param.instance, c.asked
+        );
+        track_assert!(obs.value.is_finite(), ErrorKind::InvalidInput; obs.id, obs.value);
+        if !c.alive {
+            return Ok(());
+        }
+
+        if c.results.len() <= obs.param.instance {
+            c.results.resize(obs.param.instance + 1, None);
+        }
+        c.results[obs.param.instance] = Some(obs.value);
+        track!(self.race())
+    }
+}
+
+#[derive(Debug)]
+struct Candidate<P> {
+    id: ObsId,
+    config: P,
+    asked: usize,
+    results: Vec<Option<f64>>,
+    alive: bool,
+}
+impl<P> Candidate<P> {
+    fn new(id: ObsId, config: P) -> Self {
+        Self {
+            id,
+            config,
+            asked: 0,
+            results: Vec::new(),
+            alive: true,
+        }
+    }
+
+    fn mean(&self, n: usize) -> f64 {
+        self.results[..n]
+            .iter()
+            .map(|r| r.unwrap_or(0.0))
+            .sum::<f64>()
+            / n as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::ContinuousDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::random::RandomOptimizer;
+    use rand;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn racing_works() -> TestResult {
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut opt = track!(RacingOptimizerBuilder::new()
+            .race_size(4)?
+            .min_instances(3)?
+            .max_instances(10)?
+            .finish(inner))?;
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        for _ in 0..100 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let noise = if obs.param.instance % 2 == 0 {
+                0.01
+            } else {
+                -0.01
+            };
+            let value = obs.param.config + noise;
+            track!(opt.tell(obs.map_value(|()| value)))?;
+        }
+        assert!(opt.elite().is_some());
+
+        Ok(())
+    }
+}
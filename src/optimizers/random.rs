@@ -1,5 +1,6 @@
 //! Random optimizer.
-use crate::{Domain, IdGen, Obs, Optimizer, Result};
+use crate::init::{Generate, RandomGenerator};
+use crate::{Domain, ErrorContext, ErrorKind, IdGen, Obs, Optimizer, Result, SharedOptimizer};
 use rand::distributions::Distribution;
 use rand::Rng;
 use std::marker::PhantomData;
@@ -7,9 +8,14 @@ use std::marker::PhantomData;
 /// Random optimizer.
 ///
 /// This optimizer samples parameters at random from the given domain.
+/// The sampling can be replaced by any `Generate` implementation (e.g., `LatinHypercube` or `Sobol`).
+///
+/// With the default `RandomGenerator`, this optimizer has no mutable state,
+/// so it also implements `SharedOptimizer` without any locks.
 #[derive(Debug)]
-pub struct RandomOptimizer<P, V> {
+pub struct RandomOptimizer<P, V, T = RandomGenerator> {
     param_domain: P,
+    generator: T,
     _value: PhantomData<V>,
 }
 impl<P, V> RandomOptimizer<P, V>
@@ -18,25 +24,58 @@ where
 {
     /// Makes a new `RandomOptimizer` instance.
     pub fn new(param_domain: P) -> Self {
+        Self::with_generator(param_domain, RandomGenerator)
+    }
+}
+impl<P, V, T> RandomOptimizer<P, V, T>
+where
+    P: Domain,
+    T: Generate<P>,
+{
+    /// Makes a new `RandomOptimizer` instance that samples parameters by the given generator.
+    pub fn with_generator(param_domain: P, generator: T) -> Self {
         Self {
             param_domain,
+            generator,
             _value: PhantomData,
         }
     }
+
+    /// Returns a reference to the generator.
+    pub fn generator(&self) -> &T {
+        &self.generator
+    }
 }
-impl<P, V> Optimizer for RandomOptimizer<P, V>
+impl<P, V, T> Optimizer for RandomOptimizer<P, V, T>
+where
+    P: Domain,
+    T: Generate<P>,
+{
+    type Param = P::Point;
+    type Value = V;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let param = track!(self.generator.generate(rng, &self.param_domain))?;
+        track!(Obs::new(idg, param))
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        track!(check_param(&self.param_domain, &obs))
+    }
+}
+impl<P, V> SharedOptimizer for RandomOptimizer<P, V>
 where
     P: Domain + Distribution<<P as Domain>::Point>,
 {
     type Param = P::Point;
     type Value = V;
 
-    fn ask<R: Rng, G: IdGen>(&mut self, mut rng: R, idg: G) -> Result<Obs<Self::Param>> {
+    fn ask<R: Rng, G: IdGen>(&self, mut rng: R, idg: G) -> Result<Obs<Self::Param>> {
         track!(Obs::new(idg, self.param_domain.sample(&mut rng)))
     }
 
-    fn tell(&mut self, _obs: Obs<Self::Param, Self::Value>) -> Result<()> {
-        Ok(())
+    fn tell(&self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        track!(check_param(&self.param_domain, &obs))
     }
 }
 impl<P, V> Default for RandomOptimizer<P, V>
@@ -48,23 +87,57 @@ where
     }
 }
 
+fn check_param<P: Domain, V>(domain: &P, obs: &Obs<P::Point, V>) -> Result<()> {
+    if !domain.contains(&obs.param) {
+        track_panic!(
+            ErrorKind::InvalidInput,
+            ErrorContext::new("Out of the domain parameter").obs_id(obs.id)
+        );
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::domains::DiscreteDomain;
+    use crate::domains::{ContinuousDomain, DiscreteDomain, VecDomain};
     use crate::generators::SerialIdGenerator;
+    use crate::init::Sobol;
     use rand;
     use trackable::result::TestResult;
 
     #[test]
     fn random_works() -> TestResult {
-        let mut opt = RandomOptimizer::new(track!(DiscreteDomain::new(10))?);
+        let opt = RandomOptimizer::new(track!(DiscreteDomain::new(10))?);
         let mut rng = rand::thread_rng();
         let mut idg = SerialIdGenerator::new();
 
         let obs = track!(opt.ask(&mut rng, &mut idg))?;
         track!(opt.tell(obs))?;
 
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        assert!(opt.tell(obs.map_param(|_| 10)).is_err());
+
+        let domain = VecDomain(vec![track!(ContinuousDomain::new(0.0, 1.0))?; 2]);
+        let mut opt = RandomOptimizer::with_generator(domain, Sobol::new());
+        for _ in 0..4 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            track!(opt.tell(obs.map_value(|()| 0.0)))?;
+        }
+
+        // Domains that don't override `Domain::contains` accept every parameter.
+        struct Coin;
+        impl Domain for Coin {
+            type Point = bool;
+        }
+        impl Distribution<bool> for Coin {
+            fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> bool {
+                rng.gen()
+            }
+        }
+        let opt = RandomOptimizer::<_, ()>::new(Coin);
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        track!(opt.tell(obs.map_param(|b| !b)))?;
         Ok(())
     }
 }
@@ -0,0 +1,320 @@
+//! Adaptive discretization of continuous domains.
+//!
+//! `RefiningOptimizer` lets optimizers for discrete domains search continuous ones.
+//! The search starts on a coarse grid covering the whole domains, and the grid is repeatedly
+//! zoomed in around the best parameter found so far (i.e., the classic zoom strategy).
+//! Since the grid points are evaluated rather than arbitrary floating point numbers,
+//! repeated evaluations of the same point are likely, which is useful for noisy objectives.
+use crate::domains::{ContinuousDomain, DiscreteDomain, GridDomain, VecDomain};
+use crate::{ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use rand::Rng;
+use std::collections::HashMap;
+use std::fmt;
+
+/// An optimizer adapter that searches continuous domains by refining grids around promising regions.
+///
+/// The inner optimizer searches the indices of the grid points (i.e., `VecDomain<DiscreteDomain>`
+/// having `resolution` points per dimension).
+/// After `refine_after` observations have been told at the current level,
+/// the grid is shrunk around the best parameter, and a new inner optimizer is built by the given factory function.
+/// The factory is invoked with the domain of the indices and the prior observations that fall on the new grid
+/// (snapped to their nearest grid points), so that it can warm-start the new optimizer.
+///
+/// Observations asked before a refinement and told after it are recorded in the history,
+/// but they are not told to the new inner optimizer.
+pub struct RefiningOptimizer<O: Optimizer, F> {
+    inner: O,
+    factory: F,
+    domains: Vec<ContinuousDomain>,
+    resolution: usize,
+    refine_after: usize,
+    shrink_factor: f64,
+    windows: Vec<ContinuousDomain>,
+    grids: Vec<Vec<f64>>,
+    level: usize,
+    told_in_level: usize,
+    history: Vec<Obs<Vec<f64>, O::Value>>,
+    asked: HashMap<ObsId, (usize, Vec<u64>)>,
+}
+impl<O, F> RefiningOptimizer<O, F>
+where
+    O: Optimizer<Param = Vec<u64>>,
+    O::Value: Ord + Clone,
+    F: FnMut(VecDomain<DiscreteDomain>, &[Obs<Vec<u64>, O::Value>]) -> Result<O>,
+{
+    /// Makes a new `RefiningOptimizer` instance.
+    ///
+    /// The first inner optimizer is built by invoking `factory` with no observations.
+    ///
+    /// # Errors
+    ///
+    /// If `domains` is empty, `resolution` is less than `2` or `refine_after` is `0`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(
+        domains: Vec<ContinuousDomain>,
+        resolution: usize,
+        refine_after: usize,
+        mut factory: F,
+    ) -> Result<Self> {
+        track_assert!(!domains.is_empty(), ErrorKind::InvalidInput);
+        track_assert!(resolution >= 2, ErrorKind::InvalidInput; resolution);
+        track_assert!(refine_after > 0, ErrorKind::InvalidInput);
+
+        let inner = track!(factory(track!(index_domain(&domains, resolution))?, &[]))?;
+        let grids = domains.iter().map(|d| d.grid(resolution)).collect();
+        Ok(Self {
+            inner,
+            factory,
+            windows: domains.clone(),
+            domains,
+            resolution,
+            refine_after,
+            shrink_factor: 0.5,
+            grids,
+            level: 0,
+            told_in_level: 0,
+            history: Vec::new(),
+            asked: HashMap::new(),
+        })
+    }
+
+    /// Sets the factor by which the size of the grid is multiplied at each refinement.
+    ///
+    /// The default value is `0.5`.
+    ///
+    /// # Errors
+    ///
+    /// If `factor` is not in the range `(0.0, 1.0)`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn set_shrink_factor(&mut self, factor: f64) -> Result<()> {
+        track_assert!(0.0 < factor && factor < 1.0, ErrorKind::InvalidInput; factor);
+        self.shrink_factor = factor;
+        Ok(())
+    }
+
+    /// Returns the number of the refinements done so far.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the regions currently covered by the grid.
+    pub fn windows(&self) -> &[ContinuousDomain] {
+        &self.windows
+    }
+
+    /// Returns the current grid points of each dimension.
+    pub fn grids(&self) -> &[Vec<f64>] {
+        &self.grids
+    }
+
+    /// Returns all the observations told so far.
+    pub fn history(&self) -> &[Obs<Vec<f64>, O::Value>] {
+        &self.history
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    fn refine(&mut self) -> Result<()> {
+        let best = self
+            .history
+            .iter()
+            .min_by(|a, b| a.value.cmp(&b.value).then(a.id.cmp(&b.id)));
+        let best = track_assert_some!(best, ErrorKind::Bug);
+
+        let mut windows = Vec::with_capacity(self.domains.len());
+        for ((domain, window), &x) in self.domains.iter().zip(&self.windows).zip(&best.param) {
+            let size = window.size() * self.shrink_factor;
+            let low = (x - size / 2.0).max(domain.low()).min(domain.high() - size);
+            let window = track!(ContinuousDomain::new(low, low + size))?;
+            windows.push(track!(
+                window.with_bounds(domain.low_bound(), domain.high_bound())
+            )?);
+        }
+        self.grids = windows.iter().map(|w| w.grid(self.resolution)).collect();
+        self.windows = windows;
+        self.level += 1;
+        self.told_in_level = 0;
+
+        let remapped = self
+            .history
+            .iter()
+            .filter_map(|obs| {
+                let param = self.snap(&obs.param)?;
+                Some(Obs {
+                    id: obs.id,
+                    param,
+                    value: obs.value.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let domain = track!(index_domain(&self.domains, self.resolution))?;
+        self.inner = track!((self.factory)(domain, &remapped))?;
+        Ok(())
+    }
+
+    // Returns the indices of the grid points nearest to `param` if it is within the half step of them.
+    fn snap(&self, param: &[f64]) -> Option<Vec<u64>> {
+        param
+            .iter()
+            .zip(&self.grids)
+            .map(|(&x, grid)| {
+                let half_step = (grid[1] - grid[0]) / 2.0;
+                let (i, p) = grid
+                    .iter()
+                    .enumerate()
+                    .min_by(|a, b| (a.1 - x).abs().total_cmp(&(b.1 - x).abs()))?;
+                if (p - x).abs() <= half_step {
+                    Some(i as u64)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+impl<O, F> Optimizer for RefiningOptimizer<O, F>
+where
+    O: Optimizer<Param = Vec<u64>>,
+    O::Value: Ord + Clone,
+    F: FnMut(VecDomain<DiscreteDomain>, &[Obs<Vec<u64>, O::Value>]) -> Result<O>,
+{
+    type Param = Vec<f64>;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let obs = track!(self.inner.ask(rng, idg))?;
+        track_assert_eq!(obs.param.len(), self.grids.len(), ErrorKind::InvalidInput; obs.id);
+        let param = obs
+            .param
+            .iter()
+            .zip(&self.grids)
+            .zip(&self.domains)
+            .map(|((&i, grid), domain)| {
+                let x =
+                    track_assert_some!(grid.get(i as usize), ErrorKind::InvalidInput; obs.id, i);
+                Ok(domain.clamp(*x))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.asked.insert(obs.id, (self.level, obs.param));
+        Ok(Obs {
+            id: obs.id,
+            param,
+            value: (),
+        })
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        let (level, indices) = track_assert_some!(
+            self.asked.remove(&obs.id),
+            ErrorKind::UnknownObservation; obs.id
+        );
+        self.history.push(obs.clone());
+        if level != self.level {
+            // The grid has been refined since the observation was asked.
+            return Ok(());
+        }
+
+        track!(self.inner.tell(Obs {
+            id: obs.id,
+            param: indices,
+            value: obs.value,
+        }))?;
+        self.told_in_level += 1;
+        if self.told_in_level >= self.refine_after {
+            track!(self.refine())?;
+        }
+        Ok(())
+    }
+}
+impl<O, F> fmt::Debug for RefiningOptimizer<O, F>
+where
+    O: Optimizer + fmt::Debug,
+    O::Value: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RefiningOptimizer")
+            .field("inner", &self.inner)
+            .field("domains", &self.domains)
+            .field("resolution", &self.resolution)
+            .field("refine_after", &self.refine_after)
+            .field("shrink_factor", &self.shrink_factor)
+            .field("windows", &self.windows)
+            .field("level", &self.level)
+            .field("history", &self.history)
+            .finish()
+    }
+}
+
+fn index_domain(
+    domains: &[ContinuousDomain],
+    resolution: usize,
+) -> Result<VecDomain<DiscreteDomain>> {
+    let domain = track!(DiscreteDomain::new(resolution as u64))?;
+    Ok(VecDomain(vec![domain; domains.len()]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::random::RandomOptimizer;
+    use crate::{Domain, FiniteF64};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn refining_works() -> TestResult {
+        let domains = vec![track!(ContinuousDomain::new(-1.0, 1.0))?; 2];
+        let mut remapped = Vec::new();
+        let mut opt = track!(RefiningOptimizer::new(
+            domains,
+            5,
+            10,
+            |domain, prior: &[Obs<_, FiniteF64>]| {
+                remapped.push(prior.len());
+                let mut inner = RandomOptimizer::new(domain);
+                for obs in prior {
+                    track!(inner.tell(obs.clone()))?;
+                }
+                Ok(inner)
+            }
+        ))?;
+        assert!(opt.set_shrink_factor(1.0).is_err());
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut idg = SerialIdGenerator::new();
+        for _ in 0..100 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            assert!(obs
+                .param
+                .iter()
+                .zip(opt.windows())
+                .all(|(x, w)| w.contains(x)));
+            let value = (obs.param[0] - 0.123).powi(2) + (obs.param[1] + 0.5).powi(2);
+            let value = track!(FiniteF64::new(value))?;
+            track!(opt.tell(obs.map_value(|()| value)))?;
+        }
+        assert_eq!(opt.level(), 10);
+        assert!(opt.windows().iter().all(|w| w.size() < 0.01));
+        assert_eq!(opt.history().len(), 100);
+
+        let best = track_assert_some!(
+            opt.history().iter().min_by_key(|o| o.value),
+            ErrorKind::Other
+        );
+        assert!(best.value.get() < 1e-3, "{}", best.value);
+
+        drop(opt);
+        assert_eq!(remapped.len(), 11);
+        assert!(remapped[1..].iter().all(|&n| n > 0), "{:?}", remapped);
+        Ok(())
+    }
+}
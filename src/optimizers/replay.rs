@@ -0,0 +1,243 @@
+//! Recording and replaying of ask-and-tell sessions.
+//!
+//! `ReplayRecorder` logs every interaction with an optimizer into a `ReplayLog`,
+//! and `ReplayOptimizer` replays the recorded session (e.g., to reproduce a reported misbehavior
+//! or to detect regressions of an evaluation pipeline).
+//!
+//! If the `serde` feature is enabled, `ReplayLog` can be serialized into any format supported by serde (e.g., JSON).
+use crate::{ErrorContext, ErrorKind, IdGen, Obs, Optimizer, Result};
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// An interaction with an optimizer.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ReplayEvent<P, V> {
+    /// An observation returned by `Optimizer::ask`.
+    Ask(Obs<P>),
+
+    /// An observation given to `Optimizer::tell`.
+    Tell(Obs<P, V>),
+}
+
+/// A log of an ask-and-tell session.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReplayLog<P, V> {
+    /// The seed of the random number generator used in the session (if known).
+    pub seed: Option<u64>,
+
+    /// The interactions in the order they occurred.
+    pub events: Vec<ReplayEvent<P, V>>,
+}
+impl<P, V> ReplayLog<P, V> {
+    /// Makes a new empty `ReplayLog` instance.
+    pub const fn new(seed: Option<u64>) -> Self {
+        Self {
+            seed,
+            events: Vec::new(),
+        }
+    }
+}
+
+/// An optimizer adapter that records every interaction with the inner optimizer.
+#[derive(Debug)]
+pub struct ReplayRecorder<O: Optimizer> {
+    inner: O,
+    log: ReplayLog<O::Param, O::Value>,
+}
+impl<O> ReplayRecorder<O>
+where
+    O: Optimizer,
+    O::Param: Clone,
+    O::Value: Clone,
+{
+    /// Makes a new `ReplayRecorder` instance.
+    ///
+    /// `seed` is the seed of the random number generator that will be passed to `ask`.
+    /// It is only stored in the log for reproducing the session later.
+    pub fn new(inner: O, seed: Option<u64>) -> Self {
+        Self {
+            inner,
+            log: ReplayLog::new(seed),
+        }
+    }
+
+    /// Returns the log recorded so far.
+    pub fn log(&self) -> &ReplayLog<O::Param, O::Value> {
+        &self.log
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `ReplayRecorder`, returning the underlying optimizer and the recorded log.
+    pub fn into_inner(self) -> (O, ReplayLog<O::Param, O::Value>) {
+        (self.inner, self.log)
+    }
+}
+impl<O> Optimizer for ReplayRecorder<O>
+where
+    O: Optimizer,
+    O::Param: Clone,
+    O::Value: Clone,
+{
+    type Param = O::Param;
+    type Value = O::Value;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let obs = track!(self.inner.ask(rng, idg))?;
+        self.log.events.push(ReplayEvent::Ask(obs.clone()));
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        self.log.events.push(ReplayEvent::Tell(obs.clone()));
+        track!(self.inner.tell(obs))
+    }
+
+    fn tell_batch(&mut self, obss: Vec<Obs<Self::Param, Self::Value>>) -> Result<()> {
+        self.log
+            .events
+            .extend(obss.iter().cloned().map(ReplayEvent::Tell));
+        track!(self.inner.tell_batch(obss))
+    }
+}
+
+/// An optimizer that replays a recorded session.
+///
+/// `ask` returns the recorded observations (without using the given random number generator and ID generator),
+/// and `tell` verifies that the given observation is the same as the recorded one.
+/// If the interactions diverge from the log, an `ErrorKind::InvalidInput` error is returned.
+#[derive(Debug)]
+pub struct ReplayOptimizer<P, V> {
+    seed: Option<u64>,
+    events: VecDeque<ReplayEvent<P, V>>,
+}
+impl<P, V> ReplayOptimizer<P, V>
+where
+    P: Clone + PartialEq,
+    V: PartialEq,
+{
+    /// Makes a new `ReplayOptimizer` instance.
+    pub fn new(log: ReplayLog<P, V>) -> Self {
+        Self {
+            seed: log.seed,
+            events: log.events.into(),
+        }
+    }
+
+    /// Returns the seed of the random number generator used in the recorded session.
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// Returns the number of the interactions that have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if all of the recorded interactions have been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+impl<P, V> Optimizer for ReplayOptimizer<P, V>
+where
+    P: Clone + PartialEq,
+    V: PartialEq,
+{
+    type Param = P;
+    type Value = V;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, _rng: R, _idg: G) -> Result<Obs<Self::Param>> {
+        match self.events.pop_front() {
+            Some(ReplayEvent::Ask(obs)) => Ok(obs),
+            Some(event) => {
+                self.events.push_front(event);
+                track_panic!(
+                    ErrorKind::InvalidInput,
+                    ErrorContext::new("A tell is expected by the recorded session")
+                );
+            }
+            None => track_panic!(ErrorKind::Other, "No more recorded interactions"),
+        }
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        match self.events.front() {
+            Some(ReplayEvent::Tell(expected)) => {
+                if expected.id != obs.id
+                    || expected.param != obs.param
+                    || expected.value != obs.value
+                {
+                    track_panic!(
+                        ErrorKind::InvalidInput,
+                        ErrorContext::new("Diverged from the recorded session").obs_id(obs.id)
+                    );
+                }
+            }
+            Some(ReplayEvent::Ask(_)) => track_panic!(
+                ErrorKind::InvalidInput,
+                ErrorContext::new("An ask is expected by the recorded session").obs_id(obs.id)
+            ),
+            None => track_panic!(ErrorKind::Other, "No more recorded interactions"),
+        }
+        self.events.pop_front();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::random::RandomOptimizer;
+    use crate::ObsId;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn replay_works() -> TestResult {
+        let inner = RandomOptimizer::new(track!(DiscreteDomain::new(100))?);
+        let mut opt = ReplayRecorder::new(inner, Some(7));
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut idg = SerialIdGenerator::new();
+        for _ in 0..5 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let value = obs.param * 2;
+            track!(opt.tell(obs.map_value(|()| value)))?;
+        }
+        let (_, log) = opt.into_inner();
+        assert_eq!(log.events.len(), 10);
+
+        let mut replay = ReplayOptimizer::new(log.clone());
+        assert_eq!(replay.seed(), Some(7));
+        while !replay.is_finished() {
+            let obs = track!(replay.ask(&mut rng, &mut idg))?;
+            let value = obs.param * 2;
+            track!(replay.tell(obs.map_value(|()| value)))?;
+        }
+        assert!(replay.ask(&mut rng, &mut idg).is_err());
+
+        let mut replay = ReplayOptimizer::new(log);
+        let obs = track!(replay.ask(&mut rng, &mut idg))?;
+        assert!(replay.ask(&mut rng, &mut idg).is_err());
+        let value = obs.param * 2 + 1;
+        let e = replay.tell(obs.map_value(|()| value)).err();
+        assert_eq!(e.and_then(|e| e.obs_id()), Some(ObsId::new(0)));
+        assert_eq!(replay.remaining(), 9);
+        Ok(())
+    }
+}
@@ -0,0 +1,254 @@
+//! Rungs shared by successive halving based optimizers.
+use crate::{Budget, ErrorKind, MfObs, ObsId, Result};
+use std::cmp;
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub struct Rungs<P, V>(Vec<Rung<P, V>>);
+impl<P, V> Rungs<P, V>
+where
+    V: Ord,
+{
+    pub fn new(min_budget: u64, max_budget: u64, reduction_factor: usize) -> Self {
+        let mut rungs = Vec::new();
+        let mut budget = min_budget;
+        while budget < max_budget {
+            let next_budget = cmp::min(max_budget, budget.saturating_mul(reduction_factor as u64));
+            let level = rungs.len();
+            rungs.push(Rung::new(
+                level,
+                budget,
+                Some(next_budget),
+                reduction_factor,
+            ));
+            budget = next_budget;
+        }
+        let level = rungs.len();
+        rungs.push(Rung::new(level, max_budget, None, reduction_factor));
+        Self(rungs)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Rung<P, V>> {
+        self.0.get(index)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Rung<P, V>> {
+        self.0.get_mut(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Rung<P, V>> {
+        self.0.iter()
+    }
+
+    pub fn last(&self) -> &Rung<P, V> {
+        &self.0[self.0.len() - 1]
+    }
+
+    pub fn last_mut(&mut self) -> &mut Rung<P, V> {
+        let i = self.0.len() - 1;
+        &mut self.0[i]
+    }
+
+    pub fn ask_promotable<F>(&mut self, mut is_promotable: F) -> Option<MfObs<P>>
+    where
+        F: FnMut(&Candidate<V>) -> bool,
+    {
+        for rung in self.0.iter_mut().rev() {
+            if let Some(obs) = rung.ask_promotable(&mut is_promotable) {
+                return Some(obs);
+            }
+        }
+        None
+    }
+
+    pub fn take_pending(&mut self, id: ObsId) -> Option<MfObs<P>> {
+        self.0.iter_mut().find_map(|rung| rung.take_pending(id))
+    }
+
+    pub fn tell(&mut self, obs: MfObs<P, V>) -> Result<()> {
+        for rung in self.0.iter_mut().rev() {
+            let p = obs.budget.consumption;
+            if rung.curr_budget <= p && p < rung.next_budget.unwrap_or(u64::MAX) {
+                track!(rung.tell(obs))?;
+                return Ok(());
+            }
+        }
+        track_panic!(ErrorKind::InvalidInput; obs.id);
+    }
+}
+
+/// A candidate of a promotion passed to the predicate of `Rung::ask_promotable`.
+///
+/// `level` is the index of the rung, `budget` is the budget the candidate would be promoted with,
+/// `rival` is the best value among the configurations that are not in the top,
+/// and `top` is the top `1 / reduction_factor` configurations (including already promoted ones).
+#[derive(Debug)]
+pub struct Candidate<'a, V> {
+    pub level: usize,
+    pub id: ObsId,
+    pub budget: Budget,
+    pub value: &'a V,
+    pub rival: Option<&'a V>,
+    pub top: &'a [(ObsId, &'a V)],
+}
+
+#[derive(Debug)]
+pub struct Rung<P, V> {
+    obss: HashMap<ObsId, Config<P, V>>,
+    level: usize,
+    curr_budget: u64,
+    next_budget: Option<u64>,
+    reduction_factor: usize,
+}
+impl<P, V> Rung<P, V>
+where
+    V: Ord,
+{
+    fn new(
+        level: usize,
+        curr_budget: u64,
+        next_budget: Option<u64>,
+        reduction_factor: usize,
+    ) -> Self {
+        Self {
+            obss: HashMap::new(),
+            level,
+            curr_budget,
+            next_budget,
+            reduction_factor,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.obss.len()
+    }
+
+    pub fn pending_len(&self) -> usize {
+        self.obss
+            .values()
+            .filter(|c| matches!(c, Config::Pending { .. }))
+            .count()
+    }
+
+    pub fn curr_budget(&self) -> u64 {
+        self.curr_budget
+    }
+
+    pub fn best_value(&self) -> Option<&V> {
+        self.obss.values().map(|c| c.value()).min()
+    }
+
+    /// Returns the pending observations sorted by their values (ties are broken by their identifiers).
+    pub fn pending(&self) -> Vec<&MfObs<P, V>> {
+        let mut obss = self
+            .obss
+            .values()
+            .filter_map(|c| match c {
+                Config::Pending { obs } => Some(obs),
+                Config::Finished { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        obss.sort_by(|a, b| a.value.cmp(&b.value).then(a.id.cmp(&b.id)));
+        obss
+    }
+
+    pub fn take_pending(&mut self, id: ObsId) -> Option<MfObs<P>> {
+        if let Some(Config::Pending { .. }) = self.obss.get(&id) {
+            if let Some(Config::Pending { obs }) = self.obss.remove(&id) {
+                return Some(obs.take_value().0);
+            }
+        }
+        None
+    }
+
+    /// Takes the best pending observation among the top `1 / reduction_factor` configurations
+    /// that satisfies `is_promotable`.
+    ///
+    /// Configurations having the same value are ranked by their identifiers,
+    /// so that the result does not depend on the iteration order of the underlying `HashMap`.
+    ///
+    /// `is_promotable` is given the pending configurations in the top in ascending order of their values.
+    pub fn ask_promotable<F>(&mut self, mut is_promotable: F) -> Option<MfObs<P>>
+    where
+        F: FnMut(&Candidate<V>) -> bool,
+    {
+        let next_budget = self.next_budget?;
+
+        // FIXME: optimize
+        let mut configs = self.obss.iter().collect::<Vec<_>>();
+        configs.sort_by(|a, b| a.1.value().cmp(b.1.value()).then(a.0.cmp(b.0)));
+
+        let mut found = None;
+        let promotables = self.obss.len() / self.reduction_factor;
+        let rival = configs.get(promotables).map(|(_, c)| c.value());
+        let top = configs
+            .iter()
+            .take(promotables)
+            .map(|&(&id, c)| (id, c.value()))
+            .collect::<Vec<_>>();
+        for (_, c) in configs.iter().take(promotables) {
+            if let Config::Pending { obs } = c {
+                let candidate = Candidate {
+                    level: self.level,
+                    id: obs.id,
+                    budget: Budget {
+                        amount: next_budget,
+                        consumption: obs.budget.consumption,
+                    },
+                    value: &obs.value,
+                    rival,
+                    top: &top,
+                };
+                if is_promotable(&candidate) {
+                    found = Some(obs.id);
+                    break;
+                }
+            }
+        }
+
+        if let Some(id) = found {
+            let (mut obs, value) = if let Config::Pending { obs } =
+                self.obss.remove(&id).unwrap_or_else(|| unreachable!())
+            {
+                obs.take_value()
+            } else {
+                unreachable!()
+            };
+
+            self.obss.insert(id, Config::Finished { value });
+
+            obs.budget.amount = next_budget;
+            Some(obs)
+        } else {
+            None
+        }
+    }
+
+    fn tell(&mut self, obs: MfObs<P, V>) -> Result<()> {
+        track_assert!(!self.obss.contains_key(&obs.id), ErrorKind::Bug);
+        track_assert!(
+            self.curr_budget <= obs.budget.consumption,
+            ErrorKind::InvalidInput; self.curr_budget, obs.budget
+        );
+        self.obss.insert(obs.id, Config::Pending { obs });
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+enum Config<P, V> {
+    Pending { obs: MfObs<P, V> },
+    Finished { value: V },
+}
+impl<P, V> Config<P, V> {
+    fn value(&self) -> &V {
+        match self {
+            Config::Pending { obs } => &obs.value,
+            Config::Finished { value } => value,
+        }
+    }
+}
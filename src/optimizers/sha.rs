@@ -0,0 +1,311 @@
+//! Synchronous **S**uccessive **HA**lving algorithm.
+//!
+//! # References
+//!
+//! - [Non-stochastic Best Arm Identification and Hyperparameter Optimization](https://arxiv.org/abs/1502.07943)
+//! - [Massively Parallel Hyperparameter Tuning](https://arxiv.org/abs/1810.05934)
+use super::rungs::Rungs;
+use crate::{
+    Budget, ErrorKind, IdGen, MfObs, MultiFidelityOptimizer, Obs, Optimizer, Ranked, Result,
+};
+use rand::Rng;
+use std::collections::VecDeque;
+
+/// Builder of `ShaOptimizer`.
+#[derive(Debug, Clone)]
+pub struct ShaOptimizerBuilder {
+    reduction_factor: usize,
+    without_checkpoint: bool,
+    cohort_size: Option<usize>,
+}
+impl ShaOptimizerBuilder {
+    /// Makes a new `ShaOptimizerBuilder` instance with the default settings.
+    pub const fn new() -> Self {
+        Self {
+            reduction_factor: 2,
+            without_checkpoint: false,
+            cohort_size: None,
+        }
+    }
+
+    /// Sets the reduction factor of the resulting optimizer.
+    ///
+    /// # Errors
+    ///
+    /// If `factor` is less than `2`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn reduction_factor(&mut self, factor: usize) -> Result<&mut Self> {
+        track_assert!(factor > 1, ErrorKind::InvalidInput; factor);
+        self.reduction_factor = factor;
+        Ok(self)
+    }
+
+    /// Makes the resulting optimizer work well with evaluators that don't have the capability of checkpointing.
+    pub fn without_checkpoint(&mut self) -> &mut Self {
+        self.without_checkpoint = true;
+        self
+    }
+
+    /// Sets the number of configurations evaluated in the lowest rung of each bracket.
+    ///
+    /// The size of the next rung is the size of the previous one divided by the reduction factor.
+    ///
+    /// If this is not specified, the minimum size that allows at least one configuration
+    /// to reach the highest rung is used.
+    ///
+    /// # Errors
+    ///
+    /// If `size` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn cohort_size(&mut self, size: usize) -> Result<&mut Self> {
+        track_assert!(size > 0, ErrorKind::InvalidInput; size);
+        self.cohort_size = Some(size);
+        Ok(self)
+    }
+
+    /// Builds a new `ShaOptimizer` instance.
+    ///
+    /// # Errors
+    ///
+    /// If the cohort size is too small for at least one configuration to reach the highest rung,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn finish<V, O>(
+        &self,
+        inner: O,
+        min_budget: u64,
+        max_budget: u64,
+    ) -> Result<ShaOptimizer<V, O>>
+    where
+        V: Ord,
+        O: Optimizer<Value = Ranked<V>>,
+    {
+        track_assert!(min_budget <= max_budget, ErrorKind::InvalidInput; min_budget, max_budget);
+        track_assert!(0 < min_budget, ErrorKind::InvalidInput; min_budget, max_budget);
+
+        let rungs = Rungs::new(min_budget, max_budget, self.reduction_factor);
+        let cohort_size = self.cohort_size.unwrap_or_else(|| {
+            (1..rungs.len()).fold(1usize, |n, _| n.saturating_mul(self.reduction_factor))
+        });
+
+        let mut rung_sizes = vec![cohort_size];
+        for _ in 1..rungs.len() {
+            let size = rung_sizes[rung_sizes.len() - 1] / self.reduction_factor;
+            track_assert!(size > 0, ErrorKind::InvalidInput; cohort_size, rungs.len());
+            rung_sizes.push(size);
+        }
+
+        Ok(ShaOptimizer {
+            inner,
+            rungs,
+            rung_sizes,
+            level: 0,
+            asked: 0,
+            canceled: VecDeque::new(),
+            initial_budget: Budget::new(min_budget),
+            without_checkpoint: self.without_checkpoint,
+            reduction_factor: self.reduction_factor,
+            min_budget,
+            max_budget,
+        })
+    }
+}
+impl Default for ShaOptimizerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Synchronous successive halving based optimizer.
+///
+/// Unlike `AshaOptimizer`, this optimizer waits until all the configurations of a rung cohort
+/// have been evaluated before promoting the best ones to the next rung.
+/// When the highest rung is completed, a new bracket is started.
+///
+/// Canceled observations (i.e., `obs.budget.consumption < obs.budget.amount`) are re-asked later.
+#[derive(Debug)]
+pub struct ShaOptimizer<V, O: Optimizer> {
+    inner: O,
+    rungs: Rungs<O::Param, V>,
+    rung_sizes: Vec<usize>,
+    level: usize,
+    asked: usize,
+    canceled: VecDeque<MfObs<O::Param>>,
+    initial_budget: Budget,
+    without_checkpoint: bool,
+    reduction_factor: usize,
+    min_budget: u64,
+    max_budget: u64,
+}
+impl<V, O> ShaOptimizer<V, O>
+where
+    V: Ord,
+    O: Optimizer<Value = Ranked<V>>,
+{
+    /// Makes a new `ShaOptimizer` instance with the default settings.
+    pub fn new(inner: O, min_budget: u64, max_budget: u64) -> Result<Self> {
+        track!(ShaOptimizerBuilder::new().finish(inner, min_budget, max_budget))
+    }
+
+    /// Returns the number of configurations evaluated in each rung of a bracket.
+    pub fn rung_sizes(&self) -> &[usize] {
+        &self.rung_sizes
+    }
+
+    /// Returns `true` if this optimizer is waiting for the current rung cohort to be evaluated.
+    ///
+    /// While waiting, `ask` returns an `ErrorKind::Other` error.
+    pub fn is_waiting(&self) -> bool {
+        self.canceled.is_empty()
+            && !self.is_level_completed()
+            && self.asked >= self.rung_sizes[self.level]
+    }
+
+    /// Returns a references to the underlying optimizer.
+    pub fn inner(&self) -> &O {
+        &self.inner
+    }
+
+    /// Returns a mutable references to the underlying optimizer.
+    pub fn inner_mut(&mut self) -> &mut O {
+        &mut self.inner
+    }
+
+    /// Consumes the `ShaOptimizer`, returning the underlying optimizer.
+    pub fn into_inner(self) -> O {
+        self.inner
+    }
+
+    fn is_level_completed(&self) -> bool {
+        self.rungs
+            .get(self.level)
+            .is_some_and(|r| r.len() >= self.rung_sizes[self.level])
+    }
+}
+impl<V, O> MultiFidelityOptimizer for ShaOptimizer<V, O>
+where
+    V: Ord + Clone,
+    O: Optimizer<Value = Ranked<V>>,
+    O::Param: Clone,
+{
+    type Param = O::Param;
+    type Value = V;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, rng: R, mut idg: G) -> Result<MfObs<Self::Param>> {
+        if let Some(mut obs) = self.canceled.pop_front() {
+            if self.without_checkpoint {
+                obs.id = track!(idg.generate())?;
+                obs.budget.consumption = 0;
+            }
+            return Ok(obs);
+        }
+
+        if self.is_level_completed() {
+            if self.level + 1 == self.rung_sizes.len() {
+                self.rungs = Rungs::new(self.min_budget, self.max_budget, self.reduction_factor);
+                self.level = 0;
+            } else {
+                self.level += 1;
+            }
+            self.asked = 0;
+        }
+
+        track_assert!(
+            self.asked < self.rung_sizes[self.level],
+            ErrorKind::Other,
+            "Waiting for the rung cohort to be evaluated: level={}, size={}",
+            self.level,
+            self.rung_sizes[self.level]
+        );
+
+        let obs = if self.level == 0 {
+            let obs = track!(self.inner.ask(rng, idg))?;
+            MfObs::from_obs(obs, self.initial_budget)
+        } else {
+            let mut obs = track_assert_some!(
+                self.rungs
+                    .get_mut(self.level - 1)
+                    .and_then(|r| r.ask_promotable(|_| true)),
+                ErrorKind::Bug
+            );
+            if self.without_checkpoint {
+                obs.id = track!(idg.generate())?;
+                obs.budget.consumption = 0;
+            }
+            obs
+        };
+        self.asked += 1;
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: MfObs<Self::Param, Self::Value>) -> Result<()> {
+        track_assert!(
+            obs.budget.consumption <= self.max_budget,
+            ErrorKind::InvalidInput; obs.id, obs.budget, self.max_budget
+        );
+
+        if obs.budget.consumption < obs.budget.amount {
+            // The evaluation of this observation was canceled.
+            self.canceled.push_back(obs.clone().take_value().0);
+        } else {
+            track!(self.rungs.tell(obs.clone()))?;
+        }
+
+        let rank = self.max_budget - obs.budget.consumption;
+        let obs = Obs::from(obs).map_value(|value| Ranked { rank, value });
+        track!(self.inner.tell(obs))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::ContinuousDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::random::RandomOptimizer;
+    use rand;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn sha_works() -> TestResult {
+        let inner = RandomOptimizer::new(track!(ContinuousDomain::new(0.0, 1.0))?);
+        let mut optimizer = track!(ShaOptimizer::<usize, _>::new(inner, 1, 4))?;
+        assert_eq!(optimizer.rung_sizes(), &[4, 2, 1]);
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+
+        // first rung
+        let mut obss = Vec::new();
+        for _ in 0..4 {
+            obss.push(track!(optimizer.ask(&mut rng, &mut idg))?);
+        }
+        assert!(optimizer.is_waiting());
+        assert!(optimizer.ask(&mut rng, &mut idg).is_err());
+
+        for (i, obs) in obss.into_iter().enumerate() {
+            let mut obs = obs.map_value(|_| 4 - i);
+            obs.budget.consumption = obs.budget.amount;
+            track!(optimizer.tell(obs))?;
+        }
+
+        // second rung
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+            assert_eq!(obs.budget.amount, 2);
+            ids.push(obs.id.get());
+
+            let mut obs = obs.map_value(|_| 0);
+            obs.budget.consumption = obs.budget.amount;
+            track!(optimizer.tell(obs))?;
+        }
+        ids.sort();
+        assert_eq!(ids, [2, 3]);
+
+        // third rung
+        let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+        assert_eq!(obs.budget.amount, 4);
+
+        Ok(())
+    }
+}
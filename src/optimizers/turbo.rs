@@ -0,0 +1,677 @@
+//! Trust-region optimizer inspired by TuRBO.
+//!
+//! # References
+//!
+//! - [Scalable Global Optimization via Local Bayesian Optimization](https://arxiv.org/abs/1910.01739)
+use crate::analysis::KnnSurrogate;
+use crate::domains::ContinuousDomain;
+use crate::{ErrorKind, IdGen, MeasuredValue, Obs, ObsId, Optimizer, Result};
+use rand::distributions::Distribution;
+use rand::Rng;
+use std::collections::HashMap;
+
+/// Builder of `TurboOptimizer`.
+#[derive(Debug, Clone)]
+pub struct TurboOptimizerBuilder {
+    regions: usize,
+    initial_samples: Option<usize>,
+    candidates: usize,
+    neighbors: usize,
+    initial_length: f64,
+    min_length: f64,
+    max_length: f64,
+    success_tolerance: usize,
+    failure_tolerance: Option<usize>,
+    pending_policy: PendingPolicy,
+}
+impl TurboOptimizerBuilder {
+    /// Makes a new `TurboOptimizerBuilder` instance with the default settings.
+    pub const fn new() -> Self {
+        Self {
+            regions: 1,
+            initial_samples: None,
+            candidates: 100,
+            neighbors: 5,
+            initial_length: 0.8,
+            min_length: 1.0 / 128.0,
+            max_length: 1.6,
+            success_tolerance: 3,
+            failure_tolerance: None,
+            pending_policy: PendingPolicy::Ignore,
+        }
+    }
+
+    /// Sets the number of the trust regions maintained in parallel.
+    ///
+    /// The default value is `1`.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn regions(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 0, ErrorKind::InvalidInput);
+        self.regions = n;
+        Ok(self)
+    }
+
+    /// Sets the number of the uniformly sampled points that start (or restart) a trust region.
+    ///
+    /// The default value is `2 * dim`.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is less than `2`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn initial_samples(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 1, ErrorKind::InvalidInput; n);
+        self.initial_samples = Some(n);
+        Ok(self)
+    }
+
+    /// Sets the number of the candidates evaluated by the local surrogate in an `ask` call.
+    ///
+    /// The default value is `100`.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn candidates(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 0, ErrorKind::InvalidInput);
+        self.candidates = n;
+        Ok(self)
+    }
+
+    /// Sets the number of the neighbors used by the local surrogate (see `KnnSurrogate`).
+    ///
+    /// The default value is `5`.
+    ///
+    /// # Errors
+    ///
+    /// If `k` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn neighbors(&mut self, k: usize) -> Result<&mut Self> {
+        track_assert!(k > 0, ErrorKind::InvalidInput);
+        self.neighbors = k;
+        Ok(self)
+    }
+
+    /// Sets the initial, minimum and maximum side lengths of a trust region.
+    ///
+    /// Lengths are relative to the size of each domain.
+    /// A trust region is restarted when its length becomes smaller than `min`.
+    /// The default values are `0.8`, `0.5^7` and `1.6` respectively.
+    ///
+    /// # Errors
+    ///
+    /// If `0 < min <= initial <= max` is not satisfied, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn lengths(&mut self, initial: f64, min: f64, max: f64) -> Result<&mut Self> {
+        track_assert!(
+            0.0 < min && min <= initial && initial <= max && max.is_finite(),
+            ErrorKind::InvalidInput; initial, min, max
+        );
+        self.initial_length = initial;
+        self.min_length = min;
+        self.max_length = max;
+        Ok(self)
+    }
+
+    /// Sets the number of the consecutive successes that double the length of a trust region.
+    ///
+    /// The default value is `3`.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn success_tolerance(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 0, ErrorKind::InvalidInput);
+        self.success_tolerance = n;
+        Ok(self)
+    }
+
+    /// Sets the number of the consecutive failures that halve the length of a trust region.
+    ///
+    /// The default value is `max(4, dim)`.
+    ///
+    /// # Errors
+    ///
+    /// If `n` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn failure_tolerance(&mut self, n: usize) -> Result<&mut Self> {
+        track_assert!(n > 0, ErrorKind::InvalidInput);
+        self.failure_tolerance = Some(n);
+        Ok(self)
+    }
+
+    /// Sets how the parameters asked but not yet told are treated by the local surrogate.
+    ///
+    /// The default value is `PendingPolicy::Ignore`.
+    pub fn pending_policy(&mut self, policy: PendingPolicy) -> &mut Self {
+        self.pending_policy = policy;
+        self
+    }
+
+    /// Builds a new `TurboOptimizer` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `params_domain` is empty, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn finish(&self, params_domain: Vec<ContinuousDomain>) -> Result<TurboOptimizer> {
+        track_assert!(!params_domain.is_empty(), ErrorKind::InvalidInput);
+        let dim = params_domain.len();
+        let regions = (0..self.regions)
+            .map(|_| TrustRegion::new(self.initial_length))
+            .collect();
+        Ok(TurboOptimizer {
+            params_domain,
+            initial_samples: self.initial_samples.unwrap_or(2 * dim).max(2),
+            failure_tolerance: self.failure_tolerance.unwrap_or_else(|| dim.max(4)),
+            builder: self.clone(),
+            regions,
+            next_region: 0,
+            asked: HashMap::new(),
+        })
+    }
+}
+impl Default for TurboOptimizerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Policy on the parameters that have been asked but not yet told (i.e., pending observations).
+///
+/// When several parameters are evaluated in parallel, ignoring the pending ones makes
+/// a model-based optimizer propose almost the same parameter again and again.
+/// The liar strategies avoid this by fitting the model as if the pending parameters had been evaluated
+/// with fictitious values.
+///
+/// # References
+///
+/// - [Kriging is well-suited to parallelize optimization](https://hal.archives-ouvertes.fr/hal-00260579)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PendingPolicy {
+    /// Pending parameters are ignored.
+    #[default]
+    Ignore,
+
+    /// Pending parameters are assumed to have the minimum (i.e., best) value observed so far.
+    ConstantLiarMin,
+
+    /// Pending parameters are assumed to have the mean value observed so far.
+    ConstantLiarMean,
+
+    /// Pending parameters are assumed to have the maximum (i.e., worst) value observed so far.
+    ConstantLiarMax,
+
+    /// Pending parameters are assumed to have the values predicted by the model fitted on the actual observations.
+    KrigingBeliever,
+}
+impl PendingPolicy {
+    /// Returns the fictitious values of `pending` under this policy.
+    ///
+    /// `observed` is the values observed so far and `predict` is the prediction of the model fitted on them.
+    /// If this policy is `Ignore` or there are no observed values, an empty vector is returned.
+    pub fn lie<P, F>(&self, observed: &[f64], pending: &[P], predict: F) -> Vec<f64>
+    where
+        F: Fn(&P) -> f64,
+    {
+        if observed.is_empty() {
+            return Vec::new();
+        }
+        let constant = match self {
+            PendingPolicy::Ignore => return Vec::new(),
+            PendingPolicy::ConstantLiarMin => {
+                observed.iter().copied().fold(f64::INFINITY, f64::min)
+            }
+            PendingPolicy::ConstantLiarMean => observed.iter().sum::<f64>() / observed.len() as f64,
+            PendingPolicy::ConstantLiarMax => {
+                observed.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+            }
+            PendingPolicy::KrigingBeliever => return pending.iter().map(predict).collect(),
+        };
+        vec![constant; pending.len()]
+    }
+}
+
+/// Trust-region optimizer.
+///
+/// This optimizer maintains one or more trust regions (hyper-rectangles) centered at the best point found in each region.
+/// Each region is explored by sampling candidates inside it and choosing the most promising one
+/// by a local k-nearest neighbor surrogate fitted on the observations of the region.
+/// A region is expanded after consecutive improvements and shrunk after consecutive failures,
+/// and it is restarted from scratch when it becomes too small.
+///
+/// Unlike the original TuRBO, which uses Gaussian process surrogates with Thompson sampling,
+/// this optimizer uses `KnnSurrogate`.
+///
+/// Values estimated from repeated measurements can be told with `tell_measured`,
+/// in which case the surrogates down-weight the observations having large standard errors.
+#[derive(Debug)]
+pub struct TurboOptimizer {
+    params_domain: Vec<ContinuousDomain>,
+    builder: TurboOptimizerBuilder,
+    initial_samples: usize,
+    failure_tolerance: usize,
+    regions: Vec<TrustRegion>,
+    next_region: usize,
+    asked: HashMap<ObsId, Asked>,
+}
+impl TurboOptimizer {
+    /// Makes a new `TurboOptimizer` instance with the default settings.
+    pub fn new(params_domain: Vec<ContinuousDomain>) -> Result<Self> {
+        track!(TurboOptimizerBuilder::new().finish(params_domain))
+    }
+
+    /// Returns the current side lengths of the trust regions.
+    pub fn lengths(&self) -> Vec<f64> {
+        self.regions.iter().map(|r| r.length).collect()
+    }
+
+    /// Returns the local surrogates of the trust regions fitted on their current observations.
+    ///
+    /// Regions that have fewer than two observations are skipped.
+    /// The returned models do not reflect any `PendingPolicy`.
+    pub fn surrogates(&self) -> Result<Vec<KnnSurrogate>> {
+        self.regions
+            .iter()
+            .filter(|r| r.obss.len() > 1)
+            .map(|r| {
+                track!(KnnSurrogate::fit_with_variances(
+                    &self.params_domain,
+                    &r.obss,
+                    &r.variances,
+                    self.builder.neighbors
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns the number of the parameters that have been asked but not yet told.
+    pub fn pending_count(&self) -> usize {
+        self.asked.len()
+    }
+
+    /// Returns the number of the restarts of the trust regions.
+    pub fn restarts(&self) -> usize {
+        self.regions.iter().map(|r| r.restarts).sum()
+    }
+
+    /// Returns the best observation found so far.
+    pub fn best(&self) -> Option<&Obs<Vec<f64>, f64>> {
+        self.regions
+            .iter()
+            .filter_map(|r| r.best_obs())
+            .chain(
+                self.regions
+                    .iter()
+                    .filter_map(|r| r.best_of_previous.as_ref()),
+            )
+            .min_by(|a, b| a.value.total_cmp(&b.value))
+    }
+
+    /// Tells the result of an observation whose value has been estimated from repeated measurements.
+    ///
+    /// The region is expanded or shrunk based on the mean,
+    /// while the local surrogates weight the observation by the inverse of its variance.
+    ///
+    /// # Errors
+    ///
+    /// The same as `Optimizer::tell`.
+    pub fn tell_measured(&mut self, obs: Obs<Vec<f64>, MeasuredValue>) -> Result<()> {
+        let variance = obs.value.variance();
+        track!(self.tell_with_variance(obs.map_value(MeasuredValue::mean), variance))
+    }
+
+    fn tell_with_variance(&mut self, obs: Obs<Vec<f64>, f64>, variance: f64) -> Result<()> {
+        let asked = track_assert_some!(
+            self.asked.get(&obs.id),
+            ErrorKind::UnknownObservation; obs.id
+        );
+        track_assert_eq!(obs.param, asked.param, ErrorKind::InvalidInput; obs.id);
+        track_assert!(obs.value.is_finite(), ErrorKind::InvalidInput; obs.id, obs.value);
+
+        let Asked {
+            region: index,
+            epoch,
+            ..
+        } = track_assert_some!(self.asked.remove(&obs.id), ErrorKind::Bug; obs.id);
+        if self.regions[index].restarts != epoch {
+            // The region has been restarted since the observation was asked,
+            // so the observation is only kept if it is the best one of the previous runs.
+            self.regions[index].keep_if_best(obs);
+            return Ok(());
+        }
+        self.tell_region(index, obs, variance);
+        Ok(())
+    }
+
+    fn sample_candidate<R: Rng>(&self, rng: &mut R, region: &TrustRegion) -> Vec<f64> {
+        let center = match region.best_obs() {
+            Some(obs) if region.obss.len() >= self.initial_samples => &obs.param,
+            _ => {
+                return self.params_domain.iter().map(|d| d.sample(rng)).collect();
+            }
+        };
+
+        let dim = self.params_domain.len();
+        let probability = (20.0 / dim as f64).min(1.0);
+        let mut x = center.clone();
+        let mut perturbed = false;
+        for (i, d) in self.params_domain.iter().enumerate() {
+            if rng.gen_bool(probability) || (!perturbed && i == dim - 1) {
+                let half = region.length * d.size() / 2.0;
+                let low = d.clamp(center[i] - half);
+                let high = d.clamp(center[i] + half);
+                if low < high {
+                    x[i] = rng.gen_range(low..high);
+                }
+                perturbed = true;
+            }
+        }
+        x
+    }
+
+    fn fit_surrogate(&self, index: usize) -> Result<KnnSurrogate> {
+        let region = &self.regions[index];
+        let k = self.builder.neighbors;
+        let surrogate = track!(KnnSurrogate::fit_with_variances(
+            &self.params_domain,
+            &region.obss,
+            &region.variances,
+            k
+        ))?;
+        if self.builder.pending_policy == PendingPolicy::Ignore {
+            return Ok(surrogate);
+        }
+
+        let mut pending = self
+            .asked
+            .iter()
+            .filter(|(_, a)| a.region == index && a.epoch == region.restarts)
+            .map(|(&id, a)| (id, &a.param))
+            .collect::<Vec<_>>();
+        if pending.is_empty() {
+            return Ok(surrogate);
+        }
+        pending.sort_by_key(|&(id, _)| id);
+
+        let observed = region.obss.iter().map(|o| o.value).collect::<Vec<_>>();
+        let lies = self
+            .builder
+            .pending_policy
+            .lie(&observed, &pending, |(_, x)| surrogate.predict(x));
+        let mut obss = region.obss.clone();
+        obss.extend(pending.iter().zip(lies).map(|(&(id, x), value)| Obs {
+            id,
+            param: x.clone(),
+            value,
+        }));
+
+        // Fictitious observations are as noisy as the real ones on average.
+        let lie_variance = region.variances.iter().sum::<f64>() / region.variances.len() as f64;
+        let mut variances = region.variances.clone();
+        variances.resize(obss.len(), lie_variance);
+        track!(KnnSurrogate::fit_with_variances(
+            &self.params_domain,
+            &obss,
+            &variances,
+            k
+        ))
+    }
+
+    fn tell_region(&mut self, index: usize, obs: Obs<Vec<f64>, f64>, variance: f64) {
+        let success_tolerance = self.builder.success_tolerance;
+        let failure_tolerance = self.failure_tolerance;
+        let initial_samples = self.initial_samples;
+        let builder = &self.builder;
+        let region = &mut self.regions[index];
+
+        let improved = region
+            .best_obs()
+            .is_none_or(|b| obs.value < b.value - 1e-3 * b.value.abs());
+        let initializing = region.obss.len() < initial_samples;
+        region.obss.push(obs);
+        region.variances.push(variance);
+        if initializing {
+            return;
+        }
+
+        if improved {
+            region.successes += 1;
+            region.failures = 0;
+        } else {
+            region.successes = 0;
+            region.failures += 1;
+        }
+        if region.successes >= success_tolerance {
+            region.length = (region.length * 2.0).min(builder.max_length);
+            region.successes = 0;
+        } else if region.failures >= failure_tolerance {
+            region.length /= 2.0;
+            region.failures = 0;
+        }
+        if region.length < builder.min_length {
+            region.restart(builder.initial_length);
+        }
+    }
+}
+impl Optimizer for TurboOptimizer {
+    type Param = Vec<f64>;
+    type Value = f64;
+
+    fn ask<R: Rng, G: IdGen>(&mut self, mut rng: R, idg: G) -> Result<Obs<Self::Param>> {
+        let index = self.next_region;
+        self.next_region = (self.next_region + 1) % self.regions.len();
+        let region = &self.regions[index];
+
+        let param = if region.obss.len() < self.initial_samples {
+            self.sample_candidate(&mut rng, region)
+        } else {
+            let surrogate = track!(self.fit_surrogate(index))?;
+            let mut best = None;
+            for _ in 0..self.builder.candidates {
+                let x = self.sample_candidate(&mut rng, region);
+                let y = surrogate.predict(&x);
+                if best.as_ref().is_none_or(|&(b, _)| y < b) {
+                    best = Some((y, x));
+                }
+            }
+            track_assert_some!(best, ErrorKind::Bug).1
+        };
+
+        let obs = track!(Obs::new(idg, param))?;
+        self.asked.insert(
+            obs.id,
+            Asked {
+                region: index,
+                epoch: region.restarts,
+                param: obs.param.clone(),
+            },
+        );
+        Ok(obs)
+    }
+
+    fn tell(&mut self, obs: Obs<Self::Param, Self::Value>) -> Result<()> {
+        track!(self.tell_with_variance(obs, 0.0))
+    }
+}
+
+#[derive(Debug)]
+struct Asked {
+    region: usize,
+    epoch: usize,
+    param: Vec<f64>,
+}
+
+#[derive(Debug)]
+struct TrustRegion {
+    length: f64,
+    successes: usize,
+    failures: usize,
+    obss: Vec<Obs<Vec<f64>, f64>>,
+    variances: Vec<f64>,
+    restarts: usize,
+    best_of_previous: Option<Obs<Vec<f64>, f64>>,
+}
+impl TrustRegion {
+    fn new(length: f64) -> Self {
+        Self {
+            length,
+            successes: 0,
+            failures: 0,
+            obss: Vec::new(),
+            variances: Vec::new(),
+            restarts: 0,
+            best_of_previous: None,
+        }
+    }
+
+    fn best_obs(&self) -> Option<&Obs<Vec<f64>, f64>> {
+        self.obss.iter().min_by(|a, b| a.value.total_cmp(&b.value))
+    }
+
+    fn keep_if_best(&mut self, obs: Obs<Vec<f64>, f64>) {
+        if self
+            .best_of_previous
+            .as_ref()
+            .is_none_or(|p| obs.value < p.value)
+        {
+            self.best_of_previous = Some(obs);
+        }
+    }
+
+    fn restart(&mut self, length: f64) {
+        if let Some(best) = self.best_obs().cloned() {
+            self.keep_if_best(best);
+        }
+        self.length = length;
+        self.successes = 0;
+        self.failures = 0;
+        self.obss.clear();
+        self.variances.clear();
+        self.restarts += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::SerialIdGenerator;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn turbo_works() -> TestResult {
+        let params_domain = (0..5)
+            .map(|_| ContinuousDomain::new(-5.0, 5.0))
+            .collect::<Result<Vec<_>>>()?;
+        let mut opt = track!(TurboOptimizerBuilder::new()
+            .regions(2)?
+            .finish(params_domain))?;
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut idg = SerialIdGenerator::new();
+
+        for _ in 0..300 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let value = obs.param.iter().map(|x| (x - 1.0).powi(2)).sum::<f64>();
+            track!(opt.tell(obs.map_value(|()| value)))?;
+        }
+        let best = track_assert_some!(opt.best(), ErrorKind::Other);
+        assert!(best.value < 1.0, "{}", best.value);
+        assert!(opt.lengths().iter().all(|&l| l <= 1.6));
+
+        let surrogates = track!(opt.surrogates())?;
+        assert!(!surrogates.is_empty());
+        let (mean, _var) = surrogates[0].predict_with_variance(&best.param);
+        assert!(mean.is_finite());
+
+        let mut opt = track!(TurboOptimizer::new(vec![track!(ContinuousDomain::new(
+            -5.0, 5.0
+        ))?]))?;
+        for _ in 0..100 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let samples = [
+                obs.param[0].powi(2),
+                obs.param[0].powi(2) + rng.gen::<f64>(),
+            ];
+            let value = track!(MeasuredValue::from_samples(&samples))?;
+            track!(opt.tell_measured(obs.map_value(|()| value)))?;
+        }
+        let best = track_assert_some!(opt.best(), ErrorKind::Other);
+        assert!(best.value < 1.0, "{}", best.value);
+
+        // Invalid observations are rejected without losing the pending parameters.
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        assert!(opt.tell(obs.clone().map_value(|()| f64::NAN)).is_err());
+        let mut other = obs.clone().map_value(|()| 0.0);
+        other.param[0] += 1.0;
+        assert!(opt.tell(other).is_err());
+        assert_eq!(opt.pending_count(), 1);
+        track!(opt.tell(obs.map_value(|()| 0.0)))?;
+        assert_eq!(opt.pending_count(), 0);
+
+        // The best observation is kept even if its region has been restarted since it was asked.
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        let index = opt.asked[&obs.id].region;
+        opt.regions[index].restart(0.8);
+        track!(opt.tell(obs.clone().map_value(|()| -1.0)))?;
+        let best = track_assert_some!(opt.best(), ErrorKind::Other);
+        assert_eq!((best.id, best.value), (obs.id, -1.0));
+        Ok(())
+    }
+
+    #[test]
+    fn pending_policy_works() -> TestResult {
+        let observed = [1.0, 2.0, 6.0];
+        let pending = [0.5, 1.5];
+        let predict = |x: &f64| x * 10.0;
+        assert!(PendingPolicy::Ignore
+            .lie(&observed, &pending, predict)
+            .is_empty());
+        assert_eq!(
+            PendingPolicy::ConstantLiarMin.lie(&observed, &pending, predict),
+            [1.0, 1.0]
+        );
+        assert_eq!(
+            PendingPolicy::ConstantLiarMean.lie(&observed, &pending, predict),
+            [3.0, 3.0]
+        );
+        assert_eq!(
+            PendingPolicy::ConstantLiarMax.lie(&observed, &pending, predict),
+            [6.0, 6.0]
+        );
+        assert_eq!(
+            PendingPolicy::KrigingBeliever.lie(&observed, &pending, predict),
+            [5.0, 15.0]
+        );
+
+        for policy in [
+            PendingPolicy::ConstantLiarMin,
+            PendingPolicy::ConstantLiarMean,
+            PendingPolicy::ConstantLiarMax,
+            PendingPolicy::KrigingBeliever,
+        ] {
+            let params_domain = (0..3)
+                .map(|_| ContinuousDomain::new(-5.0, 5.0))
+                .collect::<Result<Vec<_>>>()?;
+            let mut opt = track!(TurboOptimizerBuilder::new()
+                .pending_policy(policy)
+                .finish(params_domain))?;
+            let mut rng = StdRng::seed_from_u64(0);
+            let mut idg = SerialIdGenerator::new();
+
+            for _ in 0..25 {
+                let batch = (0..4)
+                    .map(|_| track!(opt.ask(&mut rng, &mut idg)))
+                    .collect::<Result<Vec<_>>>()?;
+                assert_eq!(opt.pending_count(), 4);
+                for obs in batch {
+                    let value = obs.param.iter().map(|x| (x - 1.0).powi(2)).sum::<f64>();
+                    track!(opt.tell(obs.map_value(|()| value)))?;
+                }
+            }
+            assert_eq!(opt.pending_count(), 0);
+            let best = track_assert_some!(opt.best(), ErrorKind::Other);
+            assert!(best.value < 1.0, "{:?}: {}", policy, best.value);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+//! Import of [Optuna] studies.
+//!
+//! This module is enabled by the `optuna` feature.
+//!
+//! The expected input is a JSON array of trials (or an object that has a `trials` field holding such an array),
+//! where each trial has the same field names as Optuna's `FrozenTrial`:
+//!
+//! ```json
+//! [
+//!   {"number": 0, "state": "COMPLETE", "value": 0.3, "params": {"x": 1.5, "y": -2}},
+//!   {"number": 1, "state": "FAIL", "value": null, "params": {"x": 0.1, "y": 3}}
+//! ]
+//! ```
+//!
+//! [Optuna]: https://optuna.org/
+use crate::{ErrorKind, Obs, ObsId, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io::Read;
+use trackable::error::ErrorKindExt;
+
+/// A trial of an Optuna study.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OptunaTrial {
+    /// The number of the trial.
+    pub number: u64,
+
+    /// The state of the trial (e.g., `"COMPLETE"` or `"TrialState.COMPLETE"`).
+    ///
+    /// If omitted, the trial is regarded as complete.
+    #[serde(default)]
+    pub state: Option<String>,
+
+    /// The objective value of the trial.
+    #[serde(default)]
+    pub value: Option<f64>,
+
+    /// The objective values of the trial (multi-objective studies).
+    #[serde(default)]
+    pub values: Option<Vec<f64>>,
+
+    /// The parameters of the trial.
+    #[serde(default)]
+    pub params: BTreeMap<String, serde_json::Value>,
+}
+impl OptunaTrial {
+    /// Returns `true` if this trial has been completed successfully.
+    pub fn is_complete(&self) -> bool {
+        self.state
+            .as_ref()
+            .is_none_or(|s| s == "COMPLETE" || s.ends_with(".COMPLETE"))
+    }
+
+    /// Converts this trial into an observation.
+    ///
+    /// The elements of the resulting parameter are ordered by `param_names`.
+    /// Boolean parameters are converted to `0.0` or `1.0`.
+    ///
+    /// # Errors
+    ///
+    /// If this trial has no objective value, or
+    /// a parameter named in `param_names` is missing or is not a number nor a boolean,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn to_obs(&self, param_names: &[&str]) -> Result<Obs<Vec<f64>, f64>> {
+        let value = self
+            .value
+            .or_else(|| self.values.as_ref().and_then(|v| v.first().copied()));
+        let value = track_assert_some!(value, ErrorKind::InvalidInput; self.number);
+
+        let mut param = Vec::with_capacity(param_names.len());
+        for &name in param_names {
+            let p = track_assert_some!(self.params.get(name), ErrorKind::InvalidInput; self.number, name);
+            let p = match p {
+                serde_json::Value::Number(n) => n.as_f64(),
+                serde_json::Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+                _ => None,
+            };
+            param.push(track_assert_some!(p, ErrorKind::InvalidInput; self.number, name));
+        }
+        Ok(Obs {
+            id: ObsId::new(self.number),
+            param,
+            value,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Study {
+    Trials(Vec<OptunaTrial>),
+    Object { trials: Vec<OptunaTrial> },
+}
+
+/// Reads the trials of an Optuna study from the given JSON.
+///
+/// # Errors
+///
+/// If the JSON is malformed, an `ErrorKind::InvalidInput` error will be returned.
+pub fn read_trials<R: Read>(reader: R) -> Result<Vec<OptunaTrial>> {
+    let study: Study =
+        track!(serde_json::from_reader(reader).map_err(|e| ErrorKind::InvalidInput.cause(e)))?;
+    match study {
+        Study::Trials(trials) | Study::Object { trials } => Ok(trials),
+    }
+}
+
+/// Reads the completed trials of an Optuna study from the given JSON and converts them into observations.
+///
+/// Trials that are not complete (e.g., `FAIL` or `PRUNED`) are skipped.
+/// See `OptunaTrial::to_obs` for the conversion.
+pub fn read_obss<R: Read>(reader: R, param_names: &[&str]) -> Result<Vec<Obs<Vec<f64>, f64>>> {
+    track!(read_trials(reader))?
+        .iter()
+        .filter(|t| t.is_complete())
+        .map(|t| track!(t.to_obs(param_names)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn read_obss_works() -> TestResult {
+        let json = r#"{"trials": [
+            {"number": 0, "state": "COMPLETE", "value": 0.3, "params": {"x": 1.5, "y": -2}},
+            {"number": 1, "state": "FAIL", "value": null, "params": {"x": 0.1, "y": 3}},
+            {"number": 2, "state": "TrialState.COMPLETE", "values": [0.1], "params": {"x": 0.0, "y": true}}
+        ]}"#;
+        let obss = track!(read_obss(json.as_bytes(), &["y", "x"]))?;
+        assert_eq!(obss.len(), 2);
+        assert_eq!(obss[0].id.get(), 0);
+        assert_eq!(obss[0].param, vec![-2.0, 1.5]);
+        assert_eq!(obss[1].param, vec![1.0, 0.0]);
+        assert_eq!(obss[1].value, 0.1);
+
+        assert!(read_obss(json.as_bytes(), &["z"]).is_err());
+        assert!(read_trials(&b"{"[..]).is_err());
+        Ok(())
+    }
+}
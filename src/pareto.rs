@@ -0,0 +1,210 @@
+//! Pareto dominance utilities for multi-objective values.
+//!
+//! In this module, lower values are considered better.
+use crate::{ErrorKind, Obs, Result};
+
+/// This trait allows checking the Pareto dominance relation between two values.
+pub trait Dominance {
+    /// Returns `true` if `self` dominates `other`, otherwise `false`.
+    ///
+    /// # Errors
+    ///
+    /// If `self` and `other` are not comparable (e.g., they have different numbers of objectives),
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    fn dominates(&self, other: &Self) -> Result<bool>;
+}
+
+impl<V: PartialOrd> Dominance for [V] {
+    fn dominates(&self, other: &Self) -> Result<bool> {
+        track_assert_eq!(self.len(), other.len(), ErrorKind::InvalidInput);
+        if self.iter().zip(other.iter()).any(|(a, b)| a > b) {
+            Ok(false)
+        } else {
+            Ok(self.iter().zip(other.iter()).any(|(a, b)| a < b))
+        }
+    }
+}
+
+impl<V: PartialOrd> Dominance for Vec<V> {
+    fn dominates(&self, other: &Self) -> Result<bool> {
+        track!(self[..].dominates(&other[..]))
+    }
+}
+
+impl<P, V: Dominance> Dominance for Obs<P, V> {
+    fn dominates(&self, other: &Self) -> Result<bool> {
+        track!(self.value.dominates(&other.value))
+    }
+}
+
+/// Sorts the given items into non-dominated fronts.
+///
+/// The first front of the result contains the items that are not dominated by any other items,
+/// the second one contains the items that are only dominated by the items of the first front, and so on.
+///
+/// This is the "fast non-dominated sorting" described in the [NSGA-II] paper.
+///
+/// [NSGA-II]: https://ieeexplore.ieee.org/document/996017
+pub fn non_dominated_sort<T: Dominance>(items: Vec<T>) -> Result<Vec<Vec<T>>> {
+    let mut dominated_count = vec![0; items.len()];
+    let mut dominates_list = vec![Vec::new(); items.len()];
+    for (i, p) in items.iter().enumerate() {
+        for (j, q) in items.iter().enumerate() {
+            if track!(p.dominates(q))? {
+                dominates_list[i].push(j);
+            } else if track!(q.dominates(p))? {
+                dominated_count[i] += 1;
+            }
+        }
+    }
+
+    let mut current = (0..items.len())
+        .filter(|&i| dominated_count[i] == 0)
+        .collect::<Vec<_>>();
+    let mut items = items.into_iter().map(Some).collect::<Vec<_>>();
+    let mut fronts = Vec::new();
+    while !current.is_empty() {
+        let mut next = Vec::new();
+        for &i in &current {
+            for &j in &dominates_list[i] {
+                dominated_count[j] -= 1;
+                if dominated_count[j] == 0 {
+                    next.push(j);
+                }
+            }
+        }
+
+        let mut front = Vec::with_capacity(current.len());
+        for i in current {
+            front.push(track_assert_some!(items[i].take(), ErrorKind::Bug));
+        }
+        fronts.push(front);
+        current = next;
+    }
+    track_assert!(items.iter().all(|x| x.is_none()), ErrorKind::Bug);
+
+    Ok(fronts)
+}
+
+/// Returns the items that are not dominated by any other items.
+pub fn pareto_front<T: Dominance>(items: &[T]) -> Result<Vec<&T>> {
+    let mut front = Vec::new();
+    for p in items {
+        let mut dominated = false;
+        for q in items {
+            if track!(q.dominates(p))? {
+                dominated = true;
+                break;
+            }
+        }
+        if !dominated {
+            front.push(p);
+        }
+    }
+    Ok(front)
+}
+
+/// Computes the hypervolume dominated by the given points and bounded by `reference`.
+///
+/// Points that do not strictly dominate `reference` in every objective are ignored.
+/// The computation is exact (by slicing the objective space) and its cost grows exponentially
+/// with the number of objectives, so this is intended for a few objectives.
+///
+/// # Errors
+///
+/// If a point has a different number of objectives from `reference`,
+/// an `ErrorKind::InvalidInput` error will be returned.
+pub fn hypervolume(points: &[Vec<f64>], reference: &[f64]) -> Result<f64> {
+    track_assert!(!reference.is_empty(), ErrorKind::InvalidInput);
+    for p in points {
+        track_assert_eq!(p.len(), reference.len(), ErrorKind::InvalidInput);
+    }
+    let points = points
+        .iter()
+        .filter(|p| p.iter().zip(reference.iter()).all(|(x, r)| x < r))
+        .map(|p| &p[..])
+        .collect::<Vec<_>>();
+    Ok(slice_hypervolume(points, reference))
+}
+
+fn slice_hypervolume(mut points: Vec<&[f64]>, reference: &[f64]) -> f64 {
+    let d = reference.len() - 1;
+    if points.is_empty() {
+        return 0.0;
+    }
+    if d == 0 {
+        let min = points.iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+        return reference[0] - min;
+    }
+
+    points.sort_by(|a, b| a[d].total_cmp(&b[d]));
+    let mut volume = 0.0;
+    for k in 0..points.len() {
+        let next = points.get(k + 1).map_or(reference[d], |p| p[d]);
+        let height = next - points[k][d];
+        if height > 0.0 {
+            let projected = points[..=k].iter().map(|p| &p[..d]).collect();
+            volume += slice_hypervolume(projected, &reference[..d]) * height;
+        }
+    }
+    volume
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn dominates_works() -> TestResult {
+        assert!(track!(vec![1, 2].dominates(&vec![2, 2]))?);
+        assert!(!track!(vec![1, 2].dominates(&vec![1, 2]))?);
+        assert!(!track!(vec![1, 3].dominates(&vec![2, 2]))?);
+        assert!(vec![1, 2].dominates(&vec![1]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn non_dominated_sort_works() -> TestResult {
+        let items = vec![vec![3, 3], vec![1, 2], vec![2, 1], vec![2, 2], vec![1, 3]];
+        let mut fronts = track!(non_dominated_sort(items))?;
+        for front in &mut fronts {
+            front.sort();
+        }
+        assert_eq!(
+            fronts,
+            vec![
+                vec![vec![1, 2], vec![2, 1]],
+                vec![vec![1, 3], vec![2, 2]],
+                vec![vec![3, 3]]
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn pareto_front_works() -> TestResult {
+        let items = vec![vec![3, 3], vec![1, 2], vec![2, 1], vec![2, 2]];
+        let front = track!(pareto_front(&items))?;
+        assert_eq!(front, vec![&vec![1, 2], &vec![2, 1]]);
+        Ok(())
+    }
+
+    #[test]
+    fn hypervolume_works() -> TestResult {
+        let points = vec![
+            vec![1.0, 2.0],
+            vec![2.0, 1.0],
+            vec![2.0, 2.0],
+            vec![4.0, 0.0],
+        ];
+        assert_eq!(track!(hypervolume(&points, &[3.0, 3.0]))?, 3.0);
+        assert_eq!(track!(hypervolume(&[], &[3.0, 3.0]))?, 0.0);
+
+        let points = vec![vec![0.0, 0.0, 1.0], vec![1.0, 1.0, 0.0]];
+        assert_eq!(track!(hypervolume(&points, &[2.0, 2.0, 2.0]))?, 5.0);
+
+        assert!(hypervolume(&points, &[2.0, 2.0]).is_err());
+        Ok(())
+    }
+}
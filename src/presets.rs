@@ -0,0 +1,131 @@
+//! Recommended optimizer stacks for common tuning scenarios.
+//!
+//! Each preset assembles an optimizer and a `StudyBudget` (i.e., the stopping condition) with sensible defaults,
+//! so that new users don't need to choose from all the optimizers in this crate.
+//! The returned values are ordinary instances, and can be adjusted freely before starting a study.
+use crate::domains::{ContinuousDomain, VecDomain};
+use crate::init::Sobol;
+use crate::optimizers::asha::{AshaOptimizer, AshaOptimizerBuilder};
+use crate::optimizers::random::RandomOptimizer;
+use crate::optimizers::turbo::{TurboOptimizer, TurboOptimizerBuilder};
+use crate::{ErrorKind, FiniteF64, Ranked, Result, StudyBudget};
+
+/// The optimizer used by `ml_hyperparameters`.
+pub type MlOptimizer = AshaOptimizer<
+    FiniteF64,
+    RandomOptimizer<VecDomain<ContinuousDomain>, Ranked<FiniteF64>, Sobol>,
+>;
+
+/// An optimizer and the budget of a study, assembled by a preset function.
+#[derive(Debug)]
+pub struct Preset<O> {
+    /// The optimizer.
+    pub optimizer: O,
+
+    /// The recommended budget of the study.
+    pub budget: StudyBudget,
+}
+
+/// Makes a preset for tuning the hyperparameters of machine learning models.
+///
+/// Training runs can be stopped early and resumed, and their budgets are, e.g., the numbers of epochs.
+/// Configurations are sampled by a Sobol sequence, and unpromising ones are pruned by ASHA
+/// with the reduction factor `3` and the minimum budget `max_budget / 27` (at least `1`).
+/// The study is stopped after `100 * dims.len()` evaluations.
+///
+/// Values told to the optimizer are `FiniteF64` (see `NanPolicy` for failed trials).
+///
+/// # Errors
+///
+/// If `dims` is empty or has more than `Sobol::MAX_DIMENSIONS` elements, or `max_budget` is `0`,
+/// an `ErrorKind::InvalidInput` error will be returned.
+pub fn ml_hyperparameters(
+    dims: Vec<ContinuousDomain>,
+    max_budget: u64,
+) -> Result<Preset<MlOptimizer>> {
+    track_assert!(!dims.is_empty(), ErrorKind::InvalidInput);
+    track_assert!(dims.len() <= Sobol::MAX_DIMENSIONS, ErrorKind::InvalidInput; dims.len());
+    track_assert!(max_budget > 0, ErrorKind::InvalidInput);
+
+    let evaluations = 100 * dims.len() as u64;
+    let inner = RandomOptimizer::with_generator(VecDomain(dims), Sobol::new());
+    let min_budget = (max_budget / 27).max(1);
+    let optimizer = track!(AshaOptimizerBuilder::new()
+        .reduction_factor(3)?
+        .finish(inner, min_budget, max_budget))?;
+    Ok(Preset {
+        optimizer,
+        budget: StudyBudget::new().max_evaluations(evaluations),
+    })
+}
+
+/// Makes a preset for calibrating the parameters of expensive simulations.
+///
+/// Evaluations are assumed to be expensive and to have no intermediate results,
+/// so a single trust region is started from `2 * dims.len()` uniform samples (at least `4`) and
+/// refined by `TurboOptimizer`.
+/// The study is stopped after `20 * dims.len()` evaluations (at least `50`).
+///
+/// # Errors
+///
+/// If `dims` is empty, an `ErrorKind::InvalidInput` error will be returned.
+pub fn simulation_calibration(dims: Vec<ContinuousDomain>) -> Result<Preset<TurboOptimizer>> {
+    track_assert!(!dims.is_empty(), ErrorKind::InvalidInput);
+
+    let evaluations = (20 * dims.len() as u64).max(50);
+    let optimizer = track!(TurboOptimizerBuilder::new()
+        .initial_samples((2 * dims.len()).max(4))?
+        .finish(dims))?;
+    Ok(Preset {
+        optimizer,
+        budget: StudyBudget::new().max_evaluations(evaluations),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generators::SerialIdGenerator;
+    use crate::{MultiFidelityOptimizer, Optimizer};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn presets_work() -> TestResult {
+        let dims = vec![track!(ContinuousDomain::new(-5.0, 5.0))?; 2];
+        assert!(ml_hyperparameters(Vec::new(), 27).is_err());
+        assert!(simulation_calibration(Vec::new()).is_err());
+
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut idg = SerialIdGenerator::new();
+
+        let Preset {
+            mut optimizer,
+            mut budget,
+        } = track!(ml_hyperparameters(dims.clone(), 27))?;
+        assert_eq!(budget.remaining_evaluations(), Some(200));
+        while !budget.is_exhausted() {
+            let mut obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+            obs.budget.consumption = obs.budget.amount;
+            let loss = obs.param.iter().map(|x| x * x).sum::<f64>() / obs.budget.amount as f64;
+            let loss = track!(FiniteF64::new(loss))?;
+            track!(optimizer.tell(obs.map_value(|()| loss)))?;
+            budget.record_evaluation();
+        }
+
+        let Preset {
+            mut optimizer,
+            mut budget,
+        } = track!(simulation_calibration(dims))?;
+        while !budget.is_exhausted() {
+            let obs = track!(optimizer.ask(&mut rng, &mut idg))?;
+            let value = obs.param.iter().map(|x| (x - 1.0).powi(2)).sum::<f64>();
+            track!(optimizer.tell(obs.map_value(|()| value)))?;
+            budget.record_evaluation();
+        }
+        let best = track_assert_some!(optimizer.best(), ErrorKind::Other);
+        assert!(best.value < 1.0, "{}", best.value);
+        Ok(())
+    }
+}
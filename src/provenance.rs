@@ -0,0 +1,149 @@
+//! Provenance of observations.
+//!
+//! Combinators such as `PortfolioOptimizer`, `PhasedOptimizer` and `AshaOptimizer` can record
+//! which of their components generated each observation into a shared `ProvenanceRegistry`.
+//! The registry can be used afterwards to attribute good trials to the right algorithm.
+use crate::{ObsId, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// A registry of the components that generated observations.
+///
+/// Cloned instances share the same records, so a registry can be given to several (possibly nested) combinators.
+/// If combinators are nested, the labels of an observation are ordered from the outermost combinator to the innermost one.
+#[derive(Debug, Clone, Default)]
+pub struct ProvenanceRegistry {
+    records: Arc<Mutex<HashMap<ObsId, Vec<String>>>>,
+}
+impl ProvenanceRegistry {
+    /// Makes a new empty `ProvenanceRegistry` instance.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the observation was generated by the component having the given label.
+    ///
+    /// Since outer combinators record after their inner components,
+    /// the label is placed in front of the labels already recorded for the observation.
+    pub fn record(&self, id: ObsId, label: String) -> Result<()> {
+        let mut records = track!(crate::lock(&self.records))?;
+        records.entry(id).or_default().insert(0, label);
+        Ok(())
+    }
+
+    /// Copies the labels of the observation `from` to the observation `to`.
+    ///
+    /// This is used when an observation is continued under a new identifier (e.g., ASHA promotions without checkpoints).
+    pub fn inherit(&self, from: ObsId, to: ObsId) -> Result<()> {
+        let mut records = track!(crate::lock(&self.records))?;
+        if let Some(labels) = records.get(&from).cloned() {
+            records.insert(to, labels);
+        }
+        Ok(())
+    }
+
+    /// Returns the labels recorded for the given observation.
+    pub fn labels(&self, id: ObsId) -> Result<Vec<String>> {
+        let records = track!(crate::lock(&self.records))?;
+        Ok(records.get(&id).cloned().unwrap_or_default())
+    }
+
+    /// Returns the path of the components that generated the given observation (e.g., `"phased.second/portfolio[1]"`).
+    pub fn path(&self, id: ObsId) -> Result<Option<String>> {
+        let labels = track!(self.labels(id))?;
+        Ok(if labels.is_empty() {
+            None
+        } else {
+            Some(labels.join("/"))
+        })
+    }
+
+    /// Returns the identifiers of the observations generated by the component having the given label.
+    ///
+    /// The identifiers are sorted in ascending order.
+    pub fn ids_of(&self, label: &str) -> Result<Vec<ObsId>> {
+        let records = track!(crate::lock(&self.records))?;
+        let mut ids = records
+            .iter()
+            .filter(|(_, labels)| labels.iter().any(|l| l == label))
+            .map(|(&id, _)| id)
+            .collect::<Vec<_>>();
+        ids.sort();
+        Ok(ids)
+    }
+
+    /// Returns the number of the recorded observations.
+    pub fn len(&self) -> Result<usize> {
+        Ok(track!(crate::lock(&self.records))?.len())
+    }
+
+    /// Returns `true` if no observations have been recorded, otherwise `false`.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(track!(self.len())? == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::asha::AshaOptimizerBuilder;
+    use crate::optimizers::phased::PhasedOptimizer;
+    use crate::optimizers::portfolio::PortfolioOptimizer;
+    use crate::optimizers::random::RandomOptimizer;
+    use crate::{MultiFidelityOptimizer, Obs, Optimizer};
+    use trackable::result::TestResult;
+
+    #[test]
+    fn provenance_works() -> TestResult {
+        let registry = ProvenanceRegistry::new();
+        let domain = track!(DiscreteDomain::new(10))?;
+        let mut first = track!(PortfolioOptimizer::new(vec![
+            RandomOptimizer::new(domain.clone()),
+            RandomOptimizer::new(domain.clone()),
+        ]))?;
+        first.set_provenance(registry.clone(), "portfolio");
+        let mut opt = PhasedOptimizer::new(first, 4, |_: &[Obs<_, _>]| {
+            Ok(RandomOptimizer::new(domain.clone()))
+        });
+        opt.set_provenance(registry.clone(), "phased");
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        let mut ids = Vec::new();
+        for _ in 0..6 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            ids.push(obs.id);
+            track!(opt.tell(obs.map_value(|()| 1usize)))?;
+        }
+        assert_eq!(track!(registry.len())?, 6);
+
+        let labels = track!(registry.labels(ids[0]))?;
+        assert_eq!(labels.len(), 2);
+        assert_eq!(labels[0], "phased.first");
+        assert!(labels[1] == "portfolio[0]" || labels[1] == "portfolio[1]");
+        assert_eq!(track!(registry.ids_of("phased.first"))?, &ids[..4]);
+        assert_eq!(track!(registry.ids_of("phased.second"))?, &ids[4..]);
+        assert_eq!(
+            track!(registry.path(ids[5]))?,
+            Some("phased.second".to_owned())
+        );
+
+        let registry = ProvenanceRegistry::new();
+        let mut asha = track!(AshaOptimizerBuilder::new()
+            .without_checkpoint()
+            .finish::<usize, _>(RandomOptimizer::new(domain.clone()), 10, 20))?;
+        asha.set_provenance(registry.clone(), "asha");
+        for value in [1, 2] {
+            let mut obs = track!(asha.ask(&mut rng, &mut idg))?.map_value(|()| value);
+            obs.budget.consumption = 10;
+            track!(asha.tell(obs))?;
+        }
+        let promoted = track!(asha.ask(&mut rng, &mut idg))?;
+        assert_eq!(promoted.budget.amount, 20);
+        assert_eq!(track!(registry.path(promoted.id))?, Some("asha".to_owned()));
+        assert_eq!(track!(registry.len())?, 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,238 @@
+//! Python bindings based on [PyO3].
+//!
+//! This module is enabled by the `python` feature.
+//!
+//! The `yamakan` Python module provides `RandomOptimizer`, `NelderMeadOptimizer` and `Nsga2Optimizer` classes
+//! which have the ask-and-tell interface:
+//!
+//! ```python
+//! import yamakan
+//!
+//! opt = yamakan.Nsga2Optimizer([0.0, 0.0], [1.0, 1.0], population_size=10, seed=0)
+//! for _ in range(100):
+//!     id, x = opt.ask()
+//!     opt.tell(id, [x[0], 1.0 - x[0] * x[1]])
+//! ```
+//!
+//! Parameters are given and returned as sequences of floats, so NumPy arrays can be passed
+//! (e.g., as `lows` and `highs`) and the returned lists can be converted by `numpy.asarray`.
+//!
+//! To build the extension module, compile this crate as a `cdylib`
+//! (e.g., `cargo rustc --release --features python,pyo3/extension-module --crate-type cdylib`).
+//!
+//! [PyO3]: https://pyo3.rs/
+use crate::domains::{ContinuousDomain, VecDomain};
+use crate::generators::SerialIdGenerator;
+use crate::optimizers::nelder_mead::NelderMeadOptimizer;
+use crate::optimizers::nsga2::{
+    Nsga2Optimizer, Nsga2Strategy, RandomGenerator, ReplaceVec, TournamentSelector,
+    UniformCrossOver,
+};
+use crate::optimizers::random::RandomOptimizer;
+use crate::{Error, ErrorKind, Obs, ObsId, Optimizer, Result};
+use ordered_float::NotNan;
+use pyo3::exceptions::{PyKeyError, PyValueError};
+use pyo3::prelude::*;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use trackable::error::ErrorKindExt;
+
+type Nsga2 = Nsga2Optimizer<
+    VecDomain<ContinuousDomain>,
+    Nsga2Strategy<
+        VecDomain<ContinuousDomain>,
+        RandomGenerator,
+        TournamentSelector,
+        UniformCrossOver,
+        ReplaceVec,
+    >,
+>;
+
+/// Python wrapper of `RandomOptimizer`.
+#[pyclass(name = "RandomOptimizer", module = "yamakan")]
+#[derive(Debug)]
+pub struct PyRandomOptimizer {
+    session: Session<RandomOptimizer<VecDomain<ContinuousDomain>, f64>>,
+}
+#[pymethods]
+impl PyRandomOptimizer {
+    #[new]
+    #[pyo3(signature = (lows, highs, seed = None))]
+    fn new(lows: Vec<f64>, highs: Vec<f64>, seed: Option<u64>) -> PyResult<Self> {
+        let domain = track_py(params_domain(&lows, &highs))?;
+        let inner = RandomOptimizer::new(VecDomain(domain));
+        Ok(Self {
+            session: Session::new(inner, seed),
+        })
+    }
+
+    fn ask(&mut self) -> PyResult<(u64, Vec<f64>)> {
+        track_py(self.session.ask())
+    }
+
+    fn tell(&mut self, id: u64, value: f64) -> PyResult<()> {
+        track_py(self.session.tell(id, value))
+    }
+}
+
+/// Python wrapper of `NelderMeadOptimizer`.
+#[pyclass(name = "NelderMeadOptimizer", module = "yamakan")]
+#[derive(Debug)]
+pub struct PyNelderMeadOptimizer {
+    session: Session<NelderMeadOptimizer<NotNan<f64>>>,
+}
+#[pymethods]
+impl PyNelderMeadOptimizer {
+    #[new]
+    #[pyo3(signature = (lows, highs, seed = None))]
+    fn new(lows: Vec<f64>, highs: Vec<f64>, seed: Option<u64>) -> PyResult<Self> {
+        let domain = track_py(params_domain(&lows, &highs))?;
+        let mut rng = make_rng(seed);
+        let inner = track_py(NelderMeadOptimizer::new(domain, &mut rng))?;
+        Ok(Self {
+            session: Session::new(inner, seed),
+        })
+    }
+
+    fn ask(&mut self) -> PyResult<(u64, Vec<f64>)> {
+        track_py(self.session.ask())
+    }
+
+    fn tell(&mut self, id: u64, value: f64) -> PyResult<()> {
+        let value = track_py(
+            NotNan::new(value).map_err(|e| Error::from(ErrorKind::InvalidInput.cause(e))),
+        )?;
+        track_py(self.session.tell(id, value))
+    }
+}
+
+/// Python wrapper of `Nsga2Optimizer`.
+#[pyclass(name = "Nsga2Optimizer", module = "yamakan")]
+#[derive(Debug)]
+pub struct PyNsga2Optimizer {
+    session: Session<Nsga2>,
+}
+#[pymethods]
+impl PyNsga2Optimizer {
+    #[new]
+    #[pyo3(signature = (lows, highs, population_size = 100, seed = None))]
+    fn new(
+        lows: Vec<f64>,
+        highs: Vec<f64>,
+        population_size: usize,
+        seed: Option<u64>,
+    ) -> PyResult<Self> {
+        let domain = track_py(params_domain(&lows, &highs))?;
+        let strategy = Nsga2Strategy::new(
+            RandomGenerator,
+            TournamentSelector::default(),
+            UniformCrossOver::default(),
+            ReplaceVec::default(),
+        );
+        let inner = track_py(Nsga2Optimizer::new(
+            VecDomain(domain),
+            population_size,
+            strategy,
+        ))?;
+        Ok(Self {
+            session: Session::new(inner, seed),
+        })
+    }
+
+    fn ask(&mut self) -> PyResult<(u64, Vec<f64>)> {
+        track_py(self.session.ask())
+    }
+
+    fn tell(&mut self, id: u64, values: Vec<f64>) -> PyResult<()> {
+        track_py(self.session.tell(id, values))
+    }
+}
+
+/// The `yamakan` Python module.
+#[pymodule]
+pub fn yamakan(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyRandomOptimizer>()?;
+    m.add_class::<PyNelderMeadOptimizer>()?;
+    m.add_class::<PyNsga2Optimizer>()?;
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Session<O> {
+    inner: O,
+    rng: StdRng,
+    idg: SerialIdGenerator,
+    asked: HashMap<ObsId, Vec<f64>>,
+}
+impl<O> Session<O>
+where
+    O: Optimizer<Param = Vec<f64>>,
+{
+    fn new(inner: O, seed: Option<u64>) -> Self {
+        Self {
+            inner,
+            rng: make_rng(seed),
+            idg: SerialIdGenerator::new(),
+            asked: HashMap::new(),
+        }
+    }
+
+    fn ask(&mut self) -> Result<(u64, Vec<f64>)> {
+        let obs = track!(self.inner.ask(&mut self.rng, &mut self.idg))?;
+        self.asked.insert(obs.id, obs.param.clone());
+        Ok((obs.id.get(), obs.param))
+    }
+
+    fn tell(&mut self, id: u64, value: O::Value) -> Result<()> {
+        let id = ObsId::new(id);
+        let param = track_assert_some!(
+            self.asked.remove(&id),
+            ErrorKind::UnknownObservation; id
+        );
+        track!(self.inner.tell(Obs { id, param, value }))
+    }
+}
+
+fn make_rng(seed: Option<u64>) -> StdRng {
+    seed.map_or_else(StdRng::from_entropy, StdRng::seed_from_u64)
+}
+
+fn params_domain(lows: &[f64], highs: &[f64]) -> Result<Vec<ContinuousDomain>> {
+    track_assert_eq!(lows.len(), highs.len(), ErrorKind::InvalidInput);
+    lows.iter()
+        .zip(highs.iter())
+        .map(|(&low, &high)| track!(ContinuousDomain::new(low, high)))
+        .collect()
+}
+
+fn track_py<T>(result: Result<T>) -> PyResult<T> {
+    result.map_err(|e| {
+        if *e.kind() == ErrorKind::UnknownObservation {
+            PyKeyError::new_err(e.to_string())
+        } else {
+            PyValueError::new_err(e.to_string())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn python_bindings_work() -> TestResult {
+        let mut opt = PyNsga2Optimizer::new(vec![0.0, 0.0], vec![1.0, 1.0], 10, Some(0))
+            .unwrap_or_else(|e| panic!("{}", e));
+        for _ in 0..30 {
+            let (id, x) = track!(opt.session.ask())?;
+            track!(opt.session.tell(id, vec![x[0], 1.0 - x[0] * x[1]]))?;
+            assert!(opt.session.tell(id, vec![0.0, 0.0]).is_err());
+        }
+
+        assert!(params_domain(&[0.0], &[]).is_err());
+        assert!(params_domain(&[1.0], &[0.0]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,517 @@
+//! Scalarization of multi-objective values.
+//!
+//! The types in this module implement `ValueTransform<Vec<f64>>`,
+//! so that single-objective optimizers can be applied to multi-objective problems via `MapValueOptimizer`.
+//!
+//! # References
+//!
+//! - [ParEGO: a hybrid algorithm with on-line landscape approximation for expensive multiobjective optimization problems](https://doi.org/10.1109/TEVC.2005.851274)
+use crate::optimizers::map_value::ValueTransform;
+use crate::{ErrorKind, Result};
+use ordered_float::OrderedFloat;
+use rand::seq::SliceRandom;
+use rand::Rng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Weighted sum scalarization.
+#[derive(Debug, Clone)]
+pub struct WeightedSum {
+    weights: Vec<f64>,
+}
+impl WeightedSum {
+    /// Makes a new `WeightedSum` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `weights` is empty or contains a negative or non-finite number,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(weights: Vec<f64>) -> Result<Self> {
+        track!(check_weights(&weights))?;
+        Ok(Self { weights })
+    }
+
+    /// Returns the weights of the objectives.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+}
+impl ValueTransform<Vec<f64>> for WeightedSum {
+    type Output = f64;
+
+    fn transform(&mut self, value: Vec<f64>) -> Result<Self::Output> {
+        track!(check_value(&value, self.weights.len()))?;
+        Ok(self.weights.iter().zip(value).map(|(w, v)| w * v).sum())
+    }
+}
+
+/// Augmented Chebyshev scalarization.
+///
+/// The scalarized value is `max_i(w_i * |v_i - z_i|) + rho * sum_i(w_i * |v_i - z_i|)`
+/// where `w` is the weights, `z` is the reference (ideal) point and `v` is the value.
+#[derive(Debug, Clone)]
+pub struct Chebyshev {
+    weights: Vec<f64>,
+    reference: Vec<f64>,
+    rho: f64,
+}
+impl Chebyshev {
+    /// Makes a new `Chebyshev` instance.
+    ///
+    /// # Errors
+    ///
+    /// If one of the following conditions is satisfied, an `ErrorKind::InvalidInput` error will be returned:
+    ///
+    /// - `weights` is empty or contains a negative or non-finite number
+    /// - the lengths of `weights` and `reference` differ
+    /// - `reference` contains a non-finite number
+    /// - `rho` is negative or non-finite
+    pub fn new(weights: Vec<f64>, reference: Vec<f64>, rho: f64) -> Result<Self> {
+        track!(check_weights(&weights))?;
+        track!(check_value(&reference, weights.len()))?;
+        track_assert!(rho.is_finite() && rho >= 0.0, ErrorKind::InvalidInput; rho);
+        Ok(Self {
+            weights,
+            reference,
+            rho,
+        })
+    }
+
+    /// Returns the weights of the objectives.
+    pub fn weights(&self) -> &[f64] {
+        &self.weights
+    }
+
+    /// Returns the reference point.
+    pub fn reference(&self) -> &[f64] {
+        &self.reference
+    }
+}
+impl ValueTransform<Vec<f64>> for Chebyshev {
+    type Output = f64;
+
+    fn transform(&mut self, value: Vec<f64>) -> Result<Self::Output> {
+        track!(check_value(&value, self.weights.len()))?;
+        let mut max = 0.0;
+        let mut sum = 0.0;
+        for ((w, z), v) in self.weights.iter().zip(self.reference.iter()).zip(value) {
+            let d = w * (v - z).abs();
+            max = f64::max(max, d);
+            sum += d;
+        }
+        Ok(max + self.rho * sum)
+    }
+}
+
+/// ParEGO scalarization.
+///
+/// This is an augmented Chebyshev scalarization (with `rho = 0.05`) whose weights are
+/// randomly drawn from the uniform simplex lattice `{0, 1/s, ..., 1}^k` (where the weights sum to `1`).
+/// Call `resample_weights` to draw new weights (e.g., at the beginning of each iteration).
+///
+/// Values are expected to be normalized into `[0, 1]`; the reference point is the origin.
+#[derive(Debug, Clone)]
+pub struct ParEgo {
+    chebyshev: Chebyshev,
+    lattice: Vec<Vec<f64>>,
+}
+impl ParEgo {
+    /// Makes a new `ParEgo` instance for `objectives` objectives and the lattice resolution `s`.
+    ///
+    /// # Errors
+    ///
+    /// If `objectives` or `s` is `0`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new<R: Rng>(rng: R, objectives: usize, s: usize) -> Result<Self> {
+        track_assert!(objectives > 0, ErrorKind::InvalidInput);
+        track_assert!(s > 0, ErrorKind::InvalidInput);
+
+        let mut lattice = Vec::new();
+        simplex_lattice(objectives, s, s, &mut Vec::new(), &mut lattice);
+        let chebyshev = track!(Chebyshev::new(
+            lattice[0].clone(),
+            vec![0.0; objectives],
+            0.05
+        ))?;
+        let mut this = Self { chebyshev, lattice };
+        this.resample_weights(rng);
+        Ok(this)
+    }
+
+    /// Returns the current weights.
+    pub fn weights(&self) -> &[f64] {
+        self.chebyshev.weights()
+    }
+
+    /// Draws new weights from the simplex lattice.
+    pub fn resample_weights<R: Rng>(&mut self, mut rng: R) {
+        if let Some(w) = self.lattice.choose(&mut rng) {
+            self.chebyshev.weights = w.clone();
+        }
+    }
+}
+impl ValueTransform<Vec<f64>> for ParEgo {
+    type Output = f64;
+
+    fn transform(&mut self, value: Vec<f64>) -> Result<Self::Output> {
+        track!(self.chebyshev.transform(value))
+    }
+}
+
+/// A value with the degree of constraint violation.
+///
+/// Values are ordered by their violations first (so feasible values come first) and then by the objective values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConstrainedValue {
+    /// The sum of the amounts by which the constraints are exceeded (`0.0` if feasible).
+    pub violation: f64,
+
+    /// The objective value.
+    pub value: f64,
+}
+impl ConstrainedValue {
+    /// Returns `true` if no constraints are violated, otherwise `false`.
+    pub fn is_feasible(&self) -> bool {
+        self.violation == 0.0
+    }
+}
+impl Eq for ConstrainedValue {}
+impl PartialOrd for ConstrainedValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for ConstrainedValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (OrderedFloat(self.violation), OrderedFloat(self.value))
+            .cmp(&(OrderedFloat(other.violation), OrderedFloat(other.value)))
+    }
+}
+
+/// Epsilon-constraint scalarization.
+///
+/// One objective is optimized while the others are constrained to be at most their thresholds.
+/// The result is a `ConstrainedValue` whose violation is the sum of the amounts exceeding the thresholds.
+#[derive(Debug, Clone)]
+pub struct EpsilonConstraint {
+    objective: usize,
+    thresholds: Vec<f64>,
+}
+impl EpsilonConstraint {
+    /// Makes a new `EpsilonConstraint` instance.
+    ///
+    /// `thresholds` has an element for each objective. The threshold of the `objective`-th objective is ignored
+    /// (and can be `f64::INFINITY`).
+    ///
+    /// # Errors
+    ///
+    /// If `objective` is out of the range of `thresholds` or a threshold is NaN,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(objective: usize, thresholds: Vec<f64>) -> Result<Self> {
+        track_assert!(objective < thresholds.len(), ErrorKind::InvalidInput; objective);
+        for &t in &thresholds {
+            track_assert!(!t.is_nan(), ErrorKind::InvalidInput);
+        }
+        Ok(Self {
+            objective,
+            thresholds,
+        })
+    }
+
+    /// Returns the index of the optimized objective.
+    pub fn objective(&self) -> usize {
+        self.objective
+    }
+
+    /// Returns the thresholds of the objectives.
+    pub fn thresholds(&self) -> &[f64] {
+        &self.thresholds
+    }
+}
+impl ValueTransform<Vec<f64>> for EpsilonConstraint {
+    type Output = ConstrainedValue;
+
+    fn transform(&mut self, value: Vec<f64>) -> Result<Self::Output> {
+        track!(check_value(&value, self.thresholds.len()))?;
+        let violation = value
+            .iter()
+            .zip(self.thresholds.iter())
+            .enumerate()
+            .filter(|&(i, _)| i != self.objective)
+            .map(|(_, (v, t))| (v - t).max(0.0))
+            .sum();
+        Ok(ConstrainedValue {
+            violation,
+            value: value[self.objective],
+        })
+    }
+}
+
+/// A vector of objective values that is ordered lexicographically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexicographicValue(pub Vec<f64>);
+impl Eq for LexicographicValue {}
+impl PartialOrd for LexicographicValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for LexicographicValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let a = self.0.iter().map(|&v| OrderedFloat(v));
+        let b = other.0.iter().map(|&v| OrderedFloat(v));
+        a.cmp(b)
+    }
+}
+
+/// Lexicographic ordering of objectives.
+///
+/// The objectives are compared in the given priority order: a less important objective only matters
+/// when the more important ones are equal.
+#[derive(Debug, Clone)]
+pub struct Lexicographic {
+    priorities: Vec<usize>,
+}
+impl Lexicographic {
+    /// Makes a new `Lexicographic` instance.
+    ///
+    /// `priorities` is the indices of the objectives from the most important one.
+    ///
+    /// # Errors
+    ///
+    /// If `priorities` is not a permutation of `0..priorities.len()`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(priorities: Vec<usize>) -> Result<Self> {
+        let mut sorted = priorities.clone();
+        sorted.sort_unstable();
+        track_assert!(
+            !sorted.is_empty() && sorted.iter().enumerate().all(|(i, &p)| i == p),
+            ErrorKind::InvalidInput; priorities
+        );
+        Ok(Self { priorities })
+    }
+
+    /// Returns the indices of the objectives from the most important one.
+    pub fn priorities(&self) -> &[usize] {
+        &self.priorities
+    }
+}
+impl ValueTransform<Vec<f64>> for Lexicographic {
+    type Output = LexicographicValue;
+
+    fn transform(&mut self, value: Vec<f64>) -> Result<Self::Output> {
+        track!(check_value(&value, self.priorities.len()))?;
+        Ok(LexicographicValue(
+            self.priorities.iter().map(|&i| value[i]).collect(),
+        ))
+    }
+}
+
+/// The optimization direction of an objective.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Direction {
+    /// Lower values are better.
+    Minimize,
+
+    /// Higher values are better.
+    Maximize,
+}
+
+/// A named objective.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Objective {
+    /// The name of the objective.
+    pub name: String,
+
+    /// The optimization direction of the objective.
+    pub direction: Direction,
+
+    /// The weight of the objective used by aggregation.
+    pub weight: f64,
+}
+impl Objective {
+    /// Makes a new `Objective` instance to be minimized with the weight `1.0`.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            direction: Direction::Minimize,
+            weight: 1.0,
+        }
+    }
+}
+
+/// Weighted aggregation of named objectives.
+///
+/// Raw objective values are converted to minimized ones (i.e., the values of `Direction::Maximize` objectives are negated)
+/// and then summed with the weights.
+///
+/// Since `MapValueOptimizer` exposes raw values as its value type, wrapping it by `ReplayRecorder` retains
+/// the raw per-objective history, which can be re-aggregated later with different weights.
+#[derive(Debug, Clone)]
+pub struct NamedObjectives {
+    objectives: Vec<Objective>,
+}
+impl NamedObjectives {
+    /// Makes a new `NamedObjectives` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `objectives` is empty, has duplicate names, or has a negative or non-finite weight,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(objectives: Vec<Objective>) -> Result<Self> {
+        let weights = objectives.iter().map(|o| o.weight).collect::<Vec<_>>();
+        track!(check_weights(&weights))?;
+        for (i, o) in objectives.iter().enumerate() {
+            track_assert!(
+                objectives[..i].iter().all(|p| p.name != o.name),
+                ErrorKind::InvalidInput; o.name
+            );
+        }
+        Ok(Self { objectives })
+    }
+
+    /// Returns the objectives.
+    pub fn objectives(&self) -> &[Objective] {
+        &self.objectives
+    }
+
+    /// Returns the index of the objective having the given name.
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.objectives.iter().position(|o| o.name == name)
+    }
+
+    /// Converts the given raw values to the minimized ones.
+    ///
+    /// The result can be told to multi-objective optimizers such as `Nsga2Optimizer`.
+    ///
+    /// # Errors
+    ///
+    /// If the number of `values` differs from the objectives or a value is not finite,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn minimized(&self, values: &[f64]) -> Result<Vec<f64>> {
+        track!(check_value(values, self.objectives.len()))?;
+        Ok(self
+            .objectives
+            .iter()
+            .zip(values.iter())
+            .map(|(o, &v)| match o.direction {
+                Direction::Minimize => v,
+                Direction::Maximize => -v,
+            })
+            .collect())
+    }
+
+    /// Aggregates the given raw values into a single value to be minimized.
+    ///
+    /// # Errors
+    ///
+    /// If the number of `values` differs from the objectives or a value is not finite,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn aggregate(&self, values: &[f64]) -> Result<f64> {
+        let minimized = track!(self.minimized(values))?;
+        Ok(self
+            .objectives
+            .iter()
+            .zip(minimized)
+            .map(|(o, v)| o.weight * v)
+            .sum())
+    }
+}
+impl ValueTransform<Vec<f64>> for NamedObjectives {
+    type Output = f64;
+
+    fn transform(&mut self, value: Vec<f64>) -> Result<Self::Output> {
+        track!(self.aggregate(&value))
+    }
+}
+
+fn simplex_lattice(
+    objectives: usize,
+    s: usize,
+    rest: usize,
+    prefix: &mut Vec<f64>,
+    lattice: &mut Vec<Vec<f64>>,
+) {
+    if prefix.len() + 1 == objectives {
+        prefix.push(rest as f64 / s as f64);
+        lattice.push(prefix.clone());
+        prefix.pop();
+        return;
+    }
+    for i in 0..=rest {
+        prefix.push(i as f64 / s as f64);
+        simplex_lattice(objectives, s, rest - i, prefix, lattice);
+        prefix.pop();
+    }
+}
+
+fn check_weights(weights: &[f64]) -> Result<()> {
+    track_assert!(!weights.is_empty(), ErrorKind::InvalidInput);
+    for &w in weights {
+        track_assert!(w.is_finite() && w >= 0.0, ErrorKind::InvalidInput; w);
+    }
+    Ok(())
+}
+
+fn check_value(value: &[f64], objectives: usize) -> Result<()> {
+    track_assert_eq!(value.len(), objectives, ErrorKind::InvalidInput);
+    for &v in value {
+        track_assert!(v.is_finite(), ErrorKind::InvalidInput; v);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn scalarize_works() -> TestResult {
+        let mut t = track!(WeightedSum::new(vec![1.0, 2.0]))?;
+        assert_eq!(track!(t.transform(vec![3.0, 0.5]))?, 4.0);
+        assert!(t.transform(vec![1.0]).is_err());
+
+        let mut t = track!(Chebyshev::new(vec![0.5, 0.5], vec![0.0, 0.0], 0.0))?;
+        assert_eq!(track!(t.transform(vec![1.0, 3.0]))?, 1.5);
+
+        let mut t = track!(ParEgo::new(rand::thread_rng(), 3, 4))?;
+        assert_eq!(t.lattice.len(), 15);
+        for _ in 0..10 {
+            t.resample_weights(rand::thread_rng());
+            assert!((t.weights().iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+        assert!(track!(t.transform(vec![0.1, 0.2, 0.3]))? >= 0.0);
+
+        let mut t = track!(EpsilonConstraint::new(0, vec![f64::INFINITY, 1.0]))?;
+        let feasible = track!(t.transform(vec![5.0, 0.5]))?;
+        let infeasible = track!(t.transform(vec![1.0, 3.0]))?;
+        assert!(feasible.is_feasible());
+        assert_eq!(infeasible.violation, 2.0);
+        assert!(feasible < infeasible);
+        assert!(EpsilonConstraint::new(2, vec![1.0, 1.0]).is_err());
+
+        let mut t = track!(Lexicographic::new(vec![1, 0]))?;
+        let a = track!(t.transform(vec![3.0, 1.0]))?;
+        let b = track!(t.transform(vec![1.0, 2.0]))?;
+        let c = track!(t.transform(vec![2.0, 1.0]))?;
+        assert_eq!(a, LexicographicValue(vec![1.0, 3.0]));
+        assert!(c < a && a < b);
+        assert!(Lexicographic::new(vec![0, 0]).is_err());
+
+        let mut accuracy = Objective::new("accuracy");
+        accuracy.direction = Direction::Maximize;
+        let mut latency = Objective::new("latency");
+        latency.weight = 0.5;
+        let mut t = track!(NamedObjectives::new(vec![accuracy, latency]))?;
+        assert_eq!(t.index("latency"), Some(1));
+        assert_eq!(track!(t.minimized(&[0.9, 2.0]))?, [-0.9, 2.0]);
+        assert_eq!(track!(t.transform(vec![0.5, 2.0]))?, 0.5);
+        let dup = vec![Objective::new("a"), Objective::new("a")];
+        assert!(NamedObjectives::new(dup).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,312 @@
+//! Statistical comparisons of noisy measurements.
+//!
+//! When an objective function is noisy, the observation that happened to get the lowest value is often not the best one.
+//! `IncumbentTracker` replaces its incumbent only when a new observation is significantly better,
+//! judged from repeated measurements of both observations.
+use crate::{ErrorKind, Obs, Result};
+use rand::Rng;
+
+/// Returns the one-sided p-value of Welch's t-test under the null hypothesis that
+/// the mean of `candidate` is not lower than the mean of `incumbent`.
+///
+/// Small p-values mean that `candidate` is likely better than `incumbent`.
+///
+/// # Errors
+///
+/// If either sample has fewer than two values or contains a non-finite value,
+/// an `ErrorKind::InvalidInput` error will be returned.
+pub fn welch_t_test(candidate: &[f64], incumbent: &[f64]) -> Result<f64> {
+    track!(check_sample(candidate, 2))?;
+    track!(check_sample(incumbent, 2))?;
+
+    let (m1, v1) = mean_and_variance(candidate);
+    let (m2, v2) = mean_and_variance(incumbent);
+    let s1 = v1 / candidate.len() as f64;
+    let s2 = v2 / incumbent.len() as f64;
+    let se2 = s1 + s2;
+    if se2 == 0.0 {
+        return Ok(if m1 < m2 { 0.0 } else { 1.0 });
+    }
+
+    let t = (m1 - m2) / se2.sqrt();
+    let df = se2.powi(2)
+        / (s1.powi(2) / (candidate.len() - 1) as f64 + s2.powi(2) / (incumbent.len() - 1) as f64);
+    Ok(student_t_cdf(t, df))
+}
+
+/// Returns the bootstrap estimate of the probability that the mean of `candidate` is not lower than the mean of `incumbent`.
+///
+/// Both samples are resampled with replacement `resamples` times,
+/// and the fraction of the resamples in which the candidate mean is not lower is returned.
+/// Unlike `welch_t_test`, this does not assume that the means are normally distributed.
+///
+/// # Errors
+///
+/// If `resamples` is `0`, either sample is empty or contains a non-finite value,
+/// an `ErrorKind::InvalidInput` error will be returned.
+pub fn bootstrap_test<R: Rng>(
+    mut rng: R,
+    candidate: &[f64],
+    incumbent: &[f64],
+    resamples: usize,
+) -> Result<f64> {
+    track_assert!(resamples > 0, ErrorKind::InvalidInput);
+    track!(check_sample(candidate, 1))?;
+    track!(check_sample(incumbent, 1))?;
+
+    let mut resampled_mean = |xs: &[f64]| {
+        (0..xs.len())
+            .map(|_| xs[rng.gen_range(0..xs.len())])
+            .sum::<f64>()
+            / xs.len() as f64
+    };
+    let not_lower = (0..resamples)
+        .filter(|_| resampled_mean(candidate) >= resampled_mean(incumbent))
+        .count();
+    Ok(not_lower as f64 / resamples as f64)
+}
+
+/// Statistical test used to compare repeated measurements.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignificanceTest {
+    /// Welch's t-test (see `welch_t_test`).
+    Welch,
+
+    /// Bootstrap test with the given number of resamples (see `bootstrap_test`).
+    Bootstrap {
+        /// The number of the resamples.
+        resamples: usize,
+    },
+}
+impl SignificanceTest {
+    /// Returns the p-value of the null hypothesis that `candidate` is not better than `incumbent`.
+    ///
+    /// # Errors
+    ///
+    /// See `welch_t_test` and `bootstrap_test`.
+    pub fn p_value<R: Rng>(&self, rng: R, candidate: &[f64], incumbent: &[f64]) -> Result<f64> {
+        match *self {
+            SignificanceTest::Welch => track!(welch_t_test(candidate, incumbent)),
+            SignificanceTest::Bootstrap { resamples } => {
+                track!(bootstrap_test(rng, candidate, incumbent, resamples))
+            }
+        }
+    }
+}
+
+/// Incumbent (i.e., best observation) tracker for noisy objectives.
+///
+/// Observations have repeated measurements as their values.
+/// A new observation replaces the incumbent only if
+/// the p-value of `SignificanceTest::p_value` is lower than the significance level.
+#[derive(Debug, Clone)]
+pub struct IncumbentTracker<P> {
+    test: SignificanceTest,
+    alpha: f64,
+    incumbent: Option<Obs<P, Vec<f64>>>,
+}
+impl<P> IncumbentTracker<P> {
+    /// Makes a new `IncumbentTracker` instance.
+    ///
+    /// `alpha` is the significance level of the test.
+    ///
+    /// # Errors
+    ///
+    /// If `alpha` is not in the range `(0.0, 1.0)`, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(test: SignificanceTest, alpha: f64) -> Result<Self> {
+        track_assert!(0.0 < alpha && alpha < 1.0, ErrorKind::InvalidInput; alpha);
+        Ok(Self {
+            test,
+            alpha,
+            incumbent: None,
+        })
+    }
+
+    /// Returns the current incumbent.
+    pub fn incumbent(&self) -> Option<&Obs<P, Vec<f64>>> {
+        self.incumbent.as_ref()
+    }
+
+    /// Offers an observation with its measurements so far.
+    ///
+    /// If `obs` is the incumbent itself, its measurements are just updated.
+    /// Otherwise, `obs` becomes the new incumbent if it is significantly better than the current one.
+    ///
+    /// Returns `true` if the incumbent has been changed to `obs`, otherwise `false`.
+    ///
+    /// # Errors
+    ///
+    /// If the measurements cannot be tested (see `SignificanceTest::p_value`),
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn offer<R: Rng>(&mut self, rng: R, obs: Obs<P, Vec<f64>>) -> Result<bool> {
+        let incumbent = match &mut self.incumbent {
+            None => {
+                track!(check_sample(&obs.value, 1); obs.id)?;
+                self.incumbent = Some(obs);
+                return Ok(true);
+            }
+            Some(incumbent) => incumbent,
+        };
+        if incumbent.id == obs.id {
+            track!(check_sample(&obs.value, 1); obs.id)?;
+            *incumbent = obs;
+            return Ok(false);
+        }
+
+        let p = track!(self.test.p_value(rng, &obs.value, &incumbent.value); obs.id)?;
+        if p < self.alpha {
+            *incumbent = obs;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+fn check_sample(xs: &[f64], min_len: usize) -> Result<()> {
+    track_assert!(xs.len() >= min_len, ErrorKind::InvalidInput; xs.len(), min_len);
+    track_assert!(xs.iter().all(|x| x.is_finite()), ErrorKind::InvalidInput);
+    Ok(())
+}
+
+/// Returns the mean and the unbiased sample variance of `xs`.
+///
+/// The variance is `0.0` if `xs` has fewer than two elements.
+pub(crate) fn mean_and_variance(xs: &[f64]) -> (f64, f64) {
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    if xs.len() < 2 {
+        return (mean, 0.0);
+    }
+    let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, var)
+}
+
+fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * regularized_incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t < 0.0 {
+        tail
+    } else {
+        1.0 - tail
+    }
+}
+
+fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let ln_front = ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        ln_front.exp() * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - ln_front.exp() * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+// Lentz's method (see "Numerical Recipes", section 6.4).
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 300;
+    const EPSILON: f64 = 1e-14;
+    const TINY: f64 = 1e-300;
+    let guard = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / guard(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / guard(1.0 + even * d);
+        c = guard(1.0 + even / c);
+        h *= d * c;
+
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / guard(1.0 + odd * d);
+        c = guard(1.0 + odd / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+// Lanczos approximation (g = 7, n = 9).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let a = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |a, (i, c)| a + c / (x + i as f64 + 1.0));
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + a.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ObsId;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn significance_works() -> TestResult {
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-10);
+        assert!((student_t_cdf(-2.0, 10.0) - 0.036_694).abs() < 1e-5);
+        assert!((student_t_cdf(0.0, 3.0) - 0.5).abs() < 1e-10);
+
+        let good = [1.0, 1.1, 0.9, 1.05, 0.95];
+        let bad = [2.0, 2.2, 1.8, 2.1, 1.9];
+        assert!(track!(welch_t_test(&good, &bad))? < 0.01);
+        assert!(track!(welch_t_test(&bad, &good))? > 0.99);
+        assert!(welch_t_test(&good[..1], &bad).is_err());
+
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(track!(bootstrap_test(&mut rng, &good, &bad, 100))? < 0.01);
+        assert!(track!(bootstrap_test(&mut rng, &bad, &good, 100))? > 0.99);
+
+        let obs = |id, value: &[f64]| Obs {
+            id: ObsId::new(id),
+            param: (),
+            value: value.to_vec(),
+        };
+        for test in [
+            SignificanceTest::Welch,
+            SignificanceTest::Bootstrap { resamples: 1000 },
+        ] {
+            assert!(IncumbentTracker::<()>::new(test, 1.0).is_err());
+            let mut tracker = track!(IncumbentTracker::new(test, 0.05))?;
+            assert!(track!(tracker.offer(&mut rng, obs(0, &bad)))?);
+
+            // A noisy improvement is not enough.
+            assert!(!track!(tracker.offer(&mut rng, obs(1, &[0.5, 3.4])))?);
+            assert!(track!(tracker.offer(&mut rng, obs(2, &good)))?);
+            assert!(!track!(tracker.offer(&mut rng, obs(2, &good[..3])))?);
+
+            let incumbent = track_assert_some!(tracker.incumbent(), ErrorKind::Other);
+            assert_eq!(incumbent.id, ObsId::new(2));
+            assert_eq!(incumbent.value.len(), 3);
+        }
+        Ok(())
+    }
+}
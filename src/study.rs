@@ -0,0 +1,135 @@
+//! Metadata of a study.
+use crate::scalarize::{NamedObjectives, Objective};
+use crate::{Result, StudyBudget};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Configuration of a study (i.e., a sequence of evaluations driven by an optimizer).
+///
+/// This holds the metadata that should stay the same when a checkpointed study is resumed,
+/// so that it is not reconstructed ad hoc at each restart,
+/// and the number of the evaluations completed so far, so that a resumed study continues where it left off.
+/// If the `serde` feature is enabled, this can be serialized into any format supported by serde (e.g., JSON)
+/// and be persisted along with the checkpoints.
+///
+/// `ParallelExecutor::run_study` (available with the `parallel` feature) runs a study as configured.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StudyConfig {
+    /// The name of the study.
+    pub name: String,
+
+    /// The objectives (and their directions) of the study.
+    pub objectives: Vec<Objective>,
+
+    /// The maximum number of evaluations (or `None` if unlimited).
+    pub max_evaluations: Option<u64>,
+
+    /// The seed of the random number generator passed to the optimizer.
+    pub seed: u64,
+
+    /// The number of the evaluations completed so far.
+    ///
+    /// `ParallelExecutor::run_study` adds the number of the evaluations it ran to this.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub completed_evaluations: u64,
+}
+impl StudyConfig {
+    /// Makes a new `StudyConfig` instance that has a single objective to be minimized.
+    ///
+    /// The number of evaluations is unlimited and the seed is `0` by default.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            objectives: vec![Objective::new("objective")],
+            max_evaluations: None,
+            seed: 0,
+            completed_evaluations: 0,
+        }
+    }
+
+    /// Makes a new `StudyBudget` limited by the evaluations remaining in `max_evaluations`.
+    pub fn budget(&self) -> StudyBudget {
+        match self.max_evaluations {
+            Some(n) => {
+                StudyBudget::new().max_evaluations(n.saturating_sub(self.completed_evaluations))
+            }
+            None => StudyBudget::new(),
+        }
+    }
+
+    /// Makes a new random number generator seeded by `seed` and `completed_evaluations`.
+    ///
+    /// A resumed study thus does not replay the random numbers used before it was suspended.
+    pub fn rng(&self) -> StdRng {
+        let progress = self
+            .completed_evaluations
+            .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        StdRng::seed_from_u64(self.seed ^ progress)
+    }
+
+    /// Makes a new `NamedObjectives` that aggregates the values of the objectives.
+    ///
+    /// # Errors
+    ///
+    /// The same as `NamedObjectives::new`.
+    pub fn named_objectives(&self) -> Result<NamedObjectives> {
+        track!(NamedObjectives::new(self.objectives.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scalarize::Direction;
+    use rand::Rng;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn study_config_works() -> TestResult {
+        let mut config = StudyConfig::new("foo");
+        assert_eq!(config.budget().remaining_evaluations(), None);
+
+        config.max_evaluations = Some(10);
+        config.objectives.push(Objective {
+            name: "accuracy".to_owned(),
+            direction: Direction::Maximize,
+            weight: 2.0,
+        });
+        assert_eq!(config.budget().remaining_evaluations(), Some(10));
+        assert_eq!(config.rng().gen::<u64>(), config.rng().gen::<u64>());
+
+        let initial = config.rng().gen::<u64>();
+        config.completed_evaluations = 4;
+        assert_eq!(config.budget().remaining_evaluations(), Some(6));
+        assert_ne!(config.rng().gen::<u64>(), initial);
+        config.completed_evaluations = 12;
+        assert!(config.budget().is_exhausted());
+        config.completed_evaluations = 0;
+        assert_eq!(
+            track!(config.named_objectives())?.aggregate(&[1.0, 0.5])?,
+            0.0
+        );
+
+        config.objectives.push(Objective::new("accuracy"));
+        assert!(config.named_objectives().is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn study_config_serde_works() -> TestResult {
+        use crate::ErrorKind;
+        use trackable::error::ErrorKindExt;
+
+        let mut config = StudyConfig::new("foo");
+        config.seed = 3;
+        let json = track!(serde_json::to_string(&config).map_err(|e| ErrorKind::Other.cause(e)))?;
+        let restored: StudyConfig =
+            track!(serde_json::from_str(&json).map_err(|e| ErrorKind::Other.cause(e)))?;
+        assert_eq!(restored, config);
+        Ok(())
+    }
+}
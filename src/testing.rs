@@ -0,0 +1,300 @@
+//! Conformance checking of `Optimizer` implementations.
+//!
+//! This module is enabled by the `testing` feature.
+//!
+//! `check_actions` drives an optimizer by a sequence of `Action`s
+//! (including out-of-order tells, tells of unknown observations and duplicate tells)
+//! and verifies the following invariants:
+//!
+//! - neither `ask` nor `tell` panics nor returns an `ErrorKind::Bug` error
+//! - an asked observation has an identifier generated by the given `IdGen` that has not been asked before
+//! - an asked parameter is contained in the parameter domain
+//! - telling an unknown observation succeeds or fails with an `ErrorKind::UnknownObservation` error
+//!
+//! A violation is reported as an `ErrorKind::Bug` error.
+//!
+//! ```
+//! use yamakan::domains::DiscreteDomain;
+//! use yamakan::optimizers::random::RandomOptimizer;
+//! use yamakan::testing::check_optimizer;
+//!
+//! let domain = DiscreteDomain::new(10).unwrap();
+//! let mut opt = RandomOptimizer::new(domain.clone());
+//! check_optimizer(&mut opt, &domain, rand::thread_rng(), 100, |_, x| *x as f64).unwrap();
+//! ```
+//!
+//! `actions` provides a [proptest] strategy of action sequences, so that
+//! failing sequences are shrunk to minimal ones when used with `check_actions` in a `proptest!` block.
+//!
+//! [proptest]: https://github.com/proptest-rs/proptest
+use crate::{Domain, Error, ErrorContext, ErrorKind, IdGen, Obs, ObsId, Optimizer, Result};
+use proptest::prelude::*;
+use rand::Rng;
+use std::collections::HashSet;
+use std::panic::{self, AssertUnwindSafe};
+
+/// An operation applied to an optimizer by `check_actions`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    /// Asks a new observation.
+    Ask,
+
+    /// Tells the evaluation result of a pending observation.
+    ///
+    /// The index is taken modulo the number of the pending observations,
+    /// so pending observations can be told in any order.
+    Tell(usize),
+
+    /// Tells an observation that has never been asked.
+    TellUnknown,
+
+    /// Tells an already told observation again.
+    ///
+    /// The index is taken modulo the number of the told observations.
+    Retell(usize),
+}
+impl Action {
+    /// Returns a randomly chosen action.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        match rng.gen_range(0..8) {
+            0..=3 => Action::Ask,
+            4..=5 => Action::Tell(rng.gen()),
+            6 => Action::TellUnknown,
+            _ => Action::Retell(rng.gen()),
+        }
+    }
+}
+
+/// Returns a proptest strategy generating sequences of at most `max_len` actions.
+pub fn actions(max_len: usize) -> impl Strategy<Value = Vec<Action>> {
+    let action = prop_oneof![
+        4 => Just(Action::Ask),
+        2 => any::<usize>().prop_map(Action::Tell),
+        1 => Just(Action::TellUnknown),
+        1 => any::<usize>().prop_map(Action::Retell),
+    ];
+    proptest::collection::vec(action, 0..=max_len)
+}
+
+/// Applies the given actions to `optimizer` and checks the invariants described in the module documentation.
+///
+/// `evaluate` computes the value of an asked parameter.
+///
+/// # Errors
+///
+/// If an invariant is violated, an `ErrorKind::Bug` error will be returned.
+pub fn check_actions<O, D, R, F>(
+    optimizer: &mut O,
+    domain: &D,
+    mut rng: R,
+    actions: &[Action],
+    mut evaluate: F,
+) -> Result<()>
+where
+    O: Optimizer,
+    O::Param: Clone,
+    D: Domain<Point = O::Param>,
+    R: Rng,
+    F: FnMut(&mut R, &O::Param) -> O::Value,
+{
+    let mut idg = RecordingIdGenerator::default();
+    let mut asked = HashSet::new();
+    let mut pending = Vec::new();
+    let mut told = Vec::new();
+    for &action in actions {
+        match action {
+            Action::Ask => {
+                let result = track!(catch(|| optimizer.ask(&mut rng, &mut idg)))?;
+                let obs = match result {
+                    Ok(obs) => obs,
+                    Err(e) => {
+                        track!(check_error(e, None))?;
+                        continue;
+                    }
+                };
+                if !idg.generated.contains(&obs.id) || !asked.insert(obs.id) {
+                    track_panic!(
+                        ErrorKind::Bug,
+                        ErrorContext::new("Asked an unknown or duplicate identifier")
+                            .obs_id(obs.id)
+                    );
+                }
+                if !domain.contains(&obs.param) {
+                    track_panic!(
+                        ErrorKind::Bug,
+                        ErrorContext::new("Asked an out of the domain parameter").obs_id(obs.id)
+                    );
+                }
+                pending.push(obs);
+            }
+            Action::Tell(i) => {
+                if pending.is_empty() {
+                    continue;
+                }
+                let obs = pending.swap_remove(i % pending.len());
+                let value = evaluate(&mut rng, &obs.param);
+                let obs = Obs {
+                    id: obs.id,
+                    param: obs.param,
+                    value,
+                };
+                let id = obs.id;
+                let param = obs.param.clone();
+                track!(tell(optimizer, obs, None))?;
+                told.push((id, param));
+            }
+            Action::TellUnknown => {
+                let param = told
+                    .first()
+                    .map(|(_, param)| param)
+                    .or_else(|| pending.first().map(|obs| &obs.param));
+                let param = match param {
+                    Some(param) => param.clone(),
+                    None => continue,
+                };
+                let id = track!(idg.generate())?;
+                let value = evaluate(&mut rng, &param);
+                track!(tell(
+                    optimizer,
+                    Obs { id, param, value },
+                    Some(ErrorKind::UnknownObservation)
+                ))?;
+            }
+            Action::Retell(i) => {
+                if told.is_empty() {
+                    continue;
+                }
+                let (id, param) = told[i % told.len()].clone();
+                let value = evaluate(&mut rng, &param);
+                track!(tell(optimizer, Obs { id, param, value }, None))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Applies `iterations` randomly chosen actions to `optimizer` and checks the invariants.
+///
+/// See `check_actions` for the details.
+pub fn check_optimizer<O, D, R, F>(
+    optimizer: &mut O,
+    domain: &D,
+    mut rng: R,
+    iterations: usize,
+    evaluate: F,
+) -> Result<()>
+where
+    O: Optimizer,
+    O::Param: Clone,
+    D: Domain<Point = O::Param>,
+    R: Rng,
+    F: FnMut(&mut R, &O::Param) -> O::Value,
+{
+    let actions = (0..iterations)
+        .map(|_| Action::random(&mut rng))
+        .collect::<Vec<_>>();
+    track!(check_actions(optimizer, domain, rng, &actions, evaluate))
+}
+
+#[derive(Debug, Default)]
+struct RecordingIdGenerator {
+    next_id: u64,
+    generated: HashSet<ObsId>,
+}
+impl IdGen for RecordingIdGenerator {
+    fn generate(&mut self) -> Result<ObsId> {
+        let id = ObsId::new(self.next_id);
+        self.next_id += 1;
+        self.generated.insert(id);
+        Ok(id)
+    }
+}
+
+fn tell<O: Optimizer>(
+    optimizer: &mut O,
+    obs: Obs<O::Param, O::Value>,
+    allowed: Option<ErrorKind>,
+) -> Result<()> {
+    let id = obs.id;
+    match track!(catch(|| optimizer.tell(obs)))? {
+        Ok(()) => Ok(()),
+        Err(e) => track!(check_error(e, allowed.map(|kind| (kind, id)))),
+    }
+}
+
+fn check_error(e: Error, expected: Option<(ErrorKind, ObsId)>) -> Result<()> {
+    let kind = *e.kind();
+    if kind == ErrorKind::Bug {
+        track_panic!(ErrorKind::Bug, ErrorContext::new(e.to_string()));
+    }
+    if let Some((expected, id)) = expected {
+        if kind != expected {
+            track_panic!(
+                ErrorKind::Bug,
+                ErrorContext::new(format!("Expected {:?} error, but got {:?}", expected, kind))
+                    .obs_id(id)
+            );
+        }
+    }
+    Ok(())
+}
+
+fn catch<T, F: FnOnce() -> T>(f: F) -> Result<T> {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(x) => Ok(x),
+        Err(_) => track_panic!(ErrorKind::Bug, ErrorContext::new("Panicked")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::{ContinuousDomain, DiscreteDomain};
+    use crate::optimizers::nelder_mead::NelderMeadOptimizer;
+    use crate::optimizers::random::RandomOptimizer;
+    use ordered_float::NotNan;
+    use trackable::result::TestResult;
+
+    #[test]
+    fn check_optimizer_works() -> TestResult {
+        let domain = track!(DiscreteDomain::new(10))?;
+        let mut opt = RandomOptimizer::new(domain.clone());
+        track!(check_optimizer(
+            &mut opt,
+            &domain,
+            rand::thread_rng(),
+            100,
+            |_, &x| x as f64
+        ))?;
+
+        let domain = vec![
+            track!(ContinuousDomain::new(-1.0, 1.0))?,
+            track!(ContinuousDomain::new(-1.0, 1.0))?,
+        ];
+        let mut opt = track!(NelderMeadOptimizer::new(domain.clone(), rand::thread_rng()))?;
+        track!(check_optimizer(
+            &mut opt,
+            &crate::domains::VecDomain(domain),
+            rand::thread_rng(),
+            100,
+            |_, x| NotNan::new(x[0] * x[0] + x[1] * x[1]).unwrap_or_else(|e| panic!("{}", e))
+        ))?;
+
+        let narrow = track!(DiscreteDomain::new(5))?;
+        let mut opt = RandomOptimizer::new(track!(DiscreteDomain::new(10))?);
+        let actions = vec![Action::Ask; 100];
+        let e = check_actions(&mut opt, &narrow, rand::thread_rng(), &actions, |_, _| ()).err();
+        assert_eq!(e.map(|e| *e.kind()), Some(ErrorKind::Bug));
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn random_optimizer_conforms(actions in actions(30)) {
+            let domain = DiscreteDomain::new(10).unwrap_or_else(|e| panic!("{}", e));
+            let mut opt = RandomOptimizer::new(domain.clone());
+            let result = check_actions(&mut opt, &domain, rand::thread_rng(), &actions, |_, x| *x);
+            prop_assert!(result.is_ok(), "{:?}", result);
+        }
+    }
+}
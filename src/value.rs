@@ -0,0 +1,350 @@
+//! Totally ordered objective values.
+use crate::optimizers::map_value::ValueTransform;
+use crate::significance::mean_and_variance;
+use crate::{ErrorKind, Result};
+use ordered_float::{NotNan, OrderedFloat};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// How to handle NaN values.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NanPolicy {
+    /// NaN values are rejected with an `ErrorKind::InvalidInput` error.
+    #[default]
+    Error,
+
+    /// NaN values are treated as the worst values.
+    Worst,
+}
+
+/// `NanPolicy` converts raw `f64` values to `FiniteF64`.
+///
+/// This makes it possible to tell raw values to optimizers requiring `Ord` values via `MapValueOptimizer`.
+impl ValueTransform<f64> for NanPolicy {
+    type Output = FiniteF64;
+
+    fn transform(&mut self, value: f64) -> Result<Self::Output> {
+        track!(FiniteF64::with_nan_policy(value, *self))
+    }
+}
+
+/// `NanPolicy` checks each element of raw multi-objective values (e.g., for `Nsga2Optimizer`).
+///
+/// With `NanPolicy::Worst`, NaN and positive infinite elements are replaced by `f64::MAX`.
+impl ValueTransform<Vec<f64>> for NanPolicy {
+    type Output = Vec<f64>;
+
+    fn transform(&mut self, values: Vec<f64>) -> Result<Self::Output> {
+        values
+            .into_iter()
+            .map(|v| track!(FiniteF64::with_nan_policy(v, *self)).map(FiniteF64::get))
+            .collect()
+    }
+}
+
+/// A finite `f64` value, which is totally ordered.
+///
+/// Negative zero is normalized to positive zero, so `Eq` and `Hash` are consistent with each other.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "f64", into = "f64"))]
+pub struct FiniteF64(f64);
+impl FiniteF64 {
+    /// The largest finite value, which is used as the worst value by `NanPolicy::Worst`.
+    pub const MAX: Self = Self(f64::MAX);
+
+    /// The smallest finite value.
+    pub const MIN: Self = Self(f64::MIN);
+
+    /// Makes a new `FiniteF64` instance.
+    ///
+    /// # Errors
+    ///
+    /// If `value` is not finite, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(value: f64) -> Result<Self> {
+        track_assert!(value.is_finite(), ErrorKind::InvalidInput; value);
+        Ok(Self(value + 0.0))
+    }
+
+    /// Makes a new `FiniteF64` instance with the given NaN policy.
+    ///
+    /// If the policy is `NanPolicy::Worst`, NaN and positive infinite values become `FiniteF64::MAX`.
+    ///
+    /// # Errors
+    ///
+    /// If `value` is negative infinity, or is NaN or positive infinity and the policy is `NanPolicy::Error`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn with_nan_policy(value: f64, policy: NanPolicy) -> Result<Self> {
+        if policy == NanPolicy::Worst && (value.is_nan() || value == f64::INFINITY) {
+            Ok(Self::MAX)
+        } else {
+            track!(Self::new(value))
+        }
+    }
+
+    /// Returns the underlying value.
+    pub const fn get(self) -> f64 {
+        self.0
+    }
+}
+impl Eq for FiniteF64 {}
+impl PartialOrd for FiniteF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for FiniteF64 {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+impl Hash for FiniteF64 {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
+    }
+}
+impl fmt::Display for FiniteF64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+impl TryFrom<f64> for FiniteF64 {
+    type Error = crate::Error;
+
+    fn try_from(value: f64) -> Result<Self> {
+        track!(Self::new(value))
+    }
+}
+impl From<FiniteF64> for f64 {
+    fn from(value: FiniteF64) -> Self {
+        value.0
+    }
+}
+impl From<FiniteF64> for NotNan<f64> {
+    fn from(value: FiniteF64) -> Self {
+        // `value` is finite, so the conversion never fails.
+        NotNan::new(value.0).unwrap_or_default()
+    }
+}
+impl From<FiniteF64> for OrderedFloat<f64> {
+    fn from(value: FiniteF64) -> Self {
+        OrderedFloat(value.0)
+    }
+}
+impl From<i32> for FiniteF64 {
+    fn from(value: i32) -> Self {
+        Self(f64::from(value))
+    }
+}
+impl TryFrom<f32> for FiniteF64 {
+    type Error = crate::Error;
+
+    fn try_from(value: f32) -> Result<Self> {
+        track!(Self::new(f64::from(value)))
+    }
+}
+
+/// An objective value estimated from repeated measurements, with its standard error.
+///
+/// Noise-aware optimizers (e.g., `TurboOptimizer::tell_measured`) use the standard error to weight observations.
+/// Optimizers that ignore it can be fed through `MapValueOptimizer::new(inner, MeasuredValue::mean)`.
+///
+/// Values are ordered by their means (and then by their standard errors and the numbers of the measurements).
+///
+/// If the `serde` feature is enabled, deserialized values are validated in the same way as `new`.
+#[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawMeasuredValue"))]
+pub struct MeasuredValue {
+    mean: f64,
+    sem: f64,
+    n: u64,
+}
+impl MeasuredValue {
+    /// Makes a new `MeasuredValue` instance.
+    ///
+    /// `sem` is the standard error of `mean`, and `n` is the number of the measurements.
+    ///
+    /// # Errors
+    ///
+    /// If `mean` is not finite, `sem` is negative or not finite, or `n` is `0`,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn new(mean: f64, sem: f64, n: u64) -> Result<Self> {
+        track_assert!(mean.is_finite(), ErrorKind::InvalidInput; mean);
+        track_assert!(sem.is_finite() && sem >= 0.0, ErrorKind::InvalidInput; sem);
+        track_assert!(n > 0, ErrorKind::InvalidInput);
+        Ok(Self { mean, sem, n })
+    }
+
+    /// Makes a new `MeasuredValue` instance from the given measurements.
+    ///
+    /// The standard error is estimated from the sample variance, so it is `0.0` if `samples` has only one element.
+    ///
+    /// # Errors
+    ///
+    /// If `samples` is empty or contains a non-finite value, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn from_samples(samples: &[f64]) -> Result<Self> {
+        track_assert!(!samples.is_empty(), ErrorKind::InvalidInput);
+        track_assert!(
+            samples.iter().all(|x| x.is_finite()),
+            ErrorKind::InvalidInput
+        );
+
+        let (mean, var) = mean_and_variance(samples);
+        let sem = (var / samples.len() as f64).sqrt();
+        track!(Self::new(mean, sem, samples.len() as u64))
+    }
+
+    /// Returns the mean of the measurements.
+    pub const fn mean(self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the standard error of the mean.
+    pub const fn sem(self) -> f64 {
+        self.sem
+    }
+
+    /// Returns the number of the measurements.
+    pub const fn n(self) -> u64 {
+        self.n
+    }
+
+    /// Returns the variance of the mean (i.e., the square of the standard error).
+    pub fn variance(self) -> f64 {
+        self.sem * self.sem
+    }
+}
+impl PartialEq for MeasuredValue {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+impl Eq for MeasuredValue {}
+impl PartialOrd for MeasuredValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for MeasuredValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (OrderedFloat(self.mean), OrderedFloat(self.sem), self.n).cmp(&(
+            OrderedFloat(other.mean),
+            OrderedFloat(other.sem),
+            other.n,
+        ))
+    }
+}
+impl From<MeasuredValue> for f64 {
+    fn from(value: MeasuredValue) -> Self {
+        value.mean
+    }
+}
+#[cfg(feature = "serde")]
+impl TryFrom<RawMeasuredValue> for MeasuredValue {
+    type Error = crate::Error;
+
+    fn try_from(raw: RawMeasuredValue) -> Result<Self> {
+        track!(Self::new(raw.mean, raw.sem, raw.n))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawMeasuredValue {
+    mean: f64,
+    sem: f64,
+    n: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::generators::SerialIdGenerator;
+    use crate::optimizers::asha::AshaOptimizer;
+    use crate::optimizers::map_value::MapValueOptimizer;
+    use crate::optimizers::random::RandomOptimizer;
+    use crate::{MultiFidelityOptimizer, Optimizer};
+    use trackable::result::TestResult;
+
+    #[test]
+    fn finite_f64_works() -> TestResult {
+        assert!(FiniteF64::new(f64::NAN).is_err());
+        assert!(FiniteF64::try_from(f64::INFINITY).is_err());
+        assert_eq!(track!(FiniteF64::new(-0.0))?, FiniteF64::from(0));
+        assert!(track!(FiniteF64::new(-1.0))? < track!(FiniteF64::new(2.5))?);
+        assert_eq!(
+            track!(FiniteF64::with_nan_policy(f64::NAN, NanPolicy::Worst))?,
+            FiniteF64::MAX
+        );
+        assert!(FiniteF64::with_nan_policy(f64::NAN, NanPolicy::Error).is_err());
+        assert_eq!(
+            track!(NanPolicy::Worst.transform(vec![1.0, f64::NAN]))?,
+            [1.0, f64::MAX]
+        );
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        let inner = RandomOptimizer::<_, FiniteF64>::new(track!(DiscreteDomain::new(10))?);
+        let mut opt = MapValueOptimizer::new(inner, NanPolicy::Worst);
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        track!(opt.tell(obs.map_value(|()| f64::NAN)))?;
+
+        let inner = RandomOptimizer::new(track!(DiscreteDomain::new(10))?);
+        let mut opt = track!(AshaOptimizer::<FiniteF64, _>::new(inner, 1, 9))?;
+        for i in 0..10 {
+            let obs = track!(opt.ask(&mut rng, &mut idg))?;
+            let value = if i % 2 == 0 { f64::NAN } else { f64::from(i) };
+            let mut obs = track!(
+                obs.try_map_value(|()| FiniteF64::with_nan_policy(value, NanPolicy::Worst))
+            )?;
+            obs.budget.consumption = obs.budget.amount;
+            track!(opt.tell(obs))?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn measured_value_works() -> TestResult {
+        assert!(MeasuredValue::new(f64::NAN, 0.0, 1).is_err());
+        assert!(MeasuredValue::new(1.0, -0.1, 1).is_err());
+        assert!(MeasuredValue::new(1.0, 0.1, 0).is_err());
+        assert!(MeasuredValue::from_samples(&[]).is_err());
+
+        let value = track!(MeasuredValue::from_samples(&[1.0, 2.0, 3.0]))?;
+        assert_eq!(value.mean(), 2.0);
+        assert_eq!(value.n(), 3);
+        assert!((value.variance() - 1.0 / 3.0).abs() < 1e-12);
+        assert_eq!(track!(MeasuredValue::from_samples(&[1.5]))?.sem(), 0.0);
+
+        let mut rng = rand::thread_rng();
+        let mut idg = SerialIdGenerator::new();
+        let inner = RandomOptimizer::<_, f64>::new(track!(DiscreteDomain::new(10))?);
+        let mut opt = MapValueOptimizer::new(inner, MeasuredValue::mean);
+        let obs = track!(opt.ask(&mut rng, &mut idg))?;
+        track!(opt.tell(obs.map_value(|()| value)))?;
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn measured_value_deserialization_is_validated() -> TestResult {
+        use trackable::error::ErrorKindExt;
+
+        let value = track!(MeasuredValue::new(1.0, 0.5, 4))?;
+        let json = track!(serde_json::to_string(&value).map_err(|e| ErrorKind::Other.cause(e)))?;
+        let restored: MeasuredValue =
+            track!(serde_json::from_str(&json).map_err(|e| ErrorKind::Other.cause(e)))?;
+        assert_eq!(restored, value);
+
+        assert!(serde_json::from_str::<MeasuredValue>(r#"{"mean":1,"sem":-3,"n":0}"#).is_err());
+        assert!(serde_json::from_str::<MeasuredValue>(r#"{"mean":1,"sem":0.5,"n":0}"#).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,158 @@
+//! Thin [wasm-bindgen] bindings of optimizers for `Vec<f64>` domains.
+//!
+//! This module is enabled by the `wasm` feature.
+//! The feature also enables the `js` feature of `getrandom`,
+//! so that the random number generator can be seeded on `wasm32-unknown-unknown`.
+//!
+//! [wasm-bindgen]: https://github.com/rustwasm/wasm-bindgen
+use crate::domains::{ContinuousDomain, VecDomain};
+use crate::generators::SerialIdGenerator;
+use crate::optimizers::nelder_mead::NelderMeadOptimizer;
+use crate::optimizers::random::RandomOptimizer;
+use crate::{ErrorKind, Obs, ObsId, Optimizer, Result};
+use ordered_float::NotNan;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::fmt;
+use trackable::error::ErrorKindExt;
+use wasm_bindgen::prelude::*;
+
+/// An observation asked by `WasmOptimizer`.
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct WasmObs {
+    id: u64,
+    param: Vec<f64>,
+}
+#[wasm_bindgen]
+impl WasmObs {
+    /// Returns the identifier of this observation.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns the parameter of this observation.
+    pub fn param(&self) -> Vec<f64> {
+        self.param.clone()
+    }
+}
+
+/// An optimizer exposing ask-and-tell interface to JavaScript.
+///
+/// The parameter of an observation is a vector of `f64`s, and
+/// the value is a (non NaN) `f64` to be minimized.
+#[wasm_bindgen]
+#[derive(Debug)]
+pub struct WasmOptimizer {
+    inner: Inner,
+    rng: StdRng,
+    idg: SerialIdGenerator,
+    asked: HashMap<ObsId, Vec<f64>>,
+}
+#[wasm_bindgen]
+impl WasmOptimizer {
+    /// Makes a new `WasmOptimizer` instance that wraps `RandomOptimizer`.
+    ///
+    /// The `i`-th element of a parameter is sampled from the range `lows[i]..highs[i]`.
+    pub fn random(lows: Vec<f64>, highs: Vec<f64>) -> Result<WasmOptimizer, JsValue> {
+        let domain = track_js(params_domain(&lows, &highs))?;
+        let inner = Inner::Random(RandomOptimizer::new(VecDomain(domain)));
+        Ok(Self::new(inner))
+    }
+
+    /// Makes a new `WasmOptimizer` instance that wraps `NelderMeadOptimizer`.
+    ///
+    /// The `i`-th element of a parameter is searched in the range `lows[i]..highs[i]`.
+    #[wasm_bindgen(js_name = nelderMead)]
+    pub fn nelder_mead(lows: Vec<f64>, highs: Vec<f64>) -> Result<WasmOptimizer, JsValue> {
+        let domain = track_js(params_domain(&lows, &highs))?;
+        let mut rng = StdRng::from_entropy();
+        let inner = Inner::NelderMead(Box::new(track_js(NelderMeadOptimizer::new(
+            domain, &mut rng,
+        ))?));
+        Ok(Self::new(inner))
+    }
+
+    /// Asks the next observation to be evaluated.
+    pub fn ask(&mut self) -> Result<WasmObs, JsValue> {
+        let obs = match &mut self.inner {
+            Inner::Random(o) => track_js(o.ask(&mut self.rng, &mut self.idg))?,
+            Inner::NelderMead(o) => track_js(o.ask(&mut self.rng, &mut self.idg))?,
+        };
+        self.asked.insert(obs.id, obs.param.clone());
+        Ok(WasmObs {
+            id: obs.id.get(),
+            param: obs.param,
+        })
+    }
+
+    /// Tells the evaluation result of the observation identified by `id`.
+    pub fn tell(&mut self, id: u64, value: f64) -> Result<(), JsValue> {
+        track_js(self.tell_value(ObsId::new(id), value))
+    }
+}
+impl WasmOptimizer {
+    fn new(inner: Inner) -> Self {
+        Self {
+            inner,
+            rng: StdRng::from_entropy(),
+            idg: SerialIdGenerator::new(),
+            asked: HashMap::new(),
+        }
+    }
+
+    fn tell_value(&mut self, id: ObsId, value: f64) -> Result<()> {
+        let param = track_assert_some!(
+            self.asked.remove(&id),
+            ErrorKind::UnknownObservation; id
+        );
+        match &mut self.inner {
+            Inner::Random(o) => track!(o.tell(Obs { id, param, value })),
+            Inner::NelderMead(o) => {
+                let value =
+                    track!(NotNan::new(value).map_err(|e| ErrorKind::InvalidInput.cause(e)))?;
+                track!(o.tell(Obs { id, param, value }))
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Inner {
+    Random(RandomOptimizer<VecDomain<ContinuousDomain>, f64>),
+    NelderMead(Box<NelderMeadOptimizer<NotNan<f64>>>),
+}
+
+fn params_domain(lows: &[f64], highs: &[f64]) -> Result<Vec<ContinuousDomain>> {
+    track_assert_eq!(lows.len(), highs.len(), ErrorKind::InvalidInput);
+    lows.iter()
+        .zip(highs.iter())
+        .map(|(&low, &high)| track!(ContinuousDomain::new(low, high)))
+        .collect()
+}
+
+fn track_js<T, E: fmt::Display>(result: std::result::Result<T, E>) -> Result<T, JsValue> {
+    result.map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wasm_optimizer_works() {
+        for mut opt in [
+            WasmOptimizer::random(vec![0.0, -1.0], vec![1.0, 1.0]).unwrap_or_else(|_| panic!()),
+            WasmOptimizer::nelder_mead(vec![0.0, -1.0], vec![1.0, 1.0])
+                .unwrap_or_else(|_| panic!()),
+        ] {
+            for _ in 0..10 {
+                let obs = opt.ask().unwrap_or_else(|_| panic!());
+                assert_eq!(obs.param().len(), 2);
+                let value = obs.param().iter().map(|x| x * x).sum();
+                opt.tell(obs.id(), value).unwrap_or_else(|_| panic!());
+            }
+        }
+    }
+}
@@ -0,0 +1,225 @@
+//! Handles for workers evaluating parameters concurrently.
+//!
+//! `WorkerHandle` bundles everything a worker thread needs to use a `SharedOptimizer`:
+//! a reference to the optimizer, an identifier generator partitioned by the worker identifier,
+//! a channel to report the results through, and a random number generator.
+//! Workers just repeat `next_trial` and `report` instead of juggling these references.
+use crate::generators::PartitionedIdGenerator;
+use crate::{ErrorKind, Obs, Result, SharedOptimizer};
+use rand::Rng;
+use std::collections::HashSet;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+type TellReceiver<O> = Receiver<Obs<<O as SharedOptimizer>::Param, <O as SharedOptimizer>::Value>>;
+
+/// A handle used by a worker to evaluate the parameters asked by a shared optimizer.
+///
+/// Handles created by `fork` share the optimizer and the tell channel,
+/// so they can be moved to other threads and used concurrently.
+/// Each handle generates identifiers by `PartitionedIdGenerator` with its own worker identifier,
+/// so the handles must have distinct worker identifiers (e.g., the indices of the threads or processes).
+/// The identifiers in use are tracked among the handles, and are released when the handles are dropped.
+///
+/// Reported values are sent to the tell channel without locking the optimizer,
+/// and are told to the optimizer by the next `next_trial` (or `flush`) call of any of the handles.
+#[derive(Debug)]
+pub struct WorkerHandle<O: SharedOptimizer, R> {
+    optimizer: Arc<O>,
+    idg: PartitionedIdGenerator,
+    rng: R,
+    current: Option<Obs<O::Param>>,
+    tx: Sender<Obs<O::Param, O::Value>>,
+    rx: Arc<Mutex<TellReceiver<O>>>,
+    workers: Arc<Mutex<HashSet<u16>>>,
+}
+impl<O, R> WorkerHandle<O, R>
+where
+    O: SharedOptimizer,
+    R: Rng,
+{
+    /// Makes a new `WorkerHandle` instance having the given worker identifier.
+    pub fn new(optimizer: Arc<O>, worker: u16, rng: R) -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            optimizer,
+            idg: PartitionedIdGenerator::new(worker),
+            rng,
+            current: None,
+            tx,
+            rx: Arc::new(Mutex::new(rx)),
+            workers: Arc::new(Mutex::new(Some(worker).into_iter().collect())),
+        }
+    }
+
+    /// Makes a new handle that shares the optimizer and the tell channel with this handle.
+    ///
+    /// # Errors
+    ///
+    /// If `worker` is used by this handle or any other live handle sharing the optimizer,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    pub fn fork<S: Rng>(&self, worker: u16, rng: S) -> Result<WorkerHandle<O, S>> {
+        let mut workers = track!(crate::lock(&self.workers))?;
+        track_assert!(workers.insert(worker), ErrorKind::InvalidInput; worker);
+        Ok(WorkerHandle {
+            optimizer: Arc::clone(&self.optimizer),
+            idg: PartitionedIdGenerator::new(worker),
+            rng,
+            current: None,
+            tx: self.tx.clone(),
+            rx: Arc::clone(&self.rx),
+            workers: Arc::clone(&self.workers),
+        })
+    }
+
+    /// Returns the worker identifier of this handle.
+    pub fn worker(&self) -> u16 {
+        self.idg.worker()
+    }
+
+    /// Asks the optimizer for the next parameter to be evaluated.
+    ///
+    /// The results reported so far are told to the optimizer before asking.
+    ///
+    /// # Errors
+    ///
+    /// If the value of the previous trial has not been reported yet,
+    /// an `ErrorKind::InvalidInput` error will be returned.
+    /// And errors returned by `flush` are propagated.
+    pub fn next_trial(&mut self) -> Result<&O::Param> {
+        track_assert!(
+            self.current.is_none(),
+            ErrorKind::InvalidInput,
+            "The previous trial has not been reported"
+        );
+        track!(self.flush())?;
+        let obs = track!(self.optimizer.ask(&mut self.rng, &mut self.idg))?;
+        Ok(&self.current.insert(obs).param)
+    }
+
+    /// Returns the trial being evaluated by this worker.
+    pub fn current_trial(&self) -> Option<&Obs<O::Param>> {
+        self.current.as_ref()
+    }
+
+    /// Reports the value of the current trial.
+    ///
+    /// The observation is sent to the tell channel, and is told to the optimizer later.
+    ///
+    /// # Errors
+    ///
+    /// If there is no trial being evaluated, an `ErrorKind::InvalidInput` error will be returned.
+    pub fn report(&mut self, value: O::Value) -> Result<()> {
+        let obs = track_assert_some!(self.current.take(), ErrorKind::InvalidInput);
+        if self.tx.send(obs.map_value(|()| value)).is_err() {
+            // The receiver is owned by this handle.
+            track_panic!(ErrorKind::Bug, "The tell channel has been closed");
+        }
+        Ok(())
+    }
+
+    /// Tells all the results in the tell channel (reported by any of the handles) to the optimizer.
+    ///
+    /// Returns the number of the told observations.
+    /// This should be called after the workers have finished, so that the last results are told.
+    ///
+    /// # Errors
+    ///
+    /// Errors returned by the optimizer are propagated.
+    pub fn flush(&self) -> Result<usize> {
+        let rx = track!(crate::lock(&self.rx))?;
+        let mut told = 0;
+        while let Ok(obs) = rx.try_recv() {
+            track!(self.optimizer.tell(obs))?;
+            told += 1;
+        }
+        Ok(told)
+    }
+
+    /// Returns a reference to the shared optimizer.
+    pub fn optimizer(&self) -> &Arc<O> {
+        &self.optimizer
+    }
+}
+impl<O: SharedOptimizer, R> Drop for WorkerHandle<O, R> {
+    fn drop(&mut self) {
+        if let Ok(mut workers) = self.workers.lock() {
+            workers.remove(&self.idg.worker());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domains::DiscreteDomain;
+    use crate::optimizers::random::RandomOptimizer;
+    use crate::Optimizer;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::sync::Mutex;
+    use std::thread;
+    use trackable::result::TestResult;
+
+    #[derive(Debug, Default)]
+    struct Recorder(HashSet<u64>);
+    impl Optimizer for Recorder {
+        type Param = ();
+        type Value = u64;
+
+        fn ask<R: Rng, G: crate::IdGen>(&mut self, _rng: R, idg: G) -> Result<Obs<()>> {
+            track!(Obs::new(idg, ()))
+        }
+
+        fn tell(&mut self, obs: Obs<(), u64>) -> Result<()> {
+            track_assert_eq!(obs.id.get(), obs.value, ErrorKind::InvalidInput);
+            track_assert!(self.0.insert(obs.value), ErrorKind::InvalidInput);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn worker_works() -> TestResult {
+        let optimizer = Arc::new(RandomOptimizer::new(track!(DiscreteDomain::new(10))?));
+        let mut worker = WorkerHandle::new(optimizer, 0, rand::thread_rng());
+        assert!(worker.report(1.0).is_err());
+        let param = *track!(worker.next_trial())?;
+        assert!(param < 10);
+        assert!(worker.next_trial().is_err());
+        track!(worker.report(1.0))?;
+        assert!(worker.current_trial().is_none());
+        assert_eq!(track!(worker.flush())?, 1);
+
+        let optimizer = Arc::new(Mutex::new(Recorder::default()));
+        let worker = WorkerHandle::new(optimizer, 0, StdRng::seed_from_u64(0));
+        assert!(worker.fork(0, StdRng::seed_from_u64(0)).is_err());
+        let forked = track!(worker.fork(1, StdRng::seed_from_u64(1)))?;
+        assert!(worker.fork(1, StdRng::seed_from_u64(1)).is_err());
+        assert!(forked.fork(1, StdRng::seed_from_u64(1)).is_err());
+        drop(forked);
+        thread::scope(|s| -> TestResult {
+            let handles = (1..5)
+                .map(|i| {
+                    let mut worker = track!(worker.fork(i, StdRng::seed_from_u64(u64::from(i))))?;
+                    Ok(s.spawn(move || -> Result<()> {
+                        for _ in 0..10 {
+                            track!(worker.next_trial())?;
+                            let id = track_assert_some!(worker.current_trial(), ErrorKind::Bug).id;
+                            assert_eq!(id.worker(), worker.worker());
+                            track!(worker.report(id.get()))?;
+                        }
+                        Ok(())
+                    }))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            for handle in handles {
+                let result = track_assert_some!(handle.join().ok(), ErrorKind::Other);
+                track!(result)?;
+            }
+            Ok(())
+        })?;
+        track!(worker.flush())?;
+        assert_eq!(track!(crate::lock(worker.optimizer()))?.0.len(), 40);
+        Ok(())
+    }
+}